@@ -15,9 +15,10 @@ use finalized_history_query::store::minio::MinioBlobStore;
 use finalized_history_query::store::publication::MetaPublicationStore;
 use finalized_history_query::store::scylla::ScyllaMetaStore;
 use finalized_history_query::{
-    Clause, EvmBlockHeader, FinalizedBlock, LeaseAuthority, Log, LogFilter,
+    Address, Clause, EvmBlockHeader, FinalizedBlock, LeaseAuthority, Log, LogFilter, Topic,
 };
-use log_workload_gen::config::GeneratorConfig;
+use log_workload_gen::artifact::{combined_trace_file_name, read_trace_jsonl, trace_file_name};
+use log_workload_gen::config::{GeneratorConfig, TraceCompression};
 use log_workload_gen::pipeline::run_collect_and_generate;
 use log_workload_gen::types::{
     ChainEvent, LogEntry, Message, TraceEntry, TraceProfile as WorkloadTraceProfile,
@@ -1124,11 +1125,11 @@ fn to_index_block(
                 u32::try_from(tx_idx).with_context(|| format!("tx index overflow: {tx_idx}"))?;
 
             logs.push(Log {
-                address: fixed20(log.address.as_slice())?,
+                address: Address(fixed20(log.address.as_slice())?),
                 topics: log
                     .topics()
                     .iter()
-                    .map(|topic| fixed32(topic.as_slice()))
+                    .map(|topic| fixed32(topic.as_slice()).map(Topic))
                     .collect::<Result<Vec<_>>>()?,
                 data: log.data.data.to_vec(),
                 block_num: mapped_block_num,
@@ -1178,29 +1179,41 @@ fn load_traces(dataset_dir: &Path, profiles: &[TraceProfileArg]) -> Result<Vec<T
         selected.extend_from_slice(profiles);
     }
 
+    // A dataset generated with `combined_traces` holds every profile in one
+    // file; select entries by their `profile` field instead.
+    let combined = [TraceCompression::None, TraceCompression::Gzip]
+        .into_iter()
+        .map(|compression| dataset_dir.join(combined_trace_file_name(compression)))
+        .find(|path| path.exists());
+    let combined = combined
+        .map(|path| {
+            read_trace_jsonl(&path).with_context(|| format!("read trace file {}", path.display()))
+        })
+        .transpose()?;
+
     let mut traces = Vec::new();
     for profile in selected {
-        let file = match profile {
-            TraceProfileArg::Expected => "trace_expected.jsonl",
-            TraceProfileArg::Stress => "trace_stress.jsonl",
-            TraceProfileArg::Adversarial => "trace_adversarial.jsonl",
+        let profile = match profile {
+            TraceProfileArg::Expected => WorkloadTraceProfile::Expected,
+            TraceProfileArg::Stress => WorkloadTraceProfile::Stress,
+            TraceProfileArg::Adversarial => WorkloadTraceProfile::Adversarial,
             TraceProfileArg::All => continue,
         };
 
-        let path = dataset_dir.join(file);
-        let bytes =
-            fs::read(&path).with_context(|| format!("read trace file {}", path.display()))?;
-        for line in String::from_utf8(bytes)
-            .with_context(|| format!("trace file is not valid utf8: {}", path.display()))?
-            .lines()
-        {
-            if line.trim().is_empty() {
-                continue;
-            }
-            let trace: TraceEntry =
-                serde_json::from_str(line).with_context(|| format!("parse trace json: {line}"))?;
-            traces.push(trace);
+        if let Some(entries) = &combined {
+            traces.extend(entries.iter().filter(|entry| entry.profile == profile).cloned());
+            continue;
         }
+
+        let path = [TraceCompression::None, TraceCompression::Gzip]
+            .into_iter()
+            .map(|compression| dataset_dir.join(trace_file_name(&profile, compression)))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| dataset_dir.join(trace_file_name(&profile, TraceCompression::None)));
+        traces.extend(
+            read_trace_jsonl(&path)
+                .with_context(|| format!("read trace file {}", path.display()))?,
+        );
     }
 
     Ok(traces)
@@ -1212,6 +1225,8 @@ fn trace_to_request(trace: &TraceEntry, max_results: usize) -> Result<QueryLogsR
         to_block: Some(trace.to_block),
         from_block_hash: None,
         to_block_hash: None,
+        from_timestamp: None,
+        to_timestamp: None,
         order: QueryOrder::Ascending,
         resume_id: None,
         limit: max_results,
@@ -1225,13 +1240,13 @@ fn trace_to_request(trace: &TraceEntry, max_results: usize) -> Result<QueryLogsR
     })
 }
 
-fn decode_clause20(values: &[String]) -> Result<Option<Clause<[u8; 20]>>> {
+fn decode_clause20(values: &[String]) -> Result<Option<Clause<Address>>> {
     if values.is_empty() {
         return Ok(None);
     }
     let decoded = values
         .iter()
-        .map(|v| decode_hex_fixed::<20>(v))
+        .map(|v| decode_hex_fixed::<20>(v).map(Address))
         .collect::<Result<Vec<_>>>()?;
     if decoded.len() == 1 {
         Ok(Some(Clause::One(decoded[0])))
@@ -1240,13 +1255,13 @@ fn decode_clause20(values: &[String]) -> Result<Option<Clause<[u8; 20]>>> {
     }
 }
 
-fn decode_clause32(values: &[String]) -> Result<Option<Clause<[u8; 32]>>> {
+fn decode_clause32(values: &[String]) -> Result<Option<Clause<Topic>>> {
     if values.is_empty() {
         return Ok(None);
     }
     let decoded = values
         .iter()
-        .map(|v| decode_hex_fixed::<32>(v))
+        .map(|v| decode_hex_fixed::<32>(v).map(Topic))
         .collect::<Result<Vec<_>>>()?;
     if decoded.len() == 1 {
         Ok(Some(Clause::One(decoded[0])))
@@ -24,7 +24,7 @@ use finalized_history_query::logs::materialize::LogMaterializer;
 use finalized_history_query::logs::table_specs::{
     BlobTableSpec, BlockLogBlobSpec, BlockLogHeaderSpec, LogDirBucketSpec,
 };
-use finalized_history_query::logs::types::{BlockLogHeader, DirBucket, Log};
+use finalized_history_query::logs::types::{Address, BlockLogHeader, DirBucket, Log, Topic};
 use finalized_history_query::query::runner::{QueryIdRange, QueryMaterializer, ShardBitmapSet};
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
@@ -248,6 +248,10 @@ impl QueryMaterializer for PassThroughMaterializer {
     fn into_output(item: Self::Item) -> Self::Output {
         item
     }
+
+    fn item_bytes(_item: &Self::Item) -> usize {
+        0
+    }
 }
 
 pub fn build_service() -> BenchService {
@@ -311,13 +315,13 @@ pub fn mk_log(
     log_idx: u32,
 ) -> Log {
     Log {
-        address: [address; 20],
-        topics: vec![[topic0; 32], [topic1; 32]],
+        address: Address([address; 20]),
+        topics: vec![Topic([topic0; 32]), Topic([topic1; 32])],
         data: vec![address, topic0, topic1],
         block_num,
         tx_idx,
         log_idx,
-        block_hash: [block_num as u8; 32],
+        block_hash: bench_hash(block_num),
     }
 }
 
@@ -376,6 +380,8 @@ where
             to_block: Some(to_block),
             from_block_hash: None,
             to_block_hash: None,
+            from_timestamp: None,
+            to_timestamp: None,
             order: QueryOrder::Ascending,
             resume_id,
             limit,
@@ -403,9 +409,30 @@ where
         .len()
 }
 
+/// Runs `query_logs_at_block_hashes` over the seeded blocks `block_nums`.
+pub fn block_hash_query_len<A, M, B>(
+    svc: &FinalizedHistoryService<A, M, B>,
+    block_nums: &[u64],
+    filter: LogFilter,
+) -> usize
+where
+    A: WriteAuthority,
+    M: MetaStore,
+    B: BlobStore,
+{
+    let block_hashes = block_nums.iter().copied().map(bench_hash).collect();
+    block_on(svc.query_logs_at_block_hashes(
+        Clause::Or(block_hashes),
+        filter,
+        ExecutionBudget::default(),
+    ))
+    .expect("block hash query")
+    .len()
+}
+
 pub fn contiguous_block_filter() -> LogFilter {
     LogFilter {
-        address: Some(Clause::One([90; 20])),
+        address: Some(Clause::One(Address([90; 20]))),
         topic0: None,
         topic1: None,
         topic2: None,
@@ -415,7 +442,7 @@ pub fn contiguous_block_filter() -> LogFilter {
 
 pub fn non_contiguous_block_filter() -> LogFilter {
     LogFilter {
-        address: Some(Clause::One([91; 20])),
+        address: Some(Clause::One(Address([91; 20]))),
         topic0: None,
         topic1: None,
         topic2: None,
@@ -425,7 +452,7 @@ pub fn non_contiguous_block_filter() -> LogFilter {
 
 pub fn sparse_cross_block_filter() -> LogFilter {
     LogFilter {
-        address: Some(Clause::One([92; 20])),
+        address: Some(Clause::One(Address([92; 20]))),
         topic0: None,
         topic1: None,
         topic2: None,
@@ -435,7 +462,7 @@ pub fn sparse_cross_block_filter() -> LogFilter {
 
 pub fn mixed_page_filter() -> LogFilter {
     LogFilter {
-        address: Some(Clause::One([93; 20])),
+        address: Some(Clause::One(Address([93; 20]))),
         topic0: None,
         topic1: None,
         topic2: None,
@@ -451,13 +478,13 @@ pub fn seed_sparse_cross_block_fixture(svc: &CountingBenchService, blocks: u64)
                 block_num,
                 parent,
                 vec![Log {
-                    address: [92; 20],
-                    topics: vec![[1; 32]],
+                    address: Address([92; 20]),
+                    topics: vec![Topic([1; 32])],
                     data: vec![1],
                     block_num,
                     tx_idx: 0,
                     log_idx: 0,
-                    block_hash: [block_num as u8; 32],
+                    block_hash: bench_hash(block_num),
                 }],
             );
             parent = block.block_hash;
@@ -472,13 +499,13 @@ pub fn seed_contiguous_block_fixture(svc: &CountingBenchService, block_num: u64,
     block_on(async {
         let logs = (0..matches)
             .map(|idx| Log {
-                address: [90; 20],
-                topics: vec![[2; 32]],
+                address: Address([90; 20]),
+                topics: vec![Topic([2; 32])],
                 data: vec![idx as u8],
                 block_num,
                 tx_idx: 0,
                 log_idx: idx,
-                block_hash: [block_num as u8; 32],
+                block_hash: bench_hash(block_num),
             })
             .collect();
         svc.ingest_finalized_block(mk_block(block_num, [0; 32], logs))
@@ -495,13 +522,17 @@ pub fn seed_non_contiguous_block_fixture(
     block_on(async {
         let logs = (0..total_logs)
             .map(|idx| Log {
-                address: if idx % 2 == 0 { [91; 20] } else { [0; 20] },
-                topics: vec![[3; 32]],
+                address: if idx % 2 == 0 {
+                    Address([91; 20])
+                } else {
+                    Address([0; 20])
+                },
+                topics: vec![Topic([3; 32])],
                 data: vec![idx as u8],
                 block_num,
                 tx_idx: 0,
                 log_idx: idx,
-                block_hash: [block_num as u8; 32],
+                block_hash: bench_hash(block_num),
             })
             .collect();
         svc.ingest_finalized_block(mk_block(block_num, [0; 32], logs))
@@ -518,13 +549,13 @@ pub fn seed_mixed_page_fixture(svc: &CountingBenchService) {
             parent,
             (0..16)
                 .map(|idx| Log {
-                    address: [93; 20],
-                    topics: vec![[4; 32]],
+                    address: Address([93; 20]),
+                    topics: vec![Topic([4; 32])],
                     data: vec![idx as u8],
                     block_num: 1,
                     tx_idx: 0,
                     log_idx: idx,
-                    block_hash: [1; 32],
+                    block_hash: bench_hash(1),
                 })
                 .collect(),
         );
@@ -537,13 +568,13 @@ pub fn seed_mixed_page_fixture(svc: &CountingBenchService) {
                 block_num,
                 parent,
                 vec![Log {
-                    address: [93; 20],
-                    topics: vec![[5; 32]],
+                    address: Address([93; 20]),
+                    topics: vec![Topic([5; 32])],
                     data: vec![block_num as u8],
                     block_num,
                     tx_idx: 0,
                     log_idx: 0,
-                    block_hash: [block_num as u8; 32],
+                    block_hash: bench_hash(block_num),
                 }],
             );
             parent = block.block_hash;
@@ -556,9 +587,9 @@ pub fn seed_mixed_page_fixture(svc: &CountingBenchService) {
 
 pub fn narrow_indexed_filter() -> LogFilter {
     LogFilter {
-        address: Some(Clause::One([5; 20])),
-        topic0: Some(Clause::One([5; 32])),
-        topic1: Some(Clause::One([5; 32])),
+        address: Some(Clause::One(Address([5; 20]))),
+        topic0: Some(Clause::One(Topic([5; 32]))),
+        topic1: Some(Clause::One(Topic([5; 32]))),
         topic2: None,
         topic3: None,
     }
@@ -566,8 +597,8 @@ pub fn narrow_indexed_filter() -> LogFilter {
 
 pub fn intersection_filter() -> LogFilter {
     LogFilter {
-        address: Some(Clause::One([11; 20])),
-        topic0: Some(Clause::One([11; 32])),
+        address: Some(Clause::One(Address([11; 20]))),
+        topic0: Some(Clause::One(Topic([11; 32]))),
         topic1: None,
         topic2: None,
         topic3: None,
@@ -575,7 +606,7 @@ pub fn intersection_filter() -> LogFilter {
 }
 
 pub fn wide_or_filter(width: usize) -> LogFilter {
-    let addresses = (0..width).map(|i| [(i % 64) as u8; 20]).collect();
+    let addresses = (0..width).map(|i| Address([(i % 64) as u8; 20])).collect();
     LogFilter {
         address: Some(Clause::Or(addresses)),
         topic0: None,
@@ -587,7 +618,7 @@ pub fn wide_or_filter(width: usize) -> LogFilter {
 
 pub fn pagination_filter() -> LogFilter {
     LogFilter {
-        address: Some(Clause::One([3; 20])),
+        address: Some(Clause::One(Address([3; 20]))),
         topic0: None,
         topic1: None,
         topic2: None,
@@ -595,6 +626,17 @@ pub fn pagination_filter() -> LogFilter {
     }
 }
 
+/// No indexed clause, so queries run as a block scan over every log.
+pub fn block_scan_filter() -> LogFilter {
+    LogFilter {
+        address: None,
+        topic0: Some(Clause::Any),
+        topic1: None,
+        topic2: None,
+        topic3: None,
+    }
+}
+
 pub fn log_id(shard: u64, local: u32) -> LogId {
     compose_log_id(
         LogShard::new(shard).expect("valid log shard"),
@@ -780,7 +822,11 @@ pub fn seed_publication_state(
 }
 
 pub fn stream_for_address(address: [u8; 20], shard: u64) -> String {
-    finalized_history_query::kernel::sharded_streams::sharded_stream_id("addr", &address, shard)
+    finalized_history_query::kernel::sharded_streams::sharded_stream_id(
+        finalized_history_query::StreamKind::Addr,
+        &address,
+        shard,
+    )
 }
 
 pub fn materializer<'a>(
@@ -3,11 +3,12 @@ mod common;
 use criterion::{BatchSize, BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
 
 use crate::common::{
-    build_counting_service, build_service, contiguous_block_filter, intersection_filter,
-    mixed_page_filter, narrow_indexed_filter, non_contiguous_block_filter, pagination_filter,
-    query_len, query_page, seed_contiguous_block_fixture, seed_mixed_page_fixture,
-    seed_non_contiguous_block_fixture, seed_service_blocks, seed_sparse_cross_block_fixture,
-    sparse_cross_block_filter, wide_or_filter,
+    block_hash_query_len, block_scan_filter, build_counting_service, build_service,
+    contiguous_block_filter, intersection_filter, mixed_page_filter, narrow_indexed_filter,
+    non_contiguous_block_filter, pagination_filter, query_len, query_page,
+    seed_contiguous_block_fixture, seed_mixed_page_fixture, seed_non_contiguous_block_fixture,
+    seed_service_blocks, seed_sparse_cross_block_fixture, sparse_cross_block_filter,
+    wide_or_filter,
 };
 
 fn bench_narrow_indexed_queries(c: &mut Criterion) {
@@ -67,6 +68,48 @@ fn bench_pagination_heavy_queries(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_block_scan_queries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query_end_to_end_block_scan");
+    let svc = build_service();
+    seed_service_blocks(&svc, 200, 100);
+
+    group.bench_function("full_range", |b| {
+        let filter = block_scan_filter();
+        b.iter(|| black_box(query_len(&svc, 1, 200, black_box(filter.clone()), 20_000)))
+    });
+
+    group.bench_function("early_exit", |b| {
+        let filter = block_scan_filter();
+        b.iter(|| black_box(query_len(&svc, 1, 200, black_box(filter.clone()), 150)))
+    });
+
+    group.finish();
+}
+
+fn bench_block_hash_queries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query_end_to_end_block_hash");
+    let svc = build_service();
+    seed_service_blocks(&svc, 4, 20_000);
+
+    group.bench_function("dense_single_block", |b| {
+        let filter = block_scan_filter();
+        b.iter(|| black_box(block_hash_query_len(&svc, &[1], black_box(filter.clone()))))
+    });
+
+    group.bench_function("dense_four_blocks", |b| {
+        let filter = block_scan_filter();
+        b.iter(|| {
+            black_box(block_hash_query_len(
+                &svc,
+                &[4, 3, 2, 1],
+                black_box(filter.clone()),
+            ))
+        })
+    });
+
+    group.finish();
+}
+
 fn bench_query_storage_patterns(c: &mut Criterion) {
     let mut group = c.benchmark_group("query_end_to_end_storage_patterns");
 
@@ -239,6 +282,8 @@ criterion_group!(
     bench_narrow_indexed_queries,
     bench_intersections_and_or_queries,
     bench_pagination_heavy_queries,
+    bench_block_scan_queries,
+    bench_block_hash_queries,
     bench_query_storage_patterns
 );
 criterion_main!(benches);
@@ -8,7 +8,7 @@ use finalized_history_query::api::{
     ExecutionBudget, FinalizedHistoryService, QueryLogsRequest, QueryOrder,
 };
 use finalized_history_query::config::Config;
-use finalized_history_query::logs::types::Log;
+use finalized_history_query::logs::types::{Address, Log, Topic};
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::fs::{FsBlobStore, FsMetaStore};
 use finalized_history_query::store::meta::InMemoryMetaStore;
@@ -22,8 +22,8 @@ fn static_observed_finalized_block() -> Option<u64> {
 
 fn mk_log(address: u8, topic0: u8, topic1: u8, block_num: u64, tx_idx: u32, log_idx: u32) -> Log {
     Log {
-        address: [address; 20],
-        topics: vec![[topic0; 32], [topic1; 32]],
+        address: Address([address; 20]),
+        topics: vec![Topic([topic0; 32]), Topic([topic1; 32])],
         data: vec![address, topic0, topic1],
         block_num,
         tx_idx,
@@ -122,8 +122,8 @@ async fn run_queries<A: WriteAuthority, M: MetaStore, B: BlobStore>(
                 blocks.saturating_sub(5_000).max(1),
                 blocks,
                 LogFilter {
-                    address: Some(Clause::One([(i % 64) as u8; 20])),
-                    topic0: Some(Clause::One([(i % 16) as u8; 32])),
+                    address: Some(Clause::One(Address([(i % 64) as u8; 20]))),
+                    topic0: Some(Clause::One(Topic([(i % 16) as u8; 32]))),
                     topic1: None,
                     topic2: None,
                     topic3: None,
@@ -134,14 +134,16 @@ async fn run_queries<A: WriteAuthority, M: MetaStore, B: BlobStore>(
                 blocks,
                 LogFilter {
                     address: None,
-                    topic0: Some(Clause::One([(i % 32) as u8; 32])),
-                    topic1: Some(Clause::One([(i % 64) as u8; 32])),
+                    topic0: Some(Clause::One(Topic([(i % 32) as u8; 32]))),
+                    topic1: Some(Clause::One(Topic([(i % 64) as u8; 32]))),
                     topic2: None,
                     topic3: None,
                 },
             ),
             2 => {
-                let list: Vec<[u8; 20]> = (0..32).map(|v| [((v + i) % 96) as u8; 20]).collect();
+                let list: Vec<Address> = (0..32)
+                    .map(|v| Address([((v + i) % 96) as u8; 20]))
+                    .collect();
                 (
                     blocks.saturating_sub(3_000).max(1),
                     blocks,
@@ -158,7 +160,7 @@ async fn run_queries<A: WriteAuthority, M: MetaStore, B: BlobStore>(
                 blocks.saturating_sub(1_000).max(1),
                 blocks,
                 LogFilter {
-                    address: Some(Clause::One([(i % 128) as u8; 20])),
+                    address: Some(Clause::One(Address([(i % 128) as u8; 20]))),
                     topic0: None,
                     topic1: None,
                     topic2: None,
@@ -174,6 +176,8 @@ async fn run_queries<A: WriteAuthority, M: MetaStore, B: BlobStore>(
                     to_block: Some(to_block),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: None,
                     limit: max_results,
@@ -1,41 +1,220 @@
+use std::pin::pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use futures::{Stream, StreamExt};
+
 use crate::blocks::{Block, BlocksQueryEngine, load_block};
 use crate::config::Config;
+use crate::core::clause::{Clause, has_indexed_value};
 use crate::core::header::{EvmBlockHeader, load_block_header};
+use crate::core::ids::LogId;
 pub use crate::core::page::{QueryOrder, QueryPage, QueryPageMeta};
+use crate::core::range::resolve_block_range;
 pub use crate::core::refs::BlockRef;
+use crate::core::state::BlockRecord;
+use crate::diagnostics;
+pub use crate::diagnostics::{IndexAnomaly, SelfCheckOptions, SelfCheckReport};
 use crate::error::{Error, Result};
 use crate::family::Families;
 use crate::family::FinalizedBlock;
 use crate::ingest::authority::{LeaseAuthority, ReadOnlyAuthority, WriteAuthority};
+use crate::ingest::bitmap_pages::delete_stream_pages;
 use crate::ingest::engine::IngestEngine;
+use crate::ingest::gaps::{GapTracker, contiguous_prefix};
 use crate::kernel::cache::BytesCacheMetrics;
+use crate::kernel::sharded_streams::StreamKind;
+use crate::logs::STREAM_PAGE_LOCAL_ID_SPAN;
+use crate::logs::family::LogsFamily;
 use crate::logs::filter::LogFilter;
+use crate::logs::keys::list_indexed_log_keys;
+pub use crate::logs::keys::{IndexedKeyCursor, LOG_STREAM_KINDS};
 use crate::logs::log_ref::LogRef;
-use crate::logs::materialize::LogMaterializer;
-use crate::query::engine::{FamilyQueryTables, QueryLimits, execute_family_query};
+use crate::logs::materialize::{LogMaterializer, LogPositionMaterializer};
+use crate::logs::types::{Log, LogPosition, Topic};
+use crate::observe::emit_event;
+use crate::query::engine::{
+    FamilyQueryTables, QueryLimits, execute_family_block_counts, execute_family_query,
+};
+use crate::query::explain::explain_family_query;
+pub use crate::query::explain::{ClauseExplain, QueryExplain, ShardExplain};
+use crate::query::normalized::{effective_limit, max_result_bytes};
+use crate::query::prefetch::prefetch_family_query;
+use crate::query::runner::QueryMaterializer;
+use crate::query::window::resolve_primary_window;
 use crate::runtime::Runtime;
-pub use crate::status::ServiceStatus;
-use crate::status::service_status;
+pub use crate::status::{
+    HealthReport, LatencyMetrics, LatencySnapshot, RangeSizeEstimate, ServiceStatus, StoreProbe,
+};
+use crate::status::{LatencyHistogram, estimate_range_bytes, service_health, service_status};
 use crate::store::publication::{MetaPublicationStore, PublicationStore};
 use crate::store::traits::{BlobStore, MetaStore};
+use crate::streams::StreamBitmapMeta;
+use crate::tables::StreamTables;
 use crate::traces::filter::TraceFilter;
 use crate::traces::materialize::TraceMaterializer;
 use crate::traces::view::TraceRef;
 use crate::txs::materialize::TxMaterializer;
 use crate::txs::{TxFilter, TxRef};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Decoded page bitmaps a `query_logs_batch` call keeps when the service has
+/// no stream bitmap cache of its own.
+const QUERY_BATCH_PAGE_BLOB_CACHE_ENTRIES: usize = 1_024;
+
+/// Serializes with block hashes as `0x`-prefixed hex in human-readable
+/// formats. Missing optional fields deserialize as `None`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct IndexedQueryRequest<F> {
     pub from_block: Option<u64>,
     pub to_block: Option<u64>,
+    #[serde(default, with = "crate::core::hex_serde::option")]
     pub from_block_hash: Option<[u8; 32]>,
+    #[serde(default, with = "crate::core::hex_serde::option")]
     pub to_block_hash: Option<[u8; 32]>,
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
     pub order: QueryOrder,
     pub resume_id: Option<u64>,
     pub limit: usize,
     pub filter: F,
 }
 
+impl<F: Default> IndexedQueryRequest<F> {
+    pub fn builder() -> IndexedQueryRequestBuilder<F> {
+        IndexedQueryRequestBuilder::default()
+    }
+}
+
+/// Fluent constructor for [`IndexedQueryRequest`]. Defaults to an ascending,
+/// unlimited request with an unconstrained filter. `build` rejects bound
+/// combinations that the query path would reject.
+#[derive(Debug, Clone)]
+pub struct IndexedQueryRequestBuilder<F> {
+    request: IndexedQueryRequest<F>,
+    block_hash: Option<[u8; 32]>,
+}
+
+impl<F: Default> Default for IndexedQueryRequestBuilder<F> {
+    fn default() -> Self {
+        Self {
+            request: IndexedQueryRequest {
+                from_block: None,
+                to_block: None,
+                from_block_hash: None,
+                to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
+                order: QueryOrder::Ascending,
+                resume_id: None,
+                limit: usize::MAX,
+                filter: F::default(),
+            },
+            block_hash: None,
+        }
+    }
+}
+
+impl<F> IndexedQueryRequestBuilder<F> {
+    pub fn from_block(mut self, block_num: u64) -> Self {
+        self.request.from_block = Some(block_num);
+        self
+    }
+
+    pub fn to_block(mut self, block_num: u64) -> Self {
+        self.request.to_block = Some(block_num);
+        self
+    }
+
+    /// Sets both bounds to one block hash. Cannot be combined with any other
+    /// bound.
+    pub fn block_hash(mut self, block_hash: [u8; 32]) -> Self {
+        self.block_hash = Some(block_hash);
+        self
+    }
+
+    pub fn from_block_hash(mut self, block_hash: [u8; 32]) -> Self {
+        self.request.from_block_hash = Some(block_hash);
+        self
+    }
+
+    pub fn to_block_hash(mut self, block_hash: [u8; 32]) -> Self {
+        self.request.to_block_hash = Some(block_hash);
+        self
+    }
+
+    pub fn from_timestamp(mut self, timestamp: u64) -> Self {
+        self.request.from_timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn to_timestamp(mut self, timestamp: u64) -> Self {
+        self.request.to_timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn order(mut self, order: QueryOrder) -> Self {
+        self.request.order = order;
+        self
+    }
+
+    pub fn resume_id(mut self, resume_id: u64) -> Self {
+        self.request.resume_id = Some(resume_id);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.request.limit = limit;
+        self
+    }
+
+    pub fn filter(mut self, filter: F) -> Self {
+        self.request.filter = filter;
+        self
+    }
+
+    /// Fails with `InvalidParams` when `block_hash` is combined with another
+    /// bound, or when either side has more than one bound.
+    pub fn build(self) -> Result<IndexedQueryRequest<F>> {
+        let mut request = self.request;
+        if let Some(block_hash) = self.block_hash {
+            if request.from_block.is_some()
+                || request.to_block.is_some()
+                || request.from_block_hash.is_some()
+                || request.to_block_hash.is_some()
+                || request.from_timestamp.is_some()
+                || request.to_timestamp.is_some()
+            {
+                return Err(Error::InvalidParams(
+                    "block_hash cannot be combined with other block bounds",
+                ));
+            }
+            request.from_block_hash = Some(block_hash);
+            request.to_block_hash = Some(block_hash);
+        }
+        let from_bounds = [
+            request.from_block.is_some(),
+            request.from_block_hash.is_some(),
+            request.from_timestamp.is_some(),
+        ];
+        if from_bounds.into_iter().filter(|set| *set).count() != 1 {
+            return Err(Error::InvalidParams(
+                "exactly one of from_block, from_block_hash, or from_timestamp is required",
+            ));
+        }
+        let to_bounds = [
+            request.to_block.is_some(),
+            request.to_block_hash.is_some(),
+            request.to_timestamp.is_some(),
+        ];
+        if to_bounds.into_iter().filter(|set| *set).count() != 1 {
+            return Err(Error::InvalidParams(
+                "exactly one of to_block, to_block_hash, or to_timestamp is required",
+            ));
+        }
+        Ok(request)
+    }
+}
+
 pub type QueryLogsRequest = IndexedQueryRequest<LogFilter>;
 pub type QueryTransactionsRequest = IndexedQueryRequest<TxFilter>;
 pub type QueryTracesRequest = IndexedQueryRequest<TraceFilter>;
@@ -60,6 +239,9 @@ pub struct QueryBlocksRequest {
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct ExecutionBudget {
     pub max_results: Option<usize>,
+    /// Caps the summed encoded size of returned items. A page always holds at
+    /// least one item, so an oversized item is still returned on its own.
+    pub max_result_bytes: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -75,8 +257,15 @@ pub struct FinalizedHistoryService<A: WriteAuthority, M: MetaStore, B: BlobStore
     publication_store: MetaPublicationStore<M>,
     blocks_query: BlocksQueryEngine,
     planner_max_or_terms: usize,
+    max_block_hash_terms: usize,
+    max_log_id_range_span: u64,
+    require_topic0: bool,
     pub(crate) runtime: Runtime<M, B>,
     allows_writes: bool,
+    consecutive_backend_errors: AtomicU64,
+    query_latency: LatencyHistogram,
+    ingest_latency: LatencyHistogram,
+    ingest_gaps: GapTracker,
 }
 
 impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M, B> {
@@ -88,17 +277,38 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
         allows_writes: bool,
     ) -> Self {
         let planner_max_or_terms = config.planner_max_or_terms;
+        let max_block_hash_terms = config.max_block_hash_terms;
+        let max_log_id_range_span = config.max_log_id_range_span;
+        let require_topic0 = config.require_topic0;
         let blocks_query = BlocksQueryEngine;
-        let runtime = Runtime::new(meta_store, blob_store, config.bytes_cache);
+        let runtime = Runtime::new(meta_store, blob_store, config.bytes_cache)
+            .with_stream_bitmap_cache(config.stream_bitmap_cache_entries)
+            .with_fast_planning(config.fast_planning)
+            .with_empty_result_fast_path(config.empty_result_fast_path)
+            .with_shard_presence_filter(config.shard_presence_filter);
         let publication_store = MetaPublicationStore::new(runtime.meta_store.clone());
-        let ingest = IngestEngine::new(config, authority, Families::default());
+        let families = Families {
+            logs: LogsFamily {
+                store_data: config.store_log_data,
+                rewriter: config.log_rewriter.clone(),
+            },
+            ..Families::default()
+        };
+        let ingest = IngestEngine::new(config, authority, families);
         Self {
             ingest,
             publication_store,
             blocks_query,
             planner_max_or_terms,
+            max_block_hash_terms,
+            max_log_id_range_span,
+            require_topic0,
             runtime,
             allows_writes,
+            consecutive_backend_errors: AtomicU64::new(0),
+            query_latency: LatencyHistogram::default(),
+            ingest_latency: LatencyHistogram::default(),
+            ingest_gaps: GapTracker::default(),
         }
     }
 
@@ -106,6 +316,15 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
         self.runtime.tables.metrics_snapshot()
     }
 
+    /// Latency percentiles for `query_logs`, `query_transactions`, and
+    /// `query_traces` calls, and for ingest calls, since the service was built.
+    pub fn latency_snapshot(&self) -> LatencyMetrics {
+        LatencyMetrics {
+            query: self.query_latency.snapshot(),
+            ingest: self.ingest_latency.snapshot(),
+        }
+    }
+
     pub fn meta_store(&self) -> &M {
         &self.runtime.meta_store
     }
@@ -138,8 +357,81 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
         request: QueryLogsRequest,
         budget: ExecutionBudget,
     ) -> Result<crate::core::page::QueryPage<LogRef>> {
+        self.query_logs_with_streams(&self.runtime.tables.log_streams, request, budget)
+            .await
+    }
+
+    /// Runs each request like `query_logs`, in order, sharing one decoded
+    /// page bitmap cache across the batch so sealed stream pages that several
+    /// requests touch are fetched and decoded once. Block clipping, exact
+    /// matching, budgets, and errors stay per request.
+    pub async fn query_logs_batch(
+        &self,
+        requests: Vec<QueryLogsRequest>,
+        budget: ExecutionBudget,
+    ) -> Vec<Result<crate::core::page::QueryPage<LogRef>>> {
+        let log_streams = self
+            .runtime
+            .tables
+            .log_streams
+            .with_scoped_page_blob_cache(QUERY_BATCH_PAGE_BLOB_CACHE_ENTRIES);
+        let mut results = Vec::with_capacity(requests.len());
+        for request in requests {
+            results.push(
+                self.query_logs_with_streams(&log_streams, request, budget)
+                    .await,
+            );
+        }
+        results
+    }
+
+    async fn query_logs_with_streams(
+        &self,
+        log_streams: &StreamTables<M, B, StreamBitmapMeta>,
+        request: QueryLogsRequest,
+        budget: ExecutionBudget,
+    ) -> Result<crate::core::page::QueryPage<LogRef>> {
+        self.check_log_filter_policy(&request.filter)?;
+        let started = Instant::now();
         let mut materializer = LogMaterializer::new(&self.runtime.tables);
-        execute_family_query(
+        let result = execute_family_query(
+            FamilyQueryTables {
+                tables: &self.runtime.tables,
+                stream_tables: log_streams,
+            },
+            &self.publication_store,
+            &request,
+            QueryLimits {
+                budget,
+                max_or_terms: self.planner_max_or_terms,
+            },
+            &mut materializer,
+            |record| record.logs,
+        )
+        .await;
+        self.query_latency.record(started.elapsed());
+        result
+    }
+
+    /// Runs a logs request like `query_logs` but returns each match's log ID
+    /// and block position instead of the log, resolved from stream bitmaps
+    /// and the log directory without reading any log payload. The filter
+    /// must be fully indexed (see [`LogFilter::is_fully_indexed`]); negated
+    /// or empty OR clauses need payloads and are rejected.
+    pub async fn query_log_positions(
+        &self,
+        request: QueryLogsRequest,
+        budget: ExecutionBudget,
+    ) -> Result<crate::core::page::QueryPage<LogPosition>> {
+        if !request.filter.is_fully_indexed() {
+            return Err(Error::InvalidParams(
+                "log positions require a fully indexed filter",
+            ));
+        }
+        self.check_log_filter_policy(&request.filter)?;
+        let started = Instant::now();
+        let mut materializer = LogPositionMaterializer::new(&self.runtime.tables);
+        let result = execute_family_query(
             FamilyQueryTables {
                 tables: &self.runtime.tables,
                 stream_tables: &self.runtime.tables.log_streams,
@@ -153,9 +445,302 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
             &mut materializer,
             |record| record.logs,
         )
+        .await;
+        self.query_latency.record(started.elapsed());
+        result
+    }
+
+    /// Reports how `query_logs` would plan `request`: the clipped block range,
+    /// its log-ID window, and each shard's clause order with estimates. Loads
+    /// only block records and stream page metadata.
+    pub async fn explain_logs(&self, request: &QueryLogsRequest) -> Result<QueryExplain> {
+        self.check_log_filter_policy(&request.filter)?;
+        explain_family_query::<_, _, _, _, LogId, _>(
+            FamilyQueryTables {
+                tables: &self.runtime.tables,
+                stream_tables: &self.runtime.tables.log_streams,
+            },
+            &self.publication_store,
+            request,
+            self.planner_max_or_terms,
+            |record| record.logs,
+        )
+        .await
+    }
+
+    /// Estimates how many logs `request` matches from the planner's clause
+    /// estimates, reading only page meta and unsealed fragments. Meant for
+    /// "about N results" displays; see [`QueryExplain::estimated_count`] for
+    /// why it over-estimates. `resume_id` and `limit` are ignored.
+    pub async fn estimate_log_count(&self, request: &QueryLogsRequest) -> Result<u64> {
+        Ok(self.explain_logs(request).await?.estimated_count())
+    }
+
+    /// Warms the shared stream caches for `request` by loading the page meta,
+    /// fragments, and sealed page bitmaps its indexed clauses would read,
+    /// without loading any logs. Returns the number of per-shard clause
+    /// bitmaps loaded. Only useful when the stream bitmap cache or the bitmap
+    /// page byte caches are enabled; otherwise the reads are simply repeated.
+    pub async fn prefetch_logs(&self, request: &QueryLogsRequest) -> Result<u64> {
+        self.check_log_filter_policy(&request.filter)?;
+        prefetch_family_query::<_, _, _, _, LogId, _>(
+            FamilyQueryTables {
+                tables: &self.runtime.tables,
+                stream_tables: &self.runtime.tables.log_streams,
+            },
+            &self.publication_store,
+            request,
+            self.planner_max_or_terms,
+            |record| record.logs,
+        )
+        .await
+    }
+
+    /// Applies deployment policy that is independent of the query plan. With
+    /// `Config::require_topic0`, a filter must pin topic0 to one or more
+    /// signatures.
+    fn check_log_filter_policy(&self, filter: &LogFilter) -> Result<()> {
+        if self.require_topic0 && !has_indexed_value(&filter.topic0) {
+            return Err(Error::InvalidParams("topic0 required"));
+        }
+        Ok(())
+    }
+
+    /// Diagnostics only: returns the sorted local IDs that the log stream
+    /// `stream` (a full sharded stream ID) resolves to, merged from compacted
+    /// pages and by-block fragments. See
+    /// [`diagnostics::resolve_stream_entries`].
+    pub async fn resolve_stream_entries(&self, stream: &str) -> Result<Vec<u32>> {
+        diagnostics::resolve_stream_entries(&self.runtime.tables.log_streams, stream).await
+    }
+
+    /// Audits the whole index: the publication head against the block
+    /// records, the parent-hash chain, each block's log header and blob, and
+    /// every listed log stream page. Returns every anomaly found, bounded by
+    /// `opts`. See [`diagnostics::self_check`].
+    pub async fn self_check(&self, opts: SelfCheckOptions) -> Result<SelfCheckReport> {
+        diagnostics::self_check(&self.runtime.tables, &self.publication_store, &opts).await
+    }
+
+    /// Returns the ascending block numbers in `[from_block, to_block]` that
+    /// contain at least one log with `topic0`, answered from the topic0 stream
+    /// and log directory without loading any log payloads.
+    pub async fn blocks_with_topic0(
+        &self,
+        topic0: Topic,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<u64>> {
+        let filter = LogFilter {
+            topic0: Some(Clause::One(topic0)),
+            ..LogFilter::default()
+        };
+        let mut materializer = LogMaterializer::new(&self.runtime.tables);
+        let block_counts = execute_family_block_counts(
+            FamilyQueryTables {
+                tables: &self.runtime.tables,
+                stream_tables: &self.runtime.tables.log_streams,
+            },
+            &self.publication_store,
+            (from_block, to_block),
+            &filter,
+            self.planner_max_or_terms,
+            &mut materializer,
+            |record| record.logs,
+            false,
+        )
+        .await?;
+        Ok(block_counts
+            .into_iter()
+            .map(|(block_num, _)| block_num)
+            .collect())
+    }
+
+    /// Counts the logs matching `filter` in each block of `[from_block,
+    /// to_block]` in one pass, returning ascending `(block_num, count)` pairs
+    /// for the blocks with at least one match. A fully indexed filter is
+    /// answered from stream bitmaps and the log directory; any other filter
+    /// loads each candidate log for the exact-match pass. The filter must
+    /// carry an indexed clause.
+    pub async fn count_logs_by_block(
+        &self,
+        filter: LogFilter,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<(u64, u64)>> {
+        self.check_log_filter_policy(&filter)?;
+        let mut materializer = LogMaterializer::new(&self.runtime.tables);
+        execute_family_block_counts(
+            FamilyQueryTables {
+                tables: &self.runtime.tables,
+                stream_tables: &self.runtime.tables.log_streams,
+            },
+            &self.publication_store,
+            (from_block, to_block),
+            &filter,
+            self.planner_max_or_terms,
+            &mut materializer,
+            |record| record.logs,
+            !filter.is_fully_indexed(),
+        )
         .await
     }
 
+    /// Returns the logs matching `filter` in the blocks named by
+    /// `block_hashes`, ordered by block number then log index. Each distinct
+    /// hash is resolved through `block_hash_index` and runs a single-block
+    /// indexed query, and the distinct hash count is bounded by
+    /// `max_block_hash_terms`. An unknown hash fails the whole call with
+    /// `InvalidParams`. Blocks are queried in order under what is left of
+    /// `budget`, and the call stops once the budget is spent, so a truncated
+    /// result is a prefix of the full one.
+    pub async fn query_logs_at_block_hashes(
+        &self,
+        block_hashes: Clause<[u8; 32]>,
+        filter: LogFilter,
+        budget: ExecutionBudget,
+    ) -> Result<Vec<LogRef>> {
+        let mut block_hashes = match block_hashes {
+            Clause::Any => {
+                return Err(Error::InvalidParams(
+                    "block_hashes must name at least one block",
+                ));
+            }
+            Clause::One(block_hash) => vec![block_hash],
+            Clause::Or(block_hashes) => block_hashes,
+            Clause::Not(_) => {
+                return Err(Error::InvalidParams("block_hashes cannot be negated"));
+            }
+            Clause::Range { .. } => {
+                return Err(Error::InvalidParams("block_hashes cannot be a range"));
+            }
+        };
+        block_hashes.sort_unstable();
+        block_hashes.dedup();
+        if block_hashes.len() > self.max_block_hash_terms {
+            return Err(Error::QueryTooBroad {
+                actual: block_hashes.len(),
+                max: self.max_block_hash_terms,
+            });
+        }
+        let mut remaining_results = effective_limit(usize::MAX, budget)?;
+        let mut remaining_bytes = max_result_bytes(budget)?;
+
+        // Resolving every hash up front orders the blocks, so a budget that
+        // runs out keeps the lowest blocks, and an unknown hash fails before
+        // any logs are read.
+        let mut blocks = Vec::with_capacity(block_hashes.len());
+        for block_hash in &block_hashes {
+            let Some(block_num) = self
+                .runtime
+                .tables
+                .block_hash_index
+                .get(block_hash)
+                .await?
+            else {
+                return Err(Error::InvalidParams("unknown block hash"));
+            };
+            blocks.push(block_num);
+        }
+        blocks.sort_unstable();
+
+        // Each query covers one block and returns its matches in log order, so
+        // querying the blocks by number orders the result without sorting the
+        // logs themselves.
+        let mut items = Vec::new();
+        for block_num in blocks {
+            let page = self
+                .query_logs(
+                    QueryLogsRequest {
+                        from_block: Some(block_num),
+                        to_block: Some(block_num),
+                        from_block_hash: None,
+                        to_block_hash: None,
+                        from_timestamp: None,
+                        to_timestamp: None,
+                        order: QueryOrder::Ascending,
+                        resume_id: None,
+                        limit: remaining_results,
+                        filter: filter.clone(),
+                    },
+                    ExecutionBudget {
+                        max_results: None,
+                        max_result_bytes: remaining_bytes,
+                    },
+                )
+                .await?;
+            remaining_results -= page.items.len();
+            let page_bytes = page.items.iter().map(LogRef::encoded_len).sum::<usize>();
+            if let Some(bytes) = remaining_bytes.as_mut() {
+                *bytes = bytes.saturating_sub(page_bytes);
+            }
+            items.extend(page.items);
+            if page.meta.has_more || remaining_results == 0 || remaining_bytes == Some(0) {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Loads the logs with IDs in `from_id..=to_id_inclusive`, in ID order,
+    /// returning at most `max`. IDs past the published head are skipped. This
+    /// is an export primitive: no filter is applied and no block range is
+    /// resolved, and the span is bounded by `max_log_id_range_span`.
+    pub async fn logs_by_id_range(
+        &self,
+        from_id: u64,
+        to_id_inclusive: u64,
+        max: usize,
+    ) -> Result<Vec<Log>> {
+        if from_id > to_id_inclusive {
+            return Err(Error::InvalidParams(
+                "from_id must be less than or equal to to_id_inclusive",
+            ));
+        }
+        let span = to_id_inclusive - from_id + 1;
+        if span > self.max_log_id_range_span {
+            return Err(Error::InvalidParams(
+                "log id range exceeds max_log_id_range_span",
+            ));
+        }
+
+        let head = self.indexed_finalized_head().await?;
+        if head == 0 || max == 0 {
+            return Ok(Vec::new());
+        }
+        let block_range = resolve_block_range(
+            &self.runtime.tables,
+            &self.publication_store,
+            1,
+            head,
+            QueryOrder::Ascending,
+        )
+        .await?;
+        let Some(window) = resolve_primary_window::<_, _, LogId, _>(
+            &self.runtime.tables,
+            &block_range,
+            |record| record.logs,
+        )
+        .await?
+        else {
+            return Ok(Vec::new());
+        };
+
+        let start = from_id.max(window.start.get());
+        let end_inclusive = to_id_inclusive.min(window.end_inclusive.get());
+        let mut materializer = LogMaterializer::new(&self.runtime.tables);
+        let mut logs = Vec::new();
+        for id in start..=end_inclusive {
+            if logs.len() >= max {
+                break;
+            }
+            if let Some(log) = materializer.load_by_id(LogId::new(id)).await? {
+                logs.push(log.to_owned_log());
+            }
+        }
+        Ok(logs)
+    }
+
     /// Resolves the finalized block window for a transactions request and
     /// executes the indexed query pipeline, returning a resumable page of
     /// matching transactions.
@@ -164,8 +749,9 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
         request: QueryTransactionsRequest,
         budget: ExecutionBudget,
     ) -> Result<crate::core::page::QueryPage<TxRef>> {
+        let started = Instant::now();
         let mut materializer = TxMaterializer::new(&self.runtime.tables);
-        execute_family_query(
+        let result = execute_family_query(
             FamilyQueryTables {
                 tables: &self.runtime.tables,
                 stream_tables: &self.runtime.tables.tx_streams,
@@ -179,7 +765,9 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
             &mut materializer,
             |record| record.txs,
         )
-        .await
+        .await;
+        self.query_latency.record(started.elapsed());
+        result
     }
 
     /// Resolves the finalized block window for a traces request and executes
@@ -189,8 +777,9 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
         request: QueryTracesRequest,
         budget: ExecutionBudget,
     ) -> Result<crate::core::page::QueryPage<TraceRef>> {
+        let started = Instant::now();
         let mut materializer = TraceMaterializer::new(&self.runtime.tables);
-        execute_family_query(
+        let result = execute_family_query(
             FamilyQueryTables {
                 tables: &self.runtime.tables,
                 stream_tables: &self.runtime.tables.trace_streams,
@@ -204,7 +793,9 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
             &mut materializer,
             |record| record.traces,
         )
-        .await
+        .await;
+        self.query_latency.record(started.elapsed());
+        result
     }
 
     pub async fn get_tx(&self, tx_hash: [u8; 32]) -> Result<Option<TxRef>> {
@@ -226,6 +817,32 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
         load_block_header(&self.runtime.tables, number).await
     }
 
+    pub async fn get_block_record(&self, number: u64) -> Result<Option<BlockRecord>> {
+        self.runtime.tables.block_records.get(number).await
+    }
+
+    /// Resolves `block_hash` through `block_hash_index` and returns the block
+    /// record only if it still carries that hash, so a stale index entry whose
+    /// record was removed or rewritten reads as absent.
+    pub async fn get_block_record_by_hash(
+        &self,
+        block_hash: [u8; 32],
+    ) -> Result<Option<BlockRecord>> {
+        let Some(number) = self
+            .runtime
+            .tables
+            .block_hash_index
+            .get(&block_hash)
+            .await?
+        else {
+            return Ok(None);
+        };
+        Ok(self
+            .get_block_record(number)
+            .await?
+            .filter(|record| record.block_hash == block_hash))
+    }
+
     pub async fn get_tx_receipt(&self, _tx_hash: [u8; 32]) -> Result<Option<TxReceipt>> {
         Err(Error::InvalidParams("get_tx_receipt is not implemented"))
     }
@@ -250,9 +867,107 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
             return Err(reader_only_mode_error());
         }
 
-        self.ingest
+        let started = Instant::now();
+        let result = self
+            .ingest
             .ingest_finalized_blocks(&self.runtime, &blocks)
-            .await
+            .await;
+        self.ingest_latency.record(started.elapsed());
+        match &result {
+            Ok(_) => self.clear_backend_error_streak(),
+            Err(Error::Backend(_)) => {
+                let streak = self
+                    .consecutive_backend_errors
+                    .fetch_add(1, Ordering::Relaxed)
+                    + 1;
+                if streak == 1 {
+                    emit_event!(WARN, "ingest backend errors started");
+                }
+            }
+            Err(error) => self.ingest_gaps.observe(error),
+        }
+        result
+    }
+
+    /// Ingests blocks from `blocks` in stream order, one publication per
+    /// block, until the stream ends. Stops at the first stream or ingest error
+    /// and returns it; blocks before it stay published. Returns the published
+    /// head after the last ingested block.
+    pub async fn run_ingest_loop<S>(&self, blocks: S) -> Result<u64>
+    where
+        S: Stream<Item = Result<FinalizedBlock>>,
+    {
+        let mut blocks = pin!(blocks);
+        let mut indexed_finalized_head = self.indexed_finalized_head().await?;
+        while let Some(block) = blocks.next().await {
+            indexed_finalized_head = self
+                .ingest_finalized_block(block?)
+                .await?
+                .indexed_finalized_head;
+        }
+        Ok(indexed_finalized_head)
+    }
+
+    /// Returns the inclusive block range a rejected out-of-order ingest found
+    /// missing, trimmed to what is still above the published head. Returns
+    /// `None` once ingest has filled it.
+    pub async fn missing_range(&self) -> Result<Option<(u64, u64)>> {
+        let indexed_finalized_head = self.indexed_finalized_head().await?;
+        Ok(self.ingest_gaps.current(indexed_finalized_head))
+    }
+
+    /// Returns the leading run of `blocks` that directly extends the published
+    /// head and can be passed to `ingest_finalized_blocks` now.
+    pub async fn ingestable_prefix<'b>(
+        &self,
+        blocks: &'b [FinalizedBlock],
+    ) -> Result<&'b [FinalizedBlock]> {
+        let indexed_finalized_head = self.indexed_finalized_head().await?;
+        Ok(contiguous_prefix(
+            blocks,
+            indexed_finalized_head.saturating_add(1),
+        ))
+    }
+
+    /// Admin only: removes every page artifact of the log stream `stream` (a
+    /// full sharded stream ID) and returns the number of pages removed. Runs
+    /// inside a write session, so it is fenced by the writer lease and never
+    /// overlaps this service's ingest or page sealing. Log payloads and the
+    /// log directory are untouched; only the index entries go. A stream that
+    /// still receives entries is recreated by the next block that touches
+    /// it. Page artifacts are otherwise immutable, so other processes that
+    /// cached them keep serving the deleted entries until their caches turn
+    /// over.
+    pub async fn delete_stream(&self, stream: &str) -> Result<u64> {
+        if !self.allows_writes {
+            return Err(reader_only_mode_error());
+        }
+        let observe = self.ingest.config.observe_upstream_finalized_block.as_ref();
+        let _session = self.ingest.authority.begin_write(observe()).await?;
+        let deleted = delete_stream_pages(
+            &self.runtime.tables.log_streams,
+            &self.runtime.tables.log_open_bitmap_pages,
+            stream,
+            STREAM_PAGE_LOCAL_ID_SPAN,
+        )
+        .await?;
+        emit_event!(INFO, stream, pages = deleted, "deleted stream");
+        Ok(deleted)
+    }
+
+    /// Lists the distinct values that have a log stream of `kind` (one of
+    /// [`LOG_STREAM_KINDS`]), such as every indexed address, in ascending
+    /// byte order. Returns up to `limit` values and a cursor for the next
+    /// call when the listing stopped at `limit`. Reads stream page blob keys
+    /// and open-page markers only, never bitmaps.
+    pub async fn list_indexed_keys(
+        &self,
+        kind: StreamKind,
+        cursor: Option<IndexedKeyCursor>,
+        limit: usize,
+    ) -> Result<(Vec<Vec<u8>>, Option<IndexedKeyCursor>)> {
+        let next_log_id = self.status().await?.log_state.next_log_id.get();
+        list_indexed_log_keys(&self.runtime.tables, next_log_id, kind, cursor, limit).await
     }
 
     pub async fn indexed_finalized_head(&self) -> Result<u64> {
@@ -270,6 +985,69 @@ impl<A: WriteAuthority, M: MetaStore, B: BlobStore> FinalizedHistoryService<A, M
         )
         .await
     }
+
+    /// Estimates the storage consumed by the logs of
+    /// `from_block..=to_block`, clipped to the indexed finalized head. Reads
+    /// every log payload in the range, so it is an ops primitive for capacity
+    /// planning rather than a serving call. A range holding more than
+    /// `max_log_id_range_span` logs is rejected with `InvalidParams`.
+    pub async fn estimate_range_bytes(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<RangeSizeEstimate> {
+        if from_block > to_block {
+            return Err(Error::InvalidParams(
+                "from_block must be less than or equal to to_block",
+            ));
+        }
+        let head = self.indexed_finalized_head().await?;
+        if from_block > head {
+            return Ok(RangeSizeEstimate::default());
+        }
+        estimate_range_bytes(
+            &self.runtime,
+            from_block,
+            to_block.min(head),
+            self.max_log_id_range_span,
+        )
+        .await
+    }
+
+    /// Reports the published ingest position, writer lease, backend error
+    /// streak, and a timed meta-store probe for operational alerting.
+    pub async fn health_detailed(&self) -> Result<HealthReport> {
+        service_health(
+            &self.runtime,
+            &self.publication_store,
+            &self.ingest.families,
+            self.consecutive_backend_errors.load(Ordering::Relaxed),
+        )
+        .await
+    }
+
+    /// Probes the meta store with a publication-state read and, if it
+    /// succeeds, resets the backend error streak. Returns `false` when the
+    /// probe itself fails with a backend error. Correctness rejections are
+    /// not cleared: a missing range from an out-of-order ingest stays
+    /// reported until ingest fills it.
+    pub async fn try_recover(&self) -> Result<bool> {
+        match self.publication_store.load().await {
+            Ok(_) => {
+                self.clear_backend_error_streak();
+                Ok(true)
+            }
+            Err(Error::Backend(_)) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn clear_backend_error_streak(&self) {
+        let streak = self.consecutive_backend_errors.swap(0, Ordering::Relaxed);
+        if streak > 0 {
+            emit_event!(INFO, streak, "backend error streak cleared");
+        }
+    }
 }
 
 fn reader_only_mode_error() -> Error {
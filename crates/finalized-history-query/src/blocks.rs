@@ -1,6 +1,6 @@
 use crate::api::{ExecutionBudget, QueryBlocksRequest};
 use crate::core::header::{EvmBlockHeader, load_block_header};
-use crate::core::page::{QueryPage, QueryPageMeta};
+use crate::core::page::{QueryOrder, QueryPage, QueryPageMeta};
 use crate::core::range::{ResolvedBlockRange, resolve_block_range};
 use crate::core::refs::BlockRef;
 use crate::error::{Error, Result};
@@ -75,12 +75,12 @@ impl BlocksQueryEngine {
 
         let mut items = Vec::with_capacity(effective_limit.saturating_add(1));
         let take = effective_limit.saturating_add(1);
-        let mut block_num = block_range.from_block;
-        loop {
-            if block_num > block_range.to_block || items.len() >= take {
-                break;
-            }
-
+        let blocks = block_range.from_block..=block_range.to_block;
+        let blocks: Box<dyn Iterator<Item = u64>> = match request.order {
+            QueryOrder::Ascending => Box::new(blocks),
+            QueryOrder::Descending => Box::new(blocks.rev()),
+        };
+        for block_num in blocks.take(take) {
             let Some(header) = load_block_header(tables, block_num).await? else {
                 return Err(Error::NotFound);
             };
@@ -92,11 +92,6 @@ impl BlocksQueryEngine {
                 },
                 header,
             ));
-
-            if block_num == block_range.to_block {
-                break;
-            }
-            block_num = block_num.saturating_add(1);
         }
 
         Ok(build_block_page(block_range, effective_limit, items))
@@ -1,7 +1,10 @@
 use std::fmt;
 use std::sync::Arc;
 
+use crate::core::header::BlockHasher;
+use crate::ingest::recovery::RecoveryOptions;
 use crate::kernel::cache::BytesCacheConfig;
+use crate::logs::ingest::LogRewriter;
 
 #[derive(Clone)]
 pub struct Config {
@@ -9,9 +12,23 @@ pub struct Config {
     pub publication_lease_blocks: u64,
     pub publication_lease_renew_threshold_blocks: u64,
     pub planner_max_or_terms: usize,
+    pub max_block_hash_terms: usize,
+    pub fast_planning: bool,
+    pub empty_result_fast_path: bool,
+    pub shard_presence_filter: bool,
+    pub max_log_id_range_span: u64,
+    pub require_topic0: bool,
     pub assume_empty_streams: bool,
     pub stream_append_concurrency: usize,
+    pub ingest_wal: bool,
+    pub recovery: RecoveryOptions,
+    pub store_log_data: bool,
+    pub log_rewriter: Option<LogRewriter>,
+    pub block_hasher: Option<Arc<dyn BlockHasher>>,
+    pub max_logs_per_block: usize,
+    pub max_block_bytes: usize,
     pub bytes_cache: BytesCacheConfig,
+    pub stream_bitmap_cache_entries: usize,
 }
 
 impl fmt::Debug for Config {
@@ -24,9 +41,32 @@ impl fmt::Debug for Config {
                 &self.publication_lease_renew_threshold_blocks,
             )
             .field("planner_max_or_terms", &self.planner_max_or_terms)
+            .field("max_block_hash_terms", &self.max_block_hash_terms)
+            .field("fast_planning", &self.fast_planning)
+            .field("empty_result_fast_path", &self.empty_result_fast_path)
+            .field("shard_presence_filter", &self.shard_presence_filter)
+            .field("max_log_id_range_span", &self.max_log_id_range_span)
+            .field("require_topic0", &self.require_topic0)
             .field("assume_empty_streams", &self.assume_empty_streams)
             .field("stream_append_concurrency", &self.stream_append_concurrency)
+            .field("ingest_wal", &self.ingest_wal)
+            .field("recovery", &self.recovery)
+            .field("store_log_data", &self.store_log_data)
+            .field(
+                "log_rewriter",
+                &self.log_rewriter.as_ref().map(|_| "<callback>"),
+            )
+            .field(
+                "block_hasher",
+                &self.block_hasher.as_ref().map(|_| "<hasher>"),
+            )
+            .field("max_logs_per_block", &self.max_logs_per_block)
+            .field("max_block_bytes", &self.max_block_bytes)
             .field("bytes_cache", &self.bytes_cache)
+            .field(
+                "stream_bitmap_cache_entries",
+                &self.stream_bitmap_cache_entries,
+            )
             .finish()
     }
 }
@@ -38,9 +78,23 @@ impl Default for Config {
             publication_lease_blocks: 10,
             publication_lease_renew_threshold_blocks: 2,
             planner_max_or_terms: 128,
+            max_block_hash_terms: 128,
+            fast_planning: false,
+            empty_result_fast_path: true,
+            shard_presence_filter: false,
+            max_log_id_range_span: 100_000,
+            require_topic0: false,
             assume_empty_streams: false,
             stream_append_concurrency: 96,
+            ingest_wal: false,
+            recovery: RecoveryOptions::default(),
+            store_log_data: true,
+            log_rewriter: None,
+            block_hasher: None,
+            max_logs_per_block: 1_000_000,
+            max_block_bytes: 256 << 20,
             bytes_cache: BytesCacheConfig::default(),
+            stream_bitmap_cache_entries: 0,
         }
     }
 }
@@ -78,6 +78,13 @@ impl EvmBlockHeader {
     }
 }
 
+/// Recomputes a block hash from the header's contents. The header's own
+/// `hash` field is the claim being checked and must not feed the result. See
+/// `Config::block_hasher`.
+pub trait BlockHasher: Send + Sync {
+    fn hash_header(&self, header: &EvmBlockHeader) -> [u8; 32];
+}
+
 fn serialize_logs_bloom<S>(
     logs_bloom: &[[u8; 64]; 4],
     serializer: S,
@@ -0,0 +1,80 @@
+//! Serde helpers for fixed-size byte arrays: `0x`-prefixed hex in
+//! human-readable formats such as JSON, the bare array elsewhere.
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub(crate) fn serialize<S, const N: usize>(
+    bytes: &[u8; N],
+    serializer: S,
+) -> core::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    [u8; N]: Serialize,
+{
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    } else {
+        bytes.serialize(serializer)
+    }
+}
+
+pub(crate) fn deserialize<'de, D, const N: usize>(
+    deserializer: D,
+) -> core::result::Result<[u8; N], D::Error>
+where
+    D: Deserializer<'de>,
+    [u8; N]: Deserialize<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return <[u8; N]>::deserialize(deserializer);
+    }
+    let value = String::deserialize(deserializer)?;
+    let digits = value
+        .strip_prefix("0x")
+        .ok_or_else(|| D::Error::custom("expected 0x-prefixed hex"))?;
+    let bytes = hex::decode(digits).map_err(D::Error::custom)?;
+    let len = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| D::Error::invalid_length(len, &format!("{N} bytes").as_str()))
+}
+
+/// The same encoding for an optional array; a missing field reads as `None`.
+pub(crate) mod option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Hex<const N: usize>(
+        #[serde(
+            with = "super",
+            bound(
+                serialize = "[u8; N]: Serialize",
+                deserialize = "[u8; N]: Deserialize<'de>"
+            )
+        )]
+        [u8; N],
+    );
+
+    pub(crate) fn serialize<S, const N: usize>(
+        bytes: &Option<[u8; N]>,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        [u8; N]: Serialize,
+    {
+        bytes.map(Hex).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D, const N: usize>(
+        deserializer: D,
+    ) -> core::result::Result<Option<[u8; N]>, D::Error>
+    where
+        D: Deserializer<'de>,
+        [u8; N]: Deserialize<'de>,
+    {
+        Ok(Option::<Hex<N>>::deserialize(deserializer)?.map(|hex| hex.0))
+    }
+}
@@ -320,6 +320,11 @@ impl<I: FamilyIdValue> FamilyIdRange<I> {
         let next_start = id.get().checked_add(1).map(I::new)?;
         Self::new(next_start, self.end_inclusive)
     }
+
+    pub fn resume_strictly_before(&self, id: I) -> Option<Self> {
+        let next_end = id.get().checked_sub(1).map(I::new)?;
+        Self::new(self.start, next_end)
+    }
 }
 
 pub type PrimaryIdRange = FamilyIdRange<LogId>;
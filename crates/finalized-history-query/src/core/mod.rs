@@ -1,6 +1,7 @@
 pub mod directory;
 pub mod directory_resolver;
 pub mod header;
+pub(crate) mod hex_serde;
 pub mod ids;
 pub mod layout;
 pub mod offsets;
@@ -9,7 +10,9 @@ pub mod state;
 pub mod types;
 
 pub mod clause {
-    pub use super::types::{Clause, clause_matches, has_indexed_value, optional_clause_matches};
+    pub use super::types::{
+        Clause, clause_matches, has_indexed_value, has_negated_value, optional_clause_matches,
+    };
 }
 pub mod refs {
     pub use super::types::BlockRef;
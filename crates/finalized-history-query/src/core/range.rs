@@ -38,10 +38,6 @@ pub async fn resolve_block_range<M: MetaStore, P: PublicationStore>(
     to_block: u64,
     order: QueryOrder,
 ) -> Result<ResolvedBlockRange> {
-    match order {
-        QueryOrder::Ascending => {}
-    }
-
     if from_block > to_block {
         return Err(Error::InvalidParams(
             "from_block must be less than or equal to to_block",
@@ -73,12 +69,17 @@ pub async fn resolve_block_range<M: MetaStore, P: PublicationStore>(
         return Ok(ResolvedBlockRange::empty(anchor));
     };
 
+    let examined_endpoint_ref = match order {
+        QueryOrder::Ascending => resolved_to_ref,
+        QueryOrder::Descending => resolved_from_ref,
+    };
+
     Ok(ResolvedBlockRange {
         from_block,
         to_block: clipped_to,
         resolved_from_ref,
         resolved_to_ref,
-        examined_endpoint_ref: resolved_to_ref,
+        examined_endpoint_ref,
     })
 }
 
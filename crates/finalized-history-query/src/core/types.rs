@@ -5,12 +5,75 @@ pub enum Clause<T> {
     Any,
     One(T),
     Or(Vec<T>),
+    /// Matches wherever the inner clause does not. Negation is never
+    /// indexed: it can only post-filter candidates driven by other clauses.
+    Not(Box<Clause<T>>),
+    /// Matches values in `lo..=hi`, compared byte-lexicographically for
+    /// byte newtypes. Ranges are never indexed either: streams are keyed by
+    /// exact value, so a range can only post-filter.
+    Range {
+        lo: T,
+        hi: T,
+    },
+}
+
+/// Serializes as a single-key object: `{"any": true}`, `{"one": v}`,
+/// `{"or": [..]}`, `{"not": clause}`, or `{"range": {"lo": v, "hi": v}}`.
+impl<T: serde::Serialize> serde::Serialize for Clause<T> {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(1))?;
+        match self {
+            Self::Any => map.serialize_entry("any", &true)?,
+            Self::One(value) => map.serialize_entry("one", value)?,
+            Self::Or(values) => map.serialize_entry("or", values)?,
+            Self::Not(inner) => map.serialize_entry("not", inner)?,
+            Self::Range { lo, hi } => {
+                #[derive(serde::Serialize)]
+                struct RangeRepr<'a, T> {
+                    lo: &'a T,
+                    hi: &'a T,
+                }
+                map.serialize_entry("range", &RangeRepr { lo, hi })?
+            }
+        }
+        map.end()
+    }
+}
+
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Clause<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Repr<T> {
+            Any(bool),
+            One(T),
+            Or(Vec<T>),
+            Not(Box<Clause<T>>),
+            Range { lo: T, hi: T },
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Any(true) => Ok(Self::Any),
+            Repr::Any(false) => Err(serde::de::Error::custom("`any` clause must be true")),
+            Repr::One(value) => Ok(Self::One(value)),
+            Repr::Or(values) => Ok(Self::Or(values)),
+            Repr::Not(inner) => Ok(Self::Not(inner)),
+            Repr::Range { lo, hi } => Ok(Self::Range { lo, hi }),
+        }
+    }
 }
 
 impl<T> Clause<T> {
     pub fn or_terms(&self) -> usize {
         match self {
-            Self::Any => 0,
+            Self::Any | Self::Not(_) | Self::Range { .. } => 0,
             Self::One(_) => 1,
             Self::Or(values) => values.len(),
         }
@@ -21,35 +84,49 @@ impl<T> Clause<T> {
         T: Copy + Into<Vec<u8>>,
     {
         match self {
-            Self::Any => Vec::new(),
+            Self::Any | Self::Not(_) | Self::Range { .. } => Vec::new(),
             Self::One(value) => vec![(*value).into()],
             Self::Or(values) => values.iter().copied().map(Into::into).collect(),
         }
     }
-}
-pub fn clause_matches<T: Eq>(actual: &T, clause: &Option<Clause<T>>) -> bool {
-    match clause {
-        None | Some(Clause::Any) => true,
-        Some(Clause::One(value)) => value == actual,
-        Some(Clause::Or(values)) => values.iter().any(|value| value == actual),
+
+    fn matches(&self, actual: Option<&T>) -> bool
+    where
+        T: Ord,
+    {
+        match self {
+            Self::Any => true,
+            Self::One(value) => actual == Some(value),
+            Self::Or(values) => actual.is_some_and(|actual| values.contains(actual)),
+            Self::Not(inner) => !inner.matches(actual),
+            Self::Range { lo, hi } => actual.is_some_and(|actual| lo <= actual && actual <= hi),
+        }
     }
 }
 
-pub fn optional_clause_matches<T: Eq>(actual: Option<T>, clause: &Option<Clause<T>>) -> bool {
-    match clause {
-        None | Some(Clause::Any) => true,
-        Some(Clause::One(value)) => actual.as_ref() == Some(value),
-        Some(Clause::Or(values)) => actual
-            .as_ref()
-            .map(|actual| values.iter().any(|value| value == actual))
-            .unwrap_or(false),
-    }
+pub fn clause_matches<T: Ord>(actual: &T, clause: &Option<Clause<T>>) -> bool {
+    clause
+        .as_ref()
+        .is_none_or(|clause| clause.matches(Some(actual)))
+}
+
+/// Absent values match `Any` and fail `One`, `Or`, and `Range`, so a `Not`
+/// clause matches an absent value.
+pub fn optional_clause_matches<T: Ord>(actual: Option<T>, clause: &Option<Clause<T>>) -> bool {
+    clause
+        .as_ref()
+        .is_none_or(|clause| clause.matches(actual.as_ref()))
 }
 
 pub fn has_indexed_value<T>(clause: &Option<Clause<T>>) -> bool {
     matches!(clause, Some(Clause::One(_) | Clause::Or(_)))
 }
 
+/// True for clauses that can only post-filter: negations and ranges.
+pub fn has_negated_value<T>(clause: &Option<Clause<T>>) -> bool {
+    matches!(clause, Some(Clause::Not(_) | Clause::Range { .. }))
+}
+
 // --- refs ---
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -71,9 +148,13 @@ impl BlockRef {
 
 // --- page ---
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum QueryOrder {
     Ascending,
+    /// Newest first: blocks, primary IDs, and items within a block are all
+    /// visited in reverse, and `resume_id` becomes an exclusive upper bound.
+    Descending,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -0,0 +1,634 @@
+//! Consistency auditing for stream bitmap pages and whole indexes.
+//!
+//! Queries trust `*_bitmap_page_meta` bounds and counts and prefer the
+//! compacted page blob over fragments once a page is sealed. These checks
+//! recompute each page from its `*_bitmap_by_block` fragments and report where
+//! the stored artifacts disagree. [`resolve_stream_entries`] instead returns
+//! exactly what the query path would read. [`self_check`] runs the page audit
+//! over every listed log stream page, together with block-level checks of the
+//! publication head, the parent-hash chain, and the log payload layout.
+
+use roaring::RoaringBitmap;
+
+use crate::core::ids::LogId;
+use crate::core::layout::MAX_LOCAL_ID;
+use crate::core::state::BlockRecord;
+use crate::error::{Error, Result};
+use crate::kernel::sharded_streams::{page_start_local, split_stream_page_key};
+use crate::logs::STREAM_PAGE_LOCAL_ID_SPAN;
+use crate::query::bitmap::load_stream_entries;
+use crate::store::publication::PublicationStore;
+use crate::store::traits::{BlobStore, MetaStore};
+use crate::streams::{StreamBitmapMeta, decode_bitmap_blob};
+use crate::tables::{StreamTables, Tables};
+
+/// A disagreement between a page's stored artifacts and its fragments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamMismatch {
+    /// `count` in the page meta differs from the union of the page's fragments.
+    MetaCountDrift {
+        page_start: u32,
+        meta_count: u32,
+        fragment_count: u64,
+    },
+    /// `min_local`/`max_local` in the page meta differ from the fragments.
+    MetaBoundsDrift {
+        page_start: u32,
+        meta_bounds: (u32, u32),
+        fragment_bounds: (u32, u32),
+    },
+    /// The page is sealed but its compacted blob is absent.
+    MissingPageBlob { page_start: u32 },
+    /// The compacted blob's header count differs from its bitmap length.
+    PageBlobCountMismatch {
+        page_start: u32,
+        header_count: u32,
+        bitmap_count: u64,
+    },
+    /// The compacted blob does not hold the same locals as the fragments.
+    PageBlobDiverges {
+        page_start: u32,
+        blob_count: u64,
+        fragment_count: u64,
+    },
+    /// A fragment's header count differs from its bitmap length.
+    FragmentCountMismatch {
+        page_start: u32,
+        header_count: u32,
+        bitmap_count: u64,
+    },
+    /// Locals recorded by more than one fragment of the same page.
+    DuplicateLocals { page_start: u32, count: u64 },
+    /// Locals stored under a page whose span does not contain them.
+    LocalsOutsidePage { page_start: u32, count: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamHealth {
+    pub stream_id: String,
+    pub sealed_pages: u64,
+    pub open_pages: u64,
+    pub entries: u64,
+    pub mismatches: Vec<StreamMismatch>,
+}
+
+impl StreamHealth {
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Audits every page of `stream` overlapping `[local_from, local_to]`.
+/// Decode failures are returned as errors; everything else is reported in
+/// [`StreamHealth::mismatches`].
+pub async fn verify_stream<M: MetaStore, B: BlobStore>(
+    stream_tables: &StreamTables<M, B, StreamBitmapMeta>,
+    stream: &str,
+    local_from: u32,
+    local_to: u32,
+) -> Result<StreamHealth> {
+    let mut health = StreamHealth {
+        stream_id: stream.to_owned(),
+        sealed_pages: 0,
+        open_pages: 0,
+        entries: 0,
+        mismatches: Vec::new(),
+    };
+    let mut page_start = page_start_local(local_from, STREAM_PAGE_LOCAL_ID_SPAN);
+    let last_page_start = page_start_local(local_to, STREAM_PAGE_LOCAL_ID_SPAN);
+
+    loop {
+        verify_page(stream_tables, stream, page_start, &mut health).await?;
+        if page_start >= last_page_start {
+            break;
+        }
+        let Some(next) = page_start.checked_add(STREAM_PAGE_LOCAL_ID_SPAN) else {
+            break;
+        };
+        page_start = next;
+    }
+
+    Ok(health)
+}
+
+/// Returns every local ID that `stream` resolves to for queries, ascending.
+/// Uses the same page-blob-or-fragments loading as query execution over the
+/// whole shard, so it visits every page of the stream's shard and is meant
+/// for debugging, not serving.
+pub async fn resolve_stream_entries<M: MetaStore, B: BlobStore>(
+    stream_tables: &StreamTables<M, B, StreamBitmapMeta>,
+    stream: &str,
+) -> Result<Vec<u32>> {
+    Ok(load_stream_entries(stream_tables, stream, 0, MAX_LOCAL_ID)
+        .await?
+        .into_iter()
+        .collect())
+}
+
+/// Bounds for [`self_check`]. The defaults check every block from 1 to the
+/// published head and every listed stream page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfCheckOptions {
+    pub from_block: u64,
+    pub max_blocks: Option<u64>,
+    pub max_stream_pages: Option<u64>,
+}
+
+impl Default for SelfCheckOptions {
+    fn default() -> Self {
+        Self {
+            from_block: 1,
+            max_blocks: None,
+            max_stream_pages: None,
+        }
+    }
+}
+
+/// An inconsistency found by [`self_check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexAnomaly {
+    /// The published head has no block record.
+    MissingHeadRecord { head: u64 },
+    /// A block record exists above the published head.
+    RecordBeyondHead { head: u64, block_num: u64 },
+    /// A block at or below the head has no record.
+    MissingBlockRecord { block_num: u64 },
+    /// A block's `parent_hash` is not the previous block's hash.
+    ParentHashMismatch {
+        block_num: u64,
+        parent_hash: [u8; 32],
+        previous_hash: [u8; 32],
+    },
+    /// A block's log window does not start where the previous one ended.
+    LogWindowGap {
+        block_num: u64,
+        expected_first: u64,
+        actual_first: u64,
+    },
+    /// A block with logs has no log header.
+    MissingLogHeader { block_num: u64 },
+    /// The log header and the block record disagree on the log count.
+    LogCountMismatch {
+        block_num: u64,
+        record_count: u32,
+        header_count: u64,
+    },
+    /// A block with logs has no log blob.
+    MissingLogBlob { block_num: u64 },
+    /// A log's header offsets decrease or reach past the end of the blob.
+    LogSpanOutsideBlob {
+        block_num: u64,
+        log_ordinal: u64,
+        end_offset: u64,
+        blob_len: u64,
+    },
+    /// A stored artifact failed to decode.
+    Undecodable {
+        artifact: String,
+        reason: &'static str,
+    },
+    /// A stream page disagrees with its fragments; see [`verify_stream`].
+    Stream {
+        stream_id: String,
+        mismatch: StreamMismatch,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfCheckReport {
+    pub indexed_finalized_head: u64,
+    pub blocks_checked: u64,
+    pub stream_pages_checked: u64,
+    /// Whether a bound in [`SelfCheckOptions`] stopped a scan early.
+    pub truncated: bool,
+    pub anomalies: Vec<IndexAnomaly>,
+}
+
+impl SelfCheckReport {
+    pub fn is_clean(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+const SELF_CHECK_LIST_BATCH: usize = 1_024;
+
+/// Audits a whole index and reports every anomaly found. The publication
+/// state must decode; any other decode failure is reported as
+/// [`IndexAnomaly::Undecodable`] and the scan moves on. Block checks cover
+/// `from_block` up to the published head. Stream checks cover every sealed
+/// log stream page, found by listing page blobs, and every page with an
+/// open-page marker. Store errors other than decode failures abort the check.
+pub async fn self_check<M: MetaStore, B: BlobStore, P: PublicationStore>(
+    tables: &Tables<M, B>,
+    publication_store: &P,
+    opts: &SelfCheckOptions,
+) -> Result<SelfCheckReport> {
+    let head = publication_store
+        .load_finalized_head_state()
+        .await?
+        .indexed_finalized_head;
+    let mut report = SelfCheckReport {
+        indexed_finalized_head: head,
+        blocks_checked: 0,
+        stream_pages_checked: 0,
+        truncated: false,
+        anomalies: Vec::new(),
+    };
+
+    let head_record = match checked(
+        tables.block_records.get(head).await,
+        || format!("block_record {head}"),
+        &mut report,
+    )? {
+        Loaded::Present(record) => Some(record),
+        Loaded::Absent if head > 0 => {
+            report
+                .anomalies
+                .push(IndexAnomaly::MissingHeadRecord { head });
+            None
+        }
+        Loaded::Absent | Loaded::Undecodable => None,
+    };
+    if let Some(above_head) = head.checked_add(1)
+        && !matches!(
+            checked(
+                tables.block_records.get(above_head).await,
+                || format!("block_record {above_head}"),
+                &mut report,
+            )?,
+            Loaded::Absent
+        )
+    {
+        report.anomalies.push(IndexAnomaly::RecordBeyondHead {
+            head,
+            block_num: above_head,
+        });
+    }
+
+    check_blocks(tables, head, opts, &mut report).await?;
+
+    let next_log_id = head_record
+        .and_then(|record| record.logs)
+        .map_or(0, |window| {
+            window.first_primary_id + u64::from(window.count)
+        });
+    check_stream_pages(tables, next_log_id, opts, &mut report).await?;
+    Ok(report)
+}
+
+async fn check_blocks<M: MetaStore, B: BlobStore>(
+    tables: &Tables<M, B>,
+    head: u64,
+    opts: &SelfCheckOptions,
+    report: &mut SelfCheckReport,
+) -> Result<()> {
+    let from_block = opts.from_block.max(1);
+    if from_block > head {
+        return Ok(());
+    }
+    let mut to_block = head;
+    if let Some(max_blocks) = opts.max_blocks {
+        let last = from_block.saturating_add(max_blocks.saturating_sub(1));
+        if max_blocks == 0 || last < head {
+            report.truncated = true;
+            if max_blocks == 0 {
+                return Ok(());
+            }
+            to_block = last;
+        }
+    }
+
+    // The block below the range only anchors the first link check, so its
+    // own problems are left to a scan that covers it.
+    let mut previous = match from_block {
+        1 => None,
+        _ => match tables.block_records.get(from_block - 1).await {
+            Ok(record) => record,
+            Err(Error::Decode(_)) => None,
+            Err(err) => return Err(err),
+        },
+    };
+    for block_num in from_block..=to_block {
+        report.blocks_checked += 1;
+        let record = match checked(
+            tables.block_records.get(block_num).await,
+            || format!("block_record {block_num}"),
+            report,
+        )? {
+            Loaded::Present(record) => record,
+            Loaded::Absent => {
+                // A missing head record is already `MissingHeadRecord`.
+                if block_num != head {
+                    report
+                        .anomalies
+                        .push(IndexAnomaly::MissingBlockRecord { block_num });
+                }
+                previous = None;
+                continue;
+            }
+            Loaded::Undecodable => {
+                previous = None;
+                continue;
+            }
+        };
+        if let Some(previous) = &previous {
+            check_block_links(block_num, previous, &record, report);
+        }
+        check_block_logs(tables, block_num, &record, report).await?;
+        previous = Some(record);
+    }
+    Ok(())
+}
+
+fn check_block_links(
+    block_num: u64,
+    previous: &BlockRecord,
+    record: &BlockRecord,
+    report: &mut SelfCheckReport,
+) {
+    if record.parent_hash != previous.block_hash {
+        report.anomalies.push(IndexAnomaly::ParentHashMismatch {
+            block_num,
+            parent_hash: record.parent_hash,
+            previous_hash: previous.block_hash,
+        });
+    }
+    if let (Some(previous_logs), Some(logs)) = (previous.logs, record.logs) {
+        let expected_first = previous_logs.first_primary_id + u64::from(previous_logs.count);
+        if logs.first_primary_id != expected_first {
+            report.anomalies.push(IndexAnomaly::LogWindowGap {
+                block_num,
+                expected_first,
+                actual_first: logs.first_primary_id,
+            });
+        }
+    }
+}
+
+async fn check_block_logs<M: MetaStore, B: BlobStore>(
+    tables: &Tables<M, B>,
+    block_num: u64,
+    record: &BlockRecord,
+    report: &mut SelfCheckReport,
+) -> Result<()> {
+    let Some(window) = record.logs.filter(|window| window.count > 0) else {
+        return Ok(());
+    };
+    let header = match checked(
+        tables.log_block_headers.get(block_num).await,
+        || format!("block_log_header {block_num}"),
+        report,
+    )? {
+        Loaded::Present(header) => header,
+        Loaded::Absent => {
+            report
+                .anomalies
+                .push(IndexAnomaly::MissingLogHeader { block_num });
+            return Ok(());
+        }
+        Loaded::Undecodable => return Ok(()),
+    };
+    let header_count = header.log_count() as u64;
+    if header_count != u64::from(window.count) {
+        report.anomalies.push(IndexAnomaly::LogCountMismatch {
+            block_num,
+            record_count: window.count,
+            header_count,
+        });
+    }
+    let Some(blob_len) = tables.log_block_blobs.blob_len(block_num).await? else {
+        report
+            .anomalies
+            .push(IndexAnomaly::MissingLogBlob { block_num });
+        return Ok(());
+    };
+    let mut start = header.offsets.get(0).unwrap_or(0);
+    for log_ordinal in 0..header_count {
+        let end = header
+            .offsets
+            .get(log_ordinal as usize + 1)
+            .unwrap_or(start);
+        if end < start || end > blob_len {
+            report.anomalies.push(IndexAnomaly::LogSpanOutsideBlob {
+                block_num,
+                log_ordinal,
+                end_offset: end,
+                blob_len,
+            });
+        }
+        start = start.max(end);
+    }
+    Ok(())
+}
+
+async fn check_stream_pages<M: MetaStore, B: BlobStore>(
+    tables: &Tables<M, B>,
+    next_log_id: u64,
+    opts: &SelfCheckOptions,
+    report: &mut SelfCheckReport,
+) -> Result<()> {
+    let max_pages = opts.max_stream_pages.unwrap_or(u64::MAX);
+    let mut cursor = None;
+    loop {
+        let page = tables
+            .log_streams
+            .list_page_blob_keys(b"", cursor.take(), SELF_CHECK_LIST_BATCH)
+            .await?;
+        for key in page.keys {
+            let Some((stream, page_start)) = split_stream_page_key(&key) else {
+                report.anomalies.push(IndexAnomaly::Undecodable {
+                    artifact: format!("log_bitmap_page_blob {key:02x?}"),
+                    reason: "invalid stream page blob key",
+                });
+                continue;
+            };
+            if report.stream_pages_checked >= max_pages {
+                report.truncated = true;
+                return Ok(());
+            }
+            check_stream_page(&tables.log_streams, stream, page_start, report).await?;
+        }
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    if next_log_id == 0 {
+        return Ok(());
+    }
+    let frontier_shard = LogId::new(next_log_id - 1).shard().get();
+    for shard in 0..=frontier_shard {
+        for open_page in tables.log_open_bitmap_pages.list_for_shard(shard).await? {
+            if report.stream_pages_checked >= max_pages {
+                report.truncated = true;
+                return Ok(());
+            }
+            check_stream_page(
+                &tables.log_streams,
+                &open_page.stream_id,
+                open_page.page_start_local,
+                report,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn check_stream_page<M: MetaStore, B: BlobStore>(
+    stream_tables: &StreamTables<M, B, StreamBitmapMeta>,
+    stream: &str,
+    page_start: u32,
+    report: &mut SelfCheckReport,
+) -> Result<()> {
+    report.stream_pages_checked += 1;
+    let mut health = StreamHealth {
+        stream_id: stream.to_owned(),
+        sealed_pages: 0,
+        open_pages: 0,
+        entries: 0,
+        mismatches: Vec::new(),
+    };
+    match verify_page(stream_tables, stream, page_start, &mut health).await {
+        Ok(()) => {}
+        Err(Error::Decode(reason)) => report.anomalies.push(IndexAnomaly::Undecodable {
+            artifact: format!("{stream} page {page_start}"),
+            reason,
+        }),
+        Err(err) => return Err(err),
+    }
+    report.anomalies.extend(
+        health
+            .mismatches
+            .into_iter()
+            .map(|mismatch| IndexAnomaly::Stream {
+                stream_id: stream.to_owned(),
+                mismatch,
+            }),
+    );
+    Ok(())
+}
+
+enum Loaded<T> {
+    Present(T),
+    Absent,
+    Undecodable,
+}
+
+/// Reports a decode failure as an anomaly so the scan can continue. Other
+/// errors are returned.
+fn checked<T>(
+    result: Result<Option<T>>,
+    artifact: impl FnOnce() -> String,
+    report: &mut SelfCheckReport,
+) -> Result<Loaded<T>> {
+    match result {
+        Ok(Some(value)) => Ok(Loaded::Present(value)),
+        Ok(None) => Ok(Loaded::Absent),
+        Err(Error::Decode(reason)) => {
+            report.anomalies.push(IndexAnomaly::Undecodable {
+                artifact: artifact(),
+                reason,
+            });
+            Ok(Loaded::Undecodable)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+async fn verify_page<M: MetaStore, B: BlobStore>(
+    stream_tables: &StreamTables<M, B, StreamBitmapMeta>,
+    stream: &str,
+    page_start: u32,
+    health: &mut StreamHealth,
+) -> Result<()> {
+    let mut merged = RoaringBitmap::new();
+    let mut duplicates = 0u64;
+    for bytes in stream_tables
+        .load_page_fragments(stream, page_start)
+        .await?
+    {
+        let fragment = decode_bitmap_blob(&bytes)?;
+        if u64::from(fragment.count) != fragment.bitmap.len() {
+            health
+                .mismatches
+                .push(StreamMismatch::FragmentCountMismatch {
+                    page_start,
+                    header_count: fragment.count,
+                    bitmap_count: fragment.bitmap.len(),
+                });
+        }
+        duplicates += merged.intersection_len(&fragment.bitmap);
+        merged |= &fragment.bitmap;
+    }
+    if duplicates > 0 {
+        health.mismatches.push(StreamMismatch::DuplicateLocals {
+            page_start,
+            count: duplicates,
+        });
+    }
+    let page_end = u64::from(page_start) + u64::from(STREAM_PAGE_LOCAL_ID_SPAN);
+    let outside = merged
+        .iter()
+        .filter(|local| *local < page_start || u64::from(*local) >= page_end)
+        .count() as u64;
+    if outside > 0 {
+        health.mismatches.push(StreamMismatch::LocalsOutsidePage {
+            page_start,
+            count: outside,
+        });
+    }
+
+    let Some(meta) = stream_tables.get_page_meta(stream, page_start).await? else {
+        if !merged.is_empty() {
+            health.open_pages += 1;
+            health.entries += merged.len();
+        }
+        return Ok(());
+    };
+    health.sealed_pages += 1;
+    health.entries += merged.len();
+
+    if u64::from(meta.count) != merged.len() {
+        health.mismatches.push(StreamMismatch::MetaCountDrift {
+            page_start,
+            meta_count: meta.count,
+            fragment_count: merged.len(),
+        });
+    }
+    if let (Some(min_local), Some(max_local)) = (merged.min(), merged.max())
+        && (meta.min_local, meta.max_local) != (min_local, max_local)
+    {
+        health.mismatches.push(StreamMismatch::MetaBoundsDrift {
+            page_start,
+            meta_bounds: (meta.min_local, meta.max_local),
+            fragment_bounds: (min_local, max_local),
+        });
+    }
+
+    let Some(bytes) = stream_tables.get_page_blob(stream, page_start).await? else {
+        health
+            .mismatches
+            .push(StreamMismatch::MissingPageBlob { page_start });
+        return Ok(());
+    };
+    let page_blob = decode_bitmap_blob(&bytes)?;
+    if u64::from(page_blob.count) != page_blob.bitmap.len() {
+        health
+            .mismatches
+            .push(StreamMismatch::PageBlobCountMismatch {
+                page_start,
+                header_count: page_blob.count,
+                bitmap_count: page_blob.bitmap.len(),
+            });
+    }
+    if page_blob.bitmap != merged {
+        health.mismatches.push(StreamMismatch::PageBlobDiverges {
+            page_start,
+            blob_count: page_blob.bitmap.len(),
+            fragment_count: merged.len(),
+        });
+    }
+    Ok(())
+}
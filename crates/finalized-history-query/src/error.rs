@@ -16,20 +16,24 @@ pub enum Error {
     LeaseLost,
     #[error("invalid finalized sequence: expected {expected}, got {got}")]
     InvalidSequence { expected: u64, got: u64 },
-    #[error("invalid parent linkage")]
-    InvalidParent,
+    #[error("invalid parent linkage at batch index {index}")]
+    InvalidParent { index: usize },
     #[error("finality violation")]
     FinalityViolation,
     #[error("invalid params: {0}")]
     InvalidParams(&'static str),
     #[error("decode error: {0}")]
     Decode(&'static str),
+    #[error("codec error: {0}")]
+    Codec(String),
     #[error("backend error: {0}")]
     Backend(String),
     #[error("unsupported: {0}")]
     Unsupported(&'static str),
     #[error("query too broad: clause has {actual} OR terms, max allowed is {max}")]
     QueryTooBroad { actual: usize, max: usize },
+    #[error("query too broad: negated and range clauses need an indexed clause to drive the scan")]
+    NegationWithoutIndexedClause,
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
@@ -21,6 +21,24 @@ pub struct FinalizedBlock {
     pub trace_rlp: Vec<u8>,
 }
 
+impl FinalizedBlock {
+    /// Approximate in-memory payload size: each log's address, topics, and
+    /// data, each transaction's hash, sender, and signed bytes, and the trace
+    /// RLP. Ignores fixed per-item overhead, so it is a floor on what ingest
+    /// holds for the block.
+    pub fn estimated_bytes(&self) -> usize {
+        let logs = self.logs.iter().fold(0usize, |total, log| {
+            total
+                .saturating_add(20 + 32 * log.topics.len())
+                .saturating_add(log.data.len())
+        });
+        let txs = self.txs.iter().fold(0usize, |total, tx| {
+            total.saturating_add(52 + tx.signed_tx_bytes.len())
+        });
+        logs.saturating_add(txs).saturating_add(self.trace_rlp.len())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FamilyStates {
     pub logs: LogSequencingState,
@@ -43,7 +61,7 @@ impl core::ops::AddAssign for FamilyBlockWrites {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Families {
     pub logs: LogsFamily,
     pub txs: TxsFamily,
@@ -76,11 +94,27 @@ impl Families {
 
     /// Runs the shared per-block ingest envelope around the family adapters by
     /// updating block indexes first and persisting the block record last.
+    /// `stream_append_concurrency` bounds each family's in-flight stream
+    /// fragment writes.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            name = "ingest_block",
+            skip_all,
+            fields(
+                block_num = block.block_num,
+                logs = block.logs.len(),
+                stream_count = tracing::field::Empty,
+            ),
+        )
+    )]
     pub async fn ingest_block<M, B>(
         &self,
         runtime: &Runtime<M, B>,
         states: &mut FamilyStates,
         block: &FinalizedBlock,
+        stream_append_concurrency: usize,
     ) -> Result<FamilyBlockWrites>
     where
         M: MetaStore,
@@ -104,15 +138,20 @@ impl Families {
         let writes = FamilyBlockWrites {
             logs: self
                 .logs
-                .ingest_block(runtime, &mut states.logs, block)
+                .ingest_block(runtime, &mut states.logs, block, stream_append_concurrency)
                 .await?,
             txs: self
                 .txs
-                .ingest_block(runtime, &mut states.txs, block)
+                .ingest_block(runtime, &mut states.txs, block, stream_append_concurrency)
                 .await?,
             traces: self
                 .traces
-                .ingest_block(runtime, &mut states.traces, block)
+                .ingest_block(
+                    runtime,
+                    &mut states.traces,
+                    block,
+                    stream_append_concurrency,
+                )
                 .await?,
         };
 
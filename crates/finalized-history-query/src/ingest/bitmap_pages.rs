@@ -1,13 +1,24 @@
 use std::collections::BTreeSet;
 
+use bytes::Bytes;
+use futures::stream::{FuturesUnordered, StreamExt};
 use roaring::RoaringBitmap;
 
-use crate::error::Result;
-use crate::kernel::sharded_streams::{compacted_bitmap_blob, group_stream_values_into_pages};
+use crate::core::layout::MAX_LOCAL_ID;
+use crate::error::{Error, Result};
+use crate::ingest::open_pages::OpenBitmapPage;
+use crate::kernel::sharded_streams::{
+    compacted_bitmap_blob, group_stream_values_into_pages, parse_stream_shard,
+};
+use crate::observe::emit_event;
 use crate::store::traits::{BlobStore, MetaStore};
 use crate::streams::{decode_bitmap_blob, encode_bitmap_blob};
-use crate::tables::StreamTables;
+use crate::tables::{OpenBitmapPageTable, StreamTables};
 
+/// Writes one block's stream fragments, plus each sharded stream's
+/// shard-presence row, with at most `concurrency` puts in flight. Streams are
+/// independent keys, so completion order does not matter; the first failed put
+/// aborts the remaining writes.
 pub async fn persist_stream_fragments<
     M: MetaStore,
     B: BlobStore,
@@ -17,30 +28,68 @@ pub async fn persist_stream_fragments<
     block_num: u64,
     grouped_values: impl IntoIterator<Item = (String, u32)>,
     page_span: u32,
+    concurrency: usize,
 ) -> Result<Vec<(String, u32)>> {
+    let concurrency = concurrency.max(1);
     let mut touched_pages = BTreeSet::<(String, u32)>::new();
+    let mut in_flight = FuturesUnordered::new();
 
     for (stream, pages) in group_stream_values_into_pages(grouped_values, page_span) {
+        let mut presence_pending = parse_stream_shard(&stream).is_some();
         for (page_start, bitmap) in pages {
             let Some((_count, bitmap_blob)) = compacted_bitmap_blob(bitmap, page_start) else {
                 continue;
             };
 
-            tables
-                .put_fragment(
-                    &stream,
-                    page_start,
-                    block_num,
-                    encode_bitmap_blob(&bitmap_blob)?,
-                )
-                .await?;
+            let bytes = encode_bitmap_blob(&bitmap_blob)?;
             touched_pages.insert((stream.clone(), page_start));
+            if presence_pending {
+                presence_pending = false;
+                in_flight.push(write_stream_entry(tables, stream.clone(), block_num, None));
+            }
+            in_flight.push(write_stream_entry(
+                tables,
+                stream.clone(),
+                block_num,
+                Some((page_start, bytes)),
+            ));
+            while in_flight.len() >= concurrency
+                && let Some(result) = in_flight.next().await
+            {
+                result?;
+            }
         }
     }
 
+    while let Some(result) = in_flight.next().await {
+        result?;
+    }
+
     Ok(touched_pages.into_iter().collect())
 }
 
+/// Puts one page fragment of `stream`, or its shard-presence row when
+/// `fragment` is `None`.
+async fn write_stream_entry<
+    M: MetaStore,
+    B: BlobStore,
+    T: crate::kernel::codec::StorageCodec,
+>(
+    tables: &StreamTables<M, B, T>,
+    stream: String,
+    block_num: u64,
+    fragment: Option<(u32, Bytes)>,
+) -> Result<()> {
+    match fragment {
+        Some((page_start, bytes)) => {
+            tables
+                .put_fragment(&stream, page_start, block_num, bytes)
+                .await
+        }
+        None => tables.mark_shard_presence(&stream).await,
+    }
+}
+
 pub async fn compact_stream_page<
     M: MetaStore,
     B: BlobStore,
@@ -49,7 +98,7 @@ pub async fn compact_stream_page<
     tables: &StreamTables<M, B, T>,
     stream_id: &str,
     page_start: u32,
-    make_meta: impl Fn(u32, u32, u32) -> T,
+    make_meta: impl Fn(u32, u32, u32, u64) -> T,
 ) -> Result<bool> {
     let mut merged = RoaringBitmap::new();
     for bytes in tables.load_page_fragments(stream_id, page_start).await? {
@@ -62,11 +111,54 @@ pub async fn compact_stream_page<
     let Some((count, bitmap_blob)) = compacted_bitmap_blob(merged, page_start) else {
         return Ok(false);
     };
-    let meta = make_meta(count, bitmap_blob.min_local, bitmap_blob.max_local);
+    let page_blob = encode_bitmap_blob(&bitmap_blob)?;
+    let meta = make_meta(
+        count,
+        bitmap_blob.min_local,
+        bitmap_blob.max_local,
+        page_blob.len() as u64,
+    );
 
     tables
-        .put_page_blob(stream_id, page_start, encode_bitmap_blob(&bitmap_blob)?)
+        .put_page_blob(stream_id, page_start, page_blob)
         .await?;
     tables.put_page_meta(stream_id, page_start, &meta).await?;
     Ok(true)
 }
+
+/// Removes every page of `stream` in its shard: page meta, page blob,
+/// by-block fragments, and open-page marker, then the stream's shard-presence
+/// record. Returns the number of pages that
+/// held any artifact. Visits every page span of the shard, so it is an admin
+/// operation, not a serving path. The caller must exclude concurrent ingest,
+/// or a block being ingested could write new fragments behind the delete.
+pub async fn delete_stream_pages<
+    M: MetaStore,
+    B: BlobStore,
+    T: crate::kernel::codec::StorageCodec,
+>(
+    tables: &StreamTables<M, B, T>,
+    open_pages: &OpenBitmapPageTable<M>,
+    stream: &str,
+    page_span: u32,
+) -> Result<u64> {
+    let shard =
+        parse_stream_shard(stream).ok_or(Error::InvalidParams("malformed sharded stream id"))?;
+    let mut deleted = 0u64;
+    for page_start in (0..=MAX_LOCAL_ID).step_by(page_span as usize) {
+        if !tables.delete_page(stream, page_start).await? {
+            continue;
+        }
+        open_pages
+            .delete(&OpenBitmapPage {
+                shard,
+                page_start_local: page_start,
+                stream_id: stream.to_owned(),
+            })
+            .await?;
+        emit_event!(DEBUG, stream, page_start, "deleted stream page");
+        deleted += 1;
+    }
+    tables.delete_shard_presence(stream).await?;
+    Ok(deleted)
+}
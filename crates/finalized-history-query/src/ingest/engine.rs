@@ -3,8 +3,12 @@ use crate::config::Config;
 use crate::core::state::load_block_identity;
 use crate::error::{Error, Result};
 use crate::family::{Families, FamilyBlockWrites, FamilyStates, FinalizedBlock};
-use crate::ingest::authority::{WriteAuthority, WriteSession};
-use crate::ingest::recovery::preflight_recovery;
+use crate::ingest::authority::{WriteAuthority, WriteContinuity, WriteSession};
+use crate::ingest::recovery::{preflight_recovery, recover_head_from_block_records};
+use crate::ingest::wal::{
+    IngestWalPhase, IngestWalRecord, WalReplay, clear_wal, load_wal, plan_wal_replay, write_wal,
+};
+use crate::logs::codec::validate_log;
 use crate::runtime::Runtime;
 use crate::store::traits::{BlobStore, MetaStore};
 
@@ -57,24 +61,79 @@ where
         M: MetaStore,
         B: BlobStore,
     {
-        let session = self
-            .authority
-            .begin_write(self.config.observe_upstream_finalized_block.as_ref()())
-            .await?;
-        let state = session.state();
+        let observe = self.config.observe_upstream_finalized_block.as_ref();
+        let session = self.authority.begin_write(observe()).await?;
+        let continuity = session.state().continuity;
+        let mut session = self.replay_wal(runtime, session).await?;
+        let mut indexed_finalized_head = session.state().indexed_finalized_head;
+
+        // A fresh publication state over existing block records means the
+        // state was lost; republish the verified contiguous prefix before
+        // continuing so ingest does not restart from block 1. A scan capped by
+        // `max_scan` may have stopped below the true head, so it publishes
+        // nothing.
+        let recovery = self.config.recovery;
+        if recovery.verify_blocks
+            && continuity == WriteContinuity::Fresh
+            && indexed_finalized_head == 0
+        {
+            let recovered =
+                recover_head_from_block_records(&runtime.meta_store, recovery.max_scan).await?;
+            if recovered.head > 0 && !recovered.capped {
+                session.publish(recovered.head, observe()).await?;
+                session = self.authority.begin_write(observe()).await?;
+                indexed_finalized_head = session.state().indexed_finalized_head;
+            }
+        }
+
         let family_states = self
             .families
-            .load_state_from_head(runtime, state.indexed_finalized_head)
+            .load_state_from_head(runtime, indexed_finalized_head)
             .await?;
-        preflight_recovery(state.continuity, &runtime.tables, &family_states).await?;
+        preflight_recovery(continuity, &runtime.tables, &family_states).await?;
 
         Ok(PreparedWriterState {
             session,
-            indexed_finalized_head: state.indexed_finalized_head,
+            indexed_finalized_head,
             family_states,
         })
     }
 
+    /// Resolves a batch left in flight by an earlier writer session. An applied
+    /// batch whose block records still extend the head is published under
+    /// `session`, which is then reopened. Otherwise only the record is cleared;
+    /// a partial batch's artifacts stay past the head until the next ingest
+    /// rewrites them. No-op unless `Config::ingest_wal` is set.
+    pub async fn replay_wal<'a, M, B>(
+        &'a self,
+        runtime: &Runtime<M, B>,
+        session: A::Session<'a>,
+    ) -> Result<A::Session<'a>>
+    where
+        M: MetaStore,
+        B: BlobStore,
+    {
+        if !self.config.ingest_wal {
+            return Ok(session);
+        }
+        let Some(record) = load_wal(&runtime.meta_store).await? else {
+            return Ok(session);
+        };
+
+        let indexed_finalized_head = session.state().indexed_finalized_head;
+        let session =
+            match plan_wal_replay(&runtime.meta_store, &record, indexed_finalized_head).await? {
+                WalReplay::AlreadyPublished | WalReplay::Discard => session,
+                WalReplay::Complete { last_block } => {
+                    let observe = self.config.observe_upstream_finalized_block.as_ref();
+                    session.publish(last_block, observe()).await?;
+                    self.authority.begin_write(observe()).await?
+                }
+            };
+        clear_wal(&runtime.meta_store).await?;
+        Ok(session)
+    }
+
     pub fn new(config: Config, authority: A, families: Families) -> Self {
         Self {
             config,
@@ -113,16 +172,42 @@ where
 
         let mut prepared = self.preflight_writer_state(runtime).await?;
         let indexed_finalized_head = prepared.indexed_finalized_head();
-        validate_block_sequence(runtime, blocks, indexed_finalized_head).await?;
+        validate_block_sequence(runtime, blocks, indexed_finalized_head, &self.config).await?;
+        let mut wal = self.config.ingest_wal.then(|| {
+            let family_states = prepared.family_states_mut();
+            let last = blocks.last().expect("ingest requires at least one block");
+            IngestWalRecord {
+                phase: IngestWalPhase::Planned,
+                first_block: blocks[0].block_num,
+                last_block: last.block_num,
+                last_block_hash: last.block_hash,
+                first_log_id: family_states.logs.next_log_id.get(),
+                first_tx_id: family_states.txs.next_tx_id.get(),
+                first_trace_id: family_states.traces.next_trace_id.get(),
+            }
+        });
+        if let Some(wal) = &wal {
+            write_wal(&runtime.meta_store, wal).await?;
+        }
         let mut writes = FamilyBlockWrites::default();
 
         for block in blocks {
             writes += self
                 .families
-                .ingest_block(runtime, prepared.family_states_mut(), block)
+                .ingest_block(
+                    runtime,
+                    prepared.family_states_mut(),
+                    block,
+                    self.config.stream_append_concurrency,
+                )
                 .await?;
         }
 
+        if let Some(wal) = &mut wal {
+            wal.phase = IngestWalPhase::Applied;
+            write_wal(&runtime.meta_store, wal).await?;
+        }
+
         let indexed_finalized_head = blocks
             .last()
             .map(|block| block.block_num)
@@ -133,6 +218,9 @@ where
                 self.config.observe_upstream_finalized_block.as_ref()(),
             )
             .await?;
+        if wal.is_some() {
+            clear_wal(&runtime.meta_store).await?;
+        }
 
         Ok(IngestOutcome {
             indexed_finalized_head,
@@ -147,6 +235,7 @@ async fn validate_block_sequence<M, B>(
     runtime: &Runtime<M, B>,
     blocks: &[FinalizedBlock],
     indexed_finalized_head: u64,
+    config: &Config,
 ) -> Result<()>
 where
     M: MetaStore,
@@ -168,6 +257,24 @@ where
                 "block header parent_hash must match parent_hash",
             ));
         }
+        // Size limits are checked before the hasher runs, so an oversized
+        // block is rejected without hashing it.
+        if block.logs.len() > config.max_logs_per_block {
+            return Err(Error::InvalidParams("block exceeds max_logs_per_block"));
+        }
+        if block.estimated_bytes() > config.max_block_bytes {
+            return Err(Error::InvalidParams("block exceeds max_block_bytes"));
+        }
+        if let Some(hasher) = config.block_hasher.as_deref()
+            && hasher.hash_header(&block.header) != block.block_hash
+        {
+            return Err(Error::InvalidParams(
+                "block_hash does not match the block header contents",
+            ));
+        }
+        if !block.logs.iter().all(validate_log) {
+            return Err(Error::InvalidParams("log topics exceed 4"));
+        }
     }
 
     let expected_first = indexed_finalized_head.saturating_add(1);
@@ -187,10 +294,10 @@ where
             .hash
     };
     if blocks[0].parent_hash != expected_parent {
-        return Err(Error::InvalidParent);
+        return Err(Error::InvalidParent { index: 0 });
     }
 
-    for pair in blocks.windows(2) {
+    for (offset, pair) in blocks.windows(2).enumerate() {
         let current = &pair[0];
         let next = &pair[1];
         let expected_block_num = current.block_num.saturating_add(1);
@@ -201,7 +308,7 @@ where
             });
         }
         if next.parent_hash != current.block_hash {
-            return Err(Error::InvalidParent);
+            return Err(Error::InvalidParent { index: offset + 1 });
         }
     }
 
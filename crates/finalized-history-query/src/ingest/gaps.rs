@@ -0,0 +1,75 @@
+use std::sync::Mutex;
+
+use crate::error::Error;
+use crate::family::FinalizedBlock;
+
+/// Returns the leading run of `blocks` whose numbers continue
+/// `next_block, next_block + 1, ...`. Parent linkage is left to ingest
+/// validation.
+pub fn contiguous_prefix(blocks: &[FinalizedBlock], next_block: u64) -> &[FinalizedBlock] {
+    let len = blocks
+        .iter()
+        .zip(next_block..)
+        .take_while(|(block, expected)| block.block_num == *expected)
+        .count();
+    &blocks[..len]
+}
+
+/// The most recent forward gap reported by an ingest call, as an inclusive
+/// `(first_missing, last_missing)` block range.
+#[derive(Default)]
+pub(crate) struct GapTracker {
+    gap: Mutex<Option<(u64, u64)>>,
+}
+
+impl GapTracker {
+    /// Records the gap behind an `InvalidSequence` that skipped ahead.
+    /// Replays of already-ingested blocks are not gaps and leave it unchanged.
+    pub(crate) fn observe(&self, error: &Error) {
+        let Error::InvalidSequence { expected, got } = *error else {
+            return;
+        };
+        if got > expected {
+            *self.gap.lock().unwrap_or_else(|e| e.into_inner()) = Some((expected, got - 1));
+        }
+    }
+
+    /// Returns the part of the recorded gap still above the published head,
+    /// dropping it once ingest has filled the range.
+    pub(crate) fn current(&self, indexed_finalized_head: u64) -> Option<(u64, u64)> {
+        let mut gap = self.gap.lock().unwrap_or_else(|e| e.into_inner());
+        let (first_missing, last_missing) = (*gap)?;
+        if last_missing <= indexed_finalized_head {
+            *gap = None;
+            return None;
+        }
+        Some((
+            first_missing.max(indexed_finalized_head.saturating_add(1)),
+            last_missing,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GapTracker;
+    use crate::error::Error;
+
+    #[test]
+    fn gap_is_trimmed_to_head_and_dropped_once_filled() {
+        let gaps = GapTracker::default();
+        gaps.observe(&Error::InvalidSequence {
+            expected: 3,
+            got: 7,
+        });
+        gaps.observe(&Error::InvalidSequence {
+            expected: 9,
+            got: 4,
+        });
+
+        assert_eq!(gaps.current(2), Some((3, 6)));
+        assert_eq!(gaps.current(4), Some((5, 6)));
+        assert_eq!(gaps.current(6), None);
+        assert_eq!(gaps.current(2), None);
+    }
+}
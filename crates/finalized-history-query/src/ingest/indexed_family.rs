@@ -7,6 +7,7 @@ use crate::ingest::primary_dir::compact_newly_sealed_primary_directory;
 use crate::kernel::codec::StorageCodec;
 use crate::kernel::sharded_streams::group_stream_values;
 use crate::kernel::sharded_streams::parse_stream_shard;
+use crate::observe::emit_event;
 use crate::store::traits::{BlobStore, MetaStore};
 use crate::tables::{OpenBitmapPageTable, PrimaryDirTables, StreamTables};
 
@@ -26,7 +27,7 @@ pub struct IndexedFamilyIngestArtifacts<T> {
     pub written_count: u32,
     pub touched_pages: Vec<(String, u32)>,
     pub stream_page_local_id_span: u32,
-    pub make_meta: fn(u32, u32, u32) -> T,
+    pub make_meta: fn(u32, u32, u32, u64) -> T,
 }
 
 impl<T> IndexedFamilyIngestArtifacts<T> {
@@ -128,6 +129,12 @@ where
         )
         .await?;
         tables.open_bitmap_pages.delete(&page).await?;
+        emit_event!(
+            DEBUG,
+            stream = %page.stream_id,
+            page_start = page.page_start_local,
+            "sealed stream page"
+        );
     }
 
     Ok(IndexedFamilyFinalizeResult { next_primary_id })
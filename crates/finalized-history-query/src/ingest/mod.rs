@@ -1,7 +1,9 @@
 pub mod authority;
 pub mod bitmap_pages;
 pub mod engine;
+pub mod gaps;
 pub mod indexed_family;
 pub mod open_pages;
 pub mod primary_dir;
 pub mod recovery;
+pub mod wal;
@@ -170,10 +170,11 @@ pub async fn repair_sealed_open_bitmap_pages<M: MetaStore, B: BlobStore>(
             &tables.log_streams,
             &page.stream_id,
             page.page_start_local,
-            |count, min_local, max_local| crate::logs::types::StreamBitmapMeta {
+            |count, min_local, max_local, byte_size| crate::logs::types::StreamBitmapMeta {
                 count,
                 min_local,
                 max_local,
+                byte_size,
             },
         )
         .await?;
@@ -191,10 +192,11 @@ pub async fn repair_sealed_open_bitmap_pages<M: MetaStore, B: BlobStore>(
             &tables.tx_streams,
             &page.stream_id,
             page.page_start_local,
-            |count, min_local, max_local| crate::txs::types::StreamBitmapMeta {
+            |count, min_local, max_local, byte_size| crate::txs::types::StreamBitmapMeta {
                 count,
                 min_local,
                 max_local,
+                byte_size,
             },
         )
         .await?;
@@ -212,10 +214,11 @@ pub async fn repair_sealed_open_bitmap_pages<M: MetaStore, B: BlobStore>(
             &tables.trace_streams,
             &page.stream_id,
             page.page_start_local,
-            |count, min_local, max_local| crate::traces::types::StreamBitmapMeta {
+            |count, min_local, max_local, byte_size| crate::traces::types::StreamBitmapMeta {
                 count,
                 min_local,
                 max_local,
+                byte_size,
             },
         )
         .await?;
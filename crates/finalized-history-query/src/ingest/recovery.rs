@@ -1,10 +1,38 @@
+use crate::core::layout::read_u64_be;
+use crate::core::state::{BlockRecord, BlockRecordSpec};
 use crate::error::Result;
 use crate::family::FamilyStates;
 use crate::ingest::authority::WriteContinuity;
 use crate::ingest::open_pages::repair_sealed_open_bitmap_pages;
+use crate::kernel::codec::StorageCodec;
+use crate::kernel::table_specs::PointTableSpec;
+use crate::logs::table_specs::BlockHashIndexSpec;
 use crate::store::traits::{BlobStore, MetaStore};
 use crate::tables::Tables;
 
+/// Controls what writer preflight reads when it finds publication state
+/// missing over existing block records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryOptions {
+    /// Rebuild the lost head with [`recover_head_from_block_records`]. When
+    /// false, preflight reads only publication state and the ingest WAL, and
+    /// a lost head restarts ingest from block 1.
+    pub verify_blocks: bool,
+    /// Maximum number of block records one rebuild reads. A rebuild that
+    /// reaches the cap publishes nothing, since records past it may extend
+    /// the chain, and ingest restarts from block 1.
+    pub max_scan: usize,
+}
+
+impl Default for RecoveryOptions {
+    fn default() -> Self {
+        Self {
+            verify_blocks: true,
+            max_scan: usize::MAX,
+        }
+    }
+}
+
 /// Apply any writer-side recovery required before ingest continues from the
 /// published head. Continuous leases skip recovery entirely; fresh acquisition
 /// and reacquisition repair ownership-transition state idempotently.
@@ -36,3 +64,59 @@ pub async fn preflight_recovery<M: MetaStore, B: BlobStore>(
     }
     Ok(())
 }
+
+/// The outcome of [`recover_head_from_block_records`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveredHead {
+    /// The last block of the verified prefix, or 0 when block 1 is absent.
+    pub head: u64,
+    /// The scan stopped on `max_scan` rather than at the end of the chain, so
+    /// `head` may be below the true head.
+    pub capped: bool,
+}
+
+/// Rebuilds the finalized head from block records when publication state has
+/// been lost. Finds the highest `N` such that blocks `1..=N` all have block
+/// records, each record's `parent_hash` equals its predecessor's `block_hash`,
+/// and `block_hash_index` maps each hash back to its block number, reading at
+/// most `max_scan` blocks.
+///
+/// Reads go straight to the meta store so this one-off scan neither pollutes
+/// nor is served by the query caches.
+pub async fn recover_head_from_block_records<M: MetaStore>(
+    meta_store: &M,
+    max_scan: usize,
+) -> Result<RecoveredHead> {
+    let max_head = u64::try_from(max_scan).unwrap_or(u64::MAX);
+    let mut head = 0u64;
+    let mut previous_hash = None;
+    while head < max_head {
+        let block_num = head + 1;
+        let Some(record) = meta_store
+            .get(BlockRecordSpec::TABLE, &BlockRecordSpec::key(block_num))
+            .await?
+        else {
+            break;
+        };
+        let record = BlockRecord::decode(&record.value)?;
+        if previous_hash.is_some_and(|hash| record.parent_hash != hash) {
+            break;
+        }
+        let indexed_block_num = meta_store
+            .get(
+                BlockHashIndexSpec::TABLE,
+                &BlockHashIndexSpec::key(&record.block_hash),
+            )
+            .await?
+            .and_then(|indexed| read_u64_be(&indexed.value));
+        if indexed_block_num != Some(block_num) {
+            break;
+        }
+        previous_hash = Some(record.block_hash);
+        head = block_num;
+    }
+    Ok(RecoveredHead {
+        head,
+        capped: head == max_head,
+    })
+}
@@ -0,0 +1,218 @@
+use bytes::Bytes;
+
+use crate::core::state::{BlockRecord, BlockRecordSpec};
+use crate::error::{Error, Result};
+use crate::kernel::codec::StorageCodec;
+use crate::kernel::table_specs::PointTableSpec;
+use crate::store::traits::{DelCond, MetaStore, PutCond, TableId};
+
+pub struct IngestWalSpec;
+
+impl PointTableSpec for IngestWalSpec {
+    const TABLE: TableId = TableId::new("ingest_wal");
+}
+
+pub const INGEST_WAL_TABLE: TableId = IngestWalSpec::TABLE;
+pub const INGEST_WAL_KEY: &[u8] = b"pending";
+
+const INGEST_WAL_VERSION: u8 = 1;
+
+/// How far an in-flight batch got before the writer stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestWalPhase {
+    /// Artifact writes may be partial; replay clears the record and leaves
+    /// the batch unpublished.
+    Planned,
+    /// Every block record in the batch is durable; replay publishes it.
+    Applied,
+}
+
+/// The single in-flight ingest batch: its block range, the primary IDs its
+/// appends start from, and the hash its last block must carry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IngestWalRecord {
+    pub phase: IngestWalPhase,
+    pub first_block: u64,
+    pub last_block: u64,
+    pub last_block_hash: [u8; 32],
+    pub first_log_id: u64,
+    pub first_tx_id: u64,
+    pub first_trace_id: u64,
+}
+
+/// The outcome of replaying a WAL record against the published head.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalReplay {
+    /// The batch was already published; only the record was cleared.
+    AlreadyPublished,
+    /// The batch's artifacts are complete and its last block should be published.
+    Complete { last_block: u64 },
+    /// The batch was partial or does not extend the head. Only the record is
+    /// cleared: the record does not name the keys the batch wrote, so its
+    /// artifacts stay past the head until the next ingest overwrites them.
+    Discard,
+}
+
+impl StorageCodec for IngestWalRecord {
+    fn encode(&self) -> Bytes {
+        let mut out = Vec::with_capacity(2 + 8 * 5 + 32);
+        out.push(INGEST_WAL_VERSION);
+        out.push(match self.phase {
+            IngestWalPhase::Planned => 0,
+            IngestWalPhase::Applied => 1,
+        });
+        out.extend_from_slice(&self.first_block.to_be_bytes());
+        out.extend_from_slice(&self.last_block.to_be_bytes());
+        out.extend_from_slice(&self.last_block_hash);
+        out.extend_from_slice(&self.first_log_id.to_be_bytes());
+        out.extend_from_slice(&self.first_tx_id.to_be_bytes());
+        out.extend_from_slice(&self.first_trace_id.to_be_bytes());
+        Bytes::from(out)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 2 + 8 * 5 + 32 {
+            return Err(Error::Decode("invalid ingest_wal length"));
+        }
+        if bytes[0] != INGEST_WAL_VERSION {
+            return Err(Error::Decode("invalid ingest_wal version"));
+        }
+        let phase = match bytes[1] {
+            0 => IngestWalPhase::Planned,
+            1 => IngestWalPhase::Applied,
+            _ => return Err(Error::Decode("invalid ingest_wal phase")),
+        };
+        let read_u64 = |offset: usize| {
+            let mut value = [0u8; 8];
+            value.copy_from_slice(&bytes[offset..offset + 8]);
+            u64::from_be_bytes(value)
+        };
+        let mut last_block_hash = [0u8; 32];
+        last_block_hash.copy_from_slice(&bytes[18..50]);
+        Ok(Self {
+            phase,
+            first_block: read_u64(2),
+            last_block: read_u64(10),
+            last_block_hash,
+            first_log_id: read_u64(50),
+            first_tx_id: read_u64(58),
+            first_trace_id: read_u64(66),
+        })
+    }
+}
+
+pub async fn write_wal<M: MetaStore>(meta_store: &M, record: &IngestWalRecord) -> Result<()> {
+    meta_store
+        .put(
+            INGEST_WAL_TABLE,
+            INGEST_WAL_KEY,
+            record.encode(),
+            PutCond::Any,
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn clear_wal<M: MetaStore>(meta_store: &M) -> Result<()> {
+    meta_store
+        .delete(INGEST_WAL_TABLE, INGEST_WAL_KEY, DelCond::Any)
+        .await
+}
+
+pub async fn load_wal<M: MetaStore>(meta_store: &M) -> Result<Option<IngestWalRecord>> {
+    meta_store
+        .get(INGEST_WAL_TABLE, INGEST_WAL_KEY)
+        .await?
+        .map(|record| IngestWalRecord::decode(&record.value))
+        .transpose()
+}
+
+/// Decides what to do with a WAL record left behind by an interrupted batch.
+/// An applied batch completes only when it directly extends the published
+/// head and its block records still chain from the head's hash, start at the
+/// planned primary IDs, and end at the planned last hash.
+pub async fn plan_wal_replay<M: MetaStore>(
+    meta_store: &M,
+    record: &IngestWalRecord,
+    indexed_finalized_head: u64,
+) -> Result<WalReplay> {
+    if record.last_block <= indexed_finalized_head {
+        return Ok(WalReplay::AlreadyPublished);
+    }
+    if record.phase != IngestWalPhase::Applied
+        || record.first_block != indexed_finalized_head.saturating_add(1)
+    {
+        return Ok(WalReplay::Discard);
+    }
+
+    let mut previous_hash = if indexed_finalized_head == 0 {
+        [0u8; 32]
+    } else {
+        match load_block_record(meta_store, indexed_finalized_head).await? {
+            Some(head_record) => head_record.block_hash,
+            None => return Ok(WalReplay::Discard),
+        }
+    };
+    for block_num in record.first_block..=record.last_block {
+        let Some(block_record) = load_block_record(meta_store, block_num).await? else {
+            return Ok(WalReplay::Discard);
+        };
+        if block_record.parent_hash != previous_hash {
+            return Ok(WalReplay::Discard);
+        }
+        if block_num == record.first_block && !starts_at_planned_ids(&block_record, record) {
+            return Ok(WalReplay::Discard);
+        }
+        previous_hash = block_record.block_hash;
+    }
+    if previous_hash != record.last_block_hash {
+        return Ok(WalReplay::Discard);
+    }
+
+    Ok(WalReplay::Complete {
+        last_block: record.last_block,
+    })
+}
+
+fn starts_at_planned_ids(block_record: &BlockRecord, record: &IngestWalRecord) -> bool {
+    let first_id = |window: Option<crate::core::state::PrimaryWindowRecord>| {
+        window.map(|window| window.first_primary_id)
+    };
+    first_id(block_record.logs) == Some(record.first_log_id)
+        && first_id(block_record.txs) == Some(record.first_tx_id)
+        && first_id(block_record.traces) == Some(record.first_trace_id)
+}
+
+async fn load_block_record<M: MetaStore>(
+    meta_store: &M,
+    block_num: u64,
+) -> Result<Option<BlockRecord>> {
+    meta_store
+        .get(BlockRecordSpec::TABLE, &BlockRecordSpec::key(block_num))
+        .await?
+        .map(|record| BlockRecord::decode(&record.value))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IngestWalPhase, IngestWalRecord};
+    use crate::kernel::codec::StorageCodec;
+
+    #[test]
+    fn ingest_wal_record_roundtrips() {
+        let record = IngestWalRecord {
+            phase: IngestWalPhase::Applied,
+            first_block: 7,
+            last_block: 9,
+            last_block_hash: [9; 32],
+            first_log_id: 100,
+            first_tx_id: 20,
+            first_trace_id: 3,
+        };
+
+        let decoded = IngestWalRecord::decode(&record.encode()).expect("decode wal");
+
+        assert_eq!(decoded, record);
+    }
+}
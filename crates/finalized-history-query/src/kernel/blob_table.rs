@@ -2,8 +2,9 @@ use bytes::Bytes;
 
 use crate::error::Result;
 use crate::kernel::cache::HashMapTableBytesCache;
-use crate::store::traits::{BlobStore, BlobTable};
+use crate::store::traits::{BlobStore, BlobTable, Page};
 
+#[derive(Clone)]
 pub struct CachedBlobTable<B: BlobStore> {
     blob_table: BlobTable<B>,
     pub cache: HashMapTableBytesCache,
@@ -30,4 +31,19 @@ impl<B: BlobStore> CachedBlobTable<B> {
         self.cache.put(key, bytes.clone(), bytes.len());
         Ok(())
     }
+
+    pub async fn delete_by_key(&self, key: &[u8]) -> Result<()> {
+        self.blob_table.delete(key).await?;
+        self.cache.remove(key);
+        Ok(())
+    }
+
+    pub async fn list_prefix(
+        &self,
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> Result<Page> {
+        self.blob_table.list_prefix(prefix, cursor, limit).await
+    }
 }
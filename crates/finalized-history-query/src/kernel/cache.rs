@@ -184,6 +184,12 @@ impl HashMapTableBytesCache {
         self.metrics.inserts.fetch_add(1, Ordering::Relaxed);
     }
 
+    pub fn remove(&self, key: &[u8]) {
+        if let Some(inner) = self.inner.as_ref() {
+            inner.remove(key);
+        }
+    }
+
     pub fn metrics_snapshot(&self) -> TableCacheMetrics {
         let (hits, misses, bytes_used) = match self.inner.as_ref() {
             Some(inner) => (inner.hits(), inner.misses(), inner.weight()),
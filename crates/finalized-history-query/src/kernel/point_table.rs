@@ -5,7 +5,7 @@ use bytes::Bytes;
 use crate::error::Result;
 use crate::kernel::cache::{HashMapTableBytesCache, TableCacheMetrics};
 use crate::kernel::codec::StorageCodec;
-use crate::store::traits::{KvTable, MetaStore, PutCond};
+use crate::store::traits::{DelCond, KvTable, MetaStore, PutCond};
 
 pub trait TableValueCodec: StorageCodec {}
 
@@ -27,6 +27,12 @@ impl<M: MetaStore, V> CachedPointTable<M, V> {
         }
     }
 
+    pub async fn delete(&self, key: &[u8]) -> Result<()> {
+        self.table.delete(key, DelCond::Any).await?;
+        self.cache.remove(key);
+        Ok(())
+    }
+
     pub fn metrics(&self) -> TableCacheMetrics {
         self.cache.metrics_snapshot()
     }
@@ -46,6 +52,38 @@ impl<M: MetaStore, V: TableValueCodec> CachedPointTable<M, V> {
         Ok(Some(V::decode(&record.value)?))
     }
 
+    /// Batched [`Self::get_decoded`]: serves cache hits and loads every miss
+    /// with one `multi_get`. Results are in input order.
+    pub async fn get_many_decoded(&self, keys: &[Vec<u8>]) -> Result<Vec<Option<V>>> {
+        let mut out = Vec::with_capacity(keys.len());
+        let mut miss_indexes = Vec::new();
+        let mut miss_keys = Vec::new();
+        for (index, key) in keys.iter().enumerate() {
+            match self.cache.get(key) {
+                Some(bytes) => out.push(Some(V::decode(&bytes)?)),
+                None => {
+                    out.push(None);
+                    miss_indexes.push(index);
+                    miss_keys.push(key.clone());
+                }
+            }
+        }
+        if miss_keys.is_empty() {
+            return Ok(out);
+        }
+
+        let records = self.table.multi_get(&miss_keys).await?;
+        for ((index, key), record) in miss_indexes.into_iter().zip(&miss_keys).zip(records) {
+            let Some(record) = record else {
+                continue;
+            };
+            self.cache
+                .put(key, record.value.clone(), record.value.len());
+            out[index] = Some(V::decode(&record.value)?);
+        }
+        Ok(out)
+    }
+
     pub async fn put_encoded(&self, key: &[u8], value: &V) -> Result<()> {
         let encoded = value.encode();
         let _ = self.table.put(key, encoded.clone(), PutCond::Any).await?;
@@ -2,8 +2,9 @@ use bytes::Bytes;
 
 use crate::error::Result;
 use crate::kernel::cache::{HashMapTableBytesCache, TableCacheMetrics};
-use crate::store::traits::{MetaStore, PutCond, ScannableKvTable};
+use crate::store::traits::{DelCond, MetaStore, PutCond, ScannableKvTable};
 
+#[derive(Clone)]
 pub struct ScannableFragmentTable<M: MetaStore> {
     table: ScannableKvTable<M>,
     cache: HashMapTableBytesCache,
@@ -24,17 +25,9 @@ impl<M: MetaStore> ScannableFragmentTable<M> {
                 .list_prefix(partition, b"", cursor.take(), 1_024)
                 .await?;
             for clustering in page.keys {
-                let cache_key = composite_cache_key(partition, &clustering);
-                if let Some(cached) = self.cache.get(&cache_key) {
-                    values.push(cached);
-                    continue;
+                if let Some(value) = self.load_value(partition, &clustering).await? {
+                    values.push(value);
                 }
-                let Some(record) = self.table.get(partition, &clustering).await? else {
-                    continue;
-                };
-                self.cache
-                    .put(&cache_key, record.value.clone(), record.value.len());
-                values.push(record.value);
             }
             if page.next_cursor.is_none() {
                 break;
@@ -45,6 +38,51 @@ impl<M: MetaStore> ScannableFragmentTable<M> {
         Ok(values)
     }
 
+    /// Lists every key in `partition` but loads at most `max_samples` values,
+    /// evenly spaced in key order. Returns the key count with the loaded values.
+    pub async fn sample_partition_values(
+        &self,
+        partition: &[u8],
+        max_samples: usize,
+    ) -> Result<(usize, Vec<Bytes>)> {
+        let mut cursor = None;
+        let mut keys = Vec::new();
+
+        loop {
+            let page = self
+                .table
+                .list_prefix(partition, b"", cursor.take(), 1_024)
+                .await?;
+            keys.extend(page.keys);
+            if page.next_cursor.is_none() {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+
+        let step = keys.len().div_ceil(max_samples.max(1)).max(1);
+        let mut values = Vec::new();
+        for clustering in keys.iter().step_by(step) {
+            if let Some(value) = self.load_value(partition, clustering).await? {
+                values.push(value);
+            }
+        }
+        Ok((keys.len(), values))
+    }
+
+    async fn load_value(&self, partition: &[u8], clustering: &[u8]) -> Result<Option<Bytes>> {
+        let cache_key = composite_cache_key(partition, clustering);
+        if let Some(cached) = self.cache.get(&cache_key) {
+            return Ok(Some(cached));
+        }
+        let Some(record) = self.table.get(partition, clustering).await? else {
+            return Ok(None);
+        };
+        self.cache
+            .put(&cache_key, record.value.clone(), record.value.len());
+        Ok(Some(record.value))
+    }
+
     pub async fn put_value(&self, partition: &[u8], clustering: &[u8], value: Bytes) -> Result<()> {
         let cache_key = composite_cache_key(partition, clustering);
         let len = value.len();
@@ -56,6 +94,26 @@ impl<M: MetaStore> ScannableFragmentTable<M> {
         Ok(())
     }
 
+    /// Deletes every value in `partition` and returns how many were removed.
+    pub async fn delete_partition(&self, partition: &[u8]) -> Result<u64> {
+        let mut deleted = 0u64;
+        loop {
+            let page = self.table.list_prefix(partition, b"", None, 1_024).await?;
+            for clustering in &page.keys {
+                self.table
+                    .delete(partition, clustering, DelCond::Any)
+                    .await?;
+                self.cache
+                    .remove(&composite_cache_key(partition, clustering));
+                deleted += 1;
+            }
+            if page.next_cursor.is_none() {
+                break;
+            }
+        }
+        Ok(deleted)
+    }
+
     pub fn metrics(&self) -> TableCacheMetrics {
         self.cache.metrics_snapshot()
     }
@@ -2,7 +2,7 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use roaring::RoaringBitmap;
 
-use crate::core::layout::LOCAL_ID_BITS;
+use crate::core::layout::{LOCAL_ID_BITS, read_u64_be};
 use crate::streams::{BitmapBlob, decode_bitmap_blob};
 
 pub fn hex_digit(v: u8) -> char {
@@ -13,24 +13,158 @@ pub fn hex_digit(v: u8) -> char {
     }
 }
 
-pub fn sharded_stream_id(index_kind: &str, value: &[u8], shard: u64) -> String {
-    let shard_hex_width = ((64 - LOCAL_ID_BITS) as usize).div_ceil(4);
-    let mut out =
-        String::with_capacity(index_kind.len() + 1 + value.len() * 2 + 1 + shard_hex_width);
+const SHARD_HEX_WIDTH: usize = (64 - LOCAL_ID_BITS as usize).div_ceil(4);
+
+/// The index a stream belongs to, stored as the first segment of its stream
+/// ID. Each family keeps its streams in its own tables, so transactions and
+/// traces share `From`, `To`, and `Selector`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StreamKind {
+    Addr,
+    Topic0,
+    Topic1,
+    Topic2,
+    Topic3,
+    From,
+    To,
+    Selector,
+    HasValue,
+}
+
+impl StreamKind {
+    pub const ALL: [StreamKind; 9] = [
+        StreamKind::Addr,
+        StreamKind::Topic0,
+        StreamKind::Topic1,
+        StreamKind::Topic2,
+        StreamKind::Topic3,
+        StreamKind::From,
+        StreamKind::To,
+        StreamKind::Selector,
+        StreamKind::HasValue,
+    ];
+
+    /// The kind segment written into stream IDs and page blob keys. Changing
+    /// one orphans every stream already stored under it.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            StreamKind::Addr => "addr",
+            StreamKind::Topic0 => "topic0",
+            StreamKind::Topic1 => "topic1",
+            StreamKind::Topic2 => "topic2",
+            StreamKind::Topic3 => "topic3",
+            StreamKind::From => "from",
+            StreamKind::To => "to",
+            StreamKind::Selector => "selector",
+            StreamKind::HasValue => "has_value",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|candidate| candidate.as_str() == kind)
+    }
+
+    /// The stream kind of log topic `index`, for `index` 0 through 3.
+    pub const fn log_topic(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(StreamKind::Topic0),
+            1 => Some(StreamKind::Topic1),
+            2 => Some(StreamKind::Topic2),
+            3 => Some(StreamKind::Topic3),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for StreamKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Formats `"{kind}/{value as lowercase hex}/{shard as fixed-width hex}"`.
+/// Hex digits never contain `/`, and no kind does, so the first and last
+/// separators split the ID unambiguously.
+pub fn sharded_stream_id(index_kind: StreamKind, value: &[u8], shard: u64) -> String {
+    let mut out = stream_value_key(index_kind, value);
+    out.reserve(1 + SHARD_HEX_WIDTH);
+    out.push('/');
+    out.push_str(&format!("{:0width$x}", shard, width = SHARD_HEX_WIDTH));
+    out
+}
+
+/// Formats `"{kind}/{value as lowercase hex}"`, the prefix every shard's
+/// [`sharded_stream_id`] of one value shares.
+pub fn stream_value_key(index_kind: StreamKind, value: &[u8]) -> String {
+    let index_kind = index_kind.as_str();
+    let mut out = String::with_capacity(index_kind.len() + 1 + value.len() * 2);
     out.push_str(index_kind);
     out.push('/');
     for byte in value {
         out.push(hex_digit((byte >> 4) & 0xf));
         out.push(hex_digit(byte & 0xf));
     }
-    out.push('/');
-    out.push_str(&format!("{:0width$x}", shard, width = shard_hex_width));
     out
 }
 
 pub fn parse_stream_shard(stream_id: &str) -> Option<u64> {
-    let (_, shard_hex) = stream_id.rsplit_once('/')?;
-    u64::from_str_radix(shard_hex, 16).ok()
+    split_stream_shard(stream_id).map(|(_, shard)| shard)
+}
+
+/// Splits a [`sharded_stream_id`] into its [`stream_value_key`] and shard.
+pub fn split_stream_shard(stream_id: &str) -> Option<(&str, u64)> {
+    let (value_key, shard_hex) = stream_id.rsplit_once('/')?;
+    Some((value_key, parse_shard_hex(shard_hex)?))
+}
+
+/// Splits a [`sharded_stream_id`] back into its index kind, value bytes, and
+/// shard. Only the exact output of `sharded_stream_id` parses: the kind must
+/// be a [`StreamKind`], the value must be an even number of lowercase hex
+/// digits, and the shard must have its fixed width, so no other string maps
+/// to the same parts.
+pub fn parse_stream_id(stream_id: &str) -> Option<(StreamKind, Vec<u8>, u64)> {
+    let (kind, rest) = stream_id.split_once('/')?;
+    let (value_hex, shard_hex) = rest.rsplit_once('/')?;
+    let kind = StreamKind::parse(kind)?;
+    if value_hex.len() % 2 != 0 {
+        return None;
+    }
+    let value = value_hex
+        .as_bytes()
+        .chunks_exact(2)
+        .map(|pair| Some((lower_hex_value(pair[0])? << 4) | lower_hex_value(pair[1])?))
+        .collect::<Option<Vec<_>>>()?;
+    Some((kind, value, parse_shard_hex(shard_hex)?))
+}
+
+/// Splits a [`crate::kernel::table_specs::stream_page_key`], the stream ID
+/// and `/` followed by the big-endian u64 page start, into the stream ID and
+/// page start.
+pub fn split_stream_page_key(key: &[u8]) -> Option<(&str, u32)> {
+    let stream_len = key.len().checked_sub(9)?;
+    if key[stream_len] != b'/' {
+        return None;
+    }
+    let stream_id = std::str::from_utf8(&key[..stream_len]).ok()?;
+    let page_start = u32::try_from(read_u64_be(&key[stream_len + 1..])?).ok()?;
+    Some((stream_id, page_start))
+}
+
+fn parse_shard_hex(shard_hex: &str) -> Option<u64> {
+    if shard_hex.len() != SHARD_HEX_WIDTH {
+        return None;
+    }
+    shard_hex.bytes().try_fold(0u64, |shard, digit| {
+        Some((shard << 4) | u64::from(lower_hex_value(digit)?))
+    })
+}
+
+fn lower_hex_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        _ => None,
+    }
 }
 
 pub fn page_start_local(local_id: u32, page_span: u32) -> u32 {
@@ -80,24 +214,38 @@ pub fn merge_bitmap_bytes_into(
     full_range: bool,
 ) -> crate::Result<bool> {
     let bitmap_blob = decode_bitmap_blob(bytes)?;
+    Ok(merge_bitmap_blob_into(
+        &bitmap_blob,
+        out,
+        local_from,
+        local_to,
+        full_range,
+    ))
+}
+
+pub fn merge_bitmap_blob_into(
+    bitmap_blob: &BitmapBlob,
+    out: &mut RoaringBitmap,
+    local_from: u32,
+    local_to: u32,
+    full_range: bool,
+) -> bool {
     if !overlaps(
         bitmap_blob.min_local,
         bitmap_blob.max_local,
         local_from,
         local_to,
     ) {
-        return Ok(false);
+        return false;
     }
     if full_range || (bitmap_blob.min_local >= local_from && bitmap_blob.max_local <= local_to) {
         *out |= &bitmap_blob.bitmap;
-        return Ok(true);
+        return true;
     }
-    for value in bitmap_blob.bitmap {
-        if value >= local_from && value <= local_to {
-            out.insert(value);
-        }
+    for value in bitmap_blob.bitmap.range(local_from..=local_to) {
+        out.insert(value);
     }
-    Ok(true)
+    true
 }
 
 pub fn compacted_bitmap_blob(bitmap: RoaringBitmap, page_start: u32) -> Option<(u32, BitmapBlob)> {
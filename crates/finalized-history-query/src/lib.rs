@@ -2,12 +2,16 @@ pub mod api;
 pub mod blocks;
 pub mod config;
 pub mod core;
+pub mod diagnostics;
 pub mod error;
 pub mod family;
 pub mod ingest;
 pub mod kernel;
 pub mod logs;
+mod observe;
 pub mod query;
+#[cfg(feature = "rpc-adapter")]
+pub mod rpc;
 pub mod runtime;
 pub mod status;
 pub mod store;
@@ -23,7 +27,7 @@ pub use api::{
 pub use blocks::Block;
 pub use config::Config;
 pub use core::clause::Clause;
-pub use core::header::EvmBlockHeader;
+pub use core::header::{BlockHasher, EvmBlockHeader};
 pub use core::page::{QueryOrder, QueryPage, QueryPageMeta};
 pub use core::refs::BlockRef;
 pub use error::{Error, Result};
@@ -32,9 +36,10 @@ pub use ingest::authority::{
     AuthorityState, LeaseAuthority, ReadOnlyAuthority, WriteAuthority, WriteContinuity,
     WriteSession,
 };
-pub use logs::filter::LogFilter;
+pub use kernel::sharded_streams::StreamKind;
+pub use logs::filter::{LogFilter, LogFilterBuilder};
 pub use logs::log_ref::LogRef;
-pub use logs::types::Log;
+pub use logs::types::{Address, Log, LogPosition, Topic};
 pub use traces::filter::TraceFilter;
 pub use traces::types::Trace;
 pub use traces::view::TraceRef;
@@ -2,8 +2,11 @@ use bytes::Bytes;
 
 use crate::error::{Error, Result};
 use crate::kernel::codec::StorageCodec;
-use crate::logs::types::{BlockLogHeader, Log, Topic32};
+use crate::logs::types::{Address, BlockLogHeader, Log, Topic, Topic32};
 
+/// Ethereum logs carry at most four topics. Ingest rejects any batch holding
+/// a log that fails this check before writing anything, so stored logs always
+/// decode and every topic is indexed.
 pub fn validate_log(log: &Log) -> bool {
     log.topics.len() <= 4
 }
@@ -12,10 +15,10 @@ impl StorageCodec for Log {
     fn encode(&self) -> Bytes {
         let topic_count = self.topics.len() as u8;
         let mut out = Vec::with_capacity(80 + topic_count as usize * 32 + self.data.len());
-        out.extend_from_slice(&self.address);
+        out.extend_from_slice(self.address.as_ref());
         out.push(topic_count);
         for topic in &self.topics {
-            out.extend_from_slice(topic);
+            out.extend_from_slice(topic.as_ref());
         }
         out.extend_from_slice(&(self.data.len() as u32).to_be_bytes());
         out.extend_from_slice(&self.data);
@@ -27,6 +30,9 @@ impl StorageCodec for Log {
     }
 
     fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.is_empty() {
+            return Err(Error::Decode("empty log payload"));
+        }
         if bytes.len() < 20 + 1 + 4 + 8 + 4 + 4 + 32 {
             return Err(Error::Decode("log too short"));
         }
@@ -83,8 +89,8 @@ impl StorageCodec for Log {
         block_hash.copy_from_slice(&bytes[pos..pos + 32]);
 
         Ok(Self {
-            address,
-            topics,
+            address: Address(address),
+            topics: topics.into_iter().map(Topic).collect(),
             data,
             block_num: u64::from_be_bytes(block_num),
             tx_idx: u32::from_be_bytes(tx_idx),
@@ -120,8 +126,10 @@ impl StorageCodec for BlockLogHeader {
 
 #[cfg(test)]
 mod tests {
+    use std::any::TypeId;
+
     use super::*;
-    use crate::logs::types::DirBucket;
+    use crate::logs::types::{Address, Address20, DirBucket};
 
     #[test]
     fn roundtrip_large_log_dir_bucket() {
@@ -153,4 +161,33 @@ mod tests {
         let dec = BlockLogHeader::decode(&enc).expect("decode large block log header");
         assert_eq!(dec, header);
     }
+
+    #[test]
+    fn address_and_topic_are_distinct_types() {
+        assert_ne!(TypeId::of::<Address>(), TypeId::of::<Topic>());
+        assert_ne!(TypeId::of::<Address>(), TypeId::of::<Address20>());
+        assert_ne!(TypeId::of::<Topic>(), TypeId::of::<Topic32>());
+    }
+
+    #[test]
+    fn log_encoding_stores_bare_address_and_topic_bytes() {
+        let log = Log {
+            address: Address([0xaa; 20]),
+            topics: vec![Topic([0xbb; 32]), Topic([0xcc; 32])],
+            data: vec![1, 2, 3],
+            block_num: 9,
+            tx_idx: 1,
+            log_idx: 2,
+            block_hash: [0xdd; 32],
+        };
+
+        let enc = log.encode();
+        assert_eq!(&enc[..20], &[0xaa; 20]);
+        assert_eq!(enc[20], 2);
+        assert_eq!(&enc[21..53], &[0xbb; 32]);
+        assert_eq!(&enc[53..85], &[0xcc; 32]);
+
+        let dec = Log::decode(&enc).expect("decode log");
+        assert_eq!(dec, log);
+    }
 }
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::core::ids::LogId;
 use crate::core::state::BlockRecord;
 use crate::error::{Error, Result};
@@ -7,13 +9,41 @@ use crate::ingest::indexed_family::{
     finalize_indexed_family_ingest,
 };
 use crate::logs::STREAM_PAGE_LOCAL_ID_SPAN;
-use crate::logs::ingest::{persist_log_artifacts, persist_log_stream_fragments, plan_log_ingest};
+use crate::logs::ingest::{
+    LogRewriter, persist_log_artifacts, persist_log_stream_fragments, plan_log_ingest,
+    plan_rewritten_log_ingest,
+};
 use crate::logs::types::{LogSequencingState, StreamBitmapMeta};
+use crate::observe::record_span_field;
 use crate::runtime::Runtime;
 use crate::store::traits::{BlobStore, MetaStore};
 
-#[derive(Debug, Clone, Copy, Default)]
-pub struct LogsFamily;
+/// `store_data: false` stores every log with empty `data`; see
+/// `Config::store_log_data`. `rewriter` is applied to each log before it is
+/// stored or indexed; see `Config::log_rewriter`.
+#[derive(Clone)]
+pub struct LogsFamily {
+    pub store_data: bool,
+    pub rewriter: Option<LogRewriter>,
+}
+
+impl Default for LogsFamily {
+    fn default() -> Self {
+        Self {
+            store_data: true,
+            rewriter: None,
+        }
+    }
+}
+
+impl fmt::Debug for LogsFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogsFamily")
+            .field("store_data", &self.store_data)
+            .field("rewriter", &self.rewriter.as_ref().map(|_| "<callback>"))
+            .finish()
+    }
+}
 
 impl LogsFamily {
     pub fn load_state_from_head_record(
@@ -41,9 +71,16 @@ impl LogsFamily {
         runtime: &Runtime<M, B>,
         state: &mut LogSequencingState,
         block: &FinalizedBlock,
+        stream_append_concurrency: usize,
     ) -> Result<usize> {
         let from_next_log_id = state.next_log_id.get();
-        let plan = plan_log_ingest(block, from_next_log_id)?;
+        let plan = match &self.rewriter {
+            Some(rewrite) => {
+                plan_rewritten_log_ingest(block, from_next_log_id, self.store_data, rewrite)?
+            }
+            None => plan_log_ingest(block, from_next_log_id, self.store_data)?,
+        };
+        record_span_field!("stream_count", plan.stream_appends_by_stream.len());
 
         let written_count = persist_log_artifacts(&runtime.tables, block.block_num, &plan).await?;
 
@@ -51,6 +88,7 @@ impl LogsFamily {
             &runtime.tables,
             block.block_num,
             &plan.stream_appends_by_stream,
+            stream_append_concurrency,
         )
         .await?;
         let IndexedFamilyFinalizeResult { next_primary_id } = finalize_indexed_family_ingest(
@@ -65,10 +103,11 @@ impl LogsFamily {
                 written_count: written_count as u32,
                 touched_pages,
                 stream_page_local_id_span: STREAM_PAGE_LOCAL_ID_SPAN,
-                make_meta: |count, min_local, max_local| StreamBitmapMeta {
+                make_meta: |count, min_local, max_local, byte_size| StreamBitmapMeta {
                     count,
                     min_local,
                     max_local,
+                    byte_size,
                 },
             },
         )
@@ -1,15 +1,25 @@
-use crate::core::clause::{Clause, clause_matches, has_indexed_value, optional_clause_matches};
-use crate::logs::types::{Address20, Topic32};
+use crate::core::clause::{
+    Clause, clause_matches, has_indexed_value, has_negated_value, optional_clause_matches,
+};
+use crate::kernel::sharded_streams::StreamKind;
+use crate::logs::types::{Address, Topic};
 use crate::query::engine::IndexedFilter;
 use crate::query::planner::{IndexedClause, build_indexed_clause};
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Serializes with absent positions omitted, so `{}` is the empty filter.
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct LogFilter {
-    pub address: Option<Clause<Address20>>,
-    pub topic0: Option<Clause<Topic32>>,
-    pub topic1: Option<Clause<Topic32>>,
-    pub topic2: Option<Clause<Topic32>>,
-    pub topic3: Option<Clause<Topic32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Clause<Address>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic0: Option<Clause<Topic>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic1: Option<Clause<Topic>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic2: Option<Clause<Topic>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic3: Option<Clause<Topic>>,
 }
 
 impl IndexedFilter for LogFilter {
@@ -41,22 +51,30 @@ impl IndexedFilter for LogFilter {
             || has_indexed_value(&self.topic3)
     }
 
+    fn has_negated_clause(&self) -> bool {
+        has_negated_value(&self.address)
+            || has_negated_value(&self.topic0)
+            || has_negated_value(&self.topic1)
+            || has_negated_value(&self.topic2)
+            || has_negated_value(&self.topic3)
+    }
+
     fn indexed_clauses(&self) -> Vec<IndexedClause> {
         let mut clauses = Vec::new();
 
-        if let Some(clause) = build_indexed_clause("addr", &self.address) {
+        if let Some(clause) = build_indexed_clause(StreamKind::Addr, &self.address) {
             clauses.push(clause)
         }
-        if let Some(clause) = build_indexed_clause("topic1", &self.topic1) {
+        if let Some(clause) = build_indexed_clause(StreamKind::Topic1, &self.topic1) {
             clauses.push(clause)
         }
-        if let Some(clause) = build_indexed_clause("topic2", &self.topic2) {
+        if let Some(clause) = build_indexed_clause(StreamKind::Topic2, &self.topic2) {
             clauses.push(clause)
         }
-        if let Some(clause) = build_indexed_clause("topic3", &self.topic3) {
+        if let Some(clause) = build_indexed_clause(StreamKind::Topic3, &self.topic3) {
             clauses.push(clause)
         }
-        if let Some(clause) = build_indexed_clause("topic0", &self.topic0) {
+        if let Some(clause) = build_indexed_clause(StreamKind::Topic0, &self.topic0) {
             clauses.push(clause)
         }
 
@@ -64,34 +82,88 @@ impl IndexedFilter for LogFilter {
     }
 }
 
+impl LogFilter {
+    pub fn builder() -> LogFilterBuilder {
+        LogFilterBuilder::default()
+    }
+
+    /// Whether bitmap intersection alone decides a match. Every log position
+    /// is indexed under its full value, so only negated and empty OR clauses
+    /// still need the payload for an exact-match pass.
+    pub fn is_fully_indexed(&self) -> bool {
+        fn indexed<T>(clause: &Option<Clause<T>>) -> bool {
+            match clause {
+                None | Some(Clause::Any | Clause::One(_)) => true,
+                Some(Clause::Or(values)) => !values.is_empty(),
+                Some(Clause::Not(_) | Clause::Range { .. }) => false,
+            }
+        }
+        indexed(&self.address)
+            && indexed(&self.topic0)
+            && indexed(&self.topic1)
+            && indexed(&self.topic2)
+            && indexed(&self.topic3)
+    }
+}
+
+/// Fluent constructor for [`LogFilter`]. Each position starts unconstrained;
+/// setting a position twice keeps the last clause.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilterBuilder {
+    filter: LogFilter,
+}
+
+macro_rules! clause_setters {
+    ($field:ident, $one:ident, $or:ident, $value:ty) => {
+        pub fn $field(mut self, clause: Clause<$value>) -> Self {
+            self.filter.$field = Some(clause);
+            self
+        }
+
+        pub fn $one(self, value: $value) -> Self {
+            self.$field(Clause::One(value))
+        }
+
+        pub fn $or(self, values: impl IntoIterator<Item = $value>) -> Self {
+            self.$field(Clause::Or(values.into_iter().collect()))
+        }
+    };
+}
+
+impl LogFilterBuilder {
+    clause_setters!(address, address_one, address_or, Address);
+    clause_setters!(topic0, topic0_one, topic0_or, Topic);
+    clause_setters!(topic1, topic1_one, topic1_or, Topic);
+    clause_setters!(topic2, topic2_one, topic2_or, Topic);
+    clause_setters!(topic3, topic3_one, topic3_or, Topic);
+
+    /// Matches logs whose topic1 is any of `addresses`, left-padded to 32
+    /// bytes. It queries the ordinary `topic1` stream.
+    pub fn topic1_address_or(self, addresses: impl IntoIterator<Item = Address>) -> Self {
+        self.topic1_or(addresses.into_iter().map(Topic::from))
+    }
+
+    pub fn build(self) -> LogFilter {
+        self.filter
+    }
+}
+
 pub fn exact_match(log: &impl crate::logs::log_ref::LogView, filter: &LogFilter) -> bool {
-    if !clause_matches(log.address(), &filter.address) {
+    if !clause_matches(&Address(*log.address()), &filter.address) {
         return false;
     }
 
     let tc = log.topic_count();
-    if !optional_clause_matches(
-        if tc > 0 { Some(*log.topic(0)) } else { None },
-        &filter.topic0,
-    ) {
+    if !optional_clause_matches((tc > 0).then(|| Topic(*log.topic(0))), &filter.topic0) {
         return false;
     }
-    if !optional_clause_matches(
-        if tc > 1 { Some(*log.topic(1)) } else { None },
-        &filter.topic1,
-    ) {
+    if !optional_clause_matches((tc > 1).then(|| Topic(*log.topic(1))), &filter.topic1) {
         return false;
     }
-    if !optional_clause_matches(
-        if tc > 2 { Some(*log.topic(2)) } else { None },
-        &filter.topic2,
-    ) {
+    if !optional_clause_matches((tc > 2).then(|| Topic(*log.topic(2))), &filter.topic2) {
         return false;
     }
-    if !optional_clause_matches(
-        if tc > 3 { Some(*log.topic(3)) } else { None },
-        &filter.topic3,
-    ) {
+    if !optional_clause_matches((tc > 3).then(|| Topic(*log.topic(3))), &filter.topic3) {
         return false;
     }
     true
@@ -105,8 +177,8 @@ mod tests {
 
     fn log_with_topics(address: u8, topics: &[u8]) -> Log {
         Log {
-            address: [address; 20],
-            topics: topics.iter().map(|t| [*t; 32]).collect(),
+            address: Address([address; 20]),
+            topics: topics.iter().map(|t| Topic([*t; 32])).collect(),
             data: vec![],
             block_num: 1,
             tx_idx: 0,
@@ -115,13 +187,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn padded_address_topic_round_trips() {
+        let address = Address([0xab; 20]);
+        let topic = Topic::from(address);
+        assert_eq!(topic.0[..12], [0u8; 12]);
+        assert_eq!(topic.as_address(), Some(address));
+        assert_eq!(Topic([0xab; 32]).as_address(), None);
+    }
+
+    #[test]
+    fn builder_matches_literal_construction() {
+        let built = LogFilter::builder()
+            .address_or([Address([1; 20]), Address([2; 20])])
+            .topic0_one(Topic([10; 32]))
+            .topic2(Clause::Not(Box::new(Clause::One(Topic([12; 32])))))
+            .topic3_or([])
+            .build();
+        let literal = LogFilter {
+            address: Some(Clause::Or(vec![Address([1; 20]), Address([2; 20])])),
+            topic0: Some(Clause::One(Topic([10; 32]))),
+            topic1: None,
+            topic2: Some(Clause::Not(Box::new(Clause::One(Topic([12; 32]))))),
+            topic3: Some(Clause::Or(Vec::new())),
+        };
+        assert_eq!(built, literal);
+        assert_eq!(LogFilter::builder().build(), LogFilter::default());
+    }
+
     // --- exact_match: address clause variants ---
 
     #[test]
     fn exact_match_address_one_matches() {
         let log = log_with_topics(5, &[10]);
         let filter = LogFilter {
-            address: Some(Clause::One([5; 20])),
+            address: Some(Clause::One(Address([5; 20]))),
             ..Default::default()
         };
         assert!(exact_match(&log, &filter));
@@ -131,7 +231,7 @@ mod tests {
     fn exact_match_address_one_rejects() {
         let log = log_with_topics(5, &[10]);
         let filter = LogFilter {
-            address: Some(Clause::One([9; 20])),
+            address: Some(Clause::One(Address([9; 20]))),
             ..Default::default()
         };
         assert!(!exact_match(&log, &filter));
@@ -141,7 +241,7 @@ mod tests {
     fn exact_match_address_or_matches_second() {
         let log = log_with_topics(5, &[10]);
         let filter = LogFilter {
-            address: Some(Clause::Or(vec![[9; 20], [5; 20]])),
+            address: Some(Clause::Or(vec![Address([9; 20]), Address([5; 20])])),
             ..Default::default()
         };
         assert!(exact_match(&log, &filter));
@@ -151,7 +251,7 @@ mod tests {
     fn exact_match_address_or_rejects_all() {
         let log = log_with_topics(5, &[10]);
         let filter = LogFilter {
-            address: Some(Clause::Or(vec![[9; 20], [8; 20]])),
+            address: Some(Clause::Or(vec![Address([9; 20]), Address([8; 20])])),
             ..Default::default()
         };
         assert!(!exact_match(&log, &filter));
@@ -180,7 +280,7 @@ mod tests {
     fn exact_match_topic0_one_matches() {
         let log = log_with_topics(1, &[10, 20]);
         let filter = LogFilter {
-            topic0: Some(Clause::One([10; 32])),
+            topic0: Some(Clause::One(Topic([10; 32]))),
             ..Default::default()
         };
         assert!(exact_match(&log, &filter));
@@ -190,7 +290,7 @@ mod tests {
     fn exact_match_topic0_one_rejects() {
         let log = log_with_topics(1, &[10, 20]);
         let filter = LogFilter {
-            topic0: Some(Clause::One([99; 32])),
+            topic0: Some(Clause::One(Topic([99; 32]))),
             ..Default::default()
         };
         assert!(!exact_match(&log, &filter));
@@ -200,17 +300,29 @@ mod tests {
     fn exact_match_topic1_or_matches() {
         let log = log_with_topics(1, &[10, 20]);
         let filter = LogFilter {
-            topic1: Some(Clause::Or(vec![[99; 32], [20; 32]])),
+            topic1: Some(Clause::Or(vec![Topic([99; 32]), Topic([20; 32])])),
             ..Default::default()
         };
         assert!(exact_match(&log, &filter));
     }
 
+    #[test]
+    fn exact_match_topic1_only_ignores_topic0() {
+        let filter = LogFilter {
+            topic1: Some(Clause::One(Topic([20; 32]))),
+            ..Default::default()
+        };
+        assert!(exact_match(&log_with_topics(1, &[10, 20]), &filter));
+        assert!(exact_match(&log_with_topics(1, &[11, 20, 30]), &filter));
+        assert!(!exact_match(&log_with_topics(1, &[20]), &filter));
+        assert!(!exact_match(&log_with_topics(1, &[20, 10]), &filter));
+    }
+
     #[test]
     fn exact_match_topic2_filter_on_log_with_fewer_topics() {
         let log = log_with_topics(1, &[10]);
         let filter = LogFilter {
-            topic2: Some(Clause::One([10; 32])),
+            topic2: Some(Clause::One(Topic([10; 32]))),
             ..Default::default()
         };
         assert!(!exact_match(&log, &filter));
@@ -220,7 +332,7 @@ mod tests {
     fn exact_match_topic3_filter_on_log_with_fewer_topics() {
         let log = log_with_topics(1, &[10, 20]);
         let filter = LogFilter {
-            topic3: Some(Clause::One([10; 32])),
+            topic3: Some(Clause::One(Topic([10; 32]))),
             ..Default::default()
         };
         assert!(!exact_match(&log, &filter));
@@ -230,7 +342,7 @@ mod tests {
     fn exact_match_zero_topics_passes_no_topic_filter() {
         let log = log_with_topics(1, &[]);
         let filter = LogFilter {
-            address: Some(Clause::One([1; 20])),
+            address: Some(Clause::One(Address([1; 20]))),
             ..Default::default()
         };
         assert!(exact_match(&log, &filter));
@@ -240,7 +352,7 @@ mod tests {
     fn exact_match_zero_topics_fails_topic0_filter() {
         let log = log_with_topics(1, &[]);
         let filter = LogFilter {
-            topic0: Some(Clause::One([10; 32])),
+            topic0: Some(Clause::One(Topic([10; 32]))),
             ..Default::default()
         };
         assert!(!exact_match(&log, &filter));
@@ -250,22 +362,42 @@ mod tests {
     fn exact_match_combined_address_and_topic() {
         let log = log_with_topics(5, &[10, 20]);
         let matching = LogFilter {
-            address: Some(Clause::One([5; 20])),
-            topic0: Some(Clause::One([10; 32])),
-            topic1: Some(Clause::One([20; 32])),
+            address: Some(Clause::One(Address([5; 20]))),
+            topic0: Some(Clause::One(Topic([10; 32]))),
+            topic1: Some(Clause::One(Topic([20; 32]))),
             ..Default::default()
         };
         assert!(exact_match(&log, &matching));
 
         let wrong_topic = LogFilter {
-            address: Some(Clause::One([5; 20])),
-            topic0: Some(Clause::One([10; 32])),
-            topic1: Some(Clause::One([99; 32])),
+            address: Some(Clause::One(Address([5; 20]))),
+            topic0: Some(Clause::One(Topic([10; 32]))),
+            topic1: Some(Clause::One(Topic([99; 32]))),
             ..Default::default()
         };
         assert!(!exact_match(&log, &wrong_topic));
     }
 
+    #[test]
+    fn exact_match_not_negates_inner_clause() {
+        let filter = LogFilter {
+            topic0: Some(Clause::Not(Box::new(Clause::One(Topic([10; 32]))))),
+            ..Default::default()
+        };
+        assert!(!exact_match(&log_with_topics(5, &[10]), &filter));
+        assert!(exact_match(&log_with_topics(5, &[11]), &filter));
+        assert!(exact_match(&log_with_topics(5, &[]), &filter));
+
+        let double = LogFilter {
+            topic0: Some(Clause::Not(Box::new(Clause::Not(Box::new(Clause::One(
+                Topic([10; 32]),
+            )))))),
+            ..Default::default()
+        };
+        assert!(exact_match(&log_with_topics(5, &[10]), &double));
+        assert!(!exact_match(&log_with_topics(5, &[11]), &double));
+    }
+
     // --- has_indexed_clause ---
 
     #[test]
@@ -286,7 +418,7 @@ mod tests {
     #[test]
     fn has_indexed_clause_one_address() {
         let filter = LogFilter {
-            address: Some(Clause::One([1; 20])),
+            address: Some(Clause::One(Address([1; 20]))),
             ..Default::default()
         };
         assert!(filter.has_indexed_clause());
@@ -295,19 +427,79 @@ mod tests {
     #[test]
     fn has_indexed_clause_or_topic() {
         let filter = LogFilter {
-            topic2: Some(Clause::Or(vec![[1; 32], [2; 32]])),
+            topic2: Some(Clause::Or(vec![Topic([1; 32]), Topic([2; 32])])),
             ..Default::default()
         };
         assert!(filter.has_indexed_clause());
     }
 
+    #[test]
+    fn not_clause_is_not_indexed() {
+        let filter = LogFilter {
+            address: Some(Clause::Not(Box::new(Clause::One(Address([1; 20]))))),
+            ..Default::default()
+        };
+        assert!(!filter.has_indexed_clause());
+        assert!(filter.has_negated_clause());
+        assert!(filter.indexed_clauses().is_empty());
+        assert_eq!(filter.max_or_terms(), 0);
+    }
+
+    #[test]
+    fn range_clause_is_inclusive_and_not_indexed() {
+        let filter = LogFilter {
+            topic1: Some(Clause::Range {
+                lo: Topic([2; 32]),
+                hi: Topic([4; 32]),
+            }),
+            ..Default::default()
+        };
+        assert!(!filter.has_indexed_clause());
+        assert!(filter.has_negated_clause());
+        assert!(!filter.is_fully_indexed());
+        assert_eq!(filter.max_or_terms(), 0);
+
+        for (topic, expected) in [(1, false), (2, true), (3, true), (4, true), (5, false)] {
+            let log = log_with_topics(1, &[0, topic]);
+            assert_eq!(exact_match(&log, &filter), expected, "topic1 = {topic}");
+        }
+        assert!(!exact_match(&log_with_topics(1, &[0]), &filter));
+    }
+
+    #[test]
+    fn fully_indexed_excludes_negated_and_empty_or_clauses() {
+        let indexed = LogFilter {
+            address: Some(Clause::One(Address([1; 20]))),
+            topic0: Some(Clause::Or(vec![Topic([2; 32])])),
+            topic1: Some(Clause::Any),
+            ..Default::default()
+        };
+        assert!(indexed.is_fully_indexed());
+        assert!(LogFilter::default().is_fully_indexed());
+
+        let negated = LogFilter {
+            topic1: Some(Clause::Not(Box::new(Clause::One(Topic([3; 32]))))),
+            ..indexed.clone()
+        };
+        assert!(!negated.is_fully_indexed());
+        let empty_or = LogFilter {
+            topic2: Some(Clause::Or(Vec::new())),
+            ..indexed
+        };
+        assert!(!empty_or.is_fully_indexed());
+    }
+
     // --- max_or_terms ---
 
     #[test]
     fn max_or_terms_picks_largest() {
         let filter = LogFilter {
-            address: Some(Clause::One([1; 20])),
-            topic0: Some(Clause::Or(vec![[1; 32], [2; 32], [3; 32]])),
+            address: Some(Clause::One(Address([1; 20]))),
+            topic0: Some(Clause::Or(vec![
+                Topic([1; 32]),
+                Topic([2; 32]),
+                Topic([3; 32]),
+            ])),
             topic1: Some(Clause::Any),
             ..Default::default()
         };
@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
 use bytes::Bytes;
 
@@ -9,7 +10,7 @@ use crate::family::FinalizedBlock;
 use crate::ingest::bitmap_pages;
 use crate::ingest::indexed_family::{collect_grouped_stream_appends, iter_grouped_stream_appends};
 use crate::kernel::codec::StorageCodec;
-use crate::kernel::sharded_streams::sharded_stream_id;
+use crate::kernel::sharded_streams::{StreamKind, sharded_stream_id};
 use crate::logs::STREAM_PAGE_LOCAL_ID_SPAN;
 use crate::logs::codec::validate_log;
 use crate::logs::types::{BlockLogHeader, Log};
@@ -23,10 +24,50 @@ pub struct LogIngestPlan {
     pub stream_appends_by_stream: BTreeMap<String, Vec<u32>>,
 }
 
-pub fn plan_log_ingest(block: &FinalizedBlock, first_log_id: u64) -> Result<LogIngestPlan> {
-    validate_logs(block)?;
-    let (header, block_blob) = encode_log_block(&block.logs)?;
-    let stream_appends_by_stream = collect_log_stream_appends(block, first_log_id)?;
+/// Rewrites a log in place before it is validated, encoded, and indexed. See
+/// `Config::log_rewriter`.
+pub type LogRewriter = Arc<dyn Fn(&mut Log) + Send + Sync>;
+
+/// Validates and encodes one block's logs. With `store_data` false every log
+/// is encoded with empty `data`; indexing only uses address and topics.
+pub fn plan_log_ingest(
+    block: &FinalizedBlock,
+    first_log_id: u64,
+    store_data: bool,
+) -> Result<LogIngestPlan> {
+    plan_logs(block, &block.logs, first_log_id, store_data)
+}
+
+/// [`plan_log_ingest`] over copies of the block's logs passed through
+/// `rewrite`, so the stored bodies and the stream appends both reflect the
+/// rewritten values.
+pub fn plan_rewritten_log_ingest(
+    block: &FinalizedBlock,
+    first_log_id: u64,
+    store_data: bool,
+    rewrite: &LogRewriter,
+) -> Result<LogIngestPlan> {
+    let logs = block
+        .logs
+        .iter()
+        .cloned()
+        .map(|mut log| {
+            rewrite(&mut log);
+            log
+        })
+        .collect::<Vec<_>>();
+    plan_logs(block, &logs, first_log_id, store_data)
+}
+
+fn plan_logs(
+    block: &FinalizedBlock,
+    logs: &[Log],
+    first_log_id: u64,
+    store_data: bool,
+) -> Result<LogIngestPlan> {
+    validate_logs(block, logs)?;
+    let (header, block_blob) = encode_log_block(logs, store_data)?;
+    let stream_appends_by_stream = collect_log_stream_appends(logs, first_log_id)?;
 
     Ok(LogIngestPlan {
         header,
@@ -39,12 +80,14 @@ pub async fn persist_log_stream_fragments<M: MetaStore, B: BlobStore>(
     tables: &Tables<M, B>,
     block_num: u64,
     grouped_values: &BTreeMap<String, Vec<u32>>,
+    concurrency: usize,
 ) -> Result<Vec<(String, u32)>> {
     bitmap_pages::persist_stream_fragments(
         &tables.log_streams,
         block_num,
         iter_grouped_stream_appends(grouped_values),
         STREAM_PAGE_LOCAL_ID_SPAN,
+        concurrency,
     )
     .await
 }
@@ -61,10 +104,10 @@ pub async fn persist_log_artifacts<M: MetaStore, B: BlobStore>(
     Ok(plan.header.log_count())
 }
 
-fn validate_logs(block: &FinalizedBlock) -> Result<()> {
+fn validate_logs(block: &FinalizedBlock, logs: &[Log]) -> Result<()> {
     let mut previous_tx_idx = None;
 
-    for (index, log) in block.logs.iter().enumerate() {
+    for (index, log) in logs.iter().enumerate() {
         if !validate_log(log) {
             return Err(Error::InvalidParams("log topics exceed 4"));
         }
@@ -99,7 +142,7 @@ fn validate_logs(block: &FinalizedBlock) -> Result<()> {
     Ok(())
 }
 
-fn encode_log_block(logs: &[Log]) -> Result<(BlockLogHeader, Bytes)> {
+fn encode_log_block(logs: &[Log], store_data: bool) -> Result<(BlockLogHeader, Bytes)> {
     let mut offsets = BucketedOffsets::new();
     let mut out = Vec::<u8>::new();
 
@@ -107,7 +150,15 @@ fn encode_log_block(logs: &[Log]) -> Result<(BlockLogHeader, Bytes)> {
         offsets.push(
             u64::try_from(out.len()).map_err(|_| Error::Decode("block log offset overflow"))?,
         )?;
-        out.extend_from_slice(&log.encode());
+        if store_data || log.data.is_empty() {
+            out.extend_from_slice(&log.encode());
+        } else {
+            let without_data = Log {
+                data: Vec::new(),
+                ..log.clone()
+            };
+            out.extend_from_slice(&without_data.encode());
+        }
     }
 
     offsets
@@ -117,33 +168,29 @@ fn encode_log_block(logs: &[Log]) -> Result<(BlockLogHeader, Bytes)> {
 }
 
 fn collect_log_stream_appends(
-    block: &FinalizedBlock,
+    logs: &[Log],
     first_log_id: u64,
 ) -> Result<BTreeMap<String, Vec<u32>>> {
-    collect_grouped_stream_appends(first_log_id, block.logs.iter(), |log, primary_id| {
+    collect_grouped_stream_appends(first_log_id, logs.iter(), |log, primary_id| {
         Ok(stream_entries_for_log(log, LogId::new(primary_id)))
     })
 }
 
-fn stream_entries_for_log(log: &Log, global_log_id: LogId) -> Vec<(String, u32)> {
+pub(crate) fn stream_entries_for_log(log: &Log, global_log_id: LogId) -> Vec<(String, u32)> {
     let shard = global_log_id.shard().get();
     let local = global_log_id.local().get();
 
     let mut entries = Vec::with_capacity(5);
-    entries.push((sharded_stream_id("addr", &log.address, shard), local));
-
-    if let Some(topic0) = log.topics.first() {
-        entries.push((sharded_stream_id("topic0", topic0, shard), local));
-    }
-
-    for (topic_index, topic) in log.topics.iter().enumerate().skip(1).take(3) {
-        let kind = match topic_index {
-            1 => "topic1",
-            2 => "topic2",
-            3 => "topic3",
-            _ => continue,
+    entries.push((
+        sharded_stream_id(StreamKind::Addr, log.address.as_ref(), shard),
+        local,
+    ));
+
+    for (topic_index, topic) in log.topics.iter().enumerate() {
+        let Some(kind) = StreamKind::log_topic(topic_index) else {
+            break;
         };
-        entries.push((sharded_stream_id(kind, topic, shard), local));
+        entries.push((sharded_stream_id(kind, topic.as_ref(), shard), local));
     }
 
     entries
@@ -154,7 +201,7 @@ pub fn collect_stream_appends(
     block: &FinalizedBlock,
     first_log_id: u64,
 ) -> BTreeMap<String, Vec<u32>> {
-    plan_log_ingest(block, first_log_id)
+    plan_log_ingest(block, first_log_id, true)
         .expect("valid log ingest plan")
         .stream_appends_by_stream
 }
@@ -188,12 +235,14 @@ mod tests {
     use crate::ingest::primary_dir::compact_sealed_primary_directory;
     use crate::kernel::sharded_streams::page_start_local;
     use crate::logs::STREAM_PAGE_LOCAL_ID_SPAN;
-    use crate::logs::types::{BlockLogHeader, DirBucket, DirByBlock, Log, StreamBitmapMeta};
+    use crate::logs::types::{
+        Address, BlockLogHeader, DirBucket, DirByBlock, Log, StreamBitmapMeta, Topic,
+    };
 
     fn sample_log(block_num: u64, tx_idx: u32, log_idx: u32, seed: u8) -> Log {
         Log {
-            address: [seed; 20],
-            topics: vec![[seed.wrapping_add(1); 32]],
+            address: Address([seed; 20]),
+            topics: vec![Topic([seed.wrapping_add(1); 32])],
             data: vec![seed, seed.wrapping_add(2)],
             block_num,
             tx_idx,
@@ -232,7 +281,7 @@ mod tests {
             let tables = Tables::without_cache(meta.clone(), blob.clone());
             let logs = vec![sample_log(7, 0, 0, 1), sample_log(7, 0, 1, 2)];
             let block = sample_block(7, 9, logs.clone());
-            let plan = plan_log_ingest(&block, 11).expect("plan log ingest");
+            let plan = plan_log_ingest(&block, 11, true).expect("plan log ingest");
 
             persist_log_artifacts(&tables, block.block_num, &plan)
                 .await
@@ -270,9 +319,15 @@ mod tests {
             let blob = InMemoryBlobStore::default();
             let _tables = Tables::without_cache(meta.clone(), blob.clone());
             let mut block = sample_block(7, 9, vec![sample_log(7, 0, 0, 1)]);
-            block.logs[0].topics = vec![[1; 32], [2; 32], [3; 32], [4; 32], [5; 32]];
+            block.logs[0].topics = vec![
+                Topic([1; 32]),
+                Topic([2; 32]),
+                Topic([3; 32]),
+                Topic([4; 32]),
+                Topic([5; 32]),
+            ];
 
-            let err = plan_log_ingest(&block, 11).expect_err("invalid log should fail");
+            let err = plan_log_ingest(&block, 11, true).expect_err("invalid log should fail");
 
             assert!(matches!(err, Error::InvalidParams("log topics exceed 4")));
             assert!(
@@ -298,7 +353,7 @@ mod tests {
             let mut block = sample_block(7, 9, vec![sample_log(7, 0, 0, 1)]);
             block.logs[0].block_num = 8;
 
-            let err = plan_log_ingest(&block, 11).expect_err("invalid log should fail");
+            let err = plan_log_ingest(&block, 11, true).expect_err("invalid log should fail");
 
             assert!(matches!(
                 err,
@@ -314,7 +369,7 @@ mod tests {
                 Tables::without_cache(InMemoryMetaStore::default(), InMemoryBlobStore::default());
             let block = sample_block(7, 9, vec![sample_log(7, 1, 0, 1), sample_log(7, 0, 1, 2)]);
 
-            let err = plan_log_ingest(&block, 11).expect_err("invalid log order should fail");
+            let err = plan_log_ingest(&block, 11, true).expect_err("invalid log order should fail");
 
             assert!(matches!(
                 err,
@@ -330,7 +385,7 @@ mod tests {
                 Tables::without_cache(InMemoryMetaStore::default(), InMemoryBlobStore::default());
             let block = sample_block(7, 9, vec![sample_log(7, 0, 0, 1), sample_log(7, 0, 2, 2)]);
 
-            let err = plan_log_ingest(&block, 11).expect_err("invalid log index should fail");
+            let err = plan_log_ingest(&block, 11, true).expect_err("invalid log index should fail");
 
             assert!(matches!(
                 err,
@@ -356,7 +411,8 @@ mod tests {
                 trace_rlp: Vec::new(),
             };
 
-            let err = plan_log_ingest(&block, 11).expect_err("invalid block hash should fail");
+            let err =
+                plan_log_ingest(&block, 11, true).expect_err("invalid block hash should fail");
 
             assert!(matches!(
                 err,
@@ -372,7 +428,7 @@ mod tests {
             let blob = InMemoryBlobStore::default();
             let tables = Tables::without_cache(meta.clone(), blob.clone());
             let block = sample_block(7, 9, Vec::new());
-            let plan = plan_log_ingest(&block, 11).expect("plan log ingest");
+            let plan = plan_log_ingest(&block, 11, true).expect("plan log ingest");
 
             persist_log_artifacts(&tables, block.block_num, &plan)
                 .await
@@ -491,11 +547,12 @@ mod tests {
                 ],
             );
             let first_log_id = u64::from(STREAM_PAGE_LOCAL_ID_SPAN - 2);
-            let plan = plan_log_ingest(&block, first_log_id).expect("plan log ingest");
+            let plan = plan_log_ingest(&block, first_log_id, true).expect("plan log ingest");
             let touched_pages = persist_log_stream_fragments(
                 &tables,
                 block.block_num,
                 &plan.stream_appends_by_stream,
+                1,
             )
             .await
             .expect("persist stream fragments");
@@ -504,10 +561,11 @@ mod tests {
                     &tables.log_streams,
                     stream_id,
                     *page_start,
-                    |count, min_local, max_local| StreamBitmapMeta {
+                    |count, min_local, max_local, byte_size| StreamBitmapMeta {
                         count,
                         min_local,
                         max_local,
+                        byte_size,
                     },
                 )
                 .await
@@ -554,12 +612,10 @@ mod tests {
                     .count
                     > 0
             );
-            assert!(
-                StreamBitmapMeta::decode(&page_meta.value)
-                    .expect("decode stream page meta")
-                    .count
-                    > 0
-            );
+            let page_meta =
+                StreamBitmapMeta::decode(&page_meta.value).expect("decode stream page meta");
+            assert!(page_meta.count > 0);
+            assert_eq!(page_meta.byte_size, page_blob.len() as u64);
             assert!(
                 decode_bitmap_blob(&page_blob)
                     .expect("decode stream page blob")
@@ -0,0 +1,136 @@
+//! Dictionary export: the distinct values that have a log stream of a given
+//! kind, such as every indexed address or topic0 signature.
+//!
+//! Values come from two places. Sealed pages have a compacted blob keyed by
+//! stream ID, and a blob-key listing under `"{kind}/"` visits them in value
+//! order, each value's shards and pages adjacent. Pages still open, or sealed
+//! but not yet compacted, only have an open-page marker, so markers are
+//! merged in as well.
+
+use std::collections::BTreeSet;
+
+use crate::core::ids::LogId;
+use crate::error::{Error, Result};
+use crate::kernel::sharded_streams::{StreamKind, parse_stream_id, split_stream_page_key};
+use crate::store::traits::{BlobStore, MetaStore};
+use crate::tables::Tables;
+
+/// The log stream kinds accepted by [`list_indexed_log_keys`].
+pub const LOG_STREAM_KINDS: [StreamKind; 5] = [
+    StreamKind::Addr,
+    StreamKind::Topic0,
+    StreamKind::Topic1,
+    StreamKind::Topic2,
+    StreamKind::Topic3,
+];
+
+const PAGE_BLOB_LIST_BATCH: usize = 1_024;
+
+/// Resumes a key listing after the last value returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedKeyCursor {
+    last_value: Vec<u8>,
+    // Blob listing cursor of the batch that held `last_value`. The batch is
+    // listed again and values up to `last_value` are skipped.
+    batch_cursor: Option<Vec<u8>>,
+    blobs_exhausted: bool,
+}
+
+/// Returns up to `limit` distinct values of `kind` streams in ascending byte
+/// order, with a cursor when the listing stopped at `limit`. `next_log_id`
+/// bounds the shards whose open-page markers are read.
+pub(crate) async fn list_indexed_log_keys<M: MetaStore, B: BlobStore>(
+    tables: &Tables<M, B>,
+    next_log_id: u64,
+    kind: StreamKind,
+    cursor: Option<IndexedKeyCursor>,
+    limit: usize,
+) -> Result<(Vec<Vec<u8>>, Option<IndexedKeyCursor>)> {
+    if !LOG_STREAM_KINDS.contains(&kind) {
+        return Err(Error::InvalidParams("not a log stream kind"));
+    }
+    if limit == 0 {
+        return Err(Error::InvalidParams("limit must be greater than zero"));
+    }
+    let (after, mut store_cursor, mut blobs_exhausted) = match cursor {
+        Some(cursor) => (
+            Some(cursor.last_value),
+            cursor.batch_cursor,
+            cursor.blobs_exhausted,
+        ),
+        None => (None, None, false),
+    };
+    let is_new = |value: &[u8]| after.as_deref().is_none_or(|after| value > after);
+
+    let mut open_values = BTreeSet::new();
+    if next_log_id > 0 {
+        let frontier_shard = LogId::new(next_log_id - 1).shard().get();
+        for shard in 0..=frontier_shard {
+            for page in tables.log_open_bitmap_pages.list_for_shard(shard).await? {
+                if let Some((page_kind, value, _)) = parse_stream_id(&page.stream_id)
+                    && page_kind == kind
+                    && is_new(&value)
+                {
+                    open_values.insert(value);
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    let next_cursor = |out: &Vec<Vec<u8>>, batch_cursor, blobs_exhausted| IndexedKeyCursor {
+        last_value: out.last().cloned().unwrap_or_default(),
+        batch_cursor,
+        blobs_exhausted,
+    };
+    let prefix = format!("{kind}/").into_bytes();
+    while !blobs_exhausted {
+        let batch_cursor = store_cursor.clone();
+        let page = tables
+            .log_streams
+            .list_page_blob_keys(&prefix, store_cursor.take(), PAGE_BLOB_LIST_BATCH)
+            .await?;
+        for key in &page.keys {
+            let value = page_blob_key_value(key)?;
+            if !is_new(&value) || out.last() == Some(&value) {
+                continue;
+            }
+            while let Some(open_value) = open_values.pop_first() {
+                if open_value >= value {
+                    open_values.insert(open_value);
+                    break;
+                }
+                out.push(open_value);
+                if out.len() == limit {
+                    let cursor = next_cursor(&out, batch_cursor, false);
+                    return Ok((out, Some(cursor)));
+                }
+            }
+            open_values.remove(&value);
+            out.push(value);
+            if out.len() == limit {
+                let cursor = next_cursor(&out, batch_cursor, false);
+                return Ok((out, Some(cursor)));
+            }
+        }
+        store_cursor = page.next_cursor;
+        blobs_exhausted = store_cursor.is_none();
+    }
+
+    for open_value in open_values {
+        out.push(open_value);
+        if out.len() == limit {
+            let cursor = next_cursor(&out, None, true);
+            return Ok((out, Some(cursor)));
+        }
+    }
+    Ok((out, None))
+}
+
+/// Recovers the stream value from a `"{stream_id}/" + u64 page` blob key.
+fn page_blob_key_value(key: &[u8]) -> Result<Vec<u8>> {
+    let invalid = || Error::Decode("invalid stream page blob key");
+    let (stream_id, _) = split_stream_page_key(key).ok_or_else(invalid)?;
+    let (_, value, _) = parse_stream_id(stream_id).ok_or_else(invalid)?;
+    Ok(value)
+}
@@ -2,7 +2,7 @@ use bytes::Bytes;
 
 use crate::error::{Error, Result};
 use crate::family::Hash32;
-use crate::logs::types::{Address20, Log, Topic32};
+use crate::logs::types::{Address, Address20, Log, Topic, Topic32};
 
 /// Zero-copy view over an encoded log record.
 ///
@@ -28,6 +28,9 @@ pub struct LogRef {
 impl LogRef {
     pub fn new(buf: Bytes) -> Result<Self> {
         const MIN_LEN: usize = 20 + 1 + 4 + 8 + 4 + 4 + 32; // 73
+        if buf.is_empty() {
+            return Err(Error::Decode("empty log payload"));
+        }
         if buf.len() < MIN_LEN {
             return Err(Error::Decode("log too short"));
         }
@@ -108,10 +111,15 @@ impl LogRef {
         self.buf[off..off + 32].try_into().unwrap()
     }
 
+    /// Length of the encoded log, which is what `max_result_bytes` counts.
+    pub fn encoded_len(&self) -> usize {
+        self.buf.len()
+    }
+
     pub fn to_owned_log(&self) -> Log {
         Log {
-            address: *self.address(),
-            topics: self.topics().copied().collect(),
+            address: Address(*self.address()),
+            topics: self.topics().copied().map(Topic).collect(),
             data: self.data().to_vec(),
             block_num: self.block_num(),
             tx_idx: self.tx_idx(),
@@ -269,13 +277,13 @@ impl LogView for LogRef {
 
 impl LogView for Log {
     fn address(&self) -> &Address20 {
-        &self.address
+        &self.address.0
     }
     fn topic_count(&self) -> usize {
         self.topics.len()
     }
     fn topic(&self, i: usize) -> &Topic32 {
-        &self.topics[i]
+        &self.topics[i].0
     }
     fn data(&self) -> &[u8] {
         &self.data
@@ -301,8 +309,8 @@ mod tests {
 
     fn test_log() -> Log {
         Log {
-            address: [7u8; 20],
-            topics: vec![[1u8; 32], [2u8; 32]],
+            address: Address([7u8; 20]),
+            topics: vec![Topic([1u8; 32]), Topic([2u8; 32])],
             data: vec![9, 8, 7],
             block_num: 12,
             tx_idx: 3,
@@ -317,10 +325,10 @@ mod tests {
         let encoded = log.encode();
         let log_ref = LogRef::new(encoded).expect("construct LogRef");
 
-        assert_eq!(log_ref.address(), &log.address);
+        assert_eq!(log_ref.address(), &log.address.0);
         assert_eq!(log_ref.topic_count(), 2);
-        assert_eq!(log_ref.topic(0), &log.topics[0]);
-        assert_eq!(log_ref.topic(1), &log.topics[1]);
+        assert_eq!(log_ref.topic(0), &log.topics[0].0);
+        assert_eq!(log_ref.topic(1), &log.topics[1].0);
         assert_eq!(log_ref.data(), &log.data[..]);
         assert_eq!(log_ref.block_num(), log.block_num);
         assert_eq!(log_ref.tx_idx(), log.tx_idx);
@@ -335,10 +343,10 @@ mod tests {
         let log_ref = LogRef::new(encoded).expect("construct LogRef");
 
         fn check_view(view: &impl LogView, expected: &Log) {
-            assert_eq!(view.address(), &expected.address);
+            assert_eq!(view.address(), &expected.address.0);
             assert_eq!(view.topic_count(), expected.topics.len());
             for i in 0..expected.topics.len() {
-                assert_eq!(view.topic(i), &expected.topics[i]);
+                assert_eq!(view.topic(i), &expected.topics[i].0);
             }
             assert_eq!(view.data(), &expected.data[..]);
             assert_eq!(view.block_num(), expected.block_num);
@@ -354,7 +362,7 @@ mod tests {
     #[test]
     fn log_ref_zero_topics() {
         let log = Log {
-            address: [1u8; 20],
+            address: Address([1u8; 20]),
             topics: vec![],
             data: vec![42],
             block_num: 1,
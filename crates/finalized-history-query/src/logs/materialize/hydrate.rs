@@ -106,4 +106,8 @@ impl<M: MetaStore, B: BlobStore> QueryMaterializer for LogMaterializer<'_, M, B>
     fn into_output(item: Self::Item) -> Self::Output {
         item
     }
+
+    fn item_bytes(item: &Self::Item) -> usize {
+        item.encoded_len()
+    }
 }
@@ -1,4 +1,7 @@
 mod hydrate;
+mod positions;
+
+pub use positions::LogPositionMaterializer;
 
 use crate::logs::types::DirByBlock;
 use crate::query::runner::MaterializerCaches;
@@ -36,13 +39,14 @@ mod tests {
     use crate::core::ids::LogId;
     use crate::core::layout::{DIRECTORY_BUCKET_SIZE, DIRECTORY_SUB_BUCKET_SIZE};
     use crate::core::offsets::BucketedOffsets;
+    use crate::error::Error;
     use crate::kernel::codec::StorageCodec;
     use crate::kernel::table_specs::{PointTableSpec, ScannableTableSpec};
     use crate::logs::table_specs::{
         BlobTableSpec, BlockLogBlobSpec, BlockLogHeaderSpec, LogDirBucketSpec, LogDirByBlockSpec,
         LogDirSubBucketSpec,
     };
-    use crate::logs::types::{BlockLogHeader, DirBucket, DirByBlock, Log};
+    use crate::logs::types::{Address, BlockLogHeader, DirBucket, DirByBlock, Log, Topic};
     use crate::query::runner::QueryMaterializer;
     use crate::store::blob::InMemoryBlobStore;
     use crate::store::meta::InMemoryMetaStore;
@@ -231,8 +235,8 @@ mod tests {
             let block_num = 700u64;
             let log_id = LogId::new(DIRECTORY_SUB_BUCKET_SIZE);
             let log = Log {
-                address: [7u8; 20],
-                topics: vec![[8u8; 32]],
+                address: Address([7u8; 20]),
+                topics: vec![Topic([8u8; 32])],
                 data: vec![1, 2, 3],
                 block_num,
                 tx_idx: 1,
@@ -324,8 +328,8 @@ mod tests {
             let block_num = 701u64;
             let logs = [
                 Log {
-                    address: [1u8; 20],
-                    topics: vec![[11u8; 32]],
+                    address: Address([1u8; 20]),
+                    topics: vec![Topic([11u8; 32])],
                     data: vec![1],
                     block_num,
                     tx_idx: 0,
@@ -333,8 +337,8 @@ mod tests {
                     block_hash: [9u8; 32],
                 },
                 Log {
-                    address: [2u8; 20],
-                    topics: vec![[12u8; 32]],
+                    address: Address([2u8; 20]),
+                    topics: vec![Topic([12u8; 32])],
                     data: vec![2, 2],
                     block_num,
                     tx_idx: 0,
@@ -342,8 +346,8 @@ mod tests {
                     block_hash: [9u8; 32],
                 },
                 Log {
-                    address: [3u8; 20],
-                    topics: vec![[13u8; 32]],
+                    address: Address([3u8; 20]),
+                    topics: vec![Topic([13u8; 32])],
                     data: vec![3, 3, 3],
                     block_num,
                     tx_idx: 0,
@@ -408,6 +412,147 @@ mod tests {
         });
     }
 
+    async fn write_single_block_logs(
+        meta: &InMemoryMetaStore,
+        blob: &InMemoryBlobStore,
+        block_num: u64,
+        first_primary_id: u64,
+        log_count: u64,
+        header_offsets: &[u64],
+        blob_bytes: Bytes,
+    ) {
+        meta.scan_put(
+            LogDirByBlockSpec::TABLE,
+            &LogDirByBlockSpec::partition(LogDirSubBucketSpec::sub_bucket_start(first_primary_id)),
+            &LogDirByBlockSpec::clustering(block_num),
+            DirByBlock {
+                block_num,
+                first_primary_id,
+                end_primary_id_exclusive: first_primary_id + log_count,
+            }
+            .encode(),
+            PutCond::Any,
+        )
+        .await
+        .expect("write directory fragment");
+        let mut offsets = BucketedOffsets::new();
+        for offset in header_offsets {
+            offsets.push(*offset).expect("offset");
+        }
+        meta.put(
+            BlockLogHeaderSpec::TABLE,
+            &BlockLogHeaderSpec::key(block_num),
+            BlockLogHeader { offsets }.encode(),
+            PutCond::Any,
+        )
+        .await
+        .expect("write block log header");
+        blob.put_blob(
+            BlockLogBlobSpec::TABLE,
+            &BlockLogBlobSpec::key(block_num),
+            blob_bytes,
+        )
+        .await
+        .expect("write block log blob");
+    }
+
+    fn sample_log(block_num: u64, log_idx: u32) -> Log {
+        Log {
+            address: Address([4u8; 20]),
+            topics: vec![Topic([5u8; 32])],
+            data: vec![6, 6],
+            block_num,
+            tx_idx: 0,
+            log_idx,
+            block_hash: [9u8; 32],
+        }
+    }
+
+    #[test]
+    fn load_by_id_rejects_zero_length_log_span() {
+        block_on(async {
+            let meta = InMemoryMetaStore::default();
+            let blob = InMemoryBlobStore::default();
+            let block_num = 702u64;
+            let log_id = LogId::new(DIRECTORY_SUB_BUCKET_SIZE);
+            let encoded = sample_log(block_num, 1).encode();
+            let end = encoded.len() as u64;
+            write_single_block_logs(
+                &meta,
+                &blob,
+                block_num,
+                log_id.get(),
+                2,
+                &[0, 0, end],
+                encoded,
+            )
+            .await;
+
+            let tables = Tables::new(meta, blob, BytesCacheConfig::disabled());
+            let mut materializer = LogMaterializer::new(&tables);
+            let err = QueryMaterializer::load_by_id(&mut materializer, log_id)
+                .await
+                .expect_err("zero-length span must not decode");
+            assert!(matches!(err, Error::Decode("empty log payload")));
+        });
+    }
+
+    #[test]
+    fn load_by_id_rejects_log_span_beyond_shrunk_blob() {
+        block_on(async {
+            let meta = InMemoryMetaStore::default();
+            let blob = InMemoryBlobStore::default();
+            let block_num = 703u64;
+            let log_id = LogId::new(DIRECTORY_SUB_BUCKET_SIZE);
+            let encoded = sample_log(block_num, 0).encode();
+            let end = encoded.len() as u64;
+            write_single_block_logs(
+                &meta,
+                &blob,
+                block_num,
+                log_id.get(),
+                1,
+                &[0, end],
+                encoded.slice(..encoded.len() - 8),
+            )
+            .await;
+
+            let tables = Tables::new(meta, blob, BytesCacheConfig::disabled());
+            let mut materializer = LogMaterializer::new(&tables);
+            let err = QueryMaterializer::load_by_id(&mut materializer, log_id)
+                .await
+                .expect_err("span past the blob end must not load");
+            assert!(matches!(err, Error::Decode(_)));
+        });
+    }
+
+    #[test]
+    fn load_by_id_rejects_ordinal_beyond_block_log_header() {
+        block_on(async {
+            let meta = InMemoryMetaStore::default();
+            let blob = InMemoryBlobStore::default();
+            let block_num = 704u64;
+            let first_id = DIRECTORY_SUB_BUCKET_SIZE;
+            let encoded = sample_log(block_num, 0).encode();
+            let end = encoded.len() as u64;
+            write_single_block_logs(&meta, &blob, block_num, first_id, 2, &[0, end], encoded).await;
+
+            let tables = Tables::new(meta, blob, BytesCacheConfig::disabled());
+            let mut materializer = LogMaterializer::new(&tables);
+            QueryMaterializer::load_by_id(&mut materializer, LogId::new(first_id))
+                .await
+                .expect("covered ordinal loads")
+                .expect("covered log");
+            let err = QueryMaterializer::load_by_id(&mut materializer, LogId::new(first_id + 1))
+                .await
+                .expect_err("uncovered ordinal must not read as missing");
+            assert!(matches!(
+                err,
+                Error::Decode("log ordinal beyond block log header")
+            ));
+        });
+    }
+
     #[test]
     fn directory_fragment_loading_returns_block_sorted_fragments() {
         block_on(async {
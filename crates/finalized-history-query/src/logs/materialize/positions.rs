@@ -0,0 +1,122 @@
+use crate::core::directory_resolver::{ResolvedPrimaryLocation, resolve_primary_id};
+use crate::core::ids::LogId;
+use crate::core::range::load_block_ref;
+use crate::core::refs::BlockRef;
+use crate::error::{Error, Result};
+use crate::logs::filter::LogFilter;
+use crate::logs::types::{DirByBlock, LogPosition};
+use crate::query::runner::{MaterializerCaches, QueryMaterializer};
+use crate::store::traits::{BlobStore, MetaStore};
+use crate::tables::Tables;
+
+/// Materializes matches as [`LogPosition`]s from the log directory alone, so
+/// no log payload is read.
+///
+/// `exact_match` accepts every candidate. Use it only with filters where
+/// [`LogFilter::is_fully_indexed`] holds, so bitmap intersection has already
+/// decided the match.
+pub struct LogPositionMaterializer<'a, M: MetaStore, B: BlobStore> {
+    tables: &'a Tables<M, B>,
+    caches: MaterializerCaches<DirByBlock>,
+}
+
+impl<'a, M: MetaStore, B: BlobStore> LogPositionMaterializer<'a, M, B> {
+    pub fn new(tables: &'a Tables<M, B>) -> Self {
+        Self {
+            tables,
+            caches: MaterializerCaches::default(),
+        }
+    }
+}
+
+fn position(id: LogId, location: ResolvedPrimaryLocation) -> Result<LogPosition> {
+    Ok(LogPosition {
+        log_id: id,
+        block_num: location.block_num,
+        log_idx: u32::try_from(location.local_ordinal)
+            .map_err(|_| Error::Decode("log index overflow"))?,
+    })
+}
+
+impl<M: MetaStore, B: BlobStore> QueryMaterializer for LogPositionMaterializer<'_, M, B> {
+    type Id = LogId;
+    type Item = LogPosition;
+    type Filter = LogFilter;
+    type Output = LogPosition;
+
+    async fn resolve_id(&mut self, id: Self::Id) -> Result<Option<ResolvedPrimaryLocation>> {
+        Ok(resolve_primary_id::<M, LogId>(
+            &self.tables.log_dir,
+            &mut self.caches.directory_fragment_cache,
+            id,
+        )
+        .await?
+        .map(|location| ResolvedPrimaryLocation {
+            block_num: location.block_num,
+            local_ordinal: location.local_ordinal,
+        }))
+    }
+
+    async fn load_run(
+        &mut self,
+        run: &[(Self::Id, ResolvedPrimaryLocation)],
+    ) -> Result<Vec<(Self::Id, Self::Item)>> {
+        run.iter()
+            .map(|&(id, location)| Ok((id, position(id, location)?)))
+            .collect()
+    }
+
+    async fn load_block(&mut self, block_num: u64) -> Result<Vec<(Self::Id, Self::Item)>> {
+        let Some(window) = self
+            .tables
+            .block_records
+            .get(block_num)
+            .await?
+            .and_then(|record| record.logs)
+        else {
+            return Ok(Vec::new());
+        };
+        (0..window.count)
+            .map(|log_idx| {
+                let id = window
+                    .first_primary_id
+                    .checked_add(u64::from(log_idx))
+                    .map(LogId::new)
+                    .ok_or(Error::Decode("log id overflow"))?;
+                Ok((
+                    id,
+                    LogPosition {
+                        log_id: id,
+                        block_num,
+                        log_idx,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    async fn block_ref_for(&mut self, item: &Self::Item) -> Result<BlockRef> {
+        if let Some(block_ref) = self.caches.block_ref_cache.get(&item.block_num) {
+            return Ok(*block_ref);
+        }
+        let block_ref = load_block_ref(self.tables, item.block_num)
+            .await?
+            .ok_or(Error::NotFound)?;
+        self.caches
+            .block_ref_cache
+            .insert(item.block_num, block_ref);
+        Ok(block_ref)
+    }
+
+    fn exact_match(&self, _item: &Self::Item, _filter: &Self::Filter) -> bool {
+        true
+    }
+
+    fn into_output(item: Self::Item) -> Self::Output {
+        item
+    }
+
+    fn item_bytes(_item: &Self::Item) -> usize {
+        size_of::<LogPosition>()
+    }
+}
@@ -2,6 +2,7 @@ pub mod codec;
 pub(crate) mod family;
 pub mod filter;
 pub(crate) mod ingest;
+pub mod keys;
 pub mod log_ref;
 pub mod materialize;
 pub mod table_specs;
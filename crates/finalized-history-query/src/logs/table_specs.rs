@@ -95,6 +95,11 @@ impl ScannableTableSpec for LogOpenBitmapPageSpec {
     const TABLE: ScannableTableId = ScannableTableId::new("log_open_bitmap_page");
 }
 
+pub struct LogStreamShardSpec;
+impl ScannableTableSpec for LogStreamShardSpec {
+    const TABLE: ScannableTableId = ScannableTableId::new("log_stream_shard");
+}
+
 pub struct BlockLogBlobSpec;
 impl BlobTableSpec for BlockLogBlobSpec {
     const TABLE: BlobTableId = BlobTableId::new("block_log_blob");
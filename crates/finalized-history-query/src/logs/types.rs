@@ -8,10 +8,109 @@ pub use crate::streams::StreamBitmapMeta;
 pub type Address20 = [u8; 20];
 pub type Topic32 = [u8; 32];
 
+/// A log emitter address. A distinct type from [`Topic`] so filters and logs
+/// cannot mix the two up. It encodes as the bare 20 bytes and serializes as
+/// `0x`-prefixed hex in human-readable formats.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct Address(#[serde(with = "crate::core::hex_serde")] pub Address20);
+
+/// A log topic. Encodes as the bare 32 bytes and serializes like
+/// [`Address`].
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct Topic(#[serde(with = "crate::core::hex_serde")] pub Topic32);
+
+macro_rules! byte_newtype {
+    ($name:ident, $array:ty) => {
+        impl From<$array> for $name {
+            fn from(bytes: $array) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl From<$name> for $array {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl From<$name> for Vec<u8> {
+            fn from(value: $name) -> Self {
+                value.0.to_vec()
+            }
+        }
+
+        impl core::ops::Deref for $name {
+            type Target = $array;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+    };
+}
+
+byte_newtype!(Address, Address20);
+byte_newtype!(Topic, Topic32);
+
+/// Left-pads the address to 32 bytes, the ABI encoding of an indexed
+/// `address` event parameter.
+impl From<Address> for Topic {
+    fn from(address: Address) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[12..].copy_from_slice(&address.0);
+        Self(bytes)
+    }
+}
+
+impl Topic {
+    /// The address this topic left-pads, or `None` when any of the 12 high
+    /// bytes is nonzero.
+    pub fn as_address(&self) -> Option<Address> {
+        if self.0[..12].iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        let mut bytes = [0u8; 20];
+        bytes.copy_from_slice(&self.0[12..]);
+        Some(Address(bytes))
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct Log {
-    pub address: Address20,
-    pub topics: Vec<Topic32>,
+    pub address: Address,
+    pub topics: Vec<Topic>,
     pub data: Vec<u8>,
     pub block_num: u64,
     pub tx_idx: u32,
@@ -19,6 +118,15 @@ pub struct Log {
     pub block_hash: Hash32,
 }
 
+/// Identifies one log by its primary ID and its position in the block,
+/// without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogPosition {
+    pub log_id: LogId,
+    pub block_num: u64,
+    pub log_idx: u32,
+}
+
 pub type DirBucket = PrimaryDirBucket;
 pub type DirByBlock = PrimaryDirFragment;
 
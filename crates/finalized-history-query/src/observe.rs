@@ -0,0 +1,22 @@
+//! Optional `tracing` instrumentation behind the `tracing` feature. Without
+//! the feature these macros expand to nothing and their arguments are never
+//! evaluated, so call sites pay nothing.
+
+/// Emits a `tracing` event at `Level::$level`.
+macro_rules! emit_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::event!(::tracing::Level::$level, $($arg)+);
+    };
+}
+
+/// Records a field declared as `tracing::field::Empty` on the current span.
+macro_rules! record_span_field {
+    ($field:literal, $value:expr) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::Span::current().record($field, $value);
+    };
+}
+
+pub(crate) use emit_event;
+pub(crate) use record_span_field;
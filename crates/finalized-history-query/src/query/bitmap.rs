@@ -2,9 +2,9 @@ use futures::stream::{FuturesUnordered, StreamExt};
 use roaring::RoaringBitmap;
 
 use crate::error::Result;
-use crate::kernel::sharded_streams::merge_bitmap_bytes_into;
 use crate::kernel::sharded_streams::overlaps;
 use crate::kernel::sharded_streams::page_start_local;
+use crate::kernel::sharded_streams::{merge_bitmap_blob_into, merge_bitmap_bytes_into};
 use crate::store::traits::{BlobStore, MetaStore};
 use crate::streams::{StreamBitmapMeta, decode_bitmap_blob};
 use crate::tables::StreamTables;
@@ -98,31 +98,64 @@ pub(crate) async fn load_stream_entries<M: MetaStore, B: BlobStore>(
     Ok(out)
 }
 
-pub(crate) async fn estimate_stream_overlap<M: MetaStore, B: BlobStore>(
+/// By-block fragments decoded per unsealed page when fast planning is on.
+const FAST_PLANNING_SAMPLE_FRAGMENTS: usize = 4;
+
+/// Sums the entry counts every stream in `stream_ids` holds in the local
+/// range, and reports whether that sum is an upper bound on the true count.
+/// Page meta is loaded for all streams at once per page; streams without
+/// meta for a page fall back to that page's fragments. Whole pages and
+/// fragments that overlap the range are counted, so the sum never
+/// undercounts. With fast planning, only a sample of those fragments is
+/// decoded and their overlapping count is scaled to the page's fragment
+/// count; a sample that skipped fragments is no longer an upper bound.
+pub(crate) async fn estimate_streams_overlap<M: MetaStore, B: BlobStore>(
     stream_tables: &StreamTables<M, B, StreamBitmapMeta>,
-    stream_id: &str,
+    stream_ids: &[String],
     local_from: u32,
     local_to: u32,
-) -> Result<u64> {
+) -> Result<(u64, bool)> {
     let mut estimated = 0u64;
+    let mut upper_bound = true;
     let mut page_start = page_start_local(local_from, 4_096);
     let last_page_start = page_start_local(local_to, 4_096);
 
     loop {
-        if let Some(meta) = stream_tables.get_page_meta(stream_id, page_start).await? {
-            if overlaps(meta.min_local, meta.max_local, local_from, local_to) {
-                estimated = estimated.saturating_add(u64::from(meta.count));
-            }
-        } else {
-            for bytes in stream_tables
-                .load_page_fragments(stream_id, page_start)
-                .await?
-            {
-                let meta = decode_bitmap_blob(&bytes)?;
+        let metas = stream_tables.get_page_metas(stream_ids, page_start).await?;
+        for (stream_id, meta) in stream_ids.iter().zip(metas) {
+            if let Some(meta) = meta {
                 if overlaps(meta.min_local, meta.max_local, local_from, local_to) {
                     estimated = estimated.saturating_add(u64::from(meta.count));
                 }
+                continue;
+            }
+            let (fragment_count, fragments) = if stream_tables.fast_planning() {
+                stream_tables
+                    .sample_page_fragments(stream_id, page_start, FAST_PLANNING_SAMPLE_FRAGMENTS)
+                    .await?
+            } else {
+                let fragments = stream_tables
+                    .load_page_fragments(stream_id, page_start)
+                    .await?;
+                (fragments.len(), fragments)
+            };
+            let mut page_estimate = 0u64;
+            for bytes in &fragments {
+                let meta = decode_bitmap_blob(bytes)?;
+                if overlaps(meta.min_local, meta.max_local, local_from, local_to) {
+                    page_estimate = page_estimate.saturating_add(u64::from(meta.count));
+                }
             }
+            if fragments.len() < fragment_count {
+                // A fragment deleted between listing and loading, as by a
+                // concurrent `delete_stream`, can leave no sample at all.
+                upper_bound = false;
+                page_estimate = page_estimate
+                    .saturating_mul(fragment_count as u64)
+                    .checked_div(fragments.len() as u64)
+                    .unwrap_or(0);
+            }
+            estimated = estimated.saturating_add(page_estimate);
         }
 
         if page_start == last_page_start {
@@ -131,7 +164,7 @@ pub(crate) async fn estimate_stream_overlap<M: MetaStore, B: BlobStore>(
         page_start = page_start.saturating_add(4_096);
     }
 
-    Ok(estimated)
+    Ok((estimated, upper_bound))
 }
 
 async fn load_bitmap_by_block_entries_for_page<M: MetaStore, B: BlobStore>(
@@ -165,16 +198,19 @@ async fn maybe_merge_cached_bitmap_blob<M: MetaStore, B: BlobStore>(
     local_from: u32,
     local_to: u32,
 ) -> Result<bool> {
-    let Some(bytes) = stream_tables.get_page_blob(stream, page_start).await? else {
+    let Some(bitmap_blob) = stream_tables
+        .get_decoded_page_blob(stream, page_start)
+        .await?
+    else {
         return Ok(false);
     };
-    merge_bitmap_bytes_into(
-        &bytes,
+    Ok(merge_bitmap_blob_into(
+        &bitmap_blob,
         out,
         local_from,
         local_to,
         local_from == 0 && local_to == crate::core::layout::MAX_LOCAL_ID,
-    )
+    ))
 }
 
 #[cfg(test)]
@@ -186,7 +222,7 @@ mod tests {
     use futures::executor::block_on;
     use roaring::RoaringBitmap;
 
-    use super::load_stream_entries;
+    use super::{estimate_streams_overlap, load_stream_entries};
     use crate::kernel::codec::StorageCodec;
     use crate::logs::table_specs::{
         BlobTableSpec, LogBitmapByBlockSpec, LogBitmapPageBlobSpec, LogBitmapPageMetaSpec,
@@ -328,6 +364,128 @@ mod tests {
         }
     }
 
+    /// Lists by-block fragments but loads none of them, as if each were
+    /// deleted between the listing and the load.
+    #[derive(Clone)]
+    struct VanishingFragmentsMetaStore {
+        inner: InMemoryMetaStore,
+    }
+
+    impl MetaStore for VanishingFragmentsMetaStore {
+        async fn get(&self, family: TableId, key: &[u8]) -> crate::Result<Option<Record>> {
+            self.inner.get(family, key).await
+        }
+
+        async fn put(
+            &self,
+            family: TableId,
+            key: &[u8],
+            value: Bytes,
+            cond: PutCond,
+        ) -> crate::Result<PutResult> {
+            self.inner.put(family, key, value, cond).await
+        }
+
+        async fn delete(
+            &self,
+            family: TableId,
+            key: &[u8],
+            cond: crate::store::traits::DelCond,
+        ) -> crate::Result<()> {
+            self.inner.delete(family, key, cond).await
+        }
+
+        async fn scan_get(
+            &self,
+            family: ScannableTableId,
+            partition: &[u8],
+            clustering: &[u8],
+        ) -> crate::Result<Option<Record>> {
+            if family == LogBitmapByBlockSpec::TABLE {
+                return Ok(None);
+            }
+            self.inner.scan_get(family, partition, clustering).await
+        }
+
+        async fn scan_put(
+            &self,
+            family: ScannableTableId,
+            partition: &[u8],
+            clustering: &[u8],
+            value: Bytes,
+            cond: PutCond,
+        ) -> crate::Result<PutResult> {
+            self.inner
+                .scan_put(family, partition, clustering, value, cond)
+                .await
+        }
+
+        async fn scan_delete(
+            &self,
+            family: ScannableTableId,
+            partition: &[u8],
+            clustering: &[u8],
+            cond: crate::store::traits::DelCond,
+        ) -> crate::Result<()> {
+            self.inner
+                .scan_delete(family, partition, clustering, cond)
+                .await
+        }
+
+        async fn scan_list(
+            &self,
+            family: ScannableTableId,
+            partition: &[u8],
+            prefix: &[u8],
+            cursor: Option<Vec<u8>>,
+            limit: usize,
+        ) -> crate::Result<Page> {
+            self.inner
+                .scan_list(family, partition, prefix, cursor, limit)
+                .await
+        }
+    }
+
+    #[test]
+    fn fast_planning_estimate_survives_fragments_vanishing_after_listing() {
+        block_on(async {
+            let meta = InMemoryMetaStore::default();
+            let stream = "addr/test/00000000";
+            let partition = LogBitmapByBlockSpec::partition(stream, 0);
+            for block_num in 1..=8u64 {
+                let mut bitmap = RoaringBitmap::new();
+                bitmap.insert(block_num as u32);
+                let fragment = BitmapBlob {
+                    min_local: block_num as u32,
+                    max_local: block_num as u32,
+                    count: 1,
+                    bitmap,
+                };
+                meta.scan_put(
+                    LogBitmapByBlockSpec::TABLE,
+                    &partition,
+                    &LogBitmapByBlockSpec::clustering(block_num),
+                    encode_bitmap_blob(&fragment).expect("encode fragment bitmap blob"),
+                    PutCond::Any,
+                )
+                .await
+                .expect("write stream fragment");
+            }
+
+            let tables = Tables::without_cache(
+                VanishingFragmentsMetaStore { inner: meta },
+                InMemoryBlobStore::default(),
+            )
+            .with_fast_planning(true);
+            let estimate =
+                estimate_streams_overlap(&tables.log_streams, &[stream.to_owned()], 0, 4_095)
+                    .await
+                    .expect("estimate");
+
+            assert_eq!(estimate, (0, false));
+        });
+    }
+
     #[test]
     fn load_stream_entries_falls_back_to_fragments_when_page_blob_is_missing() {
         block_on(async {
@@ -364,6 +522,7 @@ mod tests {
                     count: 1,
                     min_local: 11,
                     max_local: 11,
+                    byte_size: 0,
                 }
                 .encode(),
                 PutCond::Any,
@@ -411,6 +570,7 @@ mod tests {
                         count: 1,
                         min_local: 11,
                         max_local: 11,
+                        byte_size: 0,
                     }
                     .encode(),
                     PutCond::Any,
@@ -494,6 +654,7 @@ mod tests {
                         count: 1,
                         min_local: 11,
                         max_local: 11,
+                        byte_size: 0,
                     }
                     .encode(),
                     PutCond::Any,
@@ -544,6 +705,70 @@ mod tests {
         });
     }
 
+    #[test]
+    fn load_stream_entries_reuses_decoded_page_bitmap() {
+        block_on(async {
+            let stream = "addr/test/00000000";
+            let page_start = 0u32;
+            let blob_key = LogBitmapPageBlobSpec::key(stream, page_start);
+
+            let meta = InMemoryMetaStore::default();
+            let inner_blob = InMemoryBlobStore::default();
+            let blob_gets = Arc::new(AtomicU64::new(0));
+
+            let mut bitmap = RoaringBitmap::new();
+            bitmap.insert(11);
+            bitmap.insert(30);
+            let bitmap_blob = BitmapBlob {
+                min_local: 11,
+                max_local: 30,
+                count: 2,
+                bitmap,
+            };
+
+            meta.put(
+                LogBitmapPageMetaSpec::TABLE,
+                &LogBitmapPageMetaSpec::key(stream, page_start),
+                StreamBitmapMeta {
+                    count: 2,
+                    min_local: 11,
+                    max_local: 30,
+                    byte_size: 0,
+                }
+                .encode(),
+                PutCond::Any,
+            )
+            .await
+            .expect("write stream page meta");
+            inner_blob
+                .put_blob(
+                    LogBitmapPageBlobSpec::TABLE,
+                    &blob_key,
+                    encode_bitmap_blob(&bitmap_blob).expect("encode stream page bitmap blob"),
+                )
+                .await
+                .expect("write stream page blob");
+
+            let blob = CountingBlobStore {
+                inner: inner_blob,
+                target_key: blob_key,
+                get_blob_count: blob_gets.clone(),
+            };
+            let tables = Tables::without_cache(meta, blob).with_stream_bitmap_cache(16);
+
+            let first = load_stream_entries(&tables.log_streams, stream, 0, 40)
+                .await
+                .expect("first load");
+            let second = load_stream_entries(&tables.log_streams, stream, 0, 20)
+                .await
+                .expect("second load");
+
+            assert_eq!(first.iter().collect::<Vec<_>>(), vec![11, 30]);
+            assert_eq!(second.iter().collect::<Vec<_>>(), vec![11]);
+            assert_eq!(blob_gets.load(Ordering::Relaxed), 1);
+        });
+    }
+
     #[test]
     fn load_page_fragments_preserves_full_partition_coverage() {
         block_on(async {
@@ -1,4 +1,6 @@
+use crate::api::IndexedQueryRequest;
 use crate::error::{Error, Result};
+use crate::store::publication::PublicationStore;
 use crate::store::traits::{BlobStore, MetaStore};
 use crate::tables::Tables;
 
@@ -11,11 +13,7 @@ pub(crate) async fn resolve_request_block_bounds<M: MetaStore, B: BlobStore>(
 ) -> Result<(u64, u64)> {
     let from_block = match (from_block, from_block_hash) {
         (Some(number), None) => number,
-        (None, Some(hash)) => tables
-            .block_hash_index
-            .get(&hash)
-            .await?
-            .ok_or(Error::InvalidParams("unknown from_block_hash"))?,
+        (None, Some(hash)) => resolve_block_hash(tables, &hash, "unknown from_block_hash").await?,
         _ => {
             return Err(Error::InvalidParams(
                 "exactly one of from_block or from_block_hash is required",
@@ -24,11 +22,7 @@ pub(crate) async fn resolve_request_block_bounds<M: MetaStore, B: BlobStore>(
     };
     let to_block = match (to_block, to_block_hash) {
         (Some(number), None) => number,
-        (None, Some(hash)) => tables
-            .block_hash_index
-            .get(&hash)
-            .await?
-            .ok_or(Error::InvalidParams("unknown to_block_hash"))?,
+        (None, Some(hash)) => resolve_block_hash(tables, &hash, "unknown to_block_hash").await?,
         _ => {
             return Err(Error::InvalidParams(
                 "exactly one of to_block or to_block_hash is required",
@@ -37,3 +31,116 @@ pub(crate) async fn resolve_request_block_bounds<M: MetaStore, B: BlobStore>(
     };
     Ok((from_block, to_block))
 }
+
+/// Resolves an indexed request's bounds, where each side may also be given as
+/// a block timestamp. `from_timestamp` selects the first block at or after it
+/// and `to_timestamp` the last block at or before it, found by binary search
+/// over stored block headers up to the published head. Timestamps are assumed
+/// non-decreasing along the chain. A timestamp window that selects no blocks
+/// resolves to an empty range past the head.
+pub(crate) async fn resolve_indexed_request_bounds<M, B, P, F>(
+    tables: &Tables<M, B>,
+    publication_store: &P,
+    request: &IndexedQueryRequest<F>,
+) -> Result<(u64, u64)>
+where
+    M: MetaStore,
+    B: BlobStore,
+    P: PublicationStore,
+{
+    if request.from_timestamp.is_none() && request.to_timestamp.is_none() {
+        return resolve_request_block_bounds(
+            tables,
+            request.from_block,
+            request.to_block,
+            request.from_block_hash,
+            request.to_block_hash,
+        )
+        .await;
+    }
+
+    let head = publication_store
+        .load_finalized_head_state()
+        .await?
+        .indexed_finalized_head;
+    let from_block = match (
+        request.from_block,
+        request.from_block_hash,
+        request.from_timestamp,
+    ) {
+        (Some(number), None, None) => number,
+        (None, Some(hash), None) => {
+            resolve_block_hash(tables, &hash, "unknown from_block_hash").await?
+        }
+        (None, None, Some(timestamp)) => first_block_at_or_after(tables, head, timestamp).await?,
+        _ => {
+            return Err(Error::InvalidParams(
+                "exactly one of from_block, from_block_hash, or from_timestamp is required",
+            ));
+        }
+    };
+    let to_block = match (
+        request.to_block,
+        request.to_block_hash,
+        request.to_timestamp,
+    ) {
+        (Some(number), None, None) => number,
+        (None, Some(hash), None) => {
+            resolve_block_hash(tables, &hash, "unknown to_block_hash").await?
+        }
+        (None, None, Some(timestamp)) => match timestamp.checked_add(1) {
+            Some(after) => first_block_at_or_after(tables, head, after)
+                .await?
+                .saturating_sub(1),
+            None => head,
+        },
+        _ => {
+            return Err(Error::InvalidParams(
+                "exactly one of to_block, to_block_hash, or to_timestamp is required",
+            ));
+        }
+    };
+
+    if from_block > to_block {
+        let past_head = head.saturating_add(1);
+        return Ok((past_head, past_head));
+    }
+    Ok((from_block, to_block))
+}
+
+async fn resolve_block_hash<M: MetaStore, B: BlobStore>(
+    tables: &Tables<M, B>,
+    hash: &[u8; 32],
+    unknown: &'static str,
+) -> Result<u64> {
+    tables
+        .block_hash_index
+        .get(hash)
+        .await?
+        .ok_or(Error::InvalidParams(unknown))
+}
+
+/// Returns the first block in `1..=head` whose timestamp is at least
+/// `timestamp`, or `head + 1` when every block is earlier.
+async fn first_block_at_or_after<M: MetaStore, B: BlobStore>(
+    tables: &Tables<M, B>,
+    head: u64,
+    timestamp: u64,
+) -> Result<u64> {
+    let mut low = 1u64;
+    let mut high = head.saturating_add(1);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let header = tables
+            .block_headers
+            .get(mid)
+            .await?
+            .ok_or(Error::NotFound)?;
+        if header.timestamp < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
@@ -1,12 +1,14 @@
 use crate::api::{ExecutionBudget, IndexedQueryRequest};
 use crate::core::ids::FamilyIdValue;
+use crate::core::page::QueryOrder;
 use crate::core::range::resolve_block_range;
 use crate::error::{Error, Result};
-use crate::query::bounds::resolve_request_block_bounds;
-use crate::query::normalized::{effective_limit, plan_page};
+use crate::observe::record_span_field;
+use crate::query::bounds::resolve_indexed_request_bounds;
+use crate::query::normalized::{effective_limit, max_result_bytes, plan_page};
 use crate::query::planner::IndexedClause;
 use crate::query::runner::{
-    QueryMaterializer, build_page, empty_page, execute_indexed_query,
+    QueryMaterializer, build_page, empty_page, execute_indexed_block_counts, execute_indexed_query,
     execute_unfiltered_block_query,
 };
 use crate::query::window::resolve_primary_window;
@@ -17,6 +19,7 @@ use crate::tables::{StreamTables, Tables};
 
 pub(crate) trait IndexedFilter {
     fn has_indexed_clause(&self) -> bool;
+    fn has_negated_clause(&self) -> bool;
     fn max_or_terms(&self) -> usize;
     fn indexed_clauses(&self) -> Vec<IndexedClause>;
 }
@@ -33,6 +36,15 @@ pub(crate) struct FamilyQueryTables<'a, M: MetaStore, B: BlobStore> {
 
 /// Drives the shared indexed-query pipeline for one family by resolving block
 /// bounds, mapping them to primary IDs, executing bitmap search, and building a page.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        name = "query",
+        skip_all,
+        fields(order = ?request.order, returned = tracing::field::Empty),
+    )
+)]
 pub(crate) async fn execute_family_query<M, P, B, F, Q, W>(
     family_tables: FamilyQueryTables<'_, M, B>,
     publication_store: &P,
@@ -53,6 +65,9 @@ where
     let tables = family_tables.tables;
 
     let has_indexed_clause = request.filter.has_indexed_clause();
+    if !has_indexed_clause && request.filter.has_negated_clause() {
+        return Err(Error::NegationWithoutIndexedClause);
+    }
     if has_indexed_clause && request.filter.max_or_terms() > limits.max_or_terms {
         return Err(Error::QueryTooBroad {
             actual: request.filter.max_or_terms(),
@@ -60,15 +75,10 @@ where
         });
     }
 
-    let (from_block, to_block) = resolve_request_block_bounds(
-        tables,
-        request.from_block,
-        request.to_block,
-        request.from_block_hash,
-        request.to_block_hash,
-    )
-    .await?;
+    let (from_block, to_block) =
+        resolve_indexed_request_bounds(tables, publication_store, request).await?;
     let effective_limit = effective_limit(request.limit, limits.budget)?;
+    let max_bytes = max_result_bytes(limits.budget)?;
     let block_range = resolve_block_range(
         tables,
         publication_store,
@@ -96,14 +106,18 @@ where
             }
         }
 
-        return execute_unfiltered_block_query(
+        let page = execute_unfiltered_block_query(
             block_range,
             &request.filter,
             effective_limit,
+            max_bytes,
             request.resume_id.map(Q::Id::new),
+            request.order,
             materializer,
         )
-        .await;
+        .await?;
+        record_span_field!("returned", page.items.len());
+        return Ok(page);
     }
 
     let Some(id_window) =
@@ -115,6 +129,7 @@ where
         &block_range,
         id_window,
         request.resume_id.map(Q::Id::new),
+        request.order,
         effective_limit,
         "resume_id outside resolved block window",
     )?
@@ -127,13 +142,79 @@ where
         &request.filter,
         (normalized.id_range.start, normalized.id_range.end_inclusive),
         normalized.take,
+        max_bytes,
+        request.order,
         materializer,
     )
     .await?;
 
-    Ok(build_page::<Q>(
+    let page = build_page::<Q>(
         normalized.block_range,
         normalized.effective_limit,
+        max_bytes,
         matched,
-    ))
+    );
+    record_span_field!("returned", page.items.len());
+    Ok(page)
+}
+
+/// Resolves a finalized block range and returns ascending `(block_num,
+/// count)` pairs for the blocks whose primary items match `filter`. Without
+/// `exact_match` only indexed clauses are applied and no payloads are loaded.
+/// The filter must carry at least one indexed clause.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn execute_family_block_counts<M, P, B, F, Q, W>(
+    family_tables: FamilyQueryTables<'_, M, B>,
+    publication_store: &P,
+    (from_block, to_block): (u64, u64),
+    filter: &F,
+    max_or_terms: usize,
+    materializer: &mut Q,
+    select_window: W,
+    exact_match: bool,
+) -> Result<Vec<(u64, u64)>>
+where
+    M: MetaStore,
+    P: PublicationStore,
+    B: BlobStore,
+    F: IndexedFilter,
+    Q: QueryMaterializer<Filter = F>,
+    Q::Id: FamilyIdValue,
+    W: Fn(&crate::core::state::BlockRecord) -> Option<crate::core::state::PrimaryWindowRecord>,
+{
+    if !filter.has_indexed_clause() {
+        return Err(Error::InvalidParams(
+            "block query requires an indexed clause",
+        ));
+    }
+    if filter.max_or_terms() > max_or_terms {
+        return Err(Error::QueryTooBroad {
+            actual: filter.max_or_terms(),
+            max: max_or_terms,
+        });
+    }
+
+    let tables = family_tables.tables;
+    let block_range = resolve_block_range(
+        tables,
+        publication_store,
+        from_block,
+        to_block,
+        QueryOrder::Ascending,
+    )
+    .await?;
+    let Some(id_window) =
+        resolve_primary_window::<_, _, Q::Id, _>(tables, &block_range, select_window).await?
+    else {
+        return Ok(Vec::new());
+    };
+
+    execute_indexed_block_counts(
+        family_tables.stream_tables,
+        filter,
+        (id_window.start, id_window.end_inclusive),
+        materializer,
+        exact_match,
+    )
+    .await
 }
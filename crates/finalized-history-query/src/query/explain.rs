@@ -0,0 +1,165 @@
+//! Planner introspection: reports the block and primary-ID windows a request
+//! resolves to and the per-shard clause order the indexed pipeline would use,
+//! without loading any bitmaps or payloads.
+
+use crate::api::IndexedQueryRequest;
+use crate::core::ids::{FamilyIdValue, family_local_range_for_shard};
+use crate::core::layout::LOCAL_ID_BITS;
+use crate::core::range::resolve_block_range;
+use crate::core::state::{BlockRecord, PrimaryWindowRecord};
+use crate::error::{Error, Result};
+use crate::query::bounds::resolve_indexed_request_bounds;
+use crate::query::engine::{FamilyQueryTables, IndexedFilter};
+use crate::query::planner::prepare_shard_clauses;
+use crate::query::window::resolve_primary_window;
+use crate::store::publication::PublicationStore;
+use crate::store::traits::{BlobStore, MetaStore};
+
+/// The plan for one request. `resume_id` and `limit` are ignored, so the
+/// windows cover the whole resolved range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryExplain {
+    /// Resolved block range, clipped to the published head. Empty when
+    /// `from_block > to_block`.
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Inclusive primary-ID window of the block range, or `None` when the
+    /// range holds no items of this family.
+    pub id_range: Option<(u64, u64)>,
+    /// True when the filter has no indexed clause and the query walks blocks
+    /// instead of intersecting stream bitmaps.
+    pub block_scan: bool,
+    /// Indexed plan for each shard in the ID window, in ascending shard order.
+    /// Empty for block scans.
+    pub shards: Vec<ShardExplain>,
+}
+
+impl QueryExplain {
+    /// Estimated number of matching items, without executing the query. Each
+    /// shard contributes its lowest clause estimate, capped at the shard's
+    /// local window, because an intersection holds no more than its narrowest
+    /// clause. Clause estimates count whole pages and fragments that overlap
+    /// the window, and the other clauses and the exact-match pass only remove
+    /// items, so this over-estimates. With `Config::fast_planning` sampled
+    /// pages are scaled, so it can then fall short. A block scan counts every
+    /// item in the ID window.
+    pub fn estimated_count(&self) -> u64 {
+        if self.block_scan {
+            return self
+                .id_range
+                .map_or(0, |(start, end_inclusive)| end_inclusive - start + 1);
+        }
+        self.shards
+            .iter()
+            .map(|shard| {
+                let window = u64::from(shard.local_to - shard.local_from) + 1;
+                shard
+                    .clauses
+                    .iter()
+                    .map(|clause| clause.estimated_count)
+                    .min()
+                    .map_or(window, |estimate| estimate.min(window))
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardExplain {
+    pub shard: u64,
+    pub local_from: u32,
+    pub local_to: u32,
+    /// Clauses in intersection order: lowest estimate first, ties broken by
+    /// stream IDs.
+    pub clauses: Vec<ClauseExplain>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClauseExplain {
+    /// The OR-ed streams that make up the clause.
+    pub stream_ids: Vec<String>,
+    /// Summed page-meta overlap estimate of the clause's streams.
+    pub estimated_count: u64,
+}
+
+pub(crate) async fn explain_family_query<M, P, B, F, I, W>(
+    family_tables: FamilyQueryTables<'_, M, B>,
+    publication_store: &P,
+    request: &IndexedQueryRequest<F>,
+    max_or_terms: usize,
+    select_window: W,
+) -> Result<QueryExplain>
+where
+    M: MetaStore,
+    P: PublicationStore,
+    B: BlobStore,
+    F: IndexedFilter,
+    I: FamilyIdValue,
+    W: Fn(&BlockRecord) -> Option<PrimaryWindowRecord>,
+{
+    let tables = family_tables.tables;
+    let block_scan = !request.filter.has_indexed_clause();
+    if block_scan && request.filter.has_negated_clause() {
+        return Err(Error::NegationWithoutIndexedClause);
+    }
+    if !block_scan && request.filter.max_or_terms() > max_or_terms {
+        return Err(Error::QueryTooBroad {
+            actual: request.filter.max_or_terms(),
+            max: max_or_terms,
+        });
+    }
+
+    let (from_block, to_block) =
+        resolve_indexed_request_bounds(tables, publication_store, request).await?;
+    let block_range = resolve_block_range(
+        tables,
+        publication_store,
+        from_block,
+        to_block,
+        request.order,
+    )
+    .await?;
+    let id_window =
+        resolve_primary_window::<_, _, I, _>(tables, &block_range, select_window).await?;
+
+    let mut shards = Vec::new();
+    if let Some(id_window) = id_window
+        && !block_scan
+    {
+        let clause_specs = request.filter.indexed_clauses();
+        let first_shard = id_window.start.get() >> LOCAL_ID_BITS;
+        let last_shard = id_window.end_inclusive.get() >> LOCAL_ID_BITS;
+        for shard in first_shard..=last_shard {
+            let (local_from, local_to) =
+                family_local_range_for_shard(id_window.start, id_window.end_inclusive, shard);
+            let clauses = prepare_shard_clauses(
+                family_tables.stream_tables,
+                &clause_specs,
+                shard,
+                local_from,
+                local_to,
+            )
+            .await?
+            .into_iter()
+            .map(|clause| ClauseExplain {
+                stream_ids: clause.stream_ids,
+                estimated_count: clause.estimated_count,
+            })
+            .collect();
+            shards.push(ShardExplain {
+                shard,
+                local_from,
+                local_to,
+                clauses,
+            });
+        }
+    }
+
+    Ok(QueryExplain {
+        from_block: block_range.from_block,
+        to_block: block_range.to_block,
+        id_range: id_window.map(|window| (window.start.get(), window.end_inclusive.get())),
+        block_scan,
+        shards,
+    })
+}
@@ -1,7 +1,9 @@
 pub(crate) mod bitmap;
 pub(crate) mod bounds;
 pub(crate) mod engine;
+pub mod explain;
 pub(crate) mod normalized;
 pub(crate) mod planner;
+pub(crate) mod prefetch;
 pub mod runner;
 pub(crate) mod window;
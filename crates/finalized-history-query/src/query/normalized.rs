@@ -1,5 +1,6 @@
 use crate::api::ExecutionBudget;
 use crate::core::ids::{FamilyIdRange, FamilyIdValue};
+use crate::core::page::QueryOrder;
 use crate::core::range::ResolvedBlockRange;
 use crate::error::{Error, Result};
 
@@ -25,18 +26,32 @@ pub(crate) fn effective_limit(limit: usize, budget: ExecutionBudget) -> Result<u
     }
 }
 
+pub(crate) fn max_result_bytes(budget: ExecutionBudget) -> Result<Option<usize>> {
+    match budget.max_result_bytes {
+        Some(0) => Err(Error::InvalidParams(
+            "budget.max_result_bytes must be at least 1 when set",
+        )),
+        max_result_bytes => Ok(max_result_bytes),
+    }
+}
+
 pub(crate) fn plan_page<I: FamilyIdValue + Copy + Ord>(
     block_range: &ResolvedBlockRange,
     mut id_range: FamilyIdRange<I>,
-    resume_after: Option<I>,
+    resume_id: Option<I>,
+    order: QueryOrder,
     effective_limit: usize,
     resume_outside_message: &'static str,
 ) -> Result<Option<PlannedQuery<I>>> {
-    if let Some(resume_id) = resume_after {
+    if let Some(resume_id) = resume_id {
         if !id_range.contains(resume_id) {
             return Err(Error::InvalidParams(resume_outside_message));
         }
-        let Some(resumed_range) = id_range.resume_strictly_after(resume_id) else {
+        let resumed_range = match order {
+            QueryOrder::Ascending => id_range.resume_strictly_after(resume_id),
+            QueryOrder::Descending => id_range.resume_strictly_before(resume_id),
+        };
+        let Some(resumed_range) = resumed_range else {
             return Ok(None);
         };
         id_range = resumed_range;
@@ -1,6 +1,10 @@
+use std::borrow::Cow;
+
+use roaring::RoaringTreemap;
+
 use crate::core::clause::Clause;
 use crate::error::Result;
-use crate::kernel::sharded_streams::sharded_stream_id;
+use crate::kernel::sharded_streams::{StreamKind, sharded_stream_id, stream_value_key};
 use crate::query::bitmap;
 use crate::store::traits::{BlobStore, MetaStore};
 use crate::streams::StreamBitmapMeta;
@@ -8,7 +12,7 @@ use crate::tables::StreamTables;
 
 #[derive(Debug, Clone)]
 pub(crate) struct StreamSelector {
-    pub stream_kind: &'static str,
+    pub stream_kind: StreamKind,
     pub value: Vec<u8>,
 }
 
@@ -18,7 +22,7 @@ pub(crate) struct IndexedClause {
 }
 
 pub(crate) fn indexed_clause(
-    stream_kind: &'static str,
+    stream_kind: StreamKind,
     values: Vec<Vec<u8>>,
 ) -> Option<IndexedClause> {
     (!values.is_empty()).then(|| IndexedClause {
@@ -30,7 +34,7 @@ pub(crate) fn indexed_clause(
 }
 
 pub(crate) fn build_indexed_clause<T>(
-    stream_kind: &'static str,
+    stream_kind: StreamKind,
     clause: &Option<Clause<T>>,
 ) -> Option<IndexedClause>
 where
@@ -41,16 +45,76 @@ where
         .and_then(|clause| indexed_clause(stream_kind, clause.indexed_values()))
 }
 
-pub(crate) fn single_selector_clause(stream_kind: &'static str, value: Vec<u8>) -> IndexedClause {
+pub(crate) fn single_selector_clause(stream_kind: StreamKind, value: Vec<u8>) -> IndexedClause {
     IndexedClause {
         selectors: vec![StreamSelector { stream_kind, value }],
     }
 }
 
+/// The shards in which each selector of each indexed clause has a stream.
+/// Loaded only when the shard presence filter is enabled; otherwise every
+/// shard is treated as present.
+pub(crate) struct ShardPresence {
+    clause_shards: Option<Vec<Vec<RoaringTreemap>>>,
+}
+
+impl ShardPresence {
+    pub(crate) async fn load<M: MetaStore, B: BlobStore>(
+        stream_tables: &StreamTables<M, B, StreamBitmapMeta>,
+        clause_specs: &[IndexedClause],
+    ) -> Result<Self> {
+        if !stream_tables.shard_presence_filter() {
+            return Ok(Self {
+                clause_shards: None,
+            });
+        }
+        let mut clause_shards = Vec::with_capacity(clause_specs.len());
+        for clause_spec in clause_specs {
+            let mut selector_shards = Vec::with_capacity(clause_spec.selectors.len());
+            for selector in &clause_spec.selectors {
+                let value_key = stream_value_key(selector.stream_kind, &selector.value);
+                selector_shards.push(stream_tables.load_stream_shards(&value_key).await?);
+            }
+            clause_shards.push(selector_shards);
+        }
+        Ok(Self {
+            clause_shards: Some(clause_shards),
+        })
+    }
+
+    /// Narrows each clause to the selectors present in `shard`. Every indexed
+    /// clause is required, so returns `None` when some clause has none left.
+    pub(crate) fn clauses_for_shard<'a>(
+        &self,
+        clause_specs: &'a [IndexedClause],
+        shard_raw: u64,
+    ) -> Option<Cow<'a, [IndexedClause]>> {
+        let Some(clause_shards) = &self.clause_shards else {
+            return Some(Cow::Borrowed(clause_specs));
+        };
+        let mut narrowed = Vec::with_capacity(clause_specs.len());
+        for (clause_spec, selector_shards) in clause_specs.iter().zip(clause_shards) {
+            let selectors = clause_spec
+                .selectors
+                .iter()
+                .zip(selector_shards)
+                .filter(|(_, shards)| shards.contains(shard_raw))
+                .map(|(selector, _)| selector.clone())
+                .collect::<Vec<_>>();
+            if selectors.is_empty() {
+                return None;
+            }
+            narrowed.push(IndexedClause { selectors });
+        }
+        Some(Cow::Owned(narrowed))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct PreparedClause {
     pub stream_ids: Vec<String>,
     pub estimated_count: u64,
+    pub estimate_is_upper_bound: bool,
 }
 
 pub(crate) async fn prepare_shard_clauses<M: MetaStore, B: BlobStore>(
@@ -62,21 +126,19 @@ pub(crate) async fn prepare_shard_clauses<M: MetaStore, B: BlobStore>(
 ) -> Result<Vec<PreparedClause>> {
     let mut prepared = Vec::with_capacity(clause_specs.len());
     for clause_spec in clause_specs {
-        let mut stream_ids = Vec::with_capacity(clause_spec.selectors.len());
-        let mut estimated_count = 0u64;
-
-        for selector in &clause_spec.selectors {
-            let stream_id = sharded_stream_id(selector.stream_kind, &selector.value, shard_raw);
-            estimated_count = estimated_count.saturating_add(
-                bitmap::estimate_stream_overlap(stream_tables, &stream_id, local_from, local_to)
-                    .await?,
-            );
-            stream_ids.push(stream_id);
-        }
+        let stream_ids = clause_spec
+            .selectors
+            .iter()
+            .map(|selector| sharded_stream_id(selector.stream_kind, &selector.value, shard_raw))
+            .collect::<Vec<_>>();
+        let (estimated_count, estimate_is_upper_bound) =
+            bitmap::estimate_streams_overlap(stream_tables, &stream_ids, local_from, local_to)
+                .await?;
 
         prepared.push(PreparedClause {
             stream_ids,
             estimated_count,
+            estimate_is_upper_bound,
         });
     }
 
@@ -0,0 +1,90 @@
+//! Cache warm-up: loads the stream bitmaps a request's indexed clauses would
+//! intersect, without resolving or materializing any matches, so later
+//! queries over the same range find the pages already cached.
+
+use crate::api::IndexedQueryRequest;
+use crate::core::ids::{FamilyIdValue, family_local_range_for_shard};
+use crate::core::layout::LOCAL_ID_BITS;
+use crate::core::range::resolve_block_range;
+use crate::core::state::{BlockRecord, PrimaryWindowRecord};
+use crate::error::{Error, Result};
+use crate::query::bitmap::load_prepared_clause_bitmap;
+use crate::query::bounds::resolve_indexed_request_bounds;
+use crate::query::engine::{FamilyQueryTables, IndexedFilter};
+use crate::query::planner::prepare_shard_clauses;
+use crate::query::window::resolve_primary_window;
+use crate::store::publication::PublicationStore;
+use crate::store::traits::{BlobStore, MetaStore};
+
+/// Loads every clause bitmap of every shard in the request's ID window and
+/// returns the number of clause bitmaps loaded. Unlike the query path, an
+/// empty clause does not stop the shard, so all of the request's streams are
+/// read. `resume_id` and `limit` are ignored, and block scans load nothing.
+pub(crate) async fn prefetch_family_query<M, P, B, F, I, W>(
+    family_tables: FamilyQueryTables<'_, M, B>,
+    publication_store: &P,
+    request: &IndexedQueryRequest<F>,
+    max_or_terms: usize,
+    select_window: W,
+) -> Result<u64>
+where
+    M: MetaStore,
+    P: PublicationStore,
+    B: BlobStore,
+    F: IndexedFilter,
+    I: FamilyIdValue,
+    W: Fn(&BlockRecord) -> Option<PrimaryWindowRecord>,
+{
+    let tables = family_tables.tables;
+    if !request.filter.has_indexed_clause() {
+        if request.filter.has_negated_clause() {
+            return Err(Error::NegationWithoutIndexedClause);
+        }
+        return Ok(0);
+    }
+    if request.filter.max_or_terms() > max_or_terms {
+        return Err(Error::QueryTooBroad {
+            actual: request.filter.max_or_terms(),
+            max: max_or_terms,
+        });
+    }
+
+    let (from_block, to_block) =
+        resolve_indexed_request_bounds(tables, publication_store, request).await?;
+    let block_range = resolve_block_range(
+        tables,
+        publication_store,
+        from_block,
+        to_block,
+        request.order,
+    )
+    .await?;
+    let Some(id_window) =
+        resolve_primary_window::<_, _, I, _>(tables, &block_range, select_window).await?
+    else {
+        return Ok(0);
+    };
+
+    let clause_specs = request.filter.indexed_clauses();
+    let first_shard = id_window.start.get() >> LOCAL_ID_BITS;
+    let last_shard = id_window.end_inclusive.get() >> LOCAL_ID_BITS;
+    let mut loaded = 0u64;
+    for shard in first_shard..=last_shard {
+        let (local_from, local_to) =
+            family_local_range_for_shard(id_window.start, id_window.end_inclusive, shard);
+        let clauses = prepare_shard_clauses(
+            family_tables.stream_tables,
+            &clause_specs,
+            shard,
+            local_from,
+            local_to,
+        )
+        .await?;
+        for clause in &clauses {
+            load_prepared_clause_bitmap(family_tables.stream_tables, clause, local_from, local_to)
+                .await?;
+            loaded += 1;
+        }
+    }
+    Ok(loaded)
+}
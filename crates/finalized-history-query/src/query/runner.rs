@@ -9,17 +9,18 @@ use crate::core::ids::{
     family_local_range_for_shard,
 };
 use crate::core::layout::MAX_LOCAL_ID;
-use crate::core::page::{QueryPage, QueryPageMeta};
+use crate::core::page::{QueryOrder, QueryPage, QueryPageMeta};
 use crate::core::range::{ResolvedBlockRange, load_block_ref};
 use crate::core::refs::BlockRef;
 use crate::error::{Error, Result};
+use crate::observe::record_span_field;
 use crate::query::engine::IndexedFilter;
 use crate::store::traits::{BlobStore, MetaStore};
 use crate::streams::StreamBitmapMeta;
 use crate::tables::{StreamTables, Tables};
 
 use super::bitmap::load_prepared_clause_bitmap;
-use super::planner::prepare_shard_clauses;
+use super::planner::{ShardPresence, prepare_shard_clauses};
 pub type ShardBitmapSet = BTreeMap<u64, RoaringBitmap>;
 
 pub struct MaterializerCaches<F> {
@@ -184,6 +185,8 @@ pub trait QueryMaterializer {
     async fn block_ref_for(&mut self, item: &Self::Item) -> Result<BlockRef>;
     fn exact_match(&self, item: &Self::Item, filter: &Self::Filter) -> bool;
     fn into_output(item: Self::Item) -> Self::Output;
+    /// Encoded size of `item`, counted against `max_result_bytes`.
+    fn item_bytes(item: &Self::Item) -> usize;
 }
 
 pub async fn execute_candidates<I, M>(
@@ -273,15 +276,33 @@ pub(crate) fn empty_page<T>(block_range: &ResolvedBlockRange) -> QueryPage<T> {
     }
 }
 
+/// Trims `matched` to `effective_limit` items and then to the longest prefix
+/// within `max_bytes`, keeping at least one item. Either trim sets `has_more`.
 pub(crate) fn build_page<M: QueryMaterializer>(
     block_range: ResolvedBlockRange,
     effective_limit: usize,
+    max_bytes: Option<usize>,
     mut matched: Vec<MatchedQueryItem<M::Id, M::Item>>,
 ) -> QueryPage<M::Output> {
-    let has_more = matched.len() > effective_limit;
+    let mut has_more = matched.len() > effective_limit;
     if has_more {
         matched.truncate(effective_limit);
     }
+    if let Some(max_bytes) = max_bytes {
+        let mut bytes = 0usize;
+        let fits = matched
+            .iter()
+            .take_while(|matched_item| {
+                bytes = bytes.saturating_add(M::item_bytes(&matched_item.item));
+                bytes <= max_bytes
+            })
+            .count()
+            .max(1);
+        if fits < matched.len() {
+            has_more = true;
+            matched.truncate(fits);
+        }
+    }
 
     let next_resume_id = if has_more {
         matched.last().map(|matched_item| matched_item.id.get())
@@ -313,36 +334,65 @@ pub(crate) async fn execute_unfiltered_block_query<M: QueryMaterializer>(
     block_range: ResolvedBlockRange,
     filter: &M::Filter,
     effective_limit: usize,
-    resume_after: Option<M::Id>,
+    max_bytes: Option<usize>,
+    resume_id: Option<M::Id>,
+    order: QueryOrder,
     materializer: &mut M,
 ) -> Result<QueryPage<M::Output>> {
     let take = effective_limit.saturating_add(1);
-    let mut matched = Vec::with_capacity(take);
+    // The limit may be far above the number of items in range, so grow with
+    // the matches rather than reserving `take` up front.
+    let mut matched = Vec::new();
+    let mut bytes = 0usize;
 
-    for block_num in block_range.from_block..=block_range.to_block {
-        for (id, item) in materializer.load_block(block_num).await? {
-            if let Some(resume_after) = resume_after
-                && id <= resume_after
-            {
-                continue;
+    let blocks = block_range.from_block..=block_range.to_block;
+    let blocks: Box<dyn Iterator<Item = u64>> = match order {
+        QueryOrder::Ascending => Box::new(blocks),
+        QueryOrder::Descending => Box::new(blocks.rev()),
+    };
+    for block_num in blocks {
+        let mut block_items = materializer.load_block(block_num).await?;
+        if order == QueryOrder::Descending {
+            block_items.reverse();
+        }
+        for (id, item) in block_items {
+            if let Some(resume_id) = resume_id {
+                let already_returned = match order {
+                    QueryOrder::Ascending => id <= resume_id,
+                    QueryOrder::Descending => id >= resume_id,
+                };
+                if already_returned {
+                    continue;
+                }
             }
             if !materializer.exact_match(&item, filter) {
                 continue;
             }
 
             let block_ref = materializer.block_ref_for(&item).await?;
+            bytes = bytes.saturating_add(M::item_bytes(&item));
             matched.push(MatchedQueryItem {
                 id,
                 item,
                 block_ref,
             });
-            if matched.len() >= take {
-                return Ok(build_page::<M>(block_range, effective_limit, matched));
+            if matched.len() >= take || exceeds_byte_budget(bytes, max_bytes) {
+                return Ok(build_page::<M>(
+                    block_range,
+                    effective_limit,
+                    max_bytes,
+                    matched,
+                ));
             }
         }
     }
 
-    Ok(build_page::<M>(block_range, effective_limit, matched))
+    Ok(build_page::<M>(
+        block_range,
+        effective_limit,
+        max_bytes,
+        matched,
+    ))
 }
 
 pub async fn cached_block_ref_with_fallback<M, B, Fut>(
@@ -398,6 +448,8 @@ pub(crate) async fn execute_indexed_query<M, B, I, Q, F>(
     filter: &F,
     id_window: (I, I),
     take: usize,
+    max_bytes: Option<usize>,
+    order: QueryOrder,
     materializer: &mut Q,
 ) -> Result<Vec<MatchedQueryItem<I, Q::Item>>>
 where
@@ -410,79 +462,68 @@ where
     let (from_id, to_id_inclusive) = id_window;
     let clause_specs = filter.indexed_clauses();
     let mut matched = Vec::new();
+    let mut bytes = 0usize;
 
-    for shard_raw in from_id.shard_raw()..=to_id_inclusive.shard_raw() {
-        let (local_from, local_to) =
-            family_local_range_for_shard(from_id, to_id_inclusive, shard_raw);
-        let shard_clauses = prepare_shard_clauses(
-            stream_tables,
-            &clause_specs,
-            shard_raw,
-            local_from,
-            local_to,
-        )
-        .await?;
-
-        if shard_clauses.is_empty() {
-            continue;
-        }
-
-        let mut shard_accumulator: Option<RoaringBitmap> = None;
-        for prepared_clause in shard_clauses {
-            let clause_bitmap =
-                load_prepared_clause_bitmap(stream_tables, &prepared_clause, local_from, local_to)
-                    .await?;
-            if clause_bitmap.is_empty() {
-                shard_accumulator = Some(RoaringBitmap::new());
-                break;
-            }
-
-            match shard_accumulator.as_mut() {
-                Some(accumulator) => {
-                    *accumulator &= &clause_bitmap;
-                    if accumulator.is_empty() {
-                        break;
-                    }
-                }
-                None => shard_accumulator = Some(clause_bitmap),
-            }
-        }
-
-        let Some(shard_accumulator) = shard_accumulator else {
+    let shards = from_id.shard_raw()..=to_id_inclusive.shard_raw();
+    let shards: Box<dyn Iterator<Item = u64>> = match order {
+        QueryOrder::Ascending => Box::new(shards),
+        QueryOrder::Descending => Box::new(shards.rev()),
+    };
+    let shard_presence = ShardPresence::load(stream_tables, &clause_specs).await?;
+    for shard_raw in shards {
+        let Some(shard_clause_specs) = shard_presence.clauses_for_shard(&clause_specs, shard_raw)
+        else {
             continue;
         };
-        if shard_accumulator.is_empty() {
+        let Some(shard_accumulator) =
+            load_shard_match_bitmap(stream_tables, &shard_clause_specs, id_window, shard_raw)
+                .await?
+        else {
             continue;
-        }
+        };
 
-        let mut locals = shard_accumulator.into_iter().peekable();
+        let locals: Box<dyn Iterator<Item = u32>> = match order {
+            QueryOrder::Ascending => Box::new(shard_accumulator.into_iter()),
+            QueryOrder::Descending => Box::new(shard_accumulator.into_iter().rev()),
+        };
+        let mut locals = locals.peekable();
         while let Some(local_raw) = locals.next() {
             let id = I::compose(shard_raw, local_raw);
             let Some(location) = materializer.resolve_id(id).await? else {
                 continue;
             };
 
-            let run = collect_contiguous_chunk(
+            let mut run = collect_contiguous_chunk(
                 &mut locals,
                 shard_raw,
                 (id, location),
                 remaining_needed_for_chunk(take, matched.len()),
+                order,
                 materializer,
             )
             .await?;
 
-            for (run_id, item) in materializer.load_run(&run).await? {
+            // Materializers load runs in ascending ordinal order.
+            if order == QueryOrder::Descending {
+                run.reverse();
+            }
+            let mut run_items = materializer.load_run(&run).await?;
+            if order == QueryOrder::Descending {
+                run_items.reverse();
+            }
+            for (run_id, item) in run_items {
                 if !materializer.exact_match(&item, filter) {
                     continue;
                 }
 
                 let block_ref = materializer.block_ref_for(&item).await?;
+                bytes = bytes.saturating_add(Q::item_bytes(&item));
                 matched.push(MatchedQueryItem {
                     id: run_id,
                     item,
                     block_ref,
                 });
-                if matched.len() >= take {
+                if matched.len() >= take || exceeds_byte_budget(bytes, max_bytes) {
                     return Ok(matched);
                 }
             }
@@ -492,11 +533,169 @@ where
     Ok(matched)
 }
 
+/// Intersects every indexed clause bitmap for one shard, clipped to the ID
+/// window. Returns `None` when the shard has no candidates.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        name = "query_shard",
+        skip_all,
+        fields(
+            shard = shard_raw,
+            clause_order = tracing::field::Empty,
+            candidate_count = tracing::field::Empty,
+        ),
+    )
+)]
+async fn load_shard_match_bitmap<M, B, I>(
+    stream_tables: &StreamTables<M, B, StreamBitmapMeta>,
+    clause_specs: &[crate::query::planner::IndexedClause],
+    id_window: (I, I),
+    shard_raw: u64,
+) -> Result<Option<RoaringBitmap>>
+where
+    M: MetaStore,
+    B: BlobStore,
+    I: FamilyIdValue,
+{
+    let (from_id, to_id_inclusive) = id_window;
+    let (local_from, local_to) = family_local_range_for_shard(from_id, to_id_inclusive, shard_raw);
+    let shard_clauses =
+        prepare_shard_clauses(stream_tables, clause_specs, shard_raw, local_from, local_to).await?;
+
+    if shard_clauses.is_empty() {
+        return Ok(None);
+    }
+    // Every indexed clause is required, and an OR clause's estimate sums all
+    // of its streams, so a zero upper bound on any clause leaves no
+    // candidates in this shard.
+    if stream_tables.empty_result_fast_path()
+        && shard_clauses
+            .iter()
+            .any(|clause| clause.estimated_count == 0 && clause.estimate_is_upper_bound)
+    {
+        record_span_field!("candidate_count", 0);
+        return Ok(None);
+    }
+    record_span_field!(
+        "clause_order",
+        tracing::field::debug(
+            shard_clauses
+                .iter()
+                .map(|clause| (&clause.stream_ids, clause.estimated_count))
+                .collect::<Vec<_>>()
+        )
+    );
+
+    let mut shard_accumulator: Option<RoaringBitmap> = None;
+    for prepared_clause in shard_clauses {
+        let clause_bitmap =
+            load_prepared_clause_bitmap(stream_tables, &prepared_clause, local_from, local_to)
+                .await?;
+        if clause_bitmap.is_empty() {
+            return Ok(None);
+        }
+
+        match shard_accumulator.as_mut() {
+            Some(accumulator) => {
+                *accumulator &= &clause_bitmap;
+                if accumulator.is_empty() {
+                    return Ok(None);
+                }
+            }
+            None => shard_accumulator = Some(clause_bitmap),
+        }
+    }
+
+    record_span_field!(
+        "candidate_count",
+        shard_accumulator.as_ref().map_or(0, RoaringBitmap::len)
+    );
+    Ok(shard_accumulator)
+}
+
+/// Returns ascending `(block_num, count)` pairs for the blocks containing at
+/// least one indexed match. Without `exact_match` only the primary directory
+/// is consulted, so no payload blobs are fetched; that is exact only when
+/// every clause is exact at the stream level. With it, candidates are loaded
+/// in runs of adjacent IDs within one block and checked against the whole
+/// filter.
+pub(crate) async fn execute_indexed_block_counts<M, B, I, Q, F>(
+    stream_tables: &StreamTables<M, B, StreamBitmapMeta>,
+    filter: &F,
+    id_window: (I, I),
+    materializer: &mut Q,
+    exact_match: bool,
+) -> Result<Vec<(u64, u64)>>
+where
+    M: MetaStore,
+    B: BlobStore,
+    I: QueryId + FamilyIdValue,
+    Q: QueryMaterializer<Id = I, Filter = F>,
+    F: IndexedFilter,
+{
+    let (from_id, to_id_inclusive) = id_window;
+    let clause_specs = filter.indexed_clauses();
+    let mut block_counts: Vec<(u64, u64)> = Vec::new();
+
+    let shard_presence = ShardPresence::load(stream_tables, &clause_specs).await?;
+    for shard_raw in from_id.shard_raw()..=to_id_inclusive.shard_raw() {
+        let Some(shard_clause_specs) = shard_presence.clauses_for_shard(&clause_specs, shard_raw)
+        else {
+            continue;
+        };
+        let Some(shard_accumulator) =
+            load_shard_match_bitmap(stream_tables, &shard_clause_specs, id_window, shard_raw)
+                .await?
+        else {
+            continue;
+        };
+
+        let mut locals = shard_accumulator.into_iter().peekable();
+        while let Some(local_raw) = locals.next() {
+            let id = I::compose(shard_raw, local_raw);
+            let Some(location) = materializer.resolve_id(id).await? else {
+                continue;
+            };
+            let matches = if exact_match {
+                // A run stops at the end of its block, so it needs no cap.
+                let run = collect_contiguous_chunk(
+                    &mut locals,
+                    shard_raw,
+                    (id, location),
+                    usize::MAX,
+                    QueryOrder::Ascending,
+                    materializer,
+                )
+                .await?;
+                let items = materializer.load_run(&run).await?;
+                items
+                    .iter()
+                    .filter(|(_, item)| materializer.exact_match(item, filter))
+                    .count() as u64
+            } else {
+                1
+            };
+            if matches == 0 {
+                continue;
+            }
+            match block_counts.last_mut() {
+                Some((block_num, count)) if *block_num == location.block_num => *count += matches,
+                _ => block_counts.push((location.block_num, matches)),
+            }
+        }
+    }
+
+    Ok(block_counts)
+}
+
 async fn collect_contiguous_chunk<Iter, Q>(
     locals: &mut std::iter::Peekable<Iter>,
     shard_raw: u64,
     first: (Q::Id, ResolvedPrimaryLocation),
     max_len: usize,
+    order: QueryOrder,
     materializer: &mut Q,
 ) -> Result<Vec<(Q::Id, ResolvedPrimaryLocation)>>
 where
@@ -515,9 +714,11 @@ where
             continue;
         };
         let previous = run.last().expect("run must be non-empty").1;
-        if next_location.block_num != previous.block_num
-            || next_location.local_ordinal != previous.local_ordinal + 1
-        {
+        let adjacent = match order {
+            QueryOrder::Ascending => next_location.local_ordinal == previous.local_ordinal + 1,
+            QueryOrder::Descending => next_location.local_ordinal + 1 == previous.local_ordinal,
+        };
+        if next_location.block_num != previous.block_num || !adjacent {
             break;
         }
 
@@ -613,6 +814,12 @@ fn clip_shard_bitmaps_to_range<I: QueryId>(
     bitmaps
 }
 
+/// Once the matched bytes pass the budget, the last item only marks that the
+/// page has more, so collection can stop.
+fn exceeds_byte_budget(bytes: usize, max_bytes: Option<usize>) -> bool {
+    max_bytes.is_some_and(|max_bytes| bytes > max_bytes)
+}
+
 fn remaining_needed_for_chunk(take: usize, matched_len: usize) -> usize {
     take.saturating_sub(matched_len).max(1)
 }
@@ -626,6 +833,7 @@ mod tests {
     use super::{QueryMaterializer, collect_contiguous_chunk};
     use crate::core::directory_resolver::ResolvedPrimaryLocation;
     use crate::core::ids::{LogId, LogLocalId, LogShard, compose_log_id};
+    use crate::core::page::QueryOrder;
     use crate::core::refs::BlockRef;
     use crate::error::Result;
     struct StubMaterializer {
@@ -666,6 +874,10 @@ mod tests {
         fn into_output(_item: Self::Item) -> Self::Output {
             unreachable!("not used in these tests")
         }
+
+        fn item_bytes(_item: &Self::Item) -> usize {
+            unreachable!("not used in these tests")
+        }
     }
 
     #[test]
@@ -701,10 +913,16 @@ mod tests {
                 ]),
             };
 
-            let chunk =
-                collect_contiguous_chunk(&mut locals, shard_raw, first, 2, &mut materializer)
-                    .await
-                    .expect("collect chunk");
+            let chunk = collect_contiguous_chunk(
+                &mut locals,
+                shard_raw,
+                first,
+                2,
+                QueryOrder::Ascending,
+                &mut materializer,
+            )
+            .await
+            .expect("collect chunk");
 
             assert_eq!(chunk.len(), 2);
             assert_eq!(locals.next(), Some(2));
@@ -745,10 +963,16 @@ mod tests {
                 ]),
             };
 
-            let first_chunk =
-                collect_contiguous_chunk(&mut locals, shard_raw, first, 2, &mut materializer)
-                    .await
-                    .expect("first chunk");
+            let first_chunk = collect_contiguous_chunk(
+                &mut locals,
+                shard_raw,
+                first,
+                2,
+                QueryOrder::Ascending,
+                &mut materializer,
+            )
+            .await
+            .expect("first chunk");
             let next_local =
                 LogLocalId::new(locals.next().expect("remaining local")).expect("local");
             let second_chunk = collect_contiguous_chunk(
@@ -762,6 +986,7 @@ mod tests {
                     },
                 ),
                 2,
+                QueryOrder::Ascending,
                 &mut materializer,
             )
             .await
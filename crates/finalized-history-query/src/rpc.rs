@@ -0,0 +1,285 @@
+//! `eth_getLogs` adapter: translates an Ethereum JSON-RPC filter object into a
+//! [`QueryLogsRequest`] and renders matching logs in the `eth_getLogs` result
+//! shape. Transport is left to the caller.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{ExecutionBudget, FinalizedHistoryService, QueryLogsRequest};
+use crate::core::clause::Clause;
+use crate::core::page::QueryOrder;
+use crate::error::{Error, Result};
+use crate::ingest::authority::WriteAuthority;
+use crate::logs::filter::LogFilter;
+use crate::logs::log_ref::LogRef;
+use crate::logs::types::{Address, Topic};
+use crate::store::traits::{BlobStore, MetaStore};
+
+const INVALID_TOPIC: [&str; 4] = [
+    "invalid topic at position 0: expected 32-byte hex",
+    "invalid topic at position 1: expected 32-byte hex",
+    "invalid topic at position 2: expected 32-byte hex",
+    "invalid topic at position 3: expected 32-byte hex",
+];
+
+/// A single value or an array of values, as accepted by `address` and each
+/// `topics` position.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum ValueOrArray<T> {
+    Value(T),
+    Array(Vec<T>),
+}
+
+/// The `eth_getLogs` filter object. Block tags and hex strings are kept raw
+/// and validated when the filter is converted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetLogsFilter {
+    pub from_block: Option<String>,
+    pub to_block: Option<String>,
+    pub block_hash: Option<String>,
+    pub address: Option<ValueOrArray<String>>,
+    pub topics: Option<Vec<Option<ValueOrArray<String>>>>,
+}
+
+/// One entry of the `eth_getLogs` result array.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: String,
+    pub block_hash: String,
+    pub transaction_hash: Option<String>,
+    pub transaction_index: String,
+    pub log_index: String,
+    pub removed: bool,
+}
+
+/// Runs `eth_getLogs` for a raw filter object and returns the JSON result
+/// array. Fails rather than truncating when the matching logs exceed
+/// `budget.max_results` or `budget.max_result_bytes`.
+pub async fn eth_get_logs<A, M, B>(
+    service: &FinalizedHistoryService<A, M, B>,
+    filter: &serde_json::Value,
+    budget: ExecutionBudget,
+) -> Result<serde_json::Value>
+where
+    A: WriteAuthority,
+    M: MetaStore,
+    B: BlobStore,
+{
+    let filter = GetLogsFilter::deserialize(filter)
+        .map_err(|_| Error::InvalidParams("malformed eth_getLogs filter object"))?;
+    let logs = get_logs(service, &filter, budget).await?;
+    serde_json::to_value(logs).map_err(|e| Error::Codec(format!("serialize eth_getLogs: {e}")))
+}
+
+/// Typed form of [`eth_get_logs`].
+pub async fn get_logs<A, M, B>(
+    service: &FinalizedHistoryService<A, M, B>,
+    filter: &GetLogsFilter,
+    budget: ExecutionBudget,
+) -> Result<Vec<RpcLog>>
+where
+    A: WriteAuthority,
+    M: MetaStore,
+    B: BlobStore,
+{
+    let mut request = to_query_request(service, filter).await?;
+    request.limit = budget.max_results.unwrap_or(usize::MAX);
+    let page = service.query_logs(request, budget).await?;
+    if page.meta.has_more {
+        return Err(Error::InvalidParams(
+            "eth_getLogs result exceeds the execution budget",
+        ));
+    }
+
+    let mut tx_hashes = BTreeMap::<(u64, u32), Option<[u8; 32]>>::new();
+    let mut out = Vec::with_capacity(page.items.len());
+    for log in &page.items {
+        let key = (log.block_num(), log.tx_idx());
+        let tx_hash = match tx_hashes.get(&key) {
+            Some(tx_hash) => *tx_hash,
+            None => {
+                let tx_hash = service
+                    .runtime
+                    .tables
+                    .block_tx_blobs
+                    .load_tx_at(key.0, key.1)
+                    .await?
+                    .map(|tx| tx.tx_hash().copied())
+                    .transpose()?;
+                tx_hashes.insert(key, tx_hash);
+                tx_hash
+            }
+        };
+        out.push(to_rpc_log(log, tx_hash));
+    }
+    Ok(out)
+}
+
+/// Converts an `eth_getLogs` filter into an ascending logs request. Block
+/// tags `latest`, `safe`, and `finalized` resolve to the indexed finalized
+/// head. Indexed history starts at block 1, so `earliest` and `0x0` resolve
+/// to block 1. Missing bounds default to `latest`.
+pub async fn to_query_request<A, M, B>(
+    service: &FinalizedHistoryService<A, M, B>,
+    filter: &GetLogsFilter,
+) -> Result<QueryLogsRequest>
+where
+    A: WriteAuthority,
+    M: MetaStore,
+    B: BlobStore,
+{
+    let log_filter = to_log_filter(filter)?;
+    let mut request = QueryLogsRequest {
+        from_block: None,
+        to_block: None,
+        from_block_hash: None,
+        to_block_hash: None,
+        from_timestamp: None,
+        to_timestamp: None,
+        order: QueryOrder::Ascending,
+        resume_id: None,
+        limit: usize::MAX,
+        filter: log_filter,
+    };
+
+    if let Some(block_hash) = &filter.block_hash {
+        if filter.from_block.is_some() || filter.to_block.is_some() {
+            return Err(Error::InvalidParams(
+                "blockHash cannot be combined with fromBlock or toBlock",
+            ));
+        }
+        let block_hash = parse_fixed::<32>(block_hash, "invalid blockHash")?;
+        request.from_block_hash = Some(block_hash);
+        request.to_block_hash = Some(block_hash);
+        return Ok(request);
+    }
+
+    let head = service.indexed_finalized_head().await?;
+    request.from_block = Some(parse_block_tag(filter.from_block.as_deref(), head)?.max(1));
+    request.to_block = Some(parse_block_tag(filter.to_block.as_deref(), head)?);
+    Ok(request)
+}
+
+/// Builds the indexed log filter. A `null` topic position is a wildcard, a
+/// string is `Clause::One`, and an array is `Clause::Or`. Every address must
+/// be 20 bytes and every topic 32 bytes; a mis-sized value is rejected with
+/// an error naming its position.
+pub fn to_log_filter(filter: &GetLogsFilter) -> Result<LogFilter> {
+    let address = filter
+        .address
+        .as_ref()
+        .map(|address| {
+            to_clause(address, |value| {
+                parse_fixed::<20>(value, "invalid address: expected 20-byte hex").map(Address)
+            })
+        })
+        .transpose()?;
+
+    let topics = filter.topics.as_deref().unwrap_or_default();
+    if topics.len() > 4 {
+        return Err(Error::InvalidParams("topics accepts at most 4 positions"));
+    }
+    let mut topic_clauses = [None, None, None, None];
+    for (position, topic) in topics.iter().enumerate() {
+        topic_clauses[position] = topic
+            .as_ref()
+            .map(|topic| {
+                to_clause(topic, |value| {
+                    parse_fixed::<32>(value, INVALID_TOPIC[position]).map(Topic)
+                })
+            })
+            .transpose()?;
+    }
+    let [topic0, topic1, topic2, topic3] = topic_clauses;
+
+    Ok(LogFilter {
+        address,
+        topic0,
+        topic1,
+        topic2,
+        topic3,
+    })
+}
+
+pub fn to_rpc_log(log: &LogRef, transaction_hash: Option<[u8; 32]>) -> RpcLog {
+    RpcLog {
+        address: encode_hex(log.address()),
+        topics: log.topics().map(|topic| encode_hex(topic)).collect(),
+        data: encode_hex(log.data()),
+        block_number: encode_quantity(log.block_num()),
+        block_hash: encode_hex(log.block_hash()),
+        transaction_hash: transaction_hash.map(|hash| encode_hex(&hash)),
+        transaction_index: encode_quantity(u64::from(log.tx_idx())),
+        log_index: encode_quantity(u64::from(log.log_idx())),
+        removed: false,
+    }
+}
+
+fn to_clause<T>(
+    value: &ValueOrArray<String>,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<Clause<T>> {
+    match value {
+        ValueOrArray::Value(value) => Ok(Clause::One(parse(value)?)),
+        ValueOrArray::Array(values) => Ok(Clause::Or(
+            values
+                .iter()
+                .map(|value| parse(value))
+                .collect::<Result<_>>()?,
+        )),
+    }
+}
+
+fn parse_block_tag(tag: Option<&str>, indexed_finalized_head: u64) -> Result<u64> {
+    match tag.unwrap_or("latest") {
+        "latest" | "safe" | "finalized" => Ok(indexed_finalized_head),
+        "earliest" => Ok(1),
+        "pending" => Err(Error::InvalidParams(
+            "pending block tag is not supported by finalized history",
+        )),
+        quantity => {
+            let digits = quantity
+                .strip_prefix("0x")
+                .ok_or(Error::InvalidParams("block number must be 0x-prefixed hex"))?;
+            u64::from_str_radix(digits, 16)
+                .map_err(|_| Error::InvalidParams("invalid block number"))
+        }
+    }
+}
+
+fn parse_fixed<const N: usize>(value: &str, error: &'static str) -> Result<[u8; N]> {
+    let digits = value
+        .strip_prefix("0x")
+        .ok_or(Error::InvalidParams(error))?;
+    if digits.len() != N * 2 {
+        return Err(Error::InvalidParams(error));
+    }
+    let mut out = [0u8; N];
+    for (byte, pair) in out.iter_mut().zip(digits.as_bytes().chunks_exact(2)) {
+        let pair = core::str::from_utf8(pair).map_err(|_| Error::InvalidParams(error))?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| Error::InvalidParams(error))?;
+    }
+    Ok(out)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("0x");
+    for byte in bytes {
+        out.push(DIGITS[usize::from(byte >> 4)] as char);
+        out.push(DIGITS[usize::from(byte & 0x0f)] as char);
+    }
+    out
+}
+
+fn encode_quantity(value: u64) -> String {
+    format!("{value:#x}")
+}
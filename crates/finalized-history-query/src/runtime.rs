@@ -17,4 +17,28 @@ impl<M: MetaStore, B: BlobStore> Runtime<M, B> {
             tables,
         }
     }
+
+    /// See [`Tables::with_stream_bitmap_cache`].
+    pub fn with_stream_bitmap_cache(mut self, entries: usize) -> Self {
+        self.tables = self.tables.with_stream_bitmap_cache(entries);
+        self
+    }
+
+    /// See [`Tables::with_fast_planning`].
+    pub fn with_fast_planning(mut self, enabled: bool) -> Self {
+        self.tables = self.tables.with_fast_planning(enabled);
+        self
+    }
+
+    /// See [`Tables::with_empty_result_fast_path`].
+    pub fn with_empty_result_fast_path(mut self, enabled: bool) -> Self {
+        self.tables = self.tables.with_empty_result_fast_path(enabled);
+        self
+    }
+
+    /// See [`Tables::with_shard_presence_filter`].
+    pub fn with_shard_presence_filter(mut self, enabled: bool) -> Self {
+        self.tables = self.tables.with_shard_presence_filter(enabled);
+        self
+    }
 }
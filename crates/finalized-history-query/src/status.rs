@@ -1,9 +1,23 @@
-use crate::error::Result;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::core::header::BlockHeaderSpec;
+use crate::core::ids::LogId;
+use crate::core::state::BlockRecordSpec;
+use crate::error::{Error, Result};
 use crate::family::Families;
+use crate::kernel::sharded_streams::page_start_local;
+use crate::kernel::table_specs::{PointTableSpec, u64_key};
+use crate::logs::STREAM_PAGE_LOCAL_ID_SPAN;
+use crate::logs::ingest::stream_entries_for_log;
+use crate::logs::table_specs::{BlockHashIndexSpec, BlockLogHeaderSpec, LogBitmapByBlockSpec};
 use crate::logs::types::LogSequencingState;
 use crate::runtime::Runtime;
-use crate::store::publication::{FinalizedHeadState, PublicationStore};
-use crate::store::traits::{BlobStore, MetaStore};
+use crate::store::publication::{FinalizedHeadState, PublicationState, PublicationStore};
+use crate::store::traits::{BlobStore, MetaStore, TableId};
+use crate::streams::{StreamBitmapMeta, decode_bitmap_blob};
+use crate::tables::StreamTables;
 use crate::traces::TraceSequencingState;
 use crate::txs::TxFamilyState;
 
@@ -33,3 +47,332 @@ pub async fn service_status<M: MetaStore, P: PublicationStore, B: BlobStore>(
         trace_state: family_states.traces,
     })
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreProbe {
+    pub reachable: bool,
+    pub latency: Duration,
+}
+
+/// Operational view of the service for dashboards and alerting.
+///
+/// `publication` carries the current writer identity and lease fence, and
+/// `consecutive_backend_errors` counts ingest calls that failed with a backend
+/// error since the last successful ingest. When a store read fails, the
+/// head-derived fields are `None` and `store_probe.reachable` is false.
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    pub indexed_finalized_head: Option<u64>,
+    pub next_log_id: Option<LogId>,
+    pub publication: Option<PublicationState>,
+    pub consecutive_backend_errors: u64,
+    pub store_probe: StoreProbe,
+}
+
+/// Probes the meta store with a timed publication-state read, then derives the
+/// ingest position from the published head. Backend failures during either
+/// read are reported rather than returned.
+pub async fn service_health<M: MetaStore, P: PublicationStore, B: BlobStore>(
+    runtime: &Runtime<M, B>,
+    publication_store: &P,
+    families: &Families,
+    consecutive_backend_errors: u64,
+) -> Result<HealthReport> {
+    let started = Instant::now();
+    let probe = publication_store.load().await;
+    let mut store_probe = StoreProbe {
+        reachable: probe.is_ok(),
+        latency: started.elapsed(),
+    };
+    let Ok(publication) = probe else {
+        return Ok(HealthReport {
+            indexed_finalized_head: None,
+            next_log_id: None,
+            publication: None,
+            consecutive_backend_errors,
+            store_probe,
+        });
+    };
+
+    let indexed_finalized_head = publication
+        .as_ref()
+        .map_or(0, |state| state.indexed_finalized_head);
+    let Ok(family_states) = families
+        .load_state_from_head(runtime, indexed_finalized_head)
+        .await
+    else {
+        store_probe.reachable = false;
+        return Ok(HealthReport {
+            indexed_finalized_head: None,
+            next_log_id: None,
+            publication,
+            consecutive_backend_errors,
+            store_probe,
+        });
+    };
+    Ok(HealthReport {
+        indexed_finalized_head: Some(indexed_finalized_head),
+        next_log_id: Some(family_states.logs.next_log_id),
+        publication,
+        consecutive_backend_errors,
+        store_probe,
+    })
+}
+
+/// Approximate storage consumed by the logs of a block range.
+///
+/// `log_payload_bytes` is the size of the range's block log blobs.
+/// `stream_bytes` is each touched log stream page's fragments plus its
+/// compacted blob, if sealed, scaled by the share of the page's entries that
+/// fall in the range. `meta_record_bytes` is the keys and values of the
+/// per-block records: block record, header, log header, and hash index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RangeSizeEstimate {
+    pub blocks: u64,
+    pub logs: u64,
+    pub log_payload_bytes: u64,
+    pub stream_bytes: u64,
+    pub meta_record_bytes: u64,
+}
+
+impl RangeSizeEstimate {
+    pub fn total_bytes(&self) -> u64 {
+        self.log_payload_bytes + self.stream_bytes + self.meta_record_bytes
+    }
+}
+
+/// Estimates the storage attributable to the logs of blocks
+/// `from_block..=to_block`. Missing blocks are skipped. Stream attribution
+/// needs every log's address and topics, so this reads the whole range's log
+/// payloads and is meant for capacity planning, not serving. Fails with
+/// `InvalidParams` once the range holds more than `max_logs` logs.
+pub async fn estimate_range_bytes<M: MetaStore, B: BlobStore>(
+    runtime: &Runtime<M, B>,
+    from_block: u64,
+    to_block: u64,
+    max_logs: u64,
+) -> Result<RangeSizeEstimate> {
+    let tables = &runtime.tables;
+    let mut estimate = RangeSizeEstimate::default();
+    let mut range_entries = BTreeMap::<(String, u32), u64>::new();
+
+    for block_num in from_block..=to_block {
+        let Some(record) = tables.block_records.get(block_num).await? else {
+            continue;
+        };
+        estimate.blocks += 1;
+        let block_key = u64_key(block_num);
+        for table in [
+            BlockRecordSpec::TABLE,
+            BlockHeaderSpec::TABLE,
+            BlockLogHeaderSpec::TABLE,
+        ] {
+            estimate.meta_record_bytes += stored_record_bytes(runtime, table, &block_key).await?;
+        }
+        estimate.meta_record_bytes += stored_record_bytes(
+            runtime,
+            BlockHashIndexSpec::TABLE,
+            &BlockHashIndexSpec::key(&record.block_hash),
+        )
+        .await?;
+
+        let Some(window) = record.logs.filter(|window| window.count > 0) else {
+            continue;
+        };
+        // Checked before the payloads are read, so one call never decodes more
+        // than `max_logs` logs.
+        if estimate.logs + u64::from(window.count) > max_logs {
+            return Err(Error::InvalidParams(
+                "block range spans more logs than max_log_id_range_span",
+            ));
+        }
+        let Some(header) = tables.log_block_headers.get(block_num).await? else {
+            continue;
+        };
+        estimate.logs += u64::from(window.count);
+        estimate.log_payload_bytes += header
+            .offsets
+            .get(header.offsets.len().saturating_sub(1))
+            .unwrap_or(0);
+
+        let logs = tables
+            .log_block_blobs
+            .load_contiguous_run(block_num, 0, window.count as usize - 1)
+            .await?;
+        for (ordinal, log) in logs.iter().enumerate() {
+            let log_id = LogId::new(window.first_primary_id + ordinal as u64);
+            for (stream, local) in stream_entries_for_log(&log.to_owned_log(), log_id) {
+                let page_start = page_start_local(local, STREAM_PAGE_LOCAL_ID_SPAN);
+                *range_entries.entry((stream, page_start)).or_default() += 1;
+            }
+        }
+    }
+
+    for ((stream, page_start), in_range) in range_entries {
+        estimate.stream_bytes +=
+            attributed_page_bytes(&tables.log_streams, &stream, page_start, in_range).await?;
+    }
+    Ok(estimate)
+}
+
+async fn stored_record_bytes<M: MetaStore, B: BlobStore>(
+    runtime: &Runtime<M, B>,
+    table: TableId,
+    key: &[u8],
+) -> Result<u64> {
+    Ok(runtime
+        .meta_store
+        .get(table, key)
+        .await?
+        .map_or(0, |record| (key.len() + record.value.len()) as u64))
+}
+
+/// Scales a page's stored bytes by the share of its entries counted in
+/// `in_range`. Fragments are counted with their keys; a sealed page adds its
+/// blob, sized from `byte_size` or, for v1 meta, from the blob itself.
+async fn attributed_page_bytes<M: MetaStore, B: BlobStore>(
+    streams: &StreamTables<M, B, StreamBitmapMeta>,
+    stream: &str,
+    page_start: u32,
+    in_range: u64,
+) -> Result<u64> {
+    let fragment_key_len = LogBitmapByBlockSpec::partition(stream, page_start).len() + 8;
+    let mut page_bytes = 0u64;
+    let mut fragment_entries = 0u64;
+    for bytes in streams.load_page_fragments(stream, page_start).await? {
+        page_bytes += (fragment_key_len + bytes.len()) as u64;
+        fragment_entries += u64::from(decode_bitmap_blob(&bytes)?.count);
+    }
+    let mut page_entries = fragment_entries;
+    if let Some(meta) = streams.get_page_meta(stream, page_start).await? {
+        page_entries = page_entries.max(u64::from(meta.count));
+        page_bytes += match meta.byte_size {
+            0 => streams
+                .get_page_blob(stream, page_start)
+                .await?
+                .map_or(0, |blob| blob.len() as u64),
+            byte_size => byte_size,
+        };
+    }
+    if page_entries == 0 {
+        return Ok(0);
+    }
+    let share =
+        u128::from(page_bytes) * u128::from(in_range.min(page_entries)) / u128::from(page_entries);
+    Ok(share as u64)
+}
+
+/// Latency percentiles for one operation. Percentiles are the upper bound of
+/// the power-of-two nanosecond bucket holding the rank, capped at `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencySnapshot {
+    pub count: u64,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// In-process latency view of the service's query and ingest calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyMetrics {
+    pub query: LatencySnapshot,
+    pub ingest: LatencySnapshot,
+}
+
+const LATENCY_BUCKETS: usize = 65;
+
+/// Fixed log2 histogram of elapsed nanoseconds. Bucket `i` counts samples
+/// with `64 - leading_zeros == i`, so recording is one relaxed `fetch_add`
+/// plus a `fetch_max`.
+pub(crate) struct LatencyHistogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS],
+    max_nanos: AtomicU64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            max_nanos: AtomicU64::new(0),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub(crate) fn record(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - nanos.leading_zeros()) as usize;
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> LatencySnapshot {
+        let counts: [u64; LATENCY_BUCKETS] =
+            std::array::from_fn(|bucket| self.buckets[bucket].load(Ordering::Relaxed));
+        let count = counts.iter().sum::<u64>();
+        let max_nanos = self.max_nanos.load(Ordering::Relaxed);
+        let percentile = |quantile: f64| {
+            if count == 0 {
+                return Duration::ZERO;
+            }
+            let rank = ((count as f64 * quantile).ceil() as u64).max(1);
+            let mut seen = 0u64;
+            for (bucket, bucket_count) in counts.iter().enumerate() {
+                seen += bucket_count;
+                if seen >= rank {
+                    return Duration::from_nanos(bucket_upper_nanos(bucket).min(max_nanos));
+                }
+            }
+            Duration::from_nanos(max_nanos)
+        };
+        LatencySnapshot {
+            count,
+            p50: percentile(0.50),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+            max: Duration::from_nanos(max_nanos),
+        }
+    }
+}
+
+fn bucket_upper_nanos(bucket: usize) -> u64 {
+    match bucket {
+        0 => 0,
+        64.. => u64::MAX,
+        _ => (1u64 << bucket) - 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LatencyHistogram;
+
+    #[test]
+    fn latency_percentiles_use_bucket_bounds_capped_at_max() {
+        let histogram = LatencyHistogram::default();
+        for _ in 0..98 {
+            histogram.record(Duration::from_nanos(100));
+        }
+        histogram.record(Duration::from_nanos(5_000));
+        histogram.record(Duration::from_nanos(900));
+
+        let snapshot = histogram.snapshot();
+
+        assert_eq!(snapshot.count, 100);
+        assert_eq!(snapshot.p50, Duration::from_nanos(127));
+        assert_eq!(snapshot.p95, Duration::from_nanos(127));
+        assert_eq!(snapshot.p99, Duration::from_nanos(1_023));
+        assert_eq!(snapshot.max, Duration::from_nanos(5_000));
+    }
+
+    #[test]
+    fn empty_histogram_reports_zero() {
+        let snapshot = LatencyHistogram::default().snapshot();
+
+        assert_eq!(snapshot.count, 0);
+        assert_eq!(snapshot.max, Duration::ZERO);
+    }
+}
@@ -12,6 +12,48 @@ pub struct InMemoryBlobStore {
     inner: Arc<RwLock<HashMap<(BlobTableId, Vec<u8>), Bytes>>>,
 }
 
+/// A point-in-time copy of every blob in an [`InMemoryBlobStore`].
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryBlobSnapshot {
+    inner: HashMap<(BlobTableId, Vec<u8>), Bytes>,
+}
+
+impl InMemoryBlobStore {
+    /// Copies the current blobs. Blob bytes are reference-counted, so this
+    /// does not copy blob contents.
+    pub fn snapshot(&self) -> Result<InMemoryBlobSnapshot> {
+        let inner = self
+            .inner
+            .read()
+            .map_err(|_| Error::Backend("poisoned lock".to_string()))?
+            .clone();
+        Ok(InMemoryBlobSnapshot { inner })
+    }
+
+    /// Replaces every blob with the snapshot's contents. All clones of this
+    /// store observe the restored state.
+    pub fn restore(&self, snapshot: &InMemoryBlobSnapshot) -> Result<()> {
+        self.inner
+            .write()
+            .map_err(|_| Error::Backend("poisoned lock".to_string()))?
+            .clone_from(&snapshot.inner);
+        Ok(())
+    }
+}
+
+impl InMemoryBlobStore {
+    /// Sum of key and blob lengths over every stored blob.
+    pub fn stored_bytes(&self) -> Result<u64> {
+        Ok(self
+            .inner
+            .read()
+            .map_err(|_| Error::Backend("poisoned lock".to_string()))?
+            .iter()
+            .map(|((_, key), blob)| (key.len() + blob.len()) as u64)
+            .sum())
+    }
+}
+
 impl BlobStore for InMemoryBlobStore {
     async fn put_blob(&self, table: BlobTableId, key: &[u8], value: Bytes) -> Result<()> {
         let mut guard = self
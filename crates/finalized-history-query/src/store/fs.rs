@@ -2,21 +2,27 @@ use bytes::Bytes;
 use std::collections::HashMap;
 use std::fs;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
+use std::io::{self, ErrorKind, Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 
+use crate::core::layout::read_u64_be;
 use crate::error::{Error, Result};
 use crate::store::traits::{
     BlobStore, BlobTableId, DelCond, MetaStore, Page, PutCond, PutResult, Record, ScannableTableId,
     TableId,
 };
+use crate::store::unix_millis;
 use sha2::{Digest, Sha256};
 
+type KeyLock = futures::lock::Mutex<()>;
+
 /// Cheap clone handle to the same filesystem-backed metadata namespace.
 #[derive(Debug, Clone)]
 pub struct FsMetaStore {
     root: PathBuf,
+    retry: FsRetryPolicy,
 }
 
 impl FsMetaStore {
@@ -27,7 +33,28 @@ impl FsMetaStore {
         fs::create_dir_all(root.join("meta_scan"))
             .map_err(|e| Error::Backend(format!("create fs scannable meta dir: {e}")))?;
         let _ = min_epoch;
-        Ok(Self { root })
+        Ok(Self {
+            root,
+            retry: FsRetryPolicy::default(),
+        })
+    }
+
+    /// Retries file open/read/write calls that fail with a transient errno,
+    /// which networked filesystems such as NFS surface under load. Waits
+    /// between attempts back off exponentially from `base_delay_ms`, capped
+    /// at `max_delay_ms`, without blocking the polling thread.
+    pub fn with_retry_policy(
+        mut self,
+        max_retries: u32,
+        base_delay_ms: u64,
+        max_delay_ms: u64,
+    ) -> Self {
+        self.retry = FsRetryPolicy {
+            max_retries,
+            base_delay_ms,
+            max_delay_ms,
+        };
+        self
     }
 
     fn table_dir(&self, table: TableId) -> PathBuf {
@@ -82,7 +109,7 @@ impl FsMetaStore {
         p
     }
 
-    fn key_lock(&self, table: TableId, key: &[u8]) -> Result<Arc<Mutex<()>>> {
+    fn key_lock(&self, table: TableId, key: &[u8]) -> Result<Arc<KeyLock>> {
         self.path_lock(self.key_path(table, key))
     }
 
@@ -91,19 +118,21 @@ impl FsMetaStore {
         table: ScannableTableId,
         partition: &[u8],
         clustering: &[u8],
-    ) -> Result<Arc<Mutex<()>>> {
+    ) -> Result<Arc<KeyLock>> {
         self.path_lock(self.scan_key_path(table, partition, clustering))
     }
 
-    fn path_lock(&self, path: PathBuf) -> Result<Arc<Mutex<()>>> {
-        static LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+    /// Key locks are async mutexes: a conditional write holds its lock across
+    /// retry backoff waits, which must not block other tasks on the thread.
+    fn path_lock(&self, path: PathBuf) -> Result<Arc<KeyLock>> {
+        static LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<KeyLock>>>> = OnceLock::new();
         let locks = LOCKS.get_or_init(|| Mutex::new(HashMap::new()));
         let mut guard = locks
             .lock()
             .map_err(|_| Error::Backend("poisoned fs key lock map".to_string()))?;
         Ok(guard
             .entry(path)
-            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .or_insert_with(|| Arc::new(KeyLock::new(())))
             .clone())
     }
 }
@@ -115,9 +144,9 @@ impl MetaStore for FsMetaStore {
             return Ok(None);
         }
         let vp = self.version_path(table, key);
-        let value = read_file_bytes(&kp)?;
+        let value = read_file_bytes(&kp, &self.retry).await?;
         let version = if vp.exists() {
-            let b = read_file_bytes(&vp)?;
+            let b = read_file_bytes(&vp, &self.retry).await?;
             if b.len() != 8 {
                 return Err(Error::Decode("invalid fs version bytes"));
             }
@@ -146,13 +175,9 @@ impl MetaStore for FsMetaStore {
         } else {
             Some(self.key_lock(table, key)?)
         };
-        let _guard = if let Some(lock) = lock.as_ref() {
-            Some(
-                lock.lock()
-                    .map_err(|_| Error::Backend("poisoned fs key lock".to_string()))?,
-            )
-        } else {
-            None
+        let _guard = match lock.as_ref() {
+            Some(lock) => Some(lock.lock().await),
+            None => None,
         };
         let current = self.get(table, key).await?;
 
@@ -176,10 +201,10 @@ impl MetaStore for FsMetaStore {
             fs::create_dir_all(parent)
                 .map_err(|e| Error::Backend(format!("create fs meta table dir: {e}")))?;
         }
-        write_file_bytes(&kp, &value)?;
+        write_file_bytes(&kp, &self.retry, &value).await?;
 
         let next_version = current.map_or(1, |c| c.version + 1);
-        write_file_bytes(&vp, &next_version.to_be_bytes())?;
+        write_file_bytes(&vp, &self.retry, &next_version.to_be_bytes()).await?;
 
         Ok(PutResult {
             applied: true,
@@ -193,13 +218,9 @@ impl MetaStore for FsMetaStore {
         } else {
             Some(self.key_lock(table, key)?)
         };
-        let _guard = if let Some(lock) = lock.as_ref() {
-            Some(
-                lock.lock()
-                    .map_err(|_| Error::Backend("poisoned fs key lock".to_string()))?,
-            )
-        } else {
-            None
+        let _guard = match lock.as_ref() {
+            Some(lock) => Some(lock.lock().await),
+            None => None,
         };
         let current = self.get(table, key).await?;
         let allowed = match (cond, current.as_ref()) {
@@ -226,9 +247,9 @@ impl MetaStore for FsMetaStore {
             return Ok(None);
         }
         let vp = self.scan_version_path(table, partition, clustering);
-        let value = read_file_bytes(&kp)?;
+        let value = read_file_bytes(&kp, &self.retry).await?;
         let version = if vp.exists() {
-            let b = read_file_bytes(&vp)?;
+            let b = read_file_bytes(&vp, &self.retry).await?;
             if b.len() != 8 {
                 return Err(Error::Decode("invalid fs version bytes"));
             }
@@ -258,13 +279,9 @@ impl MetaStore for FsMetaStore {
         } else {
             Some(self.scan_key_lock(table, partition, clustering)?)
         };
-        let _guard = if let Some(lock) = lock.as_ref() {
-            Some(
-                lock.lock()
-                    .map_err(|_| Error::Backend("poisoned fs key lock".to_string()))?,
-            )
-        } else {
-            None
+        let _guard = match lock.as_ref() {
+            Some(lock) => Some(lock.lock().await),
+            None => None,
         };
         let current = self.scan_get(table, partition, clustering).await?;
 
@@ -289,10 +306,10 @@ impl MetaStore for FsMetaStore {
                 Error::Backend(format!("create fs scannable meta partition dir: {e}"))
             })?;
         }
-        write_file_bytes(&kp, &value)?;
+        write_file_bytes(&kp, &self.retry, &value).await?;
 
         let next_version = current.map_or(1, |c| c.version + 1);
-        write_file_bytes(&vp, &next_version.to_be_bytes())?;
+        write_file_bytes(&vp, &self.retry, &next_version.to_be_bytes()).await?;
 
         Ok(PutResult {
             applied: true,
@@ -312,13 +329,9 @@ impl MetaStore for FsMetaStore {
         } else {
             Some(self.scan_key_lock(table, partition, clustering)?)
         };
-        let _guard = if let Some(lock) = lock.as_ref() {
-            Some(
-                lock.lock()
-                    .map_err(|_| Error::Backend("poisoned fs key lock".to_string()))?,
-            )
-        } else {
-            None
+        let _guard = match lock.as_ref() {
+            Some(lock) => Some(lock.lock().await),
+            None => None,
         };
         let current = self.scan_get(table, partition, clustering).await?;
         let allowed = match (cond, current.as_ref()) {
@@ -379,6 +392,7 @@ impl MetaStore for FsMetaStore {
 #[derive(Debug, Clone)]
 pub struct FsBlobStore {
     root: PathBuf,
+    retry: FsRetryPolicy,
 }
 
 impl FsBlobStore {
@@ -386,7 +400,28 @@ impl FsBlobStore {
         let root = root.as_ref().to_path_buf();
         fs::create_dir_all(root.join("blob"))
             .map_err(|e| Error::Backend(format!("create fs blob dir: {e}")))?;
-        Ok(Self { root })
+        Ok(Self {
+            root,
+            retry: FsRetryPolicy::default(),
+        })
+    }
+
+    /// Retries file open/read/write calls that fail with a transient errno,
+    /// which networked filesystems such as NFS surface under load. Waits
+    /// between attempts back off exponentially from `base_delay_ms`, capped
+    /// at `max_delay_ms`, without blocking the polling thread.
+    pub fn with_retry_policy(
+        mut self,
+        max_retries: u32,
+        base_delay_ms: u64,
+        max_delay_ms: u64,
+    ) -> Self {
+        self.retry = FsRetryPolicy {
+            max_retries,
+            base_delay_ms,
+            max_delay_ms,
+        };
+        self
     }
 
     fn table_dir(&self, table: BlobTableId) -> PathBuf {
@@ -406,29 +441,67 @@ impl FsBlobStore {
         checksum.set_extension("sha256");
         checksum
     }
-}
 
-impl BlobStore for FsBlobStore {
-    async fn put_blob(&self, table: BlobTableId, key: &[u8], value: Bytes) -> Result<()> {
+    /// Writes the blob and its checksum sidecar. A TTL blob's expiry is
+    /// appended to the checksum, so plain blobs cost no extra file and
+    /// rewriting a blob replaces any earlier expiry.
+    async fn write_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: &Bytes,
+        expires_at_ms: Option<u64>,
+    ) -> Result<()> {
         let path = self.key_path(table, key);
         let checksum_path = self.checksum_path(table, key);
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)
                 .map_err(|e| Error::Backend(format!("create fs blob table dir: {e}")))?;
         }
-        let checksum = sha256_digest(&value);
-        write_file_bytes(&path, &value)?;
-        write_file_bytes(&checksum_path, checksum.as_slice())
+        let mut checksum = sha256_digest(value).to_vec();
+        if let Some(expires_at_ms) = expires_at_ms {
+            checksum.extend_from_slice(&expires_at_ms.to_be_bytes());
+        }
+        write_file_bytes(&path, &self.retry, value).await?;
+        write_file_bytes(&checksum_path, &self.retry, &checksum)
+            .await
+    }
+}
+
+impl BlobStore for FsBlobStore {
+    async fn put_blob(&self, table: BlobTableId, key: &[u8], value: Bytes) -> Result<()> {
+        self.write_blob(table, key, &value, None).await
+    }
+
+    /// Stores the expiry in the checksum sidecar. Reads verify the blob
+    /// against that sidecar, so the blob is never served without its
+    /// deadline.
+    async fn put_blob_with_ttl(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: Bytes,
+        ttl: Duration,
+    ) -> Result<()> {
+        let ttl_ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+        let expires_at_ms = unix_millis().saturating_add(ttl_ms);
+        self.write_blob(table, key, &value, Some(expires_at_ms))
+            .await
     }
 
+    /// An expired blob reads as absent and its files are removed.
     async fn get_blob(&self, table: BlobTableId, key: &[u8]) -> Result<Option<Bytes>> {
         let p = self.key_path(table, key);
         if !p.exists() {
             return Ok(None);
         }
         let checksum_path = self.checksum_path(table, key);
-        let b = read_file_bytes(&p)?;
-        let expected = read_checksum_file(&checksum_path)?;
+        let (expected, expires_at_ms) = read_checksum_file(&checksum_path, &self.retry).await?;
+        if expires_at_ms.is_some_and(|expires_at_ms| unix_millis() >= expires_at_ms) {
+            self.delete_blob(table, key).await?;
+            return Ok(None);
+        }
+        let b = read_file_bytes(&p, &self.retry).await?;
         let actual = sha256_digest(&b);
         if actual != expected {
             return Err(Error::Backend("fs blob integrity check failed".to_string()));
@@ -481,19 +554,103 @@ impl BlobStore for FsBlobStore {
     }
 }
 
-fn read_file_bytes(path: &Path) -> Result<Vec<u8>> {
-    let mut file = OpenOptions::new()
-        .read(true)
-        .open(path)
-        .map_err(|e| Error::Backend(format!("fs read open: {e}")))?;
-    set_no_cache(&file)?;
-    let mut out = Vec::new();
-    file.read_to_end(&mut out)
-        .map_err(|e| Error::Backend(format!("fs read: {e}")))?;
-    Ok(out)
+/// Retry policy for filesystem IO. Retries are disabled unless configured via
+/// `with_retry_policy`, since local disks rarely produce transient errors.
+#[derive(Debug, Clone, Copy)]
+struct FsRetryPolicy {
+    max_retries: u32,
+    base_delay_ms: u64,
+    max_delay_ms: u64,
+}
+
+impl Default for FsRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay_ms: 25,
+            max_delay_ms: 1000,
+        }
+    }
+}
+
+/// A failed filesystem call together with the step that produced it.
+#[derive(Debug)]
+struct FsIoError {
+    op: &'static str,
+    source: io::Error,
+}
+
+impl FsIoError {
+    fn at(op: &'static str) -> impl FnOnce(io::Error) -> Self {
+        move |source| Self { op, source }
+    }
+}
+
+impl FsRetryPolicy {
+    async fn run<T>(
+        &self,
+        mut f: impl FnMut() -> core::result::Result<T, FsIoError>,
+    ) -> Result<T> {
+        let mut attempt: u32 = 0;
+        loop {
+            match f() {
+                Ok(v) => return Ok(v),
+                Err(e) => {
+                    if attempt >= self.max_retries || !is_retryable_io_error(&e.source) {
+                        return Err(Error::Backend(format!("{}: {}", e.op, e.source)));
+                    }
+                    let backoff =
+                        compute_backoff_ms(attempt, self.base_delay_ms, self.max_delay_ms);
+                    backoff_sleep(Duration::from_millis(backoff)).await;
+                    attempt = attempt.saturating_add(1);
+                }
+            }
+        }
+    }
 }
 
-fn write_file_bytes(path: &Path, bytes: &[u8]) -> Result<()> {
+fn compute_backoff_ms(attempt: u32, base_ms: u64, max_ms: u64) -> u64 {
+    let factor = 1u64 << core::cmp::min(attempt, 8);
+    core::cmp::min(base_ms.saturating_mul(factor), max_ms)
+}
+
+/// Completes after `delay` without blocking the polling thread. The fs stores
+/// run on any executor, so instead of a runtime timer a short-lived thread
+/// sleeps and then completes a oneshot channel.
+async fn backoff_sleep(delay: Duration) {
+    let (done, wait) = futures::channel::oneshot::channel::<()>();
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let _ = done.send(());
+    });
+    let _ = wait.await;
+}
+
+/// EAGAIN, EINTR, and ETIMEDOUT are transient; everything else, including
+/// ENOENT and EACCES, fails immediately.
+fn is_retryable_io_error(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::WouldBlock | ErrorKind::Interrupted | ErrorKind::TimedOut
+    )
+}
+
+async fn read_file_bytes(path: &Path, retry: &FsRetryPolicy) -> Result<Vec<u8>> {
+    retry.run(|| {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .open(path)
+            .map_err(FsIoError::at("fs read open"))?;
+        set_no_cache(&file)?;
+        let mut out = Vec::new();
+        file.read_to_end(&mut out)
+            .map_err(FsIoError::at("fs read"))?;
+        Ok(out)
+    })
+    .await
+}
+
+async fn write_file_bytes(path: &Path, retry: &FsRetryPolicy, bytes: &[u8]) -> Result<()> {
     let parent = path
         .parent()
         .ok_or_else(|| Error::Backend("fs write path missing parent".to_string()))?;
@@ -505,30 +662,43 @@ fn write_file_bytes(path: &Path, bytes: &[u8]) -> Result<()> {
         std::process::id()
     ));
 
-    let mut file = OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .open(&tmp_path)
-        .map_err(|e| Error::Backend(format!("fs write open: {e}")))?;
-    set_no_cache(&file)?;
-    file.write_all(bytes)
-        .map_err(|e| Error::Backend(format!("fs write: {e}")))?;
-    file.sync_all()
-        .map_err(|e| Error::Backend(format!("fs sync: {e}")))?;
-    drop(file);
-    fs::rename(&tmp_path, path).map_err(|e| Error::Backend(format!("fs rename: {e}")))?;
-    Ok(())
+    retry.run(|| {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&tmp_path)
+            .map_err(FsIoError::at("fs write open"))?;
+        set_no_cache(&file)?;
+        file.write_all(bytes).map_err(FsIoError::at("fs write"))?;
+        file.sync_all().map_err(FsIoError::at("fs sync"))?;
+        drop(file);
+        fs::rename(&tmp_path, path).map_err(FsIoError::at("fs rename"))
+    })
+    .await
 }
 
-fn read_checksum_file(path: &Path) -> Result<[u8; 32]> {
-    let bytes = read_file_bytes(path)?;
-    bytes.try_into().map_err(|_| {
+/// Reads a checksum sidecar: the SHA-256 digest, followed by the big-endian
+/// unix-millisecond expiry for TTL blobs.
+async fn read_checksum_file(
+    path: &Path,
+    retry: &FsRetryPolicy,
+) -> Result<([u8; 32], Option<u64>)> {
+    let bytes = read_file_bytes(path, retry).await?;
+    let invalid_length = || {
         Error::Backend(format!(
             "fs blob integrity metadata has invalid length: {}",
             path.display()
         ))
-    })
+    };
+    let (digest, expiry) = bytes.split_at_checked(32).ok_or_else(invalid_length)?;
+    let digest = digest.try_into().map_err(|_| invalid_length())?;
+    let expires_at_ms = match expiry.len() {
+        0 => None,
+        8 => read_u64_be(expiry),
+        _ => return Err(invalid_length()),
+    };
+    Ok((digest, expires_at_ms))
 }
 
 fn sha256_digest(bytes: &[u8]) -> [u8; 32] {
@@ -536,21 +706,21 @@ fn sha256_digest(bytes: &[u8]) -> [u8; 32] {
 }
 
 #[cfg(all(target_os = "macos", feature = "macos-fs-nocache"))]
-fn set_no_cache(file: &File) -> Result<()> {
+fn set_no_cache(file: &File) -> core::result::Result<(), FsIoError> {
     use std::os::fd::AsRawFd;
 
     let rc = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1) };
     if rc == -1 {
-        return Err(Error::Backend(format!(
-            "macos fcntl(F_NOCACHE) failed: {}",
-            std::io::Error::last_os_error()
-        )));
+        return Err(FsIoError {
+            op: "macos fcntl(F_NOCACHE) failed",
+            source: io::Error::last_os_error(),
+        });
     }
     Ok(())
 }
 
 #[cfg(not(all(target_os = "macos", feature = "macos-fs-nocache")))]
-fn set_no_cache(_file: &File) -> Result<()> {
+fn set_no_cache(_file: &File) -> core::result::Result<(), FsIoError> {
     Ok(())
 }
 
@@ -854,4 +1024,127 @@ mod tests {
 
         let _ = fs::remove_dir_all(root);
     }
+
+    #[test]
+    fn expired_ttl_blob_reads_as_none_and_is_removed() {
+        let root = unique_temp_root("fs-blob-ttl");
+        let blob_store = FsBlobStore::new(&root).expect("fs blob store");
+
+        block_on(async {
+            blob_store
+                .put_blob_with_ttl(
+                    TEST_BLOB_TABLE,
+                    b"ephemeral",
+                    Bytes::from_static(b"scratch"),
+                    Duration::ZERO,
+                )
+                .await
+                .expect("put ttl blob");
+            blob_store
+                .put_blob_with_ttl(
+                    TEST_BLOB_TABLE,
+                    b"live",
+                    Bytes::from_static(b"scratch"),
+                    Duration::from_secs(3600),
+                )
+                .await
+                .expect("put live ttl blob");
+            let listed = blob_store
+                .list_prefix(TEST_BLOB_TABLE, b"", None, 10)
+                .await
+                .expect("list");
+            assert_eq!(listed.keys, vec![b"ephemeral".to_vec(), b"live".to_vec()]);
+
+            assert_eq!(
+                blob_store
+                    .get_blob(TEST_BLOB_TABLE, b"ephemeral")
+                    .await
+                    .expect("get expired"),
+                None
+            );
+            assert_eq!(
+                blob_store
+                    .get_blob(TEST_BLOB_TABLE, b"live")
+                    .await
+                    .expect("get live"),
+                Some(Bytes::from_static(b"scratch"))
+            );
+
+            blob_store
+                .put_blob_with_ttl(
+                    TEST_BLOB_TABLE,
+                    b"kept",
+                    Bytes::from_static(b"scratch"),
+                    Duration::ZERO,
+                )
+                .await
+                .expect("put ttl blob");
+            blob_store
+                .put_blob(TEST_BLOB_TABLE, b"kept", Bytes::from_static(b"final"))
+                .await
+                .expect("overwrite without ttl");
+            assert_eq!(
+                blob_store
+                    .get_blob(TEST_BLOB_TABLE, b"kept")
+                    .await
+                    .expect("get rewritten blob"),
+                Some(Bytes::from_static(b"final"))
+            );
+        });
+
+        for path in [
+            blob_store.key_path(TEST_BLOB_TABLE, b"ephemeral"),
+            blob_store.checksum_path(TEST_BLOB_TABLE, b"ephemeral"),
+        ] {
+            assert!(!path.exists(), "{} should be removed", path.display());
+        }
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn retry_policy_retries_transient_errors_until_success() {
+        let retry = FsRetryPolicy {
+            max_retries: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 2,
+        };
+        let mut attempts = 0u32;
+        let value = block_on(retry.run(|| {
+            attempts += 1;
+            if attempts <= 2 {
+                Err(FsIoError {
+                    op: "fs read",
+                    source: io::Error::from(ErrorKind::Interrupted),
+                })
+            } else {
+                Ok(attempts)
+            }
+        }))
+        .expect("third attempt succeeds");
+
+        assert_eq!(value, 3);
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn retry_policy_fails_immediately_on_non_retryable_errors() {
+        let retry = FsRetryPolicy {
+            max_retries: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 2,
+        };
+        let mut attempts = 0u32;
+        let err = block_on(retry.run::<()>(|| {
+            attempts += 1;
+            Err(FsIoError {
+                op: "fs read open",
+                source: io::Error::from(ErrorKind::PermissionDenied),
+            })
+        }))
+        .expect_err("permission errors are not retried");
+
+        assert_eq!(attempts, 1);
+        assert!(matches!(err, Error::Backend(msg) if msg.starts_with("fs read open: ")));
+    }
 }
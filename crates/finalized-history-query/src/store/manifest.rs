@@ -1,23 +1,24 @@
 use crate::core::header::BlockHeaderSpec;
 use crate::core::state::BlockRecordSpec;
+use crate::ingest::wal::INGEST_WAL_TABLE;
 use crate::kernel::table_specs::BlobTableSpec;
 use crate::kernel::table_specs::{PointTableSpec, ScannableTableSpec};
 use crate::logs::table_specs::{
     BlockHashIndexSpec, BlockLogBlobSpec, BlockLogHeaderSpec, LogBitmapByBlockSpec,
     LogBitmapPageBlobSpec, LogBitmapPageMetaSpec, LogDirBucketSpec, LogDirByBlockSpec,
-    LogDirSubBucketSpec, LogOpenBitmapPageSpec,
+    LogDirSubBucketSpec, LogOpenBitmapPageSpec, LogStreamShardSpec,
 };
 use crate::store::publication::PUBLICATION_STATE_TABLE;
 use crate::store::traits::{BlobTableId, ScannableTableId, TableId};
 use crate::traces::table_specs::{
     BlockTraceBlobSpec, BlockTraceHeaderSpec, TraceBitmapByBlockSpec, TraceBitmapPageBlobSpec,
     TraceBitmapPageMetaSpec, TraceDirBucketSpec, TraceDirByBlockSpec, TraceDirSubBucketSpec,
-    TraceOpenBitmapPageSpec,
+    TraceOpenBitmapPageSpec, TraceStreamShardSpec,
 };
 use crate::txs::table_specs::{
     BlockTxBlobSpec, BlockTxHeaderSpec, TxBitmapByBlockSpec, TxBitmapPageBlobSpec,
     TxBitmapPageMetaSpec, TxDirBucketSpec, TxDirByBlockSpec, TxDirSubBucketSpec, TxHashIndexSpec,
-    TxOpenBitmapPageSpec,
+    TxOpenBitmapPageSpec, TxStreamShardSpec,
 };
 
 pub const RUNTIME_POINT_TABLES: [TableId; 16] = [
@@ -39,8 +40,9 @@ pub const RUNTIME_POINT_TABLES: [TableId; 16] = [
     TraceBitmapPageMetaSpec::TABLE,
 ];
 
-pub const REQUIRED_POINT_TABLES: [TableId; 17] = [
+pub const REQUIRED_POINT_TABLES: [TableId; 18] = [
     PUBLICATION_STATE_TABLE,
+    INGEST_WAL_TABLE,
     RUNTIME_POINT_TABLES[0],
     RUNTIME_POINT_TABLES[1],
     RUNTIME_POINT_TABLES[2],
@@ -59,19 +61,22 @@ pub const REQUIRED_POINT_TABLES: [TableId; 17] = [
     RUNTIME_POINT_TABLES[15],
 ];
 
-pub const RUNTIME_SCANNABLE_TABLES: [ScannableTableId; 9] = [
+pub const RUNTIME_SCANNABLE_TABLES: [ScannableTableId; 12] = [
     LogDirByBlockSpec::TABLE,
     LogBitmapByBlockSpec::TABLE,
     LogOpenBitmapPageSpec::TABLE,
+    LogStreamShardSpec::TABLE,
     TxDirByBlockSpec::TABLE,
     TxBitmapByBlockSpec::TABLE,
     TxOpenBitmapPageSpec::TABLE,
+    TxStreamShardSpec::TABLE,
     TraceDirByBlockSpec::TABLE,
     TraceBitmapByBlockSpec::TABLE,
     TraceOpenBitmapPageSpec::TABLE,
+    TraceStreamShardSpec::TABLE,
 ];
 
-pub const REQUIRED_SCANNABLE_TABLES: [ScannableTableId; 9] = [
+pub const REQUIRED_SCANNABLE_TABLES: [ScannableTableId; 12] = [
     RUNTIME_SCANNABLE_TABLES[0],
     RUNTIME_SCANNABLE_TABLES[1],
     RUNTIME_SCANNABLE_TABLES[2],
@@ -81,6 +86,9 @@ pub const REQUIRED_SCANNABLE_TABLES: [ScannableTableId; 9] = [
     RUNTIME_SCANNABLE_TABLES[6],
     RUNTIME_SCANNABLE_TABLES[7],
     RUNTIME_SCANNABLE_TABLES[8],
+    RUNTIME_SCANNABLE_TABLES[9],
+    RUNTIME_SCANNABLE_TABLES[10],
+    RUNTIME_SCANNABLE_TABLES[11],
 ];
 
 pub const RUNTIME_BLOB_TABLES: [BlobTableId; 6] = [
@@ -24,6 +24,71 @@ impl Default for InMemoryMetaStore {
     }
 }
 
+/// A point-in-time copy of every record, including versions, in an
+/// [`InMemoryMetaStore`].
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryMetaSnapshot {
+    inner: BTreeMap<(TableId, Vec<u8>), Record>,
+    scan_inner: BTreeMap<(ScannableTableId, Vec<u8>, Vec<u8>), Record>,
+}
+
+impl InMemoryMetaStore {
+    /// Copies the current point and scannable records.
+    pub fn snapshot(&self) -> Result<InMemoryMetaSnapshot> {
+        let inner = self
+            .inner
+            .read()
+            .map_err(|_| Error::Backend("poisoned lock".to_string()))?
+            .clone();
+        let scan_inner = self
+            .scan_inner
+            .read()
+            .map_err(|_| Error::Backend("poisoned lock".to_string()))?
+            .clone();
+        Ok(InMemoryMetaSnapshot { inner, scan_inner })
+    }
+
+    /// Replaces every record with the snapshot's contents. All clones of
+    /// this store observe the restored state.
+    pub fn restore(&self, snapshot: &InMemoryMetaSnapshot) -> Result<()> {
+        let mut inner = self
+            .inner
+            .write()
+            .map_err(|_| Error::Backend("poisoned lock".to_string()))?;
+        let mut scan_inner = self
+            .scan_inner
+            .write()
+            .map_err(|_| Error::Backend("poisoned lock".to_string()))?;
+        inner.clone_from(&snapshot.inner);
+        scan_inner.clone_from(&snapshot.scan_inner);
+        Ok(())
+    }
+}
+
+impl InMemoryMetaStore {
+    /// Sum of key and value lengths over every point and scannable record.
+    /// Scannable keys count both the partition and the clustering key.
+    pub fn stored_bytes(&self) -> Result<u64> {
+        let inner = self
+            .inner
+            .read()
+            .map_err(|_| Error::Backend("poisoned lock".to_string()))?;
+        let scan_inner = self
+            .scan_inner
+            .read()
+            .map_err(|_| Error::Backend("poisoned lock".to_string()))?;
+        let point = inner
+            .iter()
+            .map(|((_, key), record)| (key.len() + record.value.len()) as u64);
+        let scannable = scan_inner
+            .iter()
+            .map(|((_, partition, clustering), record)| {
+                (partition.len() + clustering.len() + record.value.len()) as u64
+            });
+        Ok(point.chain(scannable).sum())
+    }
+}
+
 impl MetaStore for InMemoryMetaStore {
     async fn get(&self, table: TableId, key: &[u8]) -> Result<Option<Record>> {
         let guard = self
@@ -236,6 +301,7 @@ mod tests {
     use crate::logs::table_specs::LogDirByBlockSpec;
     use crate::store::traits::MetaStore;
     use crate::store::traits::PutCond;
+    use crate::store::traits::TableId;
 
     const PAGE_LIMIT: usize = 4;
     const ENTRY_COUNT: usize = PAGE_LIMIT + 1;
@@ -282,4 +348,30 @@ mod tests {
             assert_eq!(unique.len(), ENTRY_COUNT);
         });
     }
+
+    #[test]
+    fn multi_get_matches_individual_gets_in_input_order() {
+        block_on(async {
+            const TABLE: TableId = TableId::new("multi_get_test");
+            let store = InMemoryMetaStore::default();
+            for key in [b"a".as_slice(), b"c"] {
+                store
+                    .put(TABLE, key, Bytes::copy_from_slice(key), PutCond::Any)
+                    .await
+                    .expect("seed key");
+            }
+
+            let keys = vec![b"c".to_vec(), b"b".to_vec(), b"a".to_vec(), b"c".to_vec()];
+            let batched = store.multi_get(TABLE, &keys).await.expect("multi_get");
+            assert_eq!(batched.len(), keys.len());
+            for (key, record) in keys.iter().zip(batched) {
+                let single = store.get(TABLE, key).await.expect("get");
+                assert_eq!(
+                    record.map(|record| (record.value, record.version)),
+                    single.map(|record| (record.value, record.version))
+                );
+            }
+            assert!(store.multi_get(TABLE, &[]).await.expect("empty").is_empty());
+        });
+    }
 }
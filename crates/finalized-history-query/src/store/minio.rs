@@ -2,12 +2,36 @@ use aws_config::BehaviorVersion;
 use aws_credential_types::Credentials;
 use aws_sdk_s3::Client;
 use aws_sdk_s3::config::{Builder as S3ConfigBuilder, Region};
-use aws_sdk_s3::types::{ChecksumAlgorithm, ChecksumMode};
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{ChecksumAlgorithm, ChecksumMode, CompletedMultipartUpload, CompletedPart};
 use bytes::Bytes;
+use std::collections::HashMap;
 use tokio::time::{Duration, sleep};
 
 use crate::error::{Error, Result};
 use crate::store::traits::{BlobStore, BlobTableId, Page};
+use crate::store::unix_millis;
+
+const DEFAULT_MULTIPART_THRESHOLD_BYTES: usize = 8 * 1024 * 1024;
+/// S3 rejects non-final multipart parts smaller than 5 MiB.
+const MIN_MULTIPART_PART_BYTES: usize = 5 * 1024 * 1024;
+/// User metadata key holding a TTL blob's expiry as unix milliseconds.
+const EXPIRES_AT_METADATA_KEY: &str = "fhq-expires-at-ms";
+/// Object tag on TTL blobs, for a bucket lifecycle rule to reap them.
+const EPHEMERAL_TAGGING: &str = "fhq-ephemeral=true";
+
+/// How blob object keys are laid out under `<object_prefix><table>/`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ObjectKeyLayout {
+    /// `<table>/<hex_key>`.
+    #[default]
+    Flat,
+    /// `<table>/<partition>/<hex_key>`, where `partition` is a stable hash of
+    /// the key modulo `partitions`, as four hex digits. Spreads a table over
+    /// `partitions` prefixes for backends that throttle per prefix. Listing
+    /// merges one request per partition back into raw-key order.
+    HashPartitioned { partitions: u16 },
+}
 
 /// Cheap clone handle to the same MinIO bucket/prefix and shared client state.
 #[derive(Clone)]
@@ -15,9 +39,11 @@ pub struct MinioBlobStore {
     client: Client,
     bucket: String,
     object_prefix: String,
+    key_layout: ObjectKeyLayout,
     max_retries: u32,
     base_delay_ms: u64,
     max_delay_ms: u64,
+    multipart_threshold_bytes: usize,
 }
 
 impl MinioBlobStore {
@@ -52,9 +78,11 @@ impl MinioBlobStore {
             client,
             bucket: bucket.to_string(),
             object_prefix: normalize_prefix(object_prefix),
+            key_layout: ObjectKeyLayout::Flat,
             max_retries: 4,
             base_delay_ms: 25,
             max_delay_ms: 1000,
+            multipart_threshold_bytes: DEFAULT_MULTIPART_THRESHOLD_BYTES,
         })
     }
 
@@ -70,8 +98,22 @@ impl MinioBlobStore {
         self
     }
 
+    /// Payloads larger than `threshold_bytes` are uploaded as a multipart
+    /// upload in parts of `threshold_bytes` (at least the 5 MiB S3 minimum).
+    pub fn with_multipart_threshold(mut self, threshold_bytes: usize) -> Self {
+        self.multipart_threshold_bytes = threshold_bytes;
+        self
+    }
+
+    /// Objects written under one layout are not found under another, so a
+    /// store must keep the layout it was first written with.
+    pub fn with_key_layout(mut self, key_layout: ObjectKeyLayout) -> Self {
+        self.key_layout = key_layout;
+        self
+    }
+
     fn object_key(&self, table: BlobTableId, key: &[u8]) -> String {
-        format!("{}{}/{}", self.object_prefix, table.as_str(), hex(key))
+        object_key(&self.object_prefix, self.key_layout, table.as_str(), key)
     }
 
     fn object_list_prefix(&self, table: BlobTableId, prefix: &[u8]) -> String {
@@ -85,6 +127,9 @@ impl MinioBlobStore {
 impl BlobStore for MinioBlobStore {
     async fn put_blob(&self, table: BlobTableId, key: &[u8], value: Bytes) -> Result<()> {
         let object_key = self.object_key(table, key);
+        if value.len() > self.multipart_threshold_bytes {
+            return self.put_multipart(&object_key, value).await;
+        }
         let payload = value.to_vec();
         self.with_retry("put_blob", || async {
             self.client
@@ -101,40 +146,75 @@ impl BlobStore for MinioBlobStore {
         .await
     }
 
-    async fn get_blob(&self, table: BlobTableId, key: &[u8]) -> Result<Option<Bytes>> {
+    /// Stores the expiry in object metadata and tags the object as
+    /// ephemeral. Reads treat an expired object as absent and delete it; a
+    /// bucket lifecycle rule on the tag reclaims objects that are never read
+    /// again. TTL blobs are always written in a single part.
+    async fn put_blob_with_ttl(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: Bytes,
+        ttl: Duration,
+    ) -> Result<()> {
         let object_key = self.object_key(table, key);
-        self.with_retry("get_blob", || async {
-            let res = self
-                .client
-                .get_object()
+        let ttl_ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+        let expires_at_ms = unix_millis().saturating_add(ttl_ms).to_string();
+        let payload = value.to_vec();
+        self.with_retry("put_blob_with_ttl", || async {
+            self.client
+                .put_object()
                 .bucket(&self.bucket)
                 .key(&object_key)
-                .checksum_mode(ChecksumMode::Enabled)
+                .checksum_algorithm(ChecksumAlgorithm::Sha256)
+                .metadata(EXPIRES_AT_METADATA_KEY, &expires_at_ms)
+                .tagging(EPHEMERAL_TAGGING)
+                .body(payload.clone().into())
                 .send()
-                .await;
+                .await
+                .map_err(|e| Error::Backend(format!("minio put_blob_with_ttl: {e}")))?;
+            Ok(())
+        })
+        .await
+    }
 
-            let out = match res {
-                Ok(resp) => {
-                    let aggregated = resp
-                        .body
-                        .collect()
-                        .await
-                        .map_err(|e| Error::Backend(format!("minio read body: {e}")))?;
-                    Some(aggregated.into_bytes())
-                }
-                Err(err) => {
-                    let msg = err.to_string();
-                    if msg.contains("NoSuchKey") || msg.contains("not found") {
-                        None
-                    } else {
-                        return Err(Error::Backend(format!("minio get_blob: {err}")));
+    async fn get_blob(&self, table: BlobTableId, key: &[u8]) -> Result<Option<Bytes>> {
+        let object_key = self.object_key(table, key);
+        let out = self
+            .with_retry("get_blob", || async {
+                let res = self
+                    .client
+                    .get_object()
+                    .bucket(&self.bucket)
+                    .key(&object_key)
+                    .checksum_mode(ChecksumMode::Enabled)
+                    .send()
+                    .await;
+
+                let out = match res {
+                    Ok(resp) if is_expired(resp.metadata()) => Some(None),
+                    Ok(resp) => {
+                        let aggregated = resp
+                            .body
+                            .collect()
+                            .await
+                            .map_err(|e| Error::Backend(format!("minio read body: {e}")))?;
+                        Some(Some(aggregated.into_bytes()))
                     }
-                }
-            };
+                    Err(err) => {
+                        let msg = err.to_string();
+                        if msg.contains("NoSuchKey") || msg.contains("not found") {
+                            None
+                        } else {
+                            return Err(Error::Backend(format!("minio get_blob: {err}")));
+                        }
+                    }
+                };
 
-            Ok(out)
-        })
-        .await
+                Ok(out)
+            })
+            .await?;
+        self.resolve_expiry(table, key, out).await
     }
 
     async fn read_range(
@@ -149,36 +229,38 @@ impl BlobStore for MinioBlobStore {
         }
         let object_key = self.object_key(table, key);
         let range = format!("bytes={}-{}", start, end_exclusive - 1);
-        self.with_retry("read_range", || async {
-            let res = self
-                .client
-                .get_object()
-                .bucket(&self.bucket)
-                .key(&object_key)
-                .range(&range)
-                .send()
-                .await;
+        let out = self
+            .with_retry("read_range", || async {
+                let res = self
+                    .client
+                    .get_object()
+                    .bucket(&self.bucket)
+                    .key(&object_key)
+                    .range(&range)
+                    .send()
+                    .await;
 
-            match res {
-                Ok(resp) => {
-                    let aggregated = resp
-                        .body
-                        .collect()
-                        .await
-                        .map_err(|e| Error::Backend(format!("minio read_range body: {e}")))?;
-                    Ok(Some(aggregated.into_bytes()))
-                }
-                Err(err) => {
-                    let msg = err.to_string();
-                    if msg.contains("NoSuchKey") || msg.contains("not found") {
-                        Ok(None)
-                    } else {
-                        Err(Error::Backend(format!("minio read_range: {err}")))
+                match res {
+                    Ok(resp) if is_expired(resp.metadata()) => Ok(Some(None)),
+                    Ok(resp) => {
+                        let aggregated =
+                            resp.body.collect().await.map_err(|e| {
+                                Error::Backend(format!("minio read_range body: {e}"))
+                            })?;
+                        Ok(Some(Some(aggregated.into_bytes())))
+                    }
+                    Err(err) => {
+                        let msg = err.to_string();
+                        if msg.contains("NoSuchKey") || msg.contains("not found") {
+                            Ok(None)
+                        } else {
+                            Err(Error::Backend(format!("minio read_range: {err}")))
+                        }
                     }
                 }
-            }
-        })
-        .await
+            })
+            .await?;
+        self.resolve_expiry(table, key, out).await
     }
 
     async fn delete_blob(&self, table: BlobTableId, key: &[u8]) -> Result<()> {
@@ -199,6 +281,11 @@ impl BlobStore for MinioBlobStore {
         cursor: Option<Vec<u8>>,
         limit: usize,
     ) -> Result<Page> {
+        if let ObjectKeyLayout::HashPartitioned { partitions } = self.key_layout {
+            return self
+                .list_partitioned(table, prefix, cursor, limit, partitions)
+                .await;
+        }
         let mut req = self
             .client
             .list_objects_v2()
@@ -227,7 +314,7 @@ impl BlobStore for MinioBlobStore {
             let Some(k) = obj.key() else {
                 continue;
             };
-            if let Some(raw) = decode_object_key(k, &self.object_prefix)
+            if let Some(raw) = decode_object_key(k, &self.object_prefix, self.key_layout)
                 && raw.starts_with(prefix)
             {
                 keys.push(raw);
@@ -244,6 +331,153 @@ impl BlobStore for MinioBlobStore {
 }
 
 impl MinioBlobStore {
+    /// Lists every partition from just after `cursor` and merges the results,
+    /// so pages come back in raw-key order as under `Flat`. The cursor is the
+    /// last returned raw key rather than a continuation token. Each hex key
+    /// sorts like its raw bytes, so each partition's listing is already in
+    /// order.
+    async fn list_partitioned(
+        &self,
+        table: BlobTableId,
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+        partitions: u16,
+    ) -> Result<Page> {
+        let listings = (0..partitions.max(1)).map(|partition| {
+            let group = format!("{}{}/{partition:04x}/", self.object_prefix, table.as_str());
+            let mut req = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(format!("{group}{}", hex(prefix)))
+                .max_keys(limit as i32);
+            if let Some(after) = cursor.as_deref().filter(|after| !after.is_empty()) {
+                req = req.start_after(format!("{group}{}", hex(after)));
+            }
+            async move {
+                let resp = self
+                    .with_retry("list_prefix", || async {
+                        req.clone()
+                            .send()
+                            .await
+                            .map_err(|e| Error::Backend(format!("minio list_prefix: {e}")))
+                    })
+                    .await?;
+                let keys = resp
+                    .contents()
+                    .iter()
+                    .filter_map(|obj| {
+                        decode_object_key(obj.key()?, &self.object_prefix, self.key_layout)
+                    })
+                    .collect::<Vec<_>>();
+                Ok::<_, Error>((keys, resp.is_truncated().unwrap_or(false)))
+            }
+        });
+        let listings = futures::future::try_join_all(listings).await?;
+        Ok(merge_partition_listings(listings, limit))
+    }
+
+    /// Flattens a read that reports an expired object as `Some(None)`,
+    /// deleting the expired object on the way.
+    async fn resolve_expiry(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        read: Option<Option<Bytes>>,
+    ) -> Result<Option<Bytes>> {
+        match read {
+            Some(None) => {
+                self.delete_blob(table, key).await?;
+                Ok(None)
+            }
+            read => Ok(read.flatten()),
+        }
+    }
+
+    /// Uploads `value` part by part, retrying each part independently, and
+    /// aborts the upload if any step fails so no orphaned parts are billed.
+    async fn put_multipart(&self, object_key: &str, value: Bytes) -> Result<()> {
+        let upload_id = self
+            .with_retry("create_multipart_upload", || async {
+                let resp = self
+                    .client
+                    .create_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(object_key)
+                    .checksum_algorithm(ChecksumAlgorithm::Sha256)
+                    .send()
+                    .await
+                    .map_err(|e| Error::Backend(format!("minio create_multipart_upload: {e}")))?;
+                resp.upload_id()
+                    .map(str::to_string)
+                    .ok_or_else(|| Error::Backend("minio multipart upload missing id".to_string()))
+            })
+            .await?;
+
+        let result = self.upload_parts(object_key, &upload_id, &value).await;
+        if result.is_err() {
+            let _ = self
+                .client
+                .abort_multipart_upload()
+                .bucket(&self.bucket)
+                .key(object_key)
+                .upload_id(&upload_id)
+                .send()
+                .await;
+        }
+        result
+    }
+
+    async fn upload_parts(&self, object_key: &str, upload_id: &str, value: &Bytes) -> Result<()> {
+        let part_bytes = self.multipart_threshold_bytes.max(MIN_MULTIPART_PART_BYTES);
+        let mut parts = Vec::new();
+        for (index, start) in (0..value.len()).step_by(part_bytes).enumerate() {
+            let part_number = i32::try_from(index + 1)
+                .map_err(|_| Error::Backend("minio multipart part count overflow".to_string()))?;
+            let chunk = value.slice(start..(start + part_bytes).min(value.len()));
+            let part = self
+                .with_retry("upload_part", || async {
+                    let resp = self
+                        .client
+                        .upload_part()
+                        .bucket(&self.bucket)
+                        .key(object_key)
+                        .upload_id(upload_id)
+                        .part_number(part_number)
+                        .checksum_algorithm(ChecksumAlgorithm::Sha256)
+                        .body(ByteStream::from(chunk.clone()))
+                        .send()
+                        .await
+                        .map_err(|e| Error::Backend(format!("minio upload_part: {e}")))?;
+                    Ok(CompletedPart::builder()
+                        .part_number(part_number)
+                        .set_e_tag(resp.e_tag().map(str::to_string))
+                        .set_checksum_sha256(resp.checksum_sha256().map(str::to_string))
+                        .build())
+                })
+                .await?;
+            parts.push(part);
+        }
+
+        let completed = CompletedMultipartUpload::builder()
+            .set_parts(Some(parts))
+            .build();
+        self.with_retry("complete_multipart_upload", || async {
+            self.client
+                .complete_multipart_upload()
+                .bucket(&self.bucket)
+                .key(object_key)
+                .upload_id(upload_id)
+                .multipart_upload(completed.clone())
+                .send()
+                .await
+                .map_err(|e| Error::Backend(format!("minio complete_multipart_upload: {e}")))?;
+            Ok(())
+        })
+        .await
+    }
+
     async fn with_retry<T, F, Fut>(&self, _op: &str, mut f: F) -> Result<T>
     where
         F: FnMut() -> Fut,
@@ -267,6 +501,14 @@ impl MinioBlobStore {
     }
 }
 
+/// Reports whether object metadata carries an expiry that has passed.
+fn is_expired(metadata: Option<&HashMap<String, String>>) -> bool {
+    metadata
+        .and_then(|metadata| metadata.get(EXPIRES_AT_METADATA_KEY))
+        .and_then(|expires_at_ms| expires_at_ms.parse::<u64>().ok())
+        .is_some_and(|expires_at_ms| unix_millis() >= expires_at_ms)
+}
+
 fn compute_backoff_ms(attempt: u32, base_ms: u64, max_ms: u64) -> u64 {
     let factor = 1u64 << core::cmp::min(attempt, 8);
     core::cmp::min(base_ms.saturating_mul(factor), max_ms)
@@ -298,7 +540,53 @@ fn normalize_prefix(p: &str) -> String {
     }
 }
 
-fn decode_object_key(path: &str, configured_prefix: &str) -> Option<Vec<u8>> {
+fn object_key(prefix: &str, layout: ObjectKeyLayout, group: &str, key: &[u8]) -> String {
+    match layout {
+        ObjectKeyLayout::Flat => format!("{prefix}{group}/{}", hex(key)),
+        ObjectKeyLayout::HashPartitioned { partitions } => format!(
+            "{prefix}{group}/{:04x}/{}",
+            key_partition(key, partitions),
+            hex(key)
+        ),
+    }
+}
+
+/// FNV-1a of `key` modulo `partitions`. Stable across builds and platforms,
+/// unlike `DefaultHasher`, so existing objects stay addressable.
+fn key_partition(key: &[u8], partitions: u16) -> u16 {
+    let hash = key.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    (hash % u64::from(partitions.max(1))) as u16
+}
+
+/// Merges per-partition listings, each ascending and flagged when more keys
+/// follow it, into one page of at most `limit` keys. A key past the end of a
+/// truncated listing may sort before unlisted keys of that partition, so the
+/// page stops at the smallest such end.
+fn merge_partition_listings(listings: Vec<(Vec<Vec<u8>>, bool)>, limit: usize) -> Page {
+    let bound = listings
+        .iter()
+        .filter(|(_, truncated)| *truncated)
+        .filter_map(|(keys, _)| keys.last().cloned())
+        .min();
+    let mut keys = listings
+        .into_iter()
+        .flat_map(|(keys, _)| keys)
+        .filter(|key| bound.as_ref().is_none_or(|bound| key <= bound))
+        .collect::<Vec<_>>();
+    keys.sort_unstable();
+    let more = bound.is_some() || keys.len() > limit;
+    keys.truncate(limit);
+    let next_cursor = if more { keys.last().cloned() } else { None };
+    Page { keys, next_cursor }
+}
+
+fn decode_object_key(
+    path: &str,
+    configured_prefix: &str,
+    layout: ObjectKeyLayout,
+) -> Option<Vec<u8>> {
     let full = if configured_prefix.is_empty() {
         path
     } else {
@@ -306,6 +594,11 @@ fn decode_object_key(path: &str, configured_prefix: &str) -> Option<Vec<u8>> {
     };
     let mut parts = full.split('/');
     let _group = parts.next()?;
+    if let ObjectKeyLayout::HashPartitioned { partitions } = layout {
+        let partition = u16::from_str_radix(parts.next()?, 16).ok()?;
+        let raw = unhex(parts.next()?).ok()?;
+        return (partition == key_partition(&raw, partitions)).then_some(raw);
+    }
     let hex_key = parts.next()?;
     unhex(hex_key).ok()
 }
@@ -351,3 +644,63 @@ fn from_nibble(b: u8) -> Option<u8> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_keys_round_trip_under_each_layout() {
+        let layouts = [
+            ObjectKeyLayout::Flat,
+            ObjectKeyLayout::HashPartitioned { partitions: 16 },
+        ];
+        for layout in layouts {
+            for key in [&b""[..], b"\x00", b"block/42", &[0xff; 40]] {
+                let path = object_key("run/", layout, "block_log_blob", key);
+                assert_eq!(
+                    decode_object_key(&path, "run/", layout).as_deref(),
+                    Some(key)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn partition_listings_merge_into_raw_key_order() {
+        let key = |k: u8| vec![k];
+        let listings = vec![
+            (vec![key(1), key(4), key(7)], false),
+            (vec![key(2), key(3)], false),
+            (vec![key(5)], false),
+        ];
+        let page = merge_partition_listings(listings.clone(), 10);
+        assert_eq!(page.keys, [1, 2, 3, 4, 5, 7].map(key));
+        assert_eq!(page.next_cursor, None);
+
+        let page = merge_partition_listings(listings, 4);
+        assert_eq!(page.keys, [1, 2, 3, 4].map(key));
+        assert_eq!(page.next_cursor, Some(key(4)));
+
+        // Partition one may hold keys between 3 and 6 it has not listed yet.
+        let page = merge_partition_listings(
+            vec![(vec![key(1), key(6)], false), (vec![key(2), key(3)], true)],
+            10,
+        );
+        assert_eq!(page.keys, [1, 2, 3].map(key));
+        assert_eq!(page.next_cursor, Some(key(3)));
+    }
+
+    #[test]
+    fn hash_partitioned_keys_spread_over_partitions() {
+        let layout = ObjectKeyLayout::HashPartitioned { partitions: 8 };
+        let partitions: std::collections::BTreeSet<_> = (0u64..256)
+            .map(|block_num| {
+                let path = object_key("", layout, "chunks", &block_num.to_be_bytes());
+                path.split('/').nth(1).expect("partition").to_string()
+            })
+            .collect();
+        assert_eq!(partitions.len(), 8);
+        assert!(partitions.iter().all(|partition| partition.len() == 4));
+    }
+}
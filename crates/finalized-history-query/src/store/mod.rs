@@ -3,9 +3,22 @@ pub mod fs;
 pub mod manifest;
 pub mod meta;
 pub mod publication;
+pub mod tee;
 pub mod traits;
 
 #[cfg(feature = "distributed-stores")]
 pub mod minio;
+#[cfg(feature = "redis-store")]
+pub mod redis;
 #[cfg(feature = "distributed-stores")]
 pub mod scylla;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Wall-clock unix milliseconds, the unit of blob expiry deadlines.
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or(0)
+}
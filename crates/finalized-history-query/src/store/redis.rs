@@ -0,0 +1,444 @@
+use bytes::Bytes;
+use redis::aio::ConnectionManager;
+use redis::{Client, Script};
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::store::traits::{
+    DelCond, MetaStore, Page, PutCond, PutResult, Record, ScannableTableId, TableId,
+};
+
+const DEFAULT_FENCE_KEY: &str = "global";
+
+// Each group (a point table, or one partition of a scannable table) is a pair
+// of hashes: values and versions, keyed by the record key or clustering key.
+// A partition also keeps its clustering keys in a sorted set with every score
+// 0, so `scan_list` pages by lexicographic range instead of scanning the
+// hashes. All keys of a group share one hash tag, so a script touching them
+// stays on one slot.
+//
+// KEYS[1] = values hash, KEYS[2] = versions hash, KEYS[3] = optional index
+// ARGV[1] = field, ARGV[2] = "any" | "absent" | "version",
+// ARGV[3] = expected version, ARGV[4] = value
+const PUT_SCRIPT: &str = r"
+local current = redis.call('HGET', KEYS[2], ARGV[1])
+if ARGV[2] == 'absent' and current then
+  return {0, current}
+end
+if ARGV[2] == 'version' and current ~= ARGV[3] then
+  return {0, current}
+end
+redis.call('HSET', KEYS[1], ARGV[1], ARGV[4])
+if KEYS[3] then
+  redis.call('ZADD', KEYS[3], 0, ARGV[1])
+end
+return {1, redis.call('HINCRBY', KEYS[2], ARGV[1], 1)}
+";
+
+// KEYS as for PUT_SCRIPT; ARGV[1] = field, ARGV[2] = expected version
+const DELETE_IF_VERSION_SCRIPT: &str = r"
+if redis.call('HGET', KEYS[2], ARGV[1]) == ARGV[2] then
+  redis.call('HDEL', KEYS[1], ARGV[1])
+  redis.call('HDEL', KEYS[2], ARGV[1])
+  if KEYS[3] then
+    redis.call('ZREM', KEYS[3], ARGV[1])
+  end
+end
+return 0
+";
+
+/// Cheap clone handle to one multiplexed Redis connection.
+///
+/// Versions follow the in-memory store: a put that applies sets the version
+/// to one more than the current version, or 1 for an absent key, whatever its
+/// condition. Conditional puts and deletes run as Lua scripts, so the check
+/// and the write are atomic.
+#[derive(Clone)]
+pub struct RedisMetaStore {
+    conn: ConnectionManager,
+    namespace: Arc<str>,
+    fence_key: String,
+    scripts: Arc<RedisScripts>,
+}
+
+struct RedisScripts {
+    put: Script,
+    delete_if_version: Script,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GroupKeys {
+    values: Vec<u8>,
+    versions: Vec<u8>,
+    /// Sorted set of the group's fields; only partitions keep one.
+    index: Option<Vec<u8>>,
+}
+
+impl RedisMetaStore {
+    /// Connects to `url` and keeps every key under `namespace`, which must
+    /// not contain `{` or `}`.
+    pub async fn new(url: &str, namespace: &str) -> Result<Self> {
+        if namespace.contains(['{', '}']) {
+            return Err(Error::InvalidParams(
+                "redis namespace must not contain braces",
+            ));
+        }
+        let client = Client::open(url).map_err(|e| Error::Backend(format!("redis client: {e}")))?;
+        let conn = client
+            .get_connection_manager()
+            .await
+            .map_err(|e| Error::Backend(format!("redis connect: {e}")))?;
+        Ok(Self {
+            conn,
+            namespace: Arc::from(namespace),
+            fence_key: DEFAULT_FENCE_KEY.to_string(),
+            scripts: Arc::new(RedisScripts {
+                put: Script::new(PUT_SCRIPT),
+                delete_if_version: Script::new(DELETE_IF_VERSION_SCRIPT),
+            }),
+        })
+    }
+
+    pub async fn set_min_epoch(&self, min_epoch: u64) -> Result<()> {
+        redis::cmd("SET")
+            .arg(fence_key(&self.namespace, &self.fence_key))
+            .arg(min_epoch)
+            .query_async::<()>(&mut self.conn.clone())
+            .await
+            .map_err(|e| Error::Backend(format!("redis set min epoch: {e}")))
+    }
+
+    /// The fence's minimum epoch, or 0 if none was set.
+    pub async fn min_epoch(&self) -> Result<u64> {
+        let min_epoch = redis::cmd("GET")
+            .arg(fence_key(&self.namespace, &self.fence_key))
+            .query_async::<Option<u64>>(&mut self.conn.clone())
+            .await
+            .map_err(|e| Error::Backend(format!("redis get min epoch: {e}")))?;
+        Ok(min_epoch.unwrap_or(0))
+    }
+
+    async fn get_in(&self, group: &GroupKeys, field: &[u8], op: &str) -> Result<Option<Record>> {
+        let (value, version) = redis::pipe()
+            .atomic()
+            .hget(&group.values, field)
+            .hget(&group.versions, field)
+            .query_async::<(Option<Vec<u8>>, Option<u64>)>(&mut self.conn.clone())
+            .await
+            .map_err(|e| Error::Backend(format!("redis {op}: {e}")))?;
+        record(value, version)
+    }
+
+    async fn put_in(
+        &self,
+        group: &GroupKeys,
+        field: &[u8],
+        value: Bytes,
+        cond: PutCond,
+        op: &str,
+    ) -> Result<PutResult> {
+        let (mode, expected) = match cond {
+            PutCond::Any => ("any", 0),
+            PutCond::IfAbsent => ("absent", 0),
+            PutCond::IfVersion(v) => ("version", v),
+        };
+        let mut invocation = self.scripts.put.key(&group.values);
+        invocation.key(&group.versions);
+        if let Some(index) = &group.index {
+            invocation.key(index);
+        }
+        let (applied, version) = invocation
+            .arg(field)
+            .arg(mode)
+            .arg(expected)
+            .arg(value.as_ref())
+            .invoke_async::<(i64, Option<u64>)>(&mut self.conn.clone())
+            .await
+            .map_err(|e| Error::Backend(format!("redis {op}: {e}")))?;
+        Ok(PutResult {
+            applied: applied == 1,
+            version,
+        })
+    }
+
+    async fn delete_in(
+        &self,
+        group: &GroupKeys,
+        field: &[u8],
+        cond: DelCond,
+        op: &str,
+    ) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let result = match cond {
+            DelCond::Any => {
+                let mut pipe = redis::pipe();
+                pipe.atomic()
+                    .hdel(&group.values, field)
+                    .hdel(&group.versions, field);
+                if let Some(index) = &group.index {
+                    pipe.zrem(index, field);
+                }
+                pipe.query_async::<()>(&mut conn).await
+            }
+            DelCond::IfVersion(v) => {
+                let mut invocation = self.scripts.delete_if_version.key(&group.values);
+                invocation.key(&group.versions);
+                if let Some(index) = &group.index {
+                    invocation.key(index);
+                }
+                invocation
+                    .arg(field)
+                    .arg(v)
+                    .invoke_async::<()>(&mut conn)
+                    .await
+            }
+        };
+        result.map_err(|e| Error::Backend(format!("redis {op}: {e}")))
+    }
+}
+
+impl MetaStore for RedisMetaStore {
+    async fn get(&self, table: TableId, key: &[u8]) -> Result<Option<Record>> {
+        let group = point_group_keys(&self.namespace, table);
+        self.get_in(&group, key, "get").await
+    }
+
+    async fn multi_get(&self, table: TableId, keys: &[Vec<u8>]) -> Result<Vec<Option<Record>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let group = point_group_keys(&self.namespace, table);
+        let (values, versions) = redis::pipe()
+            .atomic()
+            .hmget(&group.values, keys)
+            .hmget(&group.versions, keys)
+            .query_async::<(Vec<Option<Vec<u8>>>, Vec<Option<u64>>)>(&mut self.conn.clone())
+            .await
+            .map_err(|e| Error::Backend(format!("redis multi_get: {e}")))?;
+        if values.len() != keys.len() || versions.len() != keys.len() {
+            return Err(Error::Backend("redis multi_get: short reply".to_string()));
+        }
+        values
+            .into_iter()
+            .zip(versions)
+            .map(|(value, version)| record(value, version))
+            .collect()
+    }
+
+    async fn put(
+        &self,
+        table: TableId,
+        key: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> Result<PutResult> {
+        let group = point_group_keys(&self.namespace, table);
+        self.put_in(&group, key, value, cond, "put").await
+    }
+
+    async fn delete(&self, table: TableId, key: &[u8], cond: DelCond) -> Result<()> {
+        let group = point_group_keys(&self.namespace, table);
+        self.delete_in(&group, key, cond, "delete").await
+    }
+
+    async fn scan_get(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+    ) -> Result<Option<Record>> {
+        let group = partition_group_keys(&self.namespace, table, partition);
+        self.get_in(&group, clustering, "scan_get").await
+    }
+
+    async fn scan_put(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> Result<PutResult> {
+        let group = partition_group_keys(&self.namespace, table, partition);
+        self.put_in(&group, clustering, value, cond, "scan_put")
+            .await
+    }
+
+    async fn scan_delete(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        cond: DelCond,
+    ) -> Result<()> {
+        let group = partition_group_keys(&self.namespace, table, partition);
+        self.delete_in(&group, clustering, cond, "scan_delete")
+            .await
+    }
+
+    async fn scan_list(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> Result<Page> {
+        // The page cursor is the last returned key. Each page is one
+        // ZRANGEBYLEX over the partition's index, so its cost grows with the
+        // page size, not the partition size.
+        let group = partition_group_keys(&self.namespace, table, partition);
+        let Some(index) = &group.index else {
+            return Ok(Page {
+                keys: Vec::new(),
+                next_cursor: None,
+            });
+        };
+        let (min, max) = lex_range(prefix, cursor.as_deref());
+        let keys = redis::cmd("ZRANGEBYLEX")
+            .arg(index)
+            .arg(min)
+            .arg(max)
+            .arg("LIMIT")
+            .arg(0)
+            .arg(limit)
+            .query_async::<Vec<Vec<u8>>>(&mut self.conn.clone())
+            .await
+            .map_err(|e| Error::Backend(format!("redis scan_list: {e}")))?;
+        let next_cursor = if keys.len() == limit {
+            keys.last().cloned()
+        } else {
+            None
+        };
+        Ok(Page { keys, next_cursor })
+    }
+}
+
+fn record(value: Option<Vec<u8>>, version: Option<u64>) -> Result<Option<Record>> {
+    match (value, version) {
+        (Some(value), Some(version)) => Ok(Some(Record {
+            value: Bytes::from(value),
+            version,
+        })),
+        (None, None) => Ok(None),
+        _ => Err(Error::Decode("redis value and version hashes disagree")),
+    }
+}
+
+fn point_group_keys(namespace: &str, table: TableId) -> GroupKeys {
+    group_keys(format!("{{{namespace}:{}", table.as_str()).into_bytes(), false)
+}
+
+fn partition_group_keys(namespace: &str, table: ScannableTableId, partition: &[u8]) -> GroupKeys {
+    let mut tag = format!("{{{namespace}:{}:", table.as_str()).into_bytes();
+    tag.extend_from_slice(partition);
+    group_keys(tag, true)
+}
+
+// `open_tag` is `{` followed by the group name. A `}` inside a partition ends
+// the hash tag early, but at the same byte in every key, so the group still
+// shares a slot.
+fn group_keys(open_tag: Vec<u8>, indexed: bool) -> GroupKeys {
+    let key = |suffix: &[u8]| {
+        let mut key = open_tag.clone();
+        key.extend_from_slice(suffix);
+        key
+    };
+    GroupKeys {
+        values: key(b"}:v"),
+        versions: key(b"}:n"),
+        index: indexed.then(|| key(b"}:k")),
+    }
+}
+
+/// ZRANGEBYLEX bounds for the fields that start with `prefix` and sort after
+/// `cursor`.
+fn lex_range(prefix: &[u8], cursor: Option<&[u8]>) -> (Vec<u8>, Vec<u8>) {
+    let min = match cursor {
+        Some(after) if after >= prefix => [&b"("[..], after].concat(),
+        _ => [&b"["[..], prefix].concat(),
+    };
+    // The first byte string past every key with `prefix`: the prefix with
+    // trailing 0xff bytes dropped and its last byte incremented.
+    let mut end = prefix.to_vec();
+    while end.last() == Some(&0xff) {
+        end.pop();
+    }
+    let max = match end.last_mut() {
+        Some(last) => {
+            *last += 1;
+            [&b"("[..], end.as_slice()].concat()
+        }
+        None => b"+".to_vec(),
+    };
+    (min, max)
+}
+
+fn fence_key(namespace: &str, fence_key: &str) -> String {
+    format!("{{{namespace}}}:meta_fence:{fence_key}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_and_partition_groups_never_share_keys() {
+        let table = "log_stream_fragments";
+        let point = point_group_keys("fhq", TableId::new(table));
+        assert_eq!(point.values, b"{fhq:log_stream_fragments}:v");
+        assert_eq!(point.versions, b"{fhq:log_stream_fragments}:n");
+        assert_eq!(point.index, None);
+
+        let empty = partition_group_keys("fhq", ScannableTableId::new(table), b"");
+        assert_eq!(empty.values, b"{fhq:log_stream_fragments:}:v");
+        assert_ne!(empty, point);
+
+        let binary = partition_group_keys("fhq", ScannableTableId::new(table), b"a}\x00:");
+        assert_eq!(binary.values, b"{fhq:log_stream_fragments:a}\x00:}:v");
+        assert_eq!(binary.versions, b"{fhq:log_stream_fragments:a}\x00:}:n");
+        assert_eq!(
+            binary.index.as_deref(),
+            Some(&b"{fhq:log_stream_fragments:a}\x00:}:k"[..])
+        );
+    }
+
+    #[test]
+    fn fence_key_is_namespaced() {
+        assert_eq!(
+            fence_key("fhq", DEFAULT_FENCE_KEY),
+            "{fhq}:meta_fence:global"
+        );
+    }
+
+    #[test]
+    fn lex_range_covers_exactly_the_prefix_after_the_cursor() {
+        assert_eq!(lex_range(b"", None), (b"[".to_vec(), b"+".to_vec()));
+        assert_eq!(
+            lex_range(b"addr/", None),
+            (b"[addr/".to_vec(), b"(addr0".to_vec())
+        );
+        assert_eq!(
+            lex_range(b"a\xff\xff", Some(b"a\xff\xff\x01")),
+            (b"(a\xff\xff\x01".to_vec(), b"(b".to_vec())
+        );
+        assert_eq!(
+            lex_range(b"\xff", Some(b"\x01")),
+            (b"[\xff".to_vec(), b"+".to_vec())
+        );
+    }
+
+    #[test]
+    fn record_requires_value_and_version_together() {
+        let found = record(Some(b"v".to_vec()), Some(3))
+            .expect("matched pair")
+            .expect("present");
+        assert_eq!((found.value.as_ref(), found.version), (&b"v"[..], 3));
+        assert!(record(None, None).expect("absent").is_none());
+        assert!(matches!(
+            record(Some(Vec::new()), None),
+            Err(Error::Decode(_))
+        ));
+        assert!(matches!(record(None, Some(1)), Err(Error::Decode(_))));
+    }
+}
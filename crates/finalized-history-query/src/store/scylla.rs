@@ -108,6 +108,7 @@ struct ScyllaStatements {
 #[derive(Debug)]
 struct PointTableStatements {
     get: PreparedStatement,
+    get_many: PreparedStatement,
     put_any: PreparedStatement,
     put_if_absent: PreparedStatement,
     put_if_version: PreparedStatement,
@@ -173,6 +174,15 @@ impl PointTableSchema {
                 "point_get",
             )
             .await?,
+            get_many: prepare_statement(
+                session,
+                format!(
+                    "SELECT k, v, version FROM {} WHERE bucket = ? AND k IN ?",
+                    self.table_name
+                ),
+                "point_get_many",
+            )
+            .await?,
             put_any: prepare_statement(
                 session,
                 format!(
@@ -470,6 +480,39 @@ impl MetaStore for ScyllaMetaStore {
         }))
     }
 
+    async fn multi_get(&self, table: TableId, keys: &[Vec<u8>]) -> Result<Vec<Option<Record>>> {
+        // One IN query per bucket, since a query may only touch one partition.
+        let mut by_bucket = BTreeMap::<i16, Vec<Vec<u8>>>::new();
+        for key in keys {
+            by_bucket
+                .entry(key_bucket(key))
+                .or_default()
+                .push(key.clone());
+        }
+        let stmt = self.point_statements(table)?.get_many.clone();
+        let mut found = BTreeMap::<Vec<u8>, Record>::new();
+        for (bucket, bucket_keys) in by_bucket {
+            let res = self
+                .with_retry("get_many", || async {
+                    self.session
+                        .execute_unpaged(&stmt, (bucket, bucket_keys.clone()))
+                        .await
+                        .map_err(|e| Error::Backend(format!("scylla get_many: {e}")))
+                })
+                .await?;
+            for (k, v, version) in rows_k_v_version(res)? {
+                found.insert(
+                    k,
+                    Record {
+                        value: Bytes::from(v),
+                        version: version as u64,
+                    },
+                );
+            }
+        }
+        Ok(keys.iter().map(|key| found.get(key).cloned()).collect())
+    }
+
     async fn put(
         &self,
         table: TableId,
@@ -841,6 +884,17 @@ fn first_row_v_version(res: scylla::QueryResult) -> Option<(Vec<u8>, i64)> {
     it.next().and_then(|r| r.ok())
 }
 
+fn rows_k_v_version(res: scylla::QueryResult) -> Result<Vec<(Vec<u8>, Vec<u8>, i64)>> {
+    let Ok(rows_result) = res.into_rows_result() else {
+        return Ok(Vec::new());
+    };
+    let rows = rows_result
+        .rows::<(Vec<u8>, Vec<u8>, i64)>()
+        .map_err(|e| Error::Backend(format!("decode rows: {e}")))?;
+    rows.map(|row| row.map_err(|e| Error::Backend(format!("decode row: {e}"))))
+        .collect()
+}
+
 #[allow(deprecated)]
 fn lwt_applied(res: scylla::QueryResult) -> Result<bool> {
     let legacy = res
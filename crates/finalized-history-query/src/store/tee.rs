@@ -0,0 +1,237 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use crate::error::Result;
+use crate::store::traits::{BlobStore, BlobTableId, Page};
+
+/// Dual-writes blobs to a primary and a secondary store, for migrating
+/// between backends.
+///
+/// The primary is authoritative: its errors are returned and it serves
+/// listings. Writes and deletes go to the primary first and then to the
+/// secondary. A secondary failure is counted, not returned. Reads that miss
+/// in the primary fall back to the secondary.
+#[derive(Clone)]
+pub struct TeeBlobStore<A, B> {
+    primary: A,
+    secondary: B,
+    secondary_failures: Arc<AtomicU64>,
+}
+
+impl<A: BlobStore, B: BlobStore> TeeBlobStore<A, B> {
+    pub fn new(primary: A, secondary: B) -> Self {
+        Self {
+            primary,
+            secondary,
+            secondary_failures: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    pub fn secondary(&self) -> &B {
+        &self.secondary
+    }
+
+    /// Number of secondary writes and deletes that failed, shared by every
+    /// clone of this store.
+    pub fn secondary_failures(&self) -> u64 {
+        self.secondary_failures.load(Ordering::Relaxed)
+    }
+
+    fn record_secondary(&self, result: Result<()>) {
+        if result.is_err() {
+            self.secondary_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<A: BlobStore, B: BlobStore> BlobStore for TeeBlobStore<A, B> {
+    async fn put_blob(&self, table: BlobTableId, key: &[u8], value: Bytes) -> Result<()> {
+        self.primary.put_blob(table, key, value.clone()).await?;
+        self.record_secondary(self.secondary.put_blob(table, key, value).await);
+        Ok(())
+    }
+
+    async fn put_blob_with_ttl(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: Bytes,
+        ttl: Duration,
+    ) -> Result<()> {
+        self.primary
+            .put_blob_with_ttl(table, key, value.clone(), ttl)
+            .await?;
+        self.record_secondary(
+            self.secondary
+                .put_blob_with_ttl(table, key, value, ttl)
+                .await,
+        );
+        Ok(())
+    }
+
+    async fn get_blob(&self, table: BlobTableId, key: &[u8]) -> Result<Option<Bytes>> {
+        match self.primary.get_blob(table, key).await? {
+            Some(blob) => Ok(Some(blob)),
+            None => self.secondary.get_blob(table, key).await,
+        }
+    }
+
+    async fn read_range(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        start: u64,
+        end_exclusive: u64,
+    ) -> Result<Option<Bytes>> {
+        match self
+            .primary
+            .read_range(table, key, start, end_exclusive)
+            .await?
+        {
+            Some(blob) => Ok(Some(blob)),
+            None => {
+                self.secondary
+                    .read_range(table, key, start, end_exclusive)
+                    .await
+            }
+        }
+    }
+
+    async fn delete_blob(&self, table: BlobTableId, key: &[u8]) -> Result<()> {
+        self.primary.delete_blob(table, key).await?;
+        self.record_secondary(self.secondary.delete_blob(table, key).await);
+        Ok(())
+    }
+
+    async fn list_prefix(
+        &self,
+        table: BlobTableId,
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> Result<Page> {
+        self.primary.list_prefix(table, prefix, cursor, limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::executor::block_on;
+
+    use super::TeeBlobStore;
+    use crate::error::{Error, Result};
+    use crate::store::blob::InMemoryBlobStore;
+    use crate::store::traits::{BlobStore, BlobTableId, Page};
+
+    const TEST_TABLE: BlobTableId = BlobTableId::new("test");
+
+    #[derive(Clone)]
+    struct FailingBlobStore;
+
+    impl BlobStore for FailingBlobStore {
+        async fn put_blob(&self, _table: BlobTableId, _key: &[u8], _value: Bytes) -> Result<()> {
+            Err(Error::Backend("secondary down".to_string()))
+        }
+
+        async fn get_blob(&self, _table: BlobTableId, _key: &[u8]) -> Result<Option<Bytes>> {
+            Err(Error::Backend("secondary down".to_string()))
+        }
+
+        async fn delete_blob(&self, _table: BlobTableId, _key: &[u8]) -> Result<()> {
+            Err(Error::Backend("secondary down".to_string()))
+        }
+
+        async fn list_prefix(
+            &self,
+            _table: BlobTableId,
+            _prefix: &[u8],
+            _cursor: Option<Vec<u8>>,
+            _limit: usize,
+        ) -> Result<Page> {
+            Err(Error::Backend("secondary down".to_string()))
+        }
+    }
+
+    #[test]
+    fn tee_writes_both_falls_back_on_read_and_deletes_both() {
+        block_on(async {
+            let primary = InMemoryBlobStore::default();
+            let secondary = InMemoryBlobStore::default();
+            let store = TeeBlobStore::new(primary.clone(), secondary.clone());
+            let value = Bytes::from_static(b"value");
+
+            store
+                .put_blob(TEST_TABLE, b"both", value.clone())
+                .await
+                .expect("tee put");
+            for backend in [&primary, &secondary] {
+                assert_eq!(
+                    backend.get_blob(TEST_TABLE, b"both").await.expect("get"),
+                    Some(value.clone())
+                );
+            }
+
+            secondary
+                .put_blob(TEST_TABLE, b"secondary-only", value.clone())
+                .await
+                .expect("seed secondary");
+            assert_eq!(
+                store
+                    .get_blob(TEST_TABLE, b"secondary-only")
+                    .await
+                    .expect("fallback get"),
+                Some(value.clone())
+            );
+            assert_eq!(
+                store
+                    .read_range(TEST_TABLE, b"secondary-only", 1, 3)
+                    .await
+                    .expect("fallback range"),
+                Some(Bytes::from_static(b"al"))
+            );
+
+            store
+                .delete_blob(TEST_TABLE, b"both")
+                .await
+                .expect("tee delete");
+            for backend in [&primary, &secondary] {
+                assert_eq!(
+                    backend.get_blob(TEST_TABLE, b"both").await.expect("get"),
+                    None
+                );
+            }
+            assert_eq!(store.secondary_failures(), 0);
+        });
+    }
+
+    #[test]
+    fn tee_counts_secondary_failures_without_failing_writes() {
+        block_on(async {
+            let primary = InMemoryBlobStore::default();
+            let store = TeeBlobStore::new(primary.clone(), FailingBlobStore);
+
+            store
+                .put_blob(TEST_TABLE, b"key", Bytes::from_static(b"value"))
+                .await
+                .expect("primary put succeeds");
+            store
+                .delete_blob(TEST_TABLE, b"key")
+                .await
+                .expect("primary delete succeeds");
+
+            assert_eq!(store.clone().secondary_failures(), 2);
+            assert_eq!(
+                primary.get_blob(TEST_TABLE, b"key").await.expect("get"),
+                None
+            );
+        });
+    }
+}
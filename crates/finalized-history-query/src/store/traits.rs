@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
 
@@ -101,6 +102,10 @@ impl<M: MetaStore> KvTable<M> {
         self.store.get(self.table, key).await
     }
 
+    pub async fn multi_get(&self, keys: &[Vec<u8>]) -> Result<Vec<Option<Record>>> {
+        self.store.multi_get(self.table, keys).await
+    }
+
     pub async fn put(&self, key: &[u8], value: Bytes, cond: PutCond) -> Result<PutResult> {
         self.store.put(self.table, key, value, cond).await
     }
@@ -127,6 +132,10 @@ impl<M: MetaStore> KvTableRef<'_, M> {
         self.store.get(self.table, key).await
     }
 
+    pub async fn multi_get(&self, keys: &[Vec<u8>]) -> Result<Vec<Option<Record>>> {
+        self.store.multi_get(self.table, keys).await
+    }
+
     pub async fn put(&self, key: &[u8], value: Bytes, cond: PutCond) -> Result<PutResult> {
         self.store.put(self.table, key, value, cond).await
     }
@@ -386,6 +395,16 @@ pub trait MetaStore: Clone + Send + Sync {
     }
 
     async fn get(&self, table: TableId, key: &[u8]) -> Result<Option<Record>>;
+    /// Loads several point keys in one call. Results are in input order, with
+    /// `None` for each absent key. The default issues one `get` per key;
+    /// backends that can batch reads override it.
+    async fn multi_get(&self, table: TableId, keys: &[Vec<u8>]) -> Result<Vec<Option<Record>>> {
+        let mut out = Vec::with_capacity(keys.len());
+        for key in keys {
+            out.push(self.get(table, key).await?);
+        }
+        Ok(out)
+    }
     async fn put(
         &self,
         table: TableId,
@@ -430,6 +449,10 @@ impl<T: MetaStore> MetaStore for Arc<T> {
         self.as_ref().get(table, key).await
     }
 
+    async fn multi_get(&self, table: TableId, keys: &[Vec<u8>]) -> Result<Vec<Option<Record>>> {
+        self.as_ref().multi_get(table, keys).await
+    }
+
     async fn put(
         &self,
         table: TableId,
@@ -527,6 +550,20 @@ pub trait BlobStore: Clone + Send + Sync {
     }
 
     async fn put_blob(&self, table: BlobTableId, key: &[u8], value: Bytes) -> Result<()>;
+    /// Writes a blob that reads as absent once `ttl` has elapsed, as a safety
+    /// net for temporary artifacts of work that may be abandoned. The default
+    /// ignores `ttl` and writes a permanent blob. A later `put_blob` of the
+    /// same key makes it permanent.
+    async fn put_blob_with_ttl(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: Bytes,
+        ttl: Duration,
+    ) -> Result<()> {
+        let _ = ttl;
+        self.put_blob(table, key, value).await
+    }
     async fn get_blob(&self, table: BlobTableId, key: &[u8]) -> Result<Option<Bytes>>;
     async fn read_range(
         &self,
@@ -562,6 +599,18 @@ impl<T: BlobStore> BlobStore for Arc<T> {
         self.as_ref().put_blob(table, key, value).await
     }
 
+    async fn put_blob_with_ttl(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: Bytes,
+        ttl: Duration,
+    ) -> Result<()> {
+        self.as_ref()
+            .put_blob_with_ttl(table, key, value, ttl)
+            .await
+    }
+
     async fn get_blob(&self, table: BlobTableId, key: &[u8]) -> Result<Option<Bytes>> {
         self.as_ref().get_blob(table, key).await
     }
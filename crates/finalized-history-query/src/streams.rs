@@ -15,11 +15,29 @@ pub struct BitmapBlob {
     pub bitmap: RoaringBitmap,
 }
 
+/// Summary of a sealed stream page, stored next to its compacted page blob.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct StreamBitmapMeta {
     pub count: u32,
     pub min_local: u32,
     pub max_local: u32,
+    /// Encoded length of the page blob, so per-stream storage can be summed
+    /// from metadata alone. Zero for pages sealed under the v1 encoding.
+    pub byte_size: u64,
+}
+
+/// v1 encoding without `byte_size`. Still decoded for pages sealed before v2.
+struct StreamBitmapMetaV1 {
+    count: u32,
+    min_local: u32,
+    max_local: u32,
+}
+
+struct StreamBitmapMetaV2 {
+    count: u32,
+    min_local: u32,
+    max_local: u32,
+    byte_size: u64,
 }
 
 struct BitmapBlobHeader {
@@ -42,7 +60,7 @@ fixed_codec! {
 }
 
 fixed_codec! {
-    impl StreamBitmapMeta {
+    impl StreamBitmapMetaV1 {
         length_error = "invalid stream bitmap meta length";
         version = 1;
         version_error = "unsupported stream bitmap meta version";
@@ -54,11 +72,62 @@ fixed_codec! {
     }
 }
 
+fixed_codec! {
+    impl StreamBitmapMetaV2 {
+        length_error = "invalid stream bitmap meta length";
+        version = 2;
+        version_error = "unsupported stream bitmap meta version";
+        fields {
+            count: u32,
+            min_local: u32,
+            max_local: u32,
+            byte_size: u64,
+        }
+    }
+}
+
+impl StorageCodec for StreamBitmapMeta {
+    fn encode(&self) -> Bytes {
+        StreamBitmapMetaV2 {
+            count: self.count,
+            min_local: self.min_local,
+            max_local: self.max_local,
+            byte_size: self.byte_size,
+        }
+        .encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        match bytes.first() {
+            Some(1) => {
+                let meta = StreamBitmapMetaV1::decode(bytes)?;
+                Ok(Self {
+                    count: meta.count,
+                    min_local: meta.min_local,
+                    max_local: meta.max_local,
+                    byte_size: 0,
+                })
+            }
+            Some(2) => {
+                let meta = StreamBitmapMetaV2::decode(bytes)?;
+                Ok(Self {
+                    count: meta.count,
+                    min_local: meta.min_local,
+                    max_local: meta.max_local,
+                    byte_size: meta.byte_size,
+                })
+            }
+            Some(_) => Err(Error::Decode("unsupported stream bitmap meta version")),
+            None => Err(Error::Decode("invalid stream bitmap meta length")),
+        }
+    }
+}
+
 pub fn encode_bitmap_blob(blob: &BitmapBlob) -> Result<Bytes> {
     let mut payload = Vec::new();
     blob.bitmap
         .serialize_into(&mut payload)
-        .map_err(|e| Error::Backend(format!("serialize bitmap blob: {e}")))?;
+        .map_err(|e| Error::Codec(format!("serialize bitmap blob: {e}")))?;
 
     let header = BitmapBlobHeader {
         min_local: blob.min_local,
@@ -80,7 +149,7 @@ pub fn decode_bitmap_blob(bytes: &[u8]) -> Result<BitmapBlob> {
     let payload = &bytes[BITMAP_BLOB_HEADER_LEN..];
 
     let bitmap = RoaringBitmap::deserialize_from(payload)
-        .map_err(|e| Error::Backend(format!("deserialize bitmap blob: {e}")))?;
+        .map_err(|e| Error::Codec(format!("deserialize bitmap blob: {e}")))?;
 
     Ok(BitmapBlob {
         min_local: header.min_local,
@@ -107,12 +176,53 @@ mod tests {
         let mut encoded = encode_bitmap_blob(&blob).expect("encode").to_vec();
         encoded.pop();
         let err = decode_bitmap_blob(&encoded).unwrap_err();
+        assert!(
+            matches!(err, Error::Codec(_)),
+            "expected codec error, got: {err}"
+        );
         assert!(
             err.to_string().contains("deserialize"),
             "expected deserialize error, got: {err}"
         );
     }
 
+    #[test]
+    fn stream_bitmap_meta_roundtrips_v2() {
+        let meta = StreamBitmapMeta {
+            count: 3,
+            min_local: 7,
+            max_local: 4_000,
+            byte_size: 1_234_567,
+        };
+        let encoded = meta.encode();
+        assert_eq!(encoded[0], 2);
+        assert_eq!(encoded.len(), 1 + 4 * 3 + 8);
+        assert_eq!(StreamBitmapMeta::decode(&encoded).expect("decode v2"), meta);
+    }
+
+    #[test]
+    fn stream_bitmap_meta_decodes_v1_without_byte_size() {
+        let mut v1 = vec![1u8];
+        v1.extend_from_slice(&3u32.to_be_bytes());
+        v1.extend_from_slice(&7u32.to_be_bytes());
+        v1.extend_from_slice(&4_000u32.to_be_bytes());
+        assert_eq!(
+            StreamBitmapMeta::decode(&v1).expect("decode v1"),
+            StreamBitmapMeta {
+                count: 3,
+                min_local: 7,
+                max_local: 4_000,
+                byte_size: 0,
+            }
+        );
+
+        v1[0] = 3;
+        assert!(matches!(
+            StreamBitmapMeta::decode(&v1),
+            Err(Error::Decode("unsupported stream bitmap meta version"))
+        ));
+    }
+
     #[test]
     fn decode_rejects_truncated_input() {
         let err = decode_bitmap_blob(&[0u8; 4]).unwrap_err();
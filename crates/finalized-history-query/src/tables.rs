@@ -1,4 +1,8 @@
+use std::sync::Arc;
+
 use bytes::Bytes;
+use quick_cache::sync::Cache;
+use roaring::RoaringTreemap;
 
 use crate::core::directory::{PrimaryDirBucket, PrimaryDirFragment};
 use crate::core::header::{BlockHeaderSpec, EvmBlockHeader};
@@ -14,25 +18,27 @@ use crate::kernel::cache::{cache_for, no_cache};
 use crate::kernel::codec::{StorageCodec, encode_u64};
 use crate::kernel::point_table::CachedPointTable;
 use crate::kernel::scannable_table::ScannableFragmentTable;
+use crate::kernel::sharded_streams::split_stream_shard;
 use crate::kernel::table_specs::u64_key;
 use crate::kernel::table_specs::{BlobTableSpec, PointTableSpec, ScannableTableSpec};
 use crate::logs::log_ref::LogRef;
 use crate::logs::table_specs::{
     BlockHashIndexSpec, BlockLogBlobSpec, BlockLogHeaderSpec, LogBitmapByBlockSpec,
     LogBitmapPageBlobSpec, LogBitmapPageMetaSpec, LogDirBucketSpec, LogDirByBlockSpec,
-    LogDirSubBucketSpec,
+    LogDirSubBucketSpec, LogStreamShardSpec,
 };
-use crate::store::traits::{BlobStore, BlobTable, KvTable, MetaStore, ScannableKvTable};
-use crate::streams::StreamBitmapMeta;
+use crate::store::traits::{BlobStore, BlobTable, KvTable, MetaStore, Page, ScannableKvTable};
+use crate::streams::{BitmapBlob, StreamBitmapMeta, decode_bitmap_blob};
 use crate::traces::table_specs::{
     BlockTraceBlobSpec, BlockTraceHeaderSpec, TraceBitmapByBlockSpec, TraceBitmapPageBlobSpec,
     TraceBitmapPageMetaSpec, TraceDirBucketSpec, TraceDirByBlockSpec, TraceDirSubBucketSpec,
+    TraceStreamShardSpec,
 };
 use crate::traces::types::BlockTraceHeader;
 use crate::txs::table_specs::{
     BlockTxBlobSpec, BlockTxHeaderSpec, TxBitmapByBlockSpec, TxBitmapPageBlobSpec,
     TxBitmapPageMetaSpec, TxDirBucketSpec, TxDirByBlockSpec, TxDirSubBucketSpec, TxHashIndexSpec,
-    TxOpenBitmapPageSpec,
+    TxOpenBitmapPageSpec, TxStreamShardSpec,
 };
 use crate::txs::types::{BlockTxHeader, TxLocation};
 use crate::txs::view::TxRef;
@@ -43,12 +49,20 @@ pub struct PrimaryDirTables<M: MetaStore> {
     pub(crate) fragments: PrimaryDirFragmentTable<M>,
 }
 
+#[derive(Clone)]
 pub struct StreamTables<M: MetaStore, B: BlobStore, T> {
     fragments: StreamFragmentsTable<M>,
     page_meta: StreamPageMetaTable<M, T>,
     page_blobs: StreamPageBlobTable<B>,
+    decoded_page_blobs: Option<Arc<DecodedPageBlobCache>>,
+    shards: StreamShardPresenceTable<M>,
+    fast_planning: bool,
+    empty_result_fast_path: bool,
+    shard_presence_filter: bool,
 }
 
+type DecodedPageBlobCache = Cache<(String, u32), Arc<BitmapBlob>>;
+
 impl<M: MetaStore> PrimaryDirTables<M> {
     pub async fn persist_block_fragment(
         &self,
@@ -205,6 +219,13 @@ impl<M: MetaStore, B: BlobStore> Tables<M, B> {
                     cache_for(config.log_bitmap_page_blobs.max_bytes),
                     LogBitmapPageBlobSpec::key,
                 ),
+                decoded_page_blobs: None,
+                shards: StreamShardPresenceTable::new(
+                    meta_store.scannable_table(LogStreamShardSpec::TABLE),
+                ),
+                fast_planning: false,
+                empty_result_fast_path: true,
+                shard_presence_filter: false,
             },
             tx_streams: StreamTables {
                 fragments: StreamFragmentsTable::new(
@@ -223,6 +244,13 @@ impl<M: MetaStore, B: BlobStore> Tables<M, B> {
                     no_cache(),
                     TxBitmapPageBlobSpec::key,
                 ),
+                decoded_page_blobs: None,
+                shards: StreamShardPresenceTable::new(
+                    meta_store.scannable_table(TxStreamShardSpec::TABLE),
+                ),
+                fast_planning: false,
+                empty_result_fast_path: true,
+                shard_presence_filter: false,
             },
             trace_streams: StreamTables {
                 fragments: StreamFragmentsTable::new(
@@ -241,6 +269,13 @@ impl<M: MetaStore, B: BlobStore> Tables<M, B> {
                     no_cache(),
                     TraceBitmapPageBlobSpec::key,
                 ),
+                decoded_page_blobs: None,
+                shards: StreamShardPresenceTable::new(
+                    meta_store.scannable_table(TraceStreamShardSpec::TABLE),
+                ),
+                fast_planning: false,
+                empty_result_fast_path: true,
+                shard_presence_filter: false,
             },
             log_block_blobs: BlockLogBlobTable {
                 blob_table: blob_store.table(BlockLogBlobSpec::TABLE),
@@ -275,6 +310,46 @@ impl<M: MetaStore, B: BlobStore> Tables<M, B> {
         }
     }
 
+    /// Keeps up to `entries` decoded compacted page bitmaps per stream family,
+    /// so repeated queries over the same sealed pages skip blob reads and
+    /// decoding. `0` leaves the cache disabled.
+    pub fn with_stream_bitmap_cache(mut self, entries: usize) -> Self {
+        self.log_streams.decoded_page_blobs = decoded_page_blob_cache(entries);
+        self.tx_streams.decoded_page_blobs = decoded_page_blob_cache(entries);
+        self.trace_streams.decoded_page_blobs = decoded_page_blob_cache(entries);
+        self
+    }
+
+    /// Makes clause estimates for unsealed pages decode only a sample of
+    /// each page's by-block fragments and scale the result. See
+    /// [`crate::config::Config::fast_planning`].
+    pub fn with_fast_planning(mut self, enabled: bool) -> Self {
+        self.log_streams.fast_planning = enabled;
+        self.tx_streams.fast_planning = enabled;
+        self.trace_streams.fast_planning = enabled;
+        self
+    }
+
+    /// Lets a query skip a shard without loading any bitmap when one of its
+    /// clauses has a zero upper-bound estimate. See
+    /// [`crate::config::Config::empty_result_fast_path`].
+    pub fn with_empty_result_fast_path(mut self, enabled: bool) -> Self {
+        self.log_streams.empty_result_fast_path = enabled;
+        self.tx_streams.empty_result_fast_path = enabled;
+        self.trace_streams.empty_result_fast_path = enabled;
+        self
+    }
+
+    /// Lets a query skip the shards where none of a clause's values has a
+    /// stream, using the per-value shard records ingest writes. See
+    /// [`crate::config::Config::shard_presence_filter`].
+    pub fn with_shard_presence_filter(mut self, enabled: bool) -> Self {
+        self.log_streams.shard_presence_filter = enabled;
+        self.tx_streams.shard_presence_filter = enabled;
+        self.trace_streams.shard_presence_filter = enabled;
+        self
+    }
+
     pub fn metrics_snapshot(&self) -> BytesCacheMetrics {
         BytesCacheMetrics {
             block_records: self.block_records.metrics(),
@@ -552,6 +627,7 @@ impl<M: MetaStore> PrimaryDirFragmentTable<M> {
     }
 }
 
+#[derive(Clone)]
 pub struct StreamFragmentsTable<M: MetaStore> {
     inner: ScannableFragmentTable<M>,
     partition: fn(&str, u32) -> Vec<u8>,
@@ -578,6 +654,17 @@ impl<M: MetaStore> StreamFragmentsTable<M> {
             .await
     }
 
+    pub async fn sample_page_fragments(
+        &self,
+        stream: &str,
+        page_start: u32,
+        max_samples: usize,
+    ) -> Result<(usize, Vec<Bytes>)> {
+        self.inner
+            .sample_partition_values(&(self.partition)(stream, page_start), max_samples)
+            .await
+    }
+
     pub async fn put(
         &self,
         stream: &str,
@@ -590,11 +677,18 @@ impl<M: MetaStore> StreamFragmentsTable<M> {
         self.inner.put_value(&partition, &clustering, bytes).await
     }
 
+    pub async fn delete_page(&self, stream: &str, page_start: u32) -> Result<u64> {
+        self.inner
+            .delete_partition(&(self.partition)(stream, page_start))
+            .await
+    }
+
     fn metrics(&self) -> TableCacheMetrics {
         self.inner.metrics()
     }
 }
 
+#[derive(Clone)]
 pub struct StreamPageMetaTable<M: MetaStore, T> {
     inner: CachedPointTable<M, T>,
     key: fn(&str, u32) -> Vec<u8>,
@@ -618,17 +712,30 @@ impl<M: MetaStore, T: StorageCodec> StreamPageMetaTable<M, T> {
             .await
     }
 
+    pub async fn get_many(&self, streams: &[String], page_start: u32) -> Result<Vec<Option<T>>> {
+        let keys = streams
+            .iter()
+            .map(|stream| (self.key)(stream, page_start))
+            .collect::<Vec<_>>();
+        self.inner.get_many_decoded(&keys).await
+    }
+
     pub async fn put(&self, stream: &str, page_start: u32, meta: &T) -> Result<()> {
         self.inner
             .put_encoded(&(self.key)(stream, page_start), meta)
             .await
     }
 
+    pub async fn delete(&self, stream: &str, page_start: u32) -> Result<()> {
+        self.inner.delete(&(self.key)(stream, page_start)).await
+    }
+
     fn metrics(&self) -> TableCacheMetrics {
         self.inner.metrics()
     }
 }
 
+#[derive(Clone)]
 pub struct StreamPageBlobTable<B: BlobStore> {
     inner: CachedBlobTable<B>,
     key: fn(&str, u32) -> Vec<u8>,
@@ -660,16 +767,99 @@ impl<B: BlobStore> StreamPageBlobTable<B> {
             .await
     }
 
+    pub async fn delete(&self, stream: &str, page_start: u32) -> Result<()> {
+        self.inner
+            .delete_by_key(&(self.key)(stream, page_start))
+            .await
+    }
+
+    /// Lists compacted page blob keys in key order, which groups each
+    /// stream's pages together.
+    pub async fn list_keys(
+        &self,
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> Result<Page> {
+        self.inner.list_prefix(prefix, cursor, limit).await
+    }
+
     fn metrics(&self) -> TableCacheMetrics {
         self.inner.cache.metrics_snapshot()
     }
 }
 
 impl<M: MetaStore, B: BlobStore, T: StorageCodec> StreamTables<M, B, T> {
+    pub async fn list_page_blob_keys(
+        &self,
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> Result<Page> {
+        self.page_blobs.list_keys(prefix, cursor, limit).await
+    }
+
     pub async fn load_page_fragments(&self, stream: &str, page_start: u32) -> Result<Vec<Bytes>> {
         self.fragments.load_page_fragments(stream, page_start).await
     }
 
+    /// Returns the number of by-block fragments a page holds, with at most
+    /// `max_samples` of them spread evenly across the page.
+    pub async fn sample_page_fragments(
+        &self,
+        stream: &str,
+        page_start: u32,
+        max_samples: usize,
+    ) -> Result<(usize, Vec<Bytes>)> {
+        self.fragments
+            .sample_page_fragments(stream, page_start, max_samples)
+            .await
+    }
+
+    pub fn fast_planning(&self) -> bool {
+        self.fast_planning
+    }
+
+    pub fn empty_result_fast_path(&self) -> bool {
+        self.empty_result_fast_path
+    }
+
+    pub fn shard_presence_filter(&self) -> bool {
+        self.shard_presence_filter
+    }
+
+    /// Records that `stream` has indexed entries in its shard. The row has
+    /// an empty value, so the put is unconditional and a repeat rewrites the
+    /// same row.
+    pub async fn mark_shard_presence(&self, stream: &str) -> Result<()> {
+        self.shards.mark(stream).await
+    }
+
+    pub async fn delete_shard_presence(&self, stream: &str) -> Result<()> {
+        self.shards.delete(stream).await
+    }
+
+    /// Loads the shards in which the `value_key` stream has ever had an
+    /// indexed entry.
+    pub async fn load_stream_shards(&self, value_key: &str) -> Result<RoaringTreemap> {
+        self.shards.load(value_key).await
+    }
+
+    /// A handle on the same tables whose decoded page bitmaps are shared by
+    /// every query it serves. Keeps the configured stream bitmap cache when
+    /// one is enabled; otherwise gets a fresh cache of `entries` bitmaps that
+    /// is dropped with the handle.
+    pub fn with_scoped_page_blob_cache(&self, entries: usize) -> Self
+    where
+        T: Clone,
+    {
+        let mut scoped = self.clone();
+        if scoped.decoded_page_blobs.is_none() {
+            scoped.decoded_page_blobs = decoded_page_blob_cache(entries);
+        }
+        scoped
+    }
+
     pub async fn put_fragment(
         &self,
         stream: &str,
@@ -686,6 +876,16 @@ impl<M: MetaStore, B: BlobStore, T: StorageCodec> StreamTables<M, B, T> {
         self.page_meta.get(stream, page_start).await
     }
 
+    /// Loads the page meta at `page_start` for each stream in one batched
+    /// read. Results are in `streams` order.
+    pub async fn get_page_metas(
+        &self,
+        streams: &[String],
+        page_start: u32,
+    ) -> Result<Vec<Option<T>>> {
+        self.page_meta.get_many(streams, page_start).await
+    }
+
     pub async fn put_page_meta(&self, stream: &str, page_start: u32, meta: &T) -> Result<()> {
         self.page_meta.put(stream, page_start, meta).await
     }
@@ -697,6 +897,115 @@ impl<M: MetaStore, B: BlobStore, T: StorageCodec> StreamTables<M, B, T> {
     pub async fn put_page_blob(&self, stream: &str, page_start: u32, bytes: Bytes) -> Result<()> {
         self.page_blobs.put(stream, page_start, bytes).await
     }
+
+    /// Deletes the page meta, page blob, and by-block fragments of one page,
+    /// in that order, so a reader that races the delete falls back from the
+    /// blob to fragments rather than seeing a meta without its blob. Returns
+    /// whether the page held any artifact.
+    pub async fn delete_page(&self, stream: &str, page_start: u32) -> Result<bool> {
+        let sealed = self.page_meta.get(stream, page_start).await?.is_some();
+        if sealed {
+            self.page_meta.delete(stream, page_start).await?;
+            self.page_blobs.delete(stream, page_start).await?;
+            if let Some(cache) = &self.decoded_page_blobs {
+                cache.remove(&(stream.to_owned(), page_start));
+            }
+        }
+        let fragments = self.fragments.delete_page(stream, page_start).await?;
+        Ok(sealed || fragments > 0)
+    }
+
+    /// Loads and decodes a compacted page blob, reusing the decoded bitmap
+    /// when the stream bitmap cache is enabled. Compacted pages never change
+    /// once written; the only invalidation is `delete_page`, which evicts the
+    /// entry from this handle's cache. Other processes can keep serving their
+    /// cached copy of a deleted page until it ages out.
+    pub async fn get_decoded_page_blob(
+        &self,
+        stream: &str,
+        page_start: u32,
+    ) -> Result<Option<Arc<BitmapBlob>>> {
+        let Some(cache) = &self.decoded_page_blobs else {
+            return self
+                .get_page_blob(stream, page_start)
+                .await?
+                .map(|bytes| decode_bitmap_blob(&bytes).map(Arc::new))
+                .transpose();
+        };
+        let key = (stream.to_owned(), page_start);
+        if let Some(blob) = cache.get(&key) {
+            return Ok(Some(blob));
+        }
+        let Some(bytes) = self.get_page_blob(stream, page_start).await? else {
+            return Ok(None);
+        };
+        let blob = Arc::new(decode_bitmap_blob(&bytes)?);
+        cache.insert(key, blob.clone());
+        Ok(Some(blob))
+    }
+}
+
+fn decoded_page_blob_cache(entries: usize) -> Option<Arc<DecodedPageBlobCache>> {
+    (entries > 0).then(|| Arc::new(Cache::new(entries)))
+}
+
+/// One row per `(stream value, shard)` with indexed entries, partitioned by
+/// [`crate::kernel::sharded_streams::stream_value_key`] so one listing returns every shard of a value.
+#[derive(Clone)]
+pub struct StreamShardPresenceTable<M: MetaStore> {
+    table: ScannableKvTable<M>,
+}
+
+impl<M: MetaStore> StreamShardPresenceTable<M> {
+    pub fn new(table: ScannableKvTable<M>) -> Self {
+        Self { table }
+    }
+
+    pub async fn mark(&self, stream: &str) -> Result<()> {
+        let (value_key, shard) = split_stream_shard(stream)
+            .ok_or(Error::InvalidParams("malformed sharded stream id"))?;
+        let _ = self
+            .table
+            .put(
+                value_key.as_bytes(),
+                &u64_key(shard),
+                Bytes::new(),
+                crate::store::traits::PutCond::Any,
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, stream: &str) -> Result<()> {
+        let (value_key, shard) = split_stream_shard(stream)
+            .ok_or(Error::InvalidParams("malformed sharded stream id"))?;
+        self.table
+            .delete(
+                value_key.as_bytes(),
+                &u64_key(shard),
+                crate::store::traits::DelCond::Any,
+            )
+            .await
+    }
+
+    pub async fn load(&self, value_key: &str) -> Result<RoaringTreemap> {
+        let mut cursor = None;
+        let mut shards = RoaringTreemap::new();
+        loop {
+            let page = self
+                .table
+                .list_prefix(value_key.as_bytes(), b"", cursor.take(), 1_024)
+                .await?;
+            for clustering in page.keys {
+                shards.insert(read_u64_be(&clustering).ok_or(Error::Decode("invalid shard key"))?);
+            }
+            if page.next_cursor.is_none() {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        Ok(shards)
+    }
 }
 
 pub struct OpenBitmapPageTable<M: MetaStore> {
@@ -1016,6 +1325,13 @@ impl<M: MetaStore, B: BlobStore> BlockLogBlobTable<M, B> {
         let Some(header) = self.log_block_headers.get(block_num).await? else {
             return Ok(Vec::new());
         };
+        // Log ordinals come from the directory or the block record, so an
+        // ordinal the header does not cover is corruption, not a missing log.
+        if end_local_ordinal_inclusive >= start_local_ordinal
+            && end_local_ordinal_inclusive + 1 >= header.offsets.len()
+        {
+            return Err(Error::Decode("log ordinal beyond block log header"));
+        }
         load_cached_offset_run(
             &self.cache,
             &self.blob_table,
@@ -1035,6 +1351,16 @@ impl<M: MetaStore, B: BlobStore> BlockLogBlobTable<M, B> {
         .collect()
     }
 
+    /// Returns the byte length of a block's log blob. Reads the whole blob,
+    /// so it is for audits, not serving.
+    pub async fn blob_len(&self, block_num: u64) -> Result<Option<u64>> {
+        Ok(self
+            .blob_table
+            .get(&BlockLogBlobSpec::key(block_num))
+            .await?
+            .map(|bytes| bytes.len() as u64))
+    }
+
     pub async fn put_block(
         &self,
         block_num: u64,
@@ -1105,6 +1431,9 @@ async fn load_cached_offset_run<B: BlobStore>(
     let Some(run_bytes) = blob_table.read_range(request.key, start, end).await? else {
         return Ok(Vec::new());
     };
+    if u64::try_from(run_bytes.len()).ok() != Some(end - start) {
+        return Err(Error::Decode("blob shorter than header offsets"));
+    }
 
     let mut out = Vec::with_capacity(cached.len());
     for (index, (cache_key, maybe_cached)) in cached.into_iter().enumerate() {
@@ -6,7 +6,7 @@ use crate::core::offsets::BucketedOffsets;
 use crate::error::{Error, Result};
 use crate::ingest::bitmap_pages;
 use crate::ingest::indexed_family::{collect_grouped_stream_appends, iter_grouped_stream_appends};
-use crate::kernel::sharded_streams::sharded_stream_id;
+use crate::kernel::sharded_streams::{StreamKind, sharded_stream_id};
 use crate::store::traits::{BlobStore, MetaStore};
 use crate::tables::Tables;
 use crate::traces::TRACE_STREAM_PAGE_LOCAL_ID_SPAN;
@@ -103,12 +103,14 @@ pub async fn persist_trace_stream_fragments<M: MetaStore, B: BlobStore>(
     tables: &Tables<M, B>,
     block_num: u64,
     grouped_values: &BTreeMap<String, Vec<u32>>,
+    concurrency: usize,
 ) -> Result<Vec<(String, u32)>> {
     bitmap_pages::persist_stream_fragments(
         &tables.trace_streams,
         block_num,
         iter_grouped_stream_appends(grouped_values),
         TRACE_STREAM_PAGE_LOCAL_ID_SPAN,
+        concurrency,
     )
     .await
 }
@@ -126,15 +128,24 @@ fn collect_trace_stream_appends(
             let local = global_trace_id.local().get();
             let mut values = Vec::with_capacity(4);
 
-            values.push((sharded_stream_id("from", &fields.from_addr, shard), local));
+            values.push((
+                sharded_stream_id(StreamKind::From, &fields.from_addr, shard),
+                local,
+            ));
             if let Some(to_addr) = fields.to_addr {
-                values.push((sharded_stream_id("to", &to_addr, shard), local));
+                values.push((sharded_stream_id(StreamKind::To, &to_addr, shard), local));
             }
             if let Some(selector) = fields.selector {
-                values.push((sharded_stream_id("selector", &selector, shard), local));
+                values.push((
+                    sharded_stream_id(StreamKind::Selector, &selector, shard),
+                    local,
+                ));
             }
             if fields.has_value {
-                values.push((sharded_stream_id("has_value", b"\x01", shard), local));
+                values.push((
+                    sharded_stream_id(StreamKind::HasValue, b"\x01", shard),
+                    local,
+                ));
             }
 
             Ok(values)
@@ -119,6 +119,10 @@ impl<M: MetaStore, B: BlobStore> QueryMaterializer for TraceMaterializer<'_, M,
     fn into_output(item: Self::Item) -> Self::Output {
         item
     }
+
+    fn item_bytes(item: &Self::Item) -> usize {
+        item.frame_bytes().len()
+    }
 }
 
 #[cfg(test)]
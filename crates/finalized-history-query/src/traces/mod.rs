@@ -57,6 +57,7 @@ impl TracesFamily {
         runtime: &Runtime<M, B>,
         state: &mut TraceSequencingState,
         block: &FinalizedBlock,
+        stream_append_concurrency: usize,
     ) -> Result<usize> {
         let from_next_trace_id = state.next_trace_id.get();
         let ingest_plan = plan_trace_ingest(&block.trace_rlp, from_next_trace_id)?;
@@ -69,6 +70,7 @@ impl TracesFamily {
             &runtime.tables,
             block.block_num,
             &ingest_plan.stream_appends_by_stream,
+            stream_append_concurrency,
         )
         .await?;
         let IndexedFamilyFinalizeResult { next_primary_id } = finalize_indexed_family_ingest(
@@ -83,10 +85,11 @@ impl TracesFamily {
                 written_count: trace_count_u32,
                 touched_pages,
                 stream_page_local_id_span: TRACE_STREAM_PAGE_LOCAL_ID_SPAN,
-                make_meta: |count, min_local, max_local| StreamBitmapMeta {
+                make_meta: |count, min_local, max_local, byte_size| StreamBitmapMeta {
                     count,
                     min_local,
                     max_local,
+                    byte_size,
                 },
             },
         )
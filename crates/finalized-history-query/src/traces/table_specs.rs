@@ -56,6 +56,11 @@ impl ScannableTableSpec for TraceOpenBitmapPageSpec {
     const TABLE: ScannableTableId = ScannableTableId::new("trace_open_bitmap_page");
 }
 
+pub struct TraceStreamShardSpec;
+impl ScannableTableSpec for TraceStreamShardSpec {
+    const TABLE: ScannableTableId = ScannableTableId::new("trace_stream_shard");
+}
+
 pub struct BlockTraceBlobSpec;
 impl BlobTableSpec for BlockTraceBlobSpec {
     const TABLE: BlobTableId = BlobTableId::new("block_trace_blob");
@@ -39,6 +39,7 @@ impl TxsFamily {
         runtime: &Runtime<M, B>,
         state: &mut TxFamilyState,
         block: &FinalizedBlock,
+        stream_append_concurrency: usize,
     ) -> Result<usize> {
         let from_next_tx_id = state.next_tx_id.get();
         let plan = plan_tx_ingest(block, from_next_tx_id)?;
@@ -49,6 +50,7 @@ impl TxsFamily {
             &runtime.tables,
             block.block_num,
             &plan.stream_appends_by_stream,
+            stream_append_concurrency,
         )
         .await?;
         let IndexedFamilyFinalizeResult { next_primary_id } = finalize_indexed_family_ingest(
@@ -63,10 +65,11 @@ impl TxsFamily {
                 written_count: tx_count_u32,
                 touched_pages,
                 stream_page_local_id_span: TX_STREAM_PAGE_LOCAL_ID_SPAN,
-                make_meta: |count, min_local, max_local| StreamBitmapMeta {
+                make_meta: |count, min_local, max_local, byte_size| StreamBitmapMeta {
                     count,
                     min_local,
                     max_local,
+                    byte_size,
                 },
             },
         )
@@ -1,4 +1,7 @@
-use crate::core::clause::{Clause, clause_matches, has_indexed_value, optional_clause_matches};
+use crate::core::clause::{
+    Clause, clause_matches, has_indexed_value, has_negated_value, optional_clause_matches,
+};
+use crate::kernel::sharded_streams::StreamKind;
 use crate::query::engine::IndexedFilter;
 use crate::query::planner::{IndexedClause, build_indexed_clause};
 use crate::txs::types::{Address20, Selector4};
@@ -32,16 +35,22 @@ impl IndexedFilter for TxFilter {
             || has_indexed_value(&self.selector)
     }
 
+    fn has_negated_clause(&self) -> bool {
+        has_negated_value(&self.from)
+            || has_negated_value(&self.to)
+            || has_negated_value(&self.selector)
+    }
+
     fn indexed_clauses(&self) -> Vec<IndexedClause> {
         let mut clauses = Vec::new();
 
-        if let Some(clause) = build_indexed_clause("from", &self.from) {
+        if let Some(clause) = build_indexed_clause(StreamKind::From, &self.from) {
             clauses.push(clause);
         }
-        if let Some(clause) = build_indexed_clause("to", &self.to) {
+        if let Some(clause) = build_indexed_clause(StreamKind::To, &self.to) {
             clauses.push(clause);
         }
-        if let Some(clause) = build_indexed_clause("selector", &self.selector) {
+        if let Some(clause) = build_indexed_clause(StreamKind::Selector, &self.selector) {
             clauses.push(clause);
         }
 
@@ -8,7 +8,7 @@ use crate::family::FinalizedBlock;
 use crate::ingest::bitmap_pages;
 use crate::ingest::indexed_family::{collect_grouped_stream_appends, iter_grouped_stream_appends};
 use crate::kernel::codec::StorageCodec;
-use crate::kernel::sharded_streams::sharded_stream_id;
+use crate::kernel::sharded_streams::{StreamKind, sharded_stream_id};
 use crate::store::traits::{BlobStore, MetaStore};
 use crate::tables::Tables;
 use crate::txs::TX_STREAM_PAGE_LOCAL_ID_SPAN;
@@ -82,12 +82,18 @@ pub fn collect_stream_appends(
             TxView::decode(&tx.signed_tx_bytes).map_err(|_| Error::Decode("invalid signed tx"))?;
         let mut values = Vec::with_capacity(3);
 
-        values.push((sharded_stream_id("from", &tx.sender, shard), local));
+        values.push((
+            sharded_stream_id(StreamKind::From, &tx.sender, shard),
+            local,
+        ));
         if let Some(to_addr) = signed_tx.to_addr()? {
-            values.push((sharded_stream_id("to", &to_addr, shard), local));
+            values.push((sharded_stream_id(StreamKind::To, &to_addr, shard), local));
         }
         if let Some(selector) = signed_tx.selector()? {
-            values.push((sharded_stream_id("selector", &selector, shard), local));
+            values.push((
+                sharded_stream_id(StreamKind::Selector, &selector, shard),
+                local,
+            ));
         }
 
         Ok(values)
@@ -98,12 +104,14 @@ pub async fn persist_stream_fragments<M: MetaStore, B: BlobStore>(
     tables: &Tables<M, B>,
     block_num: u64,
     grouped_values: &BTreeMap<String, Vec<u32>>,
+    concurrency: usize,
 ) -> Result<Vec<(String, u32)>> {
     bitmap_pages::persist_stream_fragments(
         &tables.tx_streams,
         block_num,
         iter_grouped_stream_appends(grouped_values),
         TX_STREAM_PAGE_LOCAL_ID_SPAN,
+        concurrency,
     )
     .await
 }
@@ -134,7 +142,7 @@ mod tests {
     use crate::error::Error;
     use crate::family::FinalizedBlock;
     use crate::kernel::codec::StorageCodec;
-    use crate::kernel::sharded_streams::sharded_stream_id;
+    use crate::kernel::sharded_streams::{StreamKind, sharded_stream_id};
     use crate::kernel::table_specs::{BlobTableSpec, PointTableSpec};
     use crate::store::blob::InMemoryBlobStore;
     use crate::store::meta::InMemoryMetaStore;
@@ -350,10 +358,10 @@ mod tests {
         let first_shard = TxId::new(0).shard().get();
         let third_shard = TxId::new(2).shard().get();
 
-        assert!(appends.contains_key(&sharded_stream_id("to", &[3u8; 20], first_shard)));
-        assert!(appends.contains_key(&sharded_stream_id("to", &[4u8; 20], third_shard)));
+        assert!(appends.contains_key(&sharded_stream_id(StreamKind::To, &[3u8; 20], first_shard)));
+        assert!(appends.contains_key(&sharded_stream_id(StreamKind::To, &[4u8; 20], third_shard)));
         assert!(appends.contains_key(&sharded_stream_id(
-            "selector",
+            StreamKind::Selector,
             &[0xaa, 0xbb, 0xcc, 0xdd],
             first_shard,
         )));
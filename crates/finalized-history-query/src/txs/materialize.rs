@@ -120,6 +120,10 @@ impl<M: MetaStore, B: BlobStore> QueryMaterializer for TxMaterializer<'_, M, B>
     fn into_output(item: Self::Item) -> Self::Output {
         item
     }
+
+    fn item_bytes(item: &Self::Item) -> usize {
+        item.encoded_len()
+    }
 }
 
 #[cfg(test)]
@@ -95,6 +95,11 @@ impl ScannableTableSpec for TxOpenBitmapPageSpec {
     const TABLE: ScannableTableId = ScannableTableId::new("tx_open_bitmap_page");
 }
 
+pub struct TxStreamShardSpec;
+impl ScannableTableSpec for TxStreamShardSpec {
+    const TABLE: ScannableTableId = ScannableTableId::new("tx_stream_shard");
+}
+
 pub struct BlockTxBlobSpec;
 impl BlobTableSpec for BlockTxBlobSpec {
     const TABLE: BlobTableId = BlobTableId::new("block_tx_blob");
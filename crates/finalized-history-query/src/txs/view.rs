@@ -74,6 +74,12 @@ impl TxRef {
         self.tx_idx
     }
 
+    /// Length of the stored tx envelope, which is what `max_result_bytes`
+    /// counts.
+    pub fn encoded_len(&self) -> usize {
+        self.envelope_bytes.len()
+    }
+
     pub fn tx_hash(&self) -> Result<&Hash32> {
         decode_fixed_hash(
             self.envelope_bytes.as_ref(),
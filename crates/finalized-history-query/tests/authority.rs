@@ -14,7 +14,7 @@ use finalized_history_query::family::Families;
 use finalized_history_query::ingest::authority::LeaseAuthority;
 use finalized_history_query::ingest::engine::IngestEngine;
 use finalized_history_query::kernel::codec::StorageCodec;
-use finalized_history_query::kernel::sharded_streams::page_start_local;
+use finalized_history_query::kernel::sharded_streams::{StreamKind, page_start_local};
 use finalized_history_query::kernel::table_specs::ScannableTableSpec;
 use finalized_history_query::logs::table_specs::{
     BlobTableSpec, BlockLogBlobSpec, LogBitmapByBlockSpec, LogDirByBlockSpec,
@@ -142,7 +142,7 @@ fn ingest_publishes_publication_state_and_immutable_frontier_artifacts() {
         );
 
         let sid = finalized_history_query::kernel::sharded_streams::sharded_stream_id(
-            "addr",
+            StreamKind::Addr,
             &[1; 20],
             finalized_history_query::core::ids::LogShard::new(0)
                 .unwrap()
@@ -4,12 +4,23 @@ mod helpers;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
-use finalized_history_query::api::FinalizedHistoryService;
+use bytes::Bytes;
+use finalized_history_query::api::{
+    ExecutionBudget, FinalizedHistoryService, QueryLogsRequest, QueryOrder,
+};
 use finalized_history_query::config::Config;
-use finalized_history_query::logs::table_specs::BlockLogBlobSpec;
+use finalized_history_query::kernel::table_specs::ScannableTableSpec;
+use finalized_history_query::logs::table_specs::{
+    BlobTableSpec, BlockLogBlobSpec, LogBitmapByBlockSpec, LogBitmapPageBlobSpec,
+};
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
+use finalized_history_query::store::traits::{
+    BlobStore, BlobTableId, DelCond, MetaStore, Page, PutCond, PutResult, Record, ScannableTableId,
+    TableId,
+};
 use finalized_history_query::tables::{BytesCacheConfig, TableCacheConfig};
+use finalized_history_query::{Clause, LogFilter, Topic};
 use futures::executor::block_on;
 
 use helpers::*;
@@ -67,6 +78,123 @@ fn service_reuses_cached_log_block_blobs_across_queries() {
     });
 }
 
+#[test]
+fn reader_cache_keeps_cold_log_blobs_resident_across_queries() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let inner_blob = Arc::new(InMemoryBlobStore::default());
+        let writer = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            inner_blob.clone(),
+            1,
+        );
+        writer
+            .ingest_finalized_block(mk_block(
+                1,
+                [0; 32],
+                vec![mk_log(7, 10, 20, 1, 0, 0), mk_log(7, 10, 21, 1, 0, 1)],
+            ))
+            .await
+            .expect("ingest block");
+
+        let get_blob_calls = Arc::new(AtomicU64::new(0));
+        let read_range_calls = Arc::new(AtomicU64::new(0));
+        let blob = CountingBlobStore {
+            inner: inner_blob,
+            target_key: BlockLogBlobSpec::key(1),
+            get_blob_calls: get_blob_calls.clone(),
+            read_range_calls: read_range_calls.clone(),
+            read_range_bytes: Arc::new(AtomicU64::new(0)),
+        };
+        let reader = FinalizedHistoryService::new_reader_only(
+            Config {
+                bytes_cache: BytesCacheConfig {
+                    log_block_blobs: TableCacheConfig {
+                        max_bytes: 1024 * 1024,
+                    },
+                    ..BytesCacheConfig::disabled()
+                },
+                ..Config::default()
+            },
+            meta,
+            blob,
+        );
+        let fetches =
+            || get_blob_calls.load(Ordering::Relaxed) + read_range_calls.load(Ordering::Relaxed);
+
+        let first = query_page(&reader, 1, 1, indexed_address_filter(7), 10, None)
+            .await
+            .expect("first query");
+        let after_first = fetches();
+        let second = query_page(&reader, 1, 1, indexed_address_filter(7), 10, None)
+            .await
+            .expect("second query");
+
+        assert_eq!(first.items, second.items);
+        assert!(after_first > 0);
+        assert_eq!(fetches(), after_first);
+        assert!(reader.cache_metrics().log_block_blobs.hits > 0);
+    });
+}
+
+#[test]
+fn exact_block_counts_load_adjacent_candidates_in_one_fetch() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let inner_blob = Arc::new(InMemoryBlobStore::default());
+        let writer = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            inner_blob.clone(),
+            1,
+        );
+        let logs = (0..4u32)
+            .map(|i| mk_log(7, 10, 20 + (i % 2) as u8, 1, 0, i))
+            .collect();
+        writer
+            .ingest_finalized_block(mk_block(1, [0; 32], logs))
+            .await
+            .expect("ingest block");
+
+        let get_blob_calls = Arc::new(AtomicU64::new(0));
+        let read_range_calls = Arc::new(AtomicU64::new(0));
+        let blob = CountingBlobStore {
+            inner: inner_blob,
+            target_key: BlockLogBlobSpec::key(1),
+            get_blob_calls: get_blob_calls.clone(),
+            read_range_calls: read_range_calls.clone(),
+            read_range_bytes: Arc::new(AtomicU64::new(0)),
+        };
+        let reader = FinalizedHistoryService::new_reader_only(
+            Config {
+                bytes_cache: BytesCacheConfig::disabled(),
+                ..Config::default()
+            },
+            meta,
+            blob,
+        );
+
+        // The negated topic1 clause is not indexed, so every address-7
+        // candidate is loaded for the exact-match pass.
+        let filter = LogFilter {
+            topic1: Some(Clause::Not(Box::new(Clause::One(Topic([21; 32]))))),
+            ..indexed_address_filter(7)
+        };
+        assert_eq!(
+            reader
+                .count_logs_by_block(filter, 1, 1)
+                .await
+                .expect("count by block"),
+            vec![(1, 2)]
+        );
+        assert_eq!(
+            get_blob_calls.load(Ordering::Relaxed) + read_range_calls.load(Ordering::Relaxed),
+            1
+        );
+    });
+}
+
 #[test]
 fn service_coalesces_contiguous_same_block_log_blob_into_one_range_read() {
     block_on(async {
@@ -219,3 +347,373 @@ fn service_reuses_cached_block_records_across_queries() {
         assert!(metrics.block_records.bytes_used > 0);
     });
 }
+
+/// Counts `get_blob` calls for compacted `addr/` stream page blobs.
+#[derive(Clone, Default)]
+struct AddrPageBlobCountingStore {
+    inner: InMemoryBlobStore,
+    addr_page_gets: Arc<AtomicU64>,
+}
+
+impl BlobStore for AddrPageBlobCountingStore {
+    async fn put_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: Bytes,
+    ) -> finalized_history_query::Result<()> {
+        self.inner.put_blob(table, key, value).await
+    }
+
+    async fn get_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+    ) -> finalized_history_query::Result<Option<Bytes>> {
+        if table == LogBitmapPageBlobSpec::TABLE && key.starts_with(b"addr/") {
+            self.addr_page_gets.fetch_add(1, Ordering::Relaxed);
+        }
+        self.inner.get_blob(table, key).await
+    }
+
+    async fn delete_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+    ) -> finalized_history_query::Result<()> {
+        self.inner.delete_blob(table, key).await
+    }
+
+    async fn list_prefix(
+        &self,
+        table: BlobTableId,
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> finalized_history_query::Result<Page> {
+        self.inner.list_prefix(table, prefix, cursor, limit).await
+    }
+}
+
+#[test]
+fn query_logs_batch_loads_shared_stream_page_once() {
+    block_on(async {
+        let blob = AddrPageBlobCountingStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            blob.clone(),
+            1,
+        );
+        // 5_000 logs seal the first 4_096-local page of the address stream;
+        // blocks 1..=4 all fall inside it.
+        for block_num in 1..=5u64 {
+            let logs = (0..1_000u32)
+                .map(|log_idx| mk_log(7, (log_idx % 3) as u8, 20, block_num, 0, log_idx))
+                .collect();
+            svc.ingest_finalized_block(mk_block(block_num, [block_num as u8 - 1; 32], logs))
+                .await
+                .expect("ingest block");
+        }
+
+        let requests = [(1, 2), (2, 3), (3, 4)]
+            .into_iter()
+            .map(|(from_block, to_block)| QueryLogsRequest {
+                from_block: Some(from_block),
+                to_block: Some(to_block),
+                from_block_hash: None,
+                to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
+                order: QueryOrder::Ascending,
+                resume_id: None,
+                limit: 5,
+                filter: indexed_address_filter(7),
+            })
+            .collect::<Vec<_>>();
+
+        let mut separate = Vec::new();
+        for request in &requests {
+            separate.push(
+                svc.query_logs(request.clone(), ExecutionBudget::default())
+                    .await
+                    .expect("separate query"),
+            );
+        }
+        assert_eq!(blob.addr_page_gets.load(Ordering::Relaxed), 3);
+
+        blob.addr_page_gets.store(0, Ordering::Relaxed);
+        let batched = svc
+            .query_logs_batch(requests, ExecutionBudget::default())
+            .await;
+        assert_eq!(blob.addr_page_gets.load(Ordering::Relaxed), 1);
+
+        assert_eq!(batched.len(), 3);
+        for ((page, expected), first_block) in batched.into_iter().zip(&separate).zip([1, 2, 3]) {
+            let page = page.expect("batched query");
+            assert_eq!(&page.items, &expected.items);
+            assert_eq!(page.items[0].block_num(), first_block);
+        }
+    });
+}
+
+/// Counts every blob read.
+#[derive(Clone, Default)]
+struct BlobReadCountingStore {
+    inner: InMemoryBlobStore,
+    reads: Arc<AtomicU64>,
+}
+
+impl BlobStore for BlobReadCountingStore {
+    async fn put_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: Bytes,
+    ) -> finalized_history_query::Result<()> {
+        self.inner.put_blob(table, key, value).await
+    }
+
+    async fn get_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+    ) -> finalized_history_query::Result<Option<Bytes>> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.inner.get_blob(table, key).await
+    }
+
+    async fn read_range(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        start: u64,
+        end_exclusive: u64,
+    ) -> finalized_history_query::Result<Option<Bytes>> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.inner
+            .read_range(table, key, start, end_exclusive)
+            .await
+    }
+
+    async fn delete_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+    ) -> finalized_history_query::Result<()> {
+        self.inner.delete_blob(table, key).await
+    }
+
+    async fn list_prefix(
+        &self,
+        table: BlobTableId,
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> finalized_history_query::Result<Page> {
+        self.inner.list_prefix(table, prefix, cursor, limit).await
+    }
+}
+
+/// Counts by-block fragment scans of `topic0/` log streams.
+#[derive(Clone, Default)]
+struct Topic0FragmentScanCountingStore {
+    inner: InMemoryMetaStore,
+    topic0_fragment_scans: Arc<AtomicU64>,
+}
+
+impl MetaStore for Topic0FragmentScanCountingStore {
+    async fn get(
+        &self,
+        table: TableId,
+        key: &[u8],
+    ) -> finalized_history_query::Result<Option<Record>> {
+        self.inner.get(table, key).await
+    }
+
+    async fn put(
+        &self,
+        table: TableId,
+        key: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> finalized_history_query::Result<PutResult> {
+        self.inner.put(table, key, value, cond).await
+    }
+
+    async fn delete(
+        &self,
+        table: TableId,
+        key: &[u8],
+        cond: DelCond,
+    ) -> finalized_history_query::Result<()> {
+        self.inner.delete(table, key, cond).await
+    }
+
+    async fn scan_get(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+    ) -> finalized_history_query::Result<Option<Record>> {
+        self.inner.scan_get(table, partition, clustering).await
+    }
+
+    async fn scan_put(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> finalized_history_query::Result<PutResult> {
+        self.inner
+            .scan_put(table, partition, clustering, value, cond)
+            .await
+    }
+
+    async fn scan_delete(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        cond: DelCond,
+    ) -> finalized_history_query::Result<()> {
+        self.inner
+            .scan_delete(table, partition, clustering, cond)
+            .await
+    }
+
+    async fn scan_list(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> finalized_history_query::Result<Page> {
+        if table == LogBitmapByBlockSpec::TABLE && partition.starts_with(b"topic0/") {
+            self.topic0_fragment_scans.fetch_add(1, Ordering::Relaxed);
+        }
+        self.inner
+            .scan_list(table, partition, prefix, cursor, limit)
+            .await
+    }
+}
+
+#[test]
+fn never_seen_topic0_returns_empty_without_loading_bitmaps() {
+    block_on(async {
+        let meta = Topic0FragmentScanCountingStore::default();
+        let blob = BlobReadCountingStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+        // 5_000 logs span two 4_096-local pages; the first seals.
+        for block_num in 1..=5u64 {
+            let logs = (0..1_000u32)
+                .map(|log_idx| mk_log(7, (log_idx % 3) as u8, 20, block_num, 0, log_idx))
+                .collect();
+            svc.ingest_finalized_block(mk_block(block_num, [block_num as u8 - 1; 32], logs))
+                .await
+                .expect("ingest block");
+        }
+        let without_fast_path = FinalizedHistoryService::new_reader_only(
+            Config {
+                empty_result_fast_path: false,
+                ..lease_writer_config()
+            },
+            meta.clone(),
+            blob.clone(),
+        );
+        let request = QueryLogsRequest::builder()
+            .from_block(1)
+            .to_block(5)
+            .limit(10)
+            .filter(LogFilter {
+                topic0: Some(Clause::One(Topic([99; 32]))),
+                ..indexed_address_filter(7)
+            })
+            .build()
+            .expect("request");
+
+        meta.topic0_fragment_scans.store(0, Ordering::Relaxed);
+        blob.reads.store(0, Ordering::Relaxed);
+        let page = svc
+            .query_logs(request.clone(), ExecutionBudget::default())
+            .await
+            .expect("fast path query");
+        assert!(page.items.is_empty());
+        assert_eq!(blob.reads.load(Ordering::Relaxed), 0);
+        // Only the planner's estimate scans the unseen stream, once per page.
+        assert_eq!(meta.topic0_fragment_scans.load(Ordering::Relaxed), 2);
+
+        meta.topic0_fragment_scans.store(0, Ordering::Relaxed);
+        let page = without_fast_path
+            .query_logs(request, ExecutionBudget::default())
+            .await
+            .expect("query without fast path");
+        assert!(page.items.is_empty());
+        assert_eq!(meta.topic0_fragment_scans.load(Ordering::Relaxed), 4);
+    });
+}
+
+#[test]
+fn prefetched_address_stream_serves_query_without_page_reads() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let blob = AddrPageBlobCountingStore::default();
+        let writer = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+        // 5_000 logs seal the first 4_096-local page of the address stream.
+        for block_num in 1..=5u64 {
+            let logs = (0..1_000u32)
+                .map(|log_idx| mk_log(7, (log_idx % 3) as u8, 20, block_num, 0, log_idx))
+                .collect();
+            writer
+                .ingest_finalized_block(mk_block(block_num, [block_num as u8 - 1; 32], logs))
+                .await
+                .expect("ingest block");
+        }
+        let reader = FinalizedHistoryService::new_reader_only(
+            Config {
+                stream_bitmap_cache_entries: 16,
+                ..lease_writer_config()
+            },
+            meta,
+            blob.clone(),
+        );
+        let request = QueryLogsRequest::builder()
+            .from_block(1)
+            .to_block(4)
+            .limit(10)
+            .filter(indexed_address_filter(7))
+            .build()
+            .expect("request");
+
+        blob.addr_page_gets.store(0, Ordering::Relaxed);
+        let loaded = reader.prefetch_logs(&request).await.expect("prefetch");
+        assert_eq!(loaded, 1);
+        assert_eq!(blob.addr_page_gets.load(Ordering::Relaxed), 1);
+
+        blob.addr_page_gets.store(0, Ordering::Relaxed);
+        let page = reader
+            .query_logs(request.clone(), ExecutionBudget::default())
+            .await
+            .expect("warm query");
+        assert_eq!(blob.addr_page_gets.load(Ordering::Relaxed), 0);
+        let expected = writer
+            .query_logs(request, ExecutionBudget::default())
+            .await
+            .expect("writer query");
+        assert_eq!(page.items, expected.items);
+        assert_eq!(page.items.len(), 10);
+    });
+}
@@ -14,11 +14,14 @@ use finalized_history_query::core::state::{
 };
 use finalized_history_query::error::{Error, Result};
 use finalized_history_query::family::Families;
+use finalized_history_query::ingest::wal::{
+    INGEST_WAL_KEY, INGEST_WAL_TABLE, IngestWalPhase, IngestWalRecord,
+};
 use finalized_history_query::kernel::codec::StorageCodec;
-use finalized_history_query::kernel::sharded_streams::page_start_local;
+use finalized_history_query::kernel::sharded_streams::{StreamKind, page_start_local};
 use finalized_history_query::kernel::table_specs::{PointTableSpec, ScannableTableSpec};
 use finalized_history_query::logs::table_specs::{LogBitmapByBlockSpec, LogBitmapPageMetaSpec};
-use finalized_history_query::logs::types::Log;
+use finalized_history_query::logs::types::{Address, Log, StreamBitmapMeta, Topic};
 use finalized_history_query::status::service_status;
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
@@ -42,6 +45,7 @@ const STREAM_PAGE_LOCAL_ID_SPAN: u32 = 4_096;
 enum FailurePhase {
     ArtifactMetaWrite,
     ArtifactBlobWrite,
+    ArtifactDelete,
     PublicationStateCas,
     PublishHeadAdvance,
 }
@@ -128,13 +132,15 @@ fn matches_phase(a: FailurePhase, b: FailurePhase) -> bool {
         ) | (
             FailurePhase::ArtifactBlobWrite,
             FailurePhase::ArtifactBlobWrite
-        ) | (
-            FailurePhase::PublicationStateCas,
-            FailurePhase::PublicationStateCas
-        ) | (
-            FailurePhase::PublishHeadAdvance,
-            FailurePhase::PublishHeadAdvance
-        )
+        ) | (FailurePhase::ArtifactDelete, FailurePhase::ArtifactDelete)
+            | (
+                FailurePhase::PublicationStateCas,
+                FailurePhase::PublicationStateCas
+            )
+            | (
+                FailurePhase::PublishHeadAdvance,
+                FailurePhase::PublishHeadAdvance
+            )
     )
 }
 
@@ -241,6 +247,9 @@ impl MetaStore for FaultyMetaStore {
         clustering: &[u8],
         cond: DelCond,
     ) -> Result<()> {
+        let logical_key = Self::scan_logical_key(family, partition, clustering);
+        self.injector
+            .maybe_fail(FailurePhase::ArtifactDelete, &logical_key)?;
         self.inner
             .scan_delete(family, partition, clustering, cond)
             .await
@@ -307,8 +316,8 @@ impl BlobStore for FaultyBlobStore {
 
 fn mk_log(address: u8, topic0: u8, topic1: u8, block_num: u64, tx_idx: u32, log_idx: u32) -> Log {
     Log {
-        address: [address; 20],
-        topics: vec![[topic0; 32], [topic1; 32]],
+        address: Address([address; 20]),
+        topics: vec![Topic([topic0; 32]), Topic([topic1; 32])],
         data: vec![address, topic0, topic1],
         block_num,
         tx_idx,
@@ -428,11 +437,46 @@ fn mk_service_with_writer(
     LeaseAuthority<MetaPublicationStore<FaultyMetaStore>>,
     FaultyMetaStore,
     FaultyBlobStore,
+> {
+    mk_service_with_config(meta, blob, injector, writer_id, Config::default())
+}
+
+fn mk_wal_service(
+    meta: Arc<InMemoryMetaStore>,
+    blob: Arc<InMemoryBlobStore>,
+    injector: Arc<FaultInjector>,
+) -> FinalizedHistoryService<
+    LeaseAuthority<MetaPublicationStore<FaultyMetaStore>>,
+    FaultyMetaStore,
+    FaultyBlobStore,
+> {
+    mk_service_with_config(
+        meta,
+        blob,
+        injector,
+        1,
+        Config {
+            ingest_wal: true,
+            ..Config::default()
+        },
+    )
+}
+
+fn mk_service_with_config(
+    meta: Arc<InMemoryMetaStore>,
+    blob: Arc<InMemoryBlobStore>,
+    injector: Arc<FaultInjector>,
+    writer_id: u64,
+    config: Config,
+) -> FinalizedHistoryService<
+    LeaseAuthority<MetaPublicationStore<FaultyMetaStore>>,
+    FaultyMetaStore,
+    FaultyBlobStore,
 > {
     FinalizedHistoryService::new_reader_writer(
         Config {
             observe_upstream_finalized_block: Arc::new(|| Some(u64::MAX / 4)),
-            ..Config::default()
+            ..config
         },
         FaultyMetaStore {
             inner: meta,
@@ -449,7 +493,10 @@ fn mk_service_with_writer(
 fn indexed_address_or_filter(addresses: &[u8]) -> LogFilter {
     LogFilter {
         address: Some(Clause::Or(
-            addresses.iter().map(|address| [*address; 20]).collect(),
+            addresses
+                .iter()
+                .map(|address| Address([*address; 20]))
+                .collect(),
         )),
         topic0: None,
         topic1: None,
@@ -474,6 +521,8 @@ async fn query_range(
                 to_block: Some(to_block),
                 from_block_hash: None,
                 to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
                 order: QueryOrder::Ascending,
                 resume_id: None,
                 limit: usize::MAX,
@@ -559,6 +608,87 @@ fn ingest_retry_survives_faults_at_immutable_publication_boundaries() {
     });
 }
 
+#[test]
+fn page_seal_retry_after_crash_between_page_meta_and_marker_delete_keeps_entries_unique() {
+    block_on(async {
+        let cases = vec![
+            (
+                "page_meta_put",
+                FailurePhase::ArtifactMetaWrite,
+                b"log_bitmap_page_meta/".to_vec(),
+            ),
+            (
+                "open_page_marker_delete",
+                FailurePhase::ArtifactDelete,
+                b"log_open_bitmap_page/".to_vec(),
+            ),
+        ];
+
+        for (label, phase, prefix) in cases {
+            let injector = Arc::new(FaultInjector::default());
+            let meta = Arc::new(InMemoryMetaStore::default());
+            let blob = Arc::new(InMemoryBlobStore::default());
+            let svc = mk_service(meta.clone(), blob.clone(), injector.clone());
+
+            // Block 1 fills all but one slot of the first page; block 2 seals it.
+            let first_page_logs = STREAM_PAGE_LOCAL_ID_SPAN - 1;
+            let block1 = mk_block(
+                1,
+                [0; 32],
+                (0..first_page_logs)
+                    .map(|log_idx| mk_log(1, 10, 20, 1, 0, log_idx))
+                    .collect(),
+            );
+            let block2 = mk_block(
+                2,
+                [1; 32],
+                (0..2)
+                    .map(|log_idx| mk_log(1, 10, 20, 2, 0, log_idx))
+                    .collect(),
+            );
+            svc.ingest_finalized_block(block1)
+                .await
+                .expect("ingest block 1");
+
+            injector.arm(phase, &prefix, 1);
+            let err = svc
+                .ingest_finalized_block(block2.clone())
+                .await
+                .expect_err(label);
+            assert!(matches!(err, Error::Backend(_)), "{label}");
+            assert_eq!(svc.indexed_finalized_head().await.expect("head"), 1);
+
+            injector.clear();
+            svc.ingest_finalized_block(block2)
+                .await
+                .expect("retry ingest");
+
+            let items = query_range(&svc, 1, 2).await;
+            assert_eq!(items.len(), first_page_logs as usize + 2, "{label}");
+            let mut positions = items
+                .iter()
+                .map(|log| (log.block_num, log.log_idx))
+                .collect::<Vec<_>>();
+            positions.dedup();
+            assert_eq!(positions.len(), items.len(), "{label}");
+
+            let sid = finalized_history_query::kernel::sharded_streams::sharded_stream_id(
+                StreamKind::Addr, &[1; 20], 0,
+            );
+            let page_meta = meta
+                .get(
+                    LogBitmapPageMetaSpec::TABLE,
+                    &LogBitmapPageMetaSpec::key(&sid, 0),
+                )
+                .await
+                .expect("load page meta")
+                .expect("sealed page meta");
+            let page_meta = StreamBitmapMeta::decode(&page_meta.value).expect("decode page meta");
+            assert_eq!(page_meta.count, STREAM_PAGE_LOCAL_ID_SPAN, "{label}");
+        }
+    });
+}
+
 #[test]
 fn failed_publication_cas_keeps_partial_artifacts_invisible_until_retry() {
     block_on(async {
@@ -580,7 +710,7 @@ fn failed_publication_cas_keeps_partial_artifacts_invisible_until_retry() {
             .expect_err("publication CAS should fail");
         assert!(matches!(err, Error::Backend(_)));
         let sid = finalized_history_query::kernel::sharded_streams::sharded_stream_id(
-            "addr",
+            StreamKind::Addr,
             &[7; 20],
             finalized_history_query::core::ids::LogShard::new(0)
                 .unwrap()
@@ -669,6 +799,8 @@ fn trace_publication_failure_keeps_partial_trace_artifacts_invisible_until_retry
                     to_block: Some(1),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: None,
                     limit: 10,
@@ -737,7 +869,7 @@ fn takeover_without_cleanup_overwrites_different_retry_payload_for_same_block()
         assert!(matches!(err, Error::Backend(_)));
 
         let sid = finalized_history_query::kernel::sharded_streams::sharded_stream_id(
-            "addr",
+            StreamKind::Addr,
             &[7; 20],
             finalized_history_query::core::ids::LogShard::new(0)
                 .unwrap()
@@ -808,3 +940,91 @@ fn takeover_without_cleanup_overwrites_different_retry_payload_for_same_block()
         assert_eq!(items.len(), 4);
     });
 }
+
+async fn load_wal_phase(meta: &InMemoryMetaStore) -> Option<IngestWalPhase> {
+    meta.get(INGEST_WAL_TABLE, INGEST_WAL_KEY)
+        .await
+        .expect("load ingest wal")
+        .map(|record| {
+            IngestWalRecord::decode(&record.value)
+                .expect("decode ingest wal")
+                .phase
+        })
+}
+
+#[test]
+fn wal_replay_publishes_block_whose_artifacts_landed_before_the_crash() {
+    block_on(async {
+        let injector = Arc::new(FaultInjector::default());
+        let meta = Arc::new(InMemoryMetaStore::default());
+        let blob = Arc::new(InMemoryBlobStore::default());
+        let svc = mk_wal_service(meta.clone(), blob.clone(), injector.clone());
+        let block_1 = mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(7, 10, 20, 1, 0, 0), mk_log(8, 11, 21, 1, 0, 1)],
+        );
+
+        let publication_state_key = FaultyMetaStore::publication_state_logical_key();
+        injector.arm(FailurePhase::PublishHeadAdvance, &publication_state_key, 1);
+        let err = svc
+            .ingest_finalized_block(block_1)
+            .await
+            .expect_err("publication CAS should fail");
+        assert!(matches!(err, Error::Backend(_)));
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 0);
+        assert_eq!(load_wal_phase(&meta).await, Some(IngestWalPhase::Applied));
+        injector.clear();
+
+        let restarted = mk_wal_service(meta.clone(), blob.clone(), injector.clone());
+        restarted
+            .ingest_finalized_block(mk_block(2, [1; 32], vec![mk_log(7, 12, 22, 2, 0, 0)]))
+            .await
+            .expect("replay completes block 1 before ingesting block 2");
+
+        assert_eq!(restarted.indexed_finalized_head().await.expect("head"), 2);
+        assert_eq!(load_wal_phase(&meta).await, None);
+        let items = query_range(&restarted, 1, 2).await;
+        assert_eq!(
+            items
+                .iter()
+                .map(|log| (log.block_num, log.log_idx))
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (1, 1), (2, 0)]
+        );
+    });
+}
+
+#[test]
+fn wal_replay_discards_block_interrupted_mid_artifact_writes() {
+    block_on(async {
+        let injector = Arc::new(FaultInjector::default());
+        let meta = Arc::new(InMemoryMetaStore::default());
+        let blob = Arc::new(InMemoryBlobStore::default());
+        let svc = mk_wal_service(meta.clone(), blob.clone(), injector.clone());
+        let block_1 = mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(7, 10, 20, 1, 0, 0), mk_log(8, 11, 21, 1, 0, 1)],
+        );
+
+        injector.arm(FailurePhase::ArtifactMetaWrite, b"block_record/", 1);
+        let err = svc
+            .ingest_finalized_block(block_1.clone())
+            .await
+            .expect_err("block record write should fail");
+        assert!(matches!(err, Error::Backend(_)));
+        assert_eq!(load_wal_phase(&meta).await, Some(IngestWalPhase::Planned));
+        injector.clear();
+
+        let restarted = mk_wal_service(meta.clone(), blob.clone(), injector.clone());
+        restarted
+            .ingest_finalized_block(block_1)
+            .await
+            .expect("discarded block is rewritten by the retry");
+
+        assert_eq!(restarted.indexed_finalized_head().await.expect("head"), 1);
+        assert_eq!(load_wal_phase(&meta).await, None);
+        assert_eq!(query_range(&restarted, 1, 1).await.len(), 2);
+    });
+}
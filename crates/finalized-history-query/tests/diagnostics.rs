@@ -0,0 +1,399 @@
+#[allow(dead_code, unused_imports)]
+mod helpers;
+
+use finalized_history_query::api::{FinalizedHistoryService, IndexAnomaly, SelfCheckOptions};
+use finalized_history_query::core::layout::MAX_LOCAL_ID;
+use finalized_history_query::diagnostics::{StreamMismatch, verify_stream};
+use finalized_history_query::ingest::bitmap_pages::{
+    compact_stream_page, persist_stream_fragments,
+};
+use finalized_history_query::kernel::sharded_streams::{StreamKind, sharded_stream_id};
+use finalized_history_query::store::blob::InMemoryBlobStore;
+use finalized_history_query::store::meta::InMemoryMetaStore;
+use finalized_history_query::streams::StreamBitmapMeta;
+use finalized_history_query::tables::Tables;
+use futures::executor::block_on;
+
+use helpers::*;
+
+const PAGE_SPAN: u32 = 4_096;
+
+fn seeded_tables(stream: &str) -> Tables<InMemoryMetaStore, InMemoryBlobStore> {
+    let tables = Tables::without_cache(InMemoryMetaStore::default(), InMemoryBlobStore::default());
+    block_on(async {
+        for (block_num, locals) in [(1, vec![3, 7]), (2, vec![9, 4_100]), (3, vec![4_200])] {
+            persist_stream_fragments(
+                &tables.log_streams,
+                block_num,
+                locals.into_iter().map(|local| (stream.to_owned(), local)),
+                PAGE_SPAN,
+                1,
+            )
+            .await
+            .expect("persist fragments");
+        }
+        assert!(
+            compact_stream_page(
+                &tables.log_streams,
+                stream,
+                0,
+                |count, min_local, max_local, byte_size| {
+                    StreamBitmapMeta {
+                        count,
+                        min_local,
+                        max_local,
+                        byte_size,
+                    }
+                }
+            )
+            .await
+            .expect("compact page")
+        );
+    });
+    tables
+}
+
+#[test]
+fn verify_stream_reports_consistent_sealed_and_open_pages() {
+    let stream = sharded_stream_id(StreamKind::Addr, &[1; 20], 0);
+    let tables = seeded_tables(&stream);
+
+    let health = block_on(verify_stream(
+        &tables.log_streams,
+        &stream,
+        0,
+        2 * PAGE_SPAN,
+    ))
+    .expect("verify stream");
+
+    assert!(health.is_consistent(), "{:?}", health.mismatches);
+    assert_eq!(health.sealed_pages, 1);
+    assert_eq!(health.open_pages, 1);
+    assert_eq!(health.entries, 5);
+}
+
+#[test]
+fn verify_stream_flags_drifted_page_meta_count() {
+    let stream = sharded_stream_id(StreamKind::Addr, &[1; 20], 0);
+    let tables = seeded_tables(&stream);
+    block_on(tables.log_streams.put_page_meta(
+        &stream,
+        0,
+        &StreamBitmapMeta {
+            count: 5,
+            min_local: 3,
+            max_local: 9,
+            byte_size: 0,
+        },
+    ))
+    .expect("overwrite page meta");
+
+    let health = block_on(verify_stream(
+        &tables.log_streams,
+        &stream,
+        0,
+        PAGE_SPAN - 1,
+    ))
+    .expect("verify stream");
+
+    assert_eq!(
+        health.mismatches,
+        vec![StreamMismatch::MetaCountDrift {
+            page_start: 0,
+            meta_count: 5,
+            fragment_count: 3,
+        }]
+    );
+}
+
+#[test]
+fn verify_stream_flags_locals_recorded_by_two_fragments() {
+    let stream = sharded_stream_id(StreamKind::Addr, &[1; 20], 0);
+    let tables = seeded_tables(&stream);
+    block_on(persist_stream_fragments(
+        &tables.log_streams,
+        4,
+        [(stream.clone(), 4_200), (stream.clone(), 4_300)],
+        PAGE_SPAN,
+        1,
+    ))
+    .expect("persist duplicate fragment");
+
+    let health = block_on(verify_stream(
+        &tables.log_streams,
+        &stream,
+        PAGE_SPAN,
+        PAGE_SPAN,
+    ))
+    .expect("verify stream");
+
+    assert_eq!(
+        health.mismatches,
+        vec![StreamMismatch::DuplicateLocals {
+            page_start: PAGE_SPAN,
+            count: 1,
+        }]
+    );
+}
+
+#[test]
+fn resolve_stream_entries_matches_ingested_address_log_ids() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        // Block 1 crosses the first page boundary so page 0 is sealed; block 2
+        // leaves the second page open.
+        let block_1_addresses = (0..PAGE_SPAN + 4)
+            .map(|i| if i % 3 == 0 { 1 } else { 2 })
+            .collect::<Vec<u8>>();
+        let block_2_addresses = [1, 2, 1, 1, 2];
+        let mut expected = Vec::new();
+        let mut next_local = 0u32;
+        for (block_num, addresses) in [(1, &block_1_addresses[..]), (2, &block_2_addresses[..])] {
+            let logs = addresses
+                .iter()
+                .enumerate()
+                .map(|(log_idx, address)| {
+                    if *address == 1 {
+                        expected.push(next_local + log_idx as u32);
+                    }
+                    mk_log(*address, 10, 20, block_num, 0, log_idx as u32)
+                })
+                .collect();
+            next_local += addresses.len() as u32;
+            let parent = if block_num == 1 {
+                [0; 32]
+            } else {
+                [block_num as u8 - 1; 32]
+            };
+            svc.ingest_finalized_block(mk_block(block_num, parent, logs))
+                .await
+                .expect("ingest");
+        }
+
+        let stream = sharded_stream_id(StreamKind::Addr, &[1; 20], 0);
+        let entries = svc
+            .resolve_stream_entries(&stream)
+            .await
+            .expect("resolve stream entries");
+        assert_eq!(entries, expected);
+
+        let other = sharded_stream_id(StreamKind::Addr, &[3; 20], 0);
+        assert!(
+            svc.resolve_stream_entries(&other)
+                .await
+                .expect("resolve unknown stream")
+                .is_empty()
+        );
+    });
+}
+
+#[test]
+fn delete_stream_removes_every_page_of_the_stream() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let blob = InMemoryBlobStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+        // Block 1 seals page 0; block 2 leaves page 1 open with a marker.
+        let block_1 = (0..PAGE_SPAN + 4)
+            .map(|log_idx| mk_log(1 + (log_idx % 2) as u8, 10, 20, 1, 0, log_idx))
+            .collect();
+        svc.ingest_finalized_block(mk_block(1, [0; 32], block_1))
+            .await
+            .expect("ingest block 1");
+        svc.ingest_finalized_block(mk_block(2, [1; 32], vec![mk_log(1, 10, 20, 2, 0, 0)]))
+            .await
+            .expect("ingest block 2");
+
+        let stream = sharded_stream_id(StreamKind::Addr, &[1; 20], 0);
+        assert_eq!(svc.delete_stream(&stream).await.expect("delete stream"), 2);
+
+        let page = query_page(&svc, 1, 2, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query deleted address");
+        assert!(page.items.is_empty());
+        let page = query_page(&svc, 1, 2, indexed_address_filter(2), 10, None)
+            .await
+            .expect("query other address");
+        assert_eq!(page.items.len(), 10);
+
+        let tables = Tables::without_cache(meta, blob);
+        let health = verify_stream(&tables.log_streams, &stream, 0, MAX_LOCAL_ID)
+            .await
+            .expect("verify deleted stream");
+        assert_eq!((health.sealed_pages, health.open_pages), (0, 0));
+        assert!(
+            tables
+                .log_open_bitmap_pages
+                .list_for_shard(0)
+                .await
+                .expect("list open pages")
+                .iter()
+                .all(|page| page.stream_id != stream)
+        );
+        assert_eq!(svc.delete_stream(&stream).await.expect("delete again"), 0);
+    });
+}
+
+#[test]
+fn list_indexed_keys_merges_sealed_and_open_streams() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        // 4_100 logs seal the first page. Address 9 only appears there, and
+        // address 5 only in the open second page.
+        let first_block = (0..4_100u32)
+            .map(|log_idx| {
+                let address = if log_idx == 0 {
+                    9
+                } else {
+                    1 + (log_idx % 3) as u8
+                };
+                mk_log(address, 10, 20, 1, 0, log_idx)
+            })
+            .collect();
+        svc.ingest_finalized_block(mk_block(1, [0; 32], first_block))
+            .await
+            .expect("ingest block 1");
+        svc.ingest_finalized_block(mk_block(2, [1; 32], vec![mk_log(5, 11, 20, 2, 0, 0)]))
+            .await
+            .expect("ingest block 2");
+
+        let (all, cursor) = svc
+            .list_indexed_keys(StreamKind::Addr, None, 100)
+            .await
+            .expect("list addresses");
+        let expected = [1u8, 2, 3, 5, 9]
+            .iter()
+            .map(|address| vec![*address; 20])
+            .collect::<Vec<_>>();
+        assert_eq!(all, expected);
+        assert!(cursor.is_none());
+
+        let mut paged = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (values, next) = svc
+                .list_indexed_keys(StreamKind::Addr, cursor, 2)
+                .await
+                .expect("list page");
+            assert!(values.len() <= 2);
+            paged.extend(values);
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(paged, expected);
+
+        let (topic0s, _) = svc
+            .list_indexed_keys(StreamKind::Topic0, None, 100)
+            .await
+            .expect("list topic0");
+        assert_eq!(topic0s, vec![vec![10; 32], vec![11; 32]]);
+        assert!(svc.list_indexed_keys(StreamKind::Selector, None, 10).await.is_err());
+    });
+}
+
+#[test]
+fn self_check_reports_exactly_a_corrupted_page_meta_count() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let blob = InMemoryBlobStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+        // Block 1 seals page 0 of both address streams; block 2 leaves page 1
+        // open.
+        let block_1 = (0..PAGE_SPAN + 4)
+            .map(|log_idx| mk_log(1 + (log_idx % 2) as u8, 10, 20, 1, 0, log_idx))
+            .collect();
+        let block_1 = mk_block(1, [0; 32], block_1);
+        let block_1_hash = block_1.block_hash;
+        svc.ingest_finalized_block(block_1)
+            .await
+            .expect("ingest block 1");
+        svc.ingest_finalized_block(mk_block(2, block_1_hash, vec![mk_log(1, 10, 20, 2, 0, 0)]))
+            .await
+            .expect("ingest block 2");
+
+        let report = svc
+            .self_check(SelfCheckOptions::default())
+            .await
+            .expect("self check");
+        assert!(report.is_clean(), "{:?}", report.anomalies);
+        assert_eq!(report.indexed_finalized_head, 2);
+        assert_eq!(report.blocks_checked, 2);
+        assert!(report.stream_pages_checked > 0);
+        assert!(!report.truncated);
+
+        let stream = sharded_stream_id(StreamKind::Addr, &[1; 20], 0);
+        let tables = Tables::without_cache(meta.clone(), blob.clone());
+        let sealed = tables
+            .log_streams
+            .get_page_meta(&stream, 0)
+            .await
+            .expect("load page meta")
+            .expect("sealed page meta");
+        tables
+            .log_streams
+            .put_page_meta(
+                &stream,
+                0,
+                &StreamBitmapMeta {
+                    count: sealed.count + 1,
+                    ..sealed
+                },
+            )
+            .await
+            .expect("corrupt page meta");
+
+        let reader = FinalizedHistoryService::new_reader_only(lease_writer_config(), meta, blob);
+        let report = reader
+            .self_check(SelfCheckOptions::default())
+            .await
+            .expect("self check");
+        assert_eq!(
+            report.anomalies,
+            vec![IndexAnomaly::Stream {
+                stream_id: stream,
+                mismatch: StreamMismatch::MetaCountDrift {
+                    page_start: 0,
+                    meta_count: sealed.count + 1,
+                    fragment_count: u64::from(sealed.count),
+                },
+            }]
+        );
+
+        let bounded = reader
+            .self_check(SelfCheckOptions {
+                max_blocks: Some(1),
+                max_stream_pages: Some(0),
+                ..SelfCheckOptions::default()
+            })
+            .await
+            .expect("bounded self check");
+        assert!(bounded.truncated);
+        assert_eq!(
+            (bounded.blocks_checked, bounded.stream_pages_checked),
+            (1, 0)
+        );
+        assert!(bounded.is_clean());
+    });
+}
@@ -7,7 +7,7 @@ use finalized_history_query::api::{
 use finalized_history_query::config::Config;
 use finalized_history_query::core::ids::LogId;
 use finalized_history_query::family::Families;
-use finalized_history_query::logs::types::Log;
+use finalized_history_query::logs::types::{Address, Log, Topic};
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
 use finalized_history_query::store::publication::MetaPublicationStore;
@@ -18,8 +18,8 @@ use futures::executor::block_on;
 
 fn mk_log(address: u8, topic0: u8, topic1: u8, block_num: u64, tx_idx: u32, log_idx: u32) -> Log {
     Log {
-        address: [address; 20],
-        topics: vec![[topic0; 32], [topic1; 32]],
+        address: Address([address; 20]),
+        topics: vec![Topic([topic0; 32]), Topic([topic1; 32])],
         data: vec![address, topic0, topic1],
         block_num,
         tx_idx,
@@ -155,6 +155,9 @@ fn naive_trace_query(
                 None | Some(Clause::Any) => true,
                 Some(Clause::One(value)) => value == &from,
                 Some(Clause::Or(values)) => values.iter().any(|value| value == &from),
+                Some(Clause::Not(_) | Clause::Range { .. }) => {
+                    unreachable!("trace filters here are never negated or ranged")
+                }
             };
             let matches_to = match &filter.to {
                 None | Some(Clause::Any) => true,
@@ -163,6 +166,9 @@ fn naive_trace_query(
                     .as_ref()
                     .map(|actual| values.iter().any(|value| value == actual))
                     .unwrap_or(false),
+                Some(Clause::Not(_) | Clause::Range { .. }) => {
+                    unreachable!("trace filters here are never negated or ranged")
+                }
             };
             let matches_selector = match &filter.selector {
                 None | Some(Clause::Any) => true,
@@ -171,6 +177,9 @@ fn naive_trace_query(
                     .as_ref()
                     .map(|actual| values.iter().any(|value| value == actual))
                     .unwrap_or(false),
+                Some(Clause::Not(_) | Clause::Range { .. }) => {
+                    unreachable!("trace filters here are never negated or ranged")
+                }
             };
             let matches_top_level = match filter.is_top_level {
                 None => true,
@@ -218,6 +227,8 @@ async fn query_trace_range(
                 to_block: Some(to_block),
                 from_block_hash: None,
                 to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
                 order: QueryOrder::Ascending,
                 resume_id: None,
                 limit: max_results.unwrap_or(usize::MAX),
@@ -296,6 +307,8 @@ async fn query_range(
                 to_block: Some(to_block),
                 from_block_hash: None,
                 to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
                 order: QueryOrder::Ascending,
                 resume_id: None,
                 limit: max_results.unwrap_or(usize::MAX),
@@ -311,16 +324,18 @@ async fn query_range(
         .collect()
 }
 
-fn matches_address(log: &Log, clause: &Option<Clause<[u8; 20]>>) -> bool {
+fn matches_address(log: &Log, clause: &Option<Clause<Address>>) -> bool {
     match clause {
         None => true,
         Some(Clause::Any) => true,
         Some(Clause::One(v)) => &log.address == v,
         Some(Clause::Or(vs)) => vs.iter().any(|v| v == &log.address),
+        Some(Clause::Not(inner)) => !matches_address(log, &Some((**inner).clone())),
+        Some(Clause::Range { lo, hi }) => lo.0 <= log.address.0 && log.address.0 <= hi.0,
     }
 }
 
-fn matches_topic(topic: Option<[u8; 32]>, clause: &Option<Clause<[u8; 32]>>) -> bool {
+fn matches_topic(topic: Option<Topic>, clause: &Option<Clause<Topic>>) -> bool {
     match clause {
         None => true,
         Some(Clause::Any) => true,
@@ -329,9 +344,69 @@ fn matches_topic(topic: Option<[u8; 32]>, clause: &Option<Clause<[u8; 32]>>) ->
             .as_ref()
             .map(|t| vs.iter().any(|v| v == t))
             .unwrap_or(false),
+        Some(Clause::Not(inner)) => !matches_topic(topic, &Some((**inner).clone())),
+        Some(Clause::Range { lo, hi }) => topic.is_some_and(|t| lo.0 <= t.0 && t.0 <= hi.0),
     }
 }
 
+fn differential_blocks() -> Vec<FinalizedBlock> {
+    vec![
+        mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 11, 21, 1, 0, 1)],
+        ),
+        mk_block(
+            2,
+            [1; 32],
+            vec![mk_log(1, 10, 22, 2, 0, 0), mk_log(3, 12, 23, 2, 0, 1)],
+        ),
+        mk_block(
+            3,
+            [2; 32],
+            vec![mk_log(2, 10, 24, 3, 0, 0), mk_log(4, 13, 25, 3, 0, 1)],
+        ),
+    ]
+}
+
+fn differential_filters() -> Vec<(u64, u64, LogFilter)> {
+    vec![
+        (
+            1,
+            3,
+            LogFilter {
+                address: Some(Clause::Or(vec![Address([1; 20]), Address([2; 20])])),
+                topic0: Some(Clause::One(Topic([10; 32]))),
+                topic1: None,
+                topic2: None,
+                topic3: None,
+            },
+        ),
+        (
+            2,
+            3,
+            LogFilter {
+                address: None,
+                topic0: Some(Clause::Or(vec![Topic([12; 32]), Topic([13; 32])])),
+                topic1: Some(Clause::Any),
+                topic2: None,
+                topic3: None,
+            },
+        ),
+        (
+            1,
+            3,
+            LogFilter {
+                address: Some(Clause::Or(vec![Address([1; 20]), Address([4; 20])])),
+                topic0: None,
+                topic1: None,
+                topic2: None,
+                topic3: None,
+            },
+        ),
+    ]
+}
+
 #[test]
 fn differential_query_matches_naive() {
     block_on(async {
@@ -346,21 +421,98 @@ fn differential_query_matches_naive() {
             1,
         );
 
+        let blocks = differential_blocks();
+        for b in &blocks {
+            svc.ingest_finalized_block(b.clone()).await.expect("ingest");
+        }
+
+        for (from_block, to_block, filter) in differential_filters() {
+            let got = query_range(&svc, from_block, to_block, filter.clone(), Some(3)).await;
+            let want = naive_query(&blocks, from_block, to_block, &filter, Some(3));
+            assert_eq!(got, want);
+        }
+    });
+}
+
+#[test]
+fn differential_block_scan_matches_indexed_path() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                observe_upstream_finalized_block: Arc::new(|| Some(u64::MAX / 4)),
+                planner_max_or_terms: 10,
+                ..Config::default()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        for b in differential_blocks() {
+            svc.ingest_finalized_block(b).await.expect("ingest");
+        }
+
+        // A filter with no indexed clause runs as a block scan. Filtering its
+        // output by each indexed filter must give what the indexed path does.
+        for (from_block, to_block, filter) in differential_filters() {
+            let scanned = query_range(&svc, from_block, to_block, LogFilter::default(), None)
+                .await
+                .into_iter()
+                .filter(|log| {
+                    matches_address(log, &filter.address)
+                        && matches_topic(log.topics.first().copied(), &filter.topic0)
+                        && matches_topic(log.topics.get(1).copied(), &filter.topic1)
+                        && matches_topic(log.topics.get(2).copied(), &filter.topic2)
+                        && matches_topic(log.topics.get(3).copied(), &filter.topic3)
+                })
+                .collect::<Vec<_>>();
+            let indexed = query_range(&svc, from_block, to_block, filter, None).await;
+            assert_eq!(scanned, indexed);
+        }
+
+        // The scan stops once the limit is reached, mid-block included.
+        let limited = query_range(&svc, 1, 3, LogFilter::default(), Some(3)).await;
+        let want = naive_query(&differential_blocks(), 1, 3, &LogFilter::default(), Some(3));
+        assert_eq!(limited, want);
+    });
+}
+
+#[test]
+fn differential_topic1_only_query_matches_naive_across_topic0_values() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                observe_upstream_finalized_block: Arc::new(|| Some(u64::MAX / 4)),
+                planner_max_or_terms: 10,
+                ..Config::default()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        // Topic1 value 20 appears under three different topic0 values, as
+        // topic0 itself (position 0), and on a log with a single topic.
+        let mut single_topic = mk_log(5, 20, 0, 2, 0, 2);
+        single_topic.topics.truncate(1);
         let blocks = vec![
             mk_block(
                 1,
                 [0; 32],
-                vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 11, 21, 1, 0, 1)],
+                vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 11, 20, 1, 0, 1)],
             ),
             mk_block(
                 2,
                 [1; 32],
-                vec![mk_log(1, 10, 22, 2, 0, 0), mk_log(3, 12, 23, 2, 0, 1)],
+                vec![
+                    mk_log(3, 12, 21, 2, 0, 0),
+                    mk_log(1, 20, 22, 2, 0, 1),
+                    single_topic,
+                ],
             ),
             mk_block(
                 3,
                 [2; 32],
-                vec![mk_log(2, 10, 24, 3, 0, 0), mk_log(4, 13, 25, 3, 0, 1)],
+                vec![mk_log(4, 13, 20, 3, 0, 0), mk_log(2, 10, 23, 3, 0, 1)],
             ),
         ];
 
@@ -373,42 +525,61 @@ fn differential_query_matches_naive() {
                 1,
                 3,
                 LogFilter {
-                    address: Some(Clause::Or(vec![[1; 20], [2; 20]])),
-                    topic0: Some(Clause::One([10; 32])),
-                    topic1: None,
-                    topic2: None,
-                    topic3: None,
+                    topic1: Some(Clause::One(Topic([20; 32]))),
+                    ..Default::default()
+                },
+            ),
+            (
+                1,
+                3,
+                LogFilter {
+                    topic0: Some(Clause::Any),
+                    topic1: Some(Clause::Or(vec![Topic([20; 32]), Topic([22; 32])])),
+                    ..Default::default()
                 },
             ),
             (
                 2,
                 3,
                 LogFilter {
-                    address: None,
-                    topic0: Some(Clause::Or(vec![[12; 32], [13; 32]])),
-                    topic1: Some(Clause::Any),
-                    topic2: None,
-                    topic3: None,
+                    topic1: Some(Clause::One(Topic([20; 32]))),
+                    ..Default::default()
                 },
             ),
             (
                 1,
                 3,
                 LogFilter {
-                    address: Some(Clause::Or(vec![[1; 20], [4; 20]])),
-                    topic0: None,
-                    topic1: None,
-                    topic2: None,
-                    topic3: None,
+                    address: Some(Clause::One(Address([2; 20]))),
+                    topic1: Some(Clause::One(Topic([20; 32]))),
+                    ..Default::default()
                 },
             ),
         ];
 
         for (from_block, to_block, filter) in filters {
-            let got = query_range(&svc, from_block, to_block, filter.clone(), Some(3)).await;
-            let want = naive_query(&blocks, from_block, to_block, &filter, Some(3));
+            let got = query_range(&svc, from_block, to_block, filter.clone(), None).await;
+            let want = naive_query(&blocks, from_block, to_block, &filter, None);
+            assert!(!want.is_empty());
             assert_eq!(got, want);
         }
+
+        let topic1_only = query_range(
+            &svc,
+            1,
+            3,
+            LogFilter {
+                topic1: Some(Clause::One(Topic([20; 32]))),
+                ..Default::default()
+            },
+            None,
+        )
+        .await;
+        let topic0s = topic1_only
+            .iter()
+            .map(|log| log.topics[0].0[0])
+            .collect::<Vec<_>>();
+        assert_eq!(topic0s, vec![10, 11, 13]);
     });
 }
 
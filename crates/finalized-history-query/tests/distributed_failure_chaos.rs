@@ -9,15 +9,15 @@ use finalized_history_query::api::{
 };
 use finalized_history_query::config::Config;
 use finalized_history_query::error::Error;
-use finalized_history_query::logs::types::Log;
+use finalized_history_query::logs::types::{Address, Log, Topic};
 use finalized_history_query::store::minio::MinioBlobStore;
 use finalized_history_query::store::scylla::ScyllaMetaStore;
 use finalized_history_query::{EvmBlockHeader, FinalizedBlock};
 
 fn mk_log(address: u8, topic0: u8, topic1: u8, block_num: u64, tx_idx: u32, log_idx: u32) -> Log {
     Log {
-        address: [address; 20],
-        topics: vec![[topic0; 32], [topic1; 32]],
+        address: Address([address; 20]),
+        topics: vec![Topic([topic0; 32]), Topic([topic1; 32])],
         data: vec![address, topic0, topic1],
         block_num,
         tx_idx,
@@ -108,6 +108,8 @@ async fn minio_outage_surfaces_backend_failures_without_latching_service_state()
                 to_block: Some(1),
                 from_block_hash: None,
                 to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
                 order: QueryOrder::Ascending,
                 resume_id: None,
                 limit: usize::MAX,
@@ -132,6 +134,8 @@ async fn minio_outage_surfaces_backend_failures_without_latching_service_state()
                 to_block: Some(2),
                 from_block_hash: None,
                 to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
                 order: QueryOrder::Ascending,
                 resume_id: None,
                 limit: usize::MAX,
@@ -119,3 +119,45 @@ async fn lwt_if_version_has_single_winner() {
     let v = String::from_utf8(rec.value.to_vec()).expect("utf8");
     assert!(v == "writer1" || v == "writer2");
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn multi_get_matches_individual_gets_across_buckets() {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    let keyspace = format!("fhq_multi_get_{stamp:x}");
+
+    let store = ScyllaMetaStore::new(&["127.0.0.1:9042".to_string()], &keyspace)
+        .await
+        .expect("connect scylla");
+    let keys = (0u8..16)
+        .map(|index| format!("multi_get_{index}").into_bytes())
+        .collect::<Vec<_>>();
+    for key in keys.iter().step_by(2) {
+        store
+            .put(
+                BLOCK_RECORD_TABLE,
+                key,
+                bytes::Bytes::copy_from_slice(key),
+                PutCond::Any,
+            )
+            .await
+            .expect("seed put");
+    }
+
+    let mut requested = keys.clone();
+    requested.reverse();
+    let batched = store
+        .multi_get(BLOCK_RECORD_TABLE, &requested)
+        .await
+        .expect("multi_get");
+    assert_eq!(batched.len(), requested.len());
+    for (key, record) in requested.iter().zip(batched) {
+        let single = store.get(BLOCK_RECORD_TABLE, key).await.expect("get");
+        assert_eq!(
+            record.map(|record| record.value),
+            single.map(|record| record.value)
+        );
+    }
+}
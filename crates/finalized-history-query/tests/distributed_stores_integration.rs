@@ -6,15 +6,17 @@ use finalized_history_query::api::{
     ExecutionBudget, FinalizedHistoryService, QueryLogsRequest, QueryOrder,
 };
 use finalized_history_query::config::Config;
-use finalized_history_query::logs::types::Log;
-use finalized_history_query::store::minio::MinioBlobStore;
+use finalized_history_query::kernel::sharded_streams::StreamKind;
+use finalized_history_query::logs::types::{Address, Log, Topic};
+use finalized_history_query::store::meta::InMemoryMetaStore;
+use finalized_history_query::store::minio::{MinioBlobStore, ObjectKeyLayout};
 use finalized_history_query::store::scylla::ScyllaMetaStore;
 use finalized_history_query::{Clause, EvmBlockHeader, FinalizedBlock, LogFilter};
 
 fn mk_log(address: u8, topic0: u8, topic1: u8, block_num: u64, tx_idx: u32, log_idx: u32) -> Log {
     Log {
-        address: [address; 20],
-        topics: vec![[topic0; 32], [topic1; 32]],
+        address: Address([address; 20]),
+        topics: vec![Topic([topic0; 32]), Topic([topic1; 32])],
         data: vec![address, topic0, topic1],
         block_num,
         tx_idx,
@@ -84,8 +86,8 @@ async fn scylla_minio_roundtrip_query() {
     svc.ingest_finalized_block(b2).await.expect("ingest b2");
 
     let filter = LogFilter {
-        address: Some(Clause::One([1; 20])),
-        topic0: Some(Clause::One([10; 32])),
+        address: Some(Clause::One(Address([1; 20]))),
+        topic0: Some(Clause::One(Topic([10; 32]))),
         topic1: None,
         topic2: None,
         topic3: None,
@@ -98,6 +100,8 @@ async fn scylla_minio_roundtrip_query() {
                 to_block: Some(2),
                 from_block_hash: None,
                 to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
                 order: QueryOrder::Ascending,
                 resume_id: None,
                 limit: 100,
@@ -112,3 +116,161 @@ async fn scylla_minio_roundtrip_query() {
     assert_eq!(got.items[0].block_num(), 1);
     assert_eq!(got.items[1].block_num(), 2);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn minio_multipart_upload_roundtrips_large_blob() {
+    use finalized_history_query::store::traits::{BlobStore, BlobTableId};
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    let blob = MinioBlobStore::new(
+        "http://127.0.0.1:9000",
+        "us-east-1",
+        "minioadmin",
+        "minioadmin",
+        "finalized-history-query-it",
+        &format!("multipart-{stamp}"),
+    )
+    .await
+    .expect("connect minio");
+
+    let table = BlobTableId::new("multipart_it");
+    let payload = (0..20 * 1024 * 1024)
+        .map(|i: usize| (i.wrapping_mul(31) % 251) as u8)
+        .collect::<Vec<_>>();
+    blob.put_blob(table, b"large", bytes::Bytes::from(payload.clone()))
+        .await
+        .expect("multipart put");
+
+    let got = blob
+        .get_blob(table, b"large")
+        .await
+        .expect("get")
+        .expect("blob present");
+    assert_eq!(got.len(), payload.len());
+    assert!(got.as_ref() == payload.as_slice());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn minio_hash_partitioned_keys_roundtrip_and_list() {
+    use finalized_history_query::store::traits::{BlobStore, BlobTableId};
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    let blob = MinioBlobStore::new(
+        "http://127.0.0.1:9000",
+        "us-east-1",
+        "minioadmin",
+        "minioadmin",
+        "finalized-history-query-it",
+        &format!("partitioned-{stamp}"),
+    )
+    .await
+    .expect("connect minio")
+    .with_key_layout(ObjectKeyLayout::HashPartitioned { partitions: 4 });
+
+    let table = BlobTableId::new("partitioned_it");
+    for block_num in 0u64..16 {
+        blob.put_blob(
+            table,
+            &block_num.to_be_bytes(),
+            bytes::Bytes::from(block_num.to_le_bytes().to_vec()),
+        )
+        .await
+        .expect("put");
+    }
+
+    let got = blob
+        .get_blob(table, &7u64.to_be_bytes())
+        .await
+        .expect("get")
+        .expect("blob present");
+    assert_eq!(got.as_ref(), 7u64.to_le_bytes());
+
+    let mut listed = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = blob
+            .list_prefix(table, &[], cursor.take(), 5)
+            .await
+            .expect("list");
+        listed.extend(page.keys);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    let expected = (0u64..16)
+        .map(|block_num| block_num.to_be_bytes().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(listed, expected);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn minio_hash_partitioned_layout_lists_indexed_keys_in_order() {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    let blob = MinioBlobStore::new(
+        "http://127.0.0.1:9000",
+        "us-east-1",
+        "minioadmin",
+        "minioadmin",
+        "finalized-history-query-it",
+        &format!("partitioned-keys-{stamp}"),
+    )
+    .await
+    .expect("connect minio")
+    .with_key_layout(ObjectKeyLayout::HashPartitioned { partitions: 4 });
+    let svc = FinalizedHistoryService::new_reader_writer(
+        Config {
+            observe_upstream_finalized_block: Arc::new(|| Some(u64::MAX / 4)),
+            ..Config::default()
+        },
+        InMemoryMetaStore::default(),
+        blob,
+        1,
+    );
+
+    // 4_100 logs seal the first page of every address stream, so the
+    // addresses are listed from page blob keys spread over the partitions.
+    // Address 200 only appears in the open second page.
+    let first_block = (0..4_100u32)
+        .map(|log_idx| mk_log((log_idx % 40) as u8, 10, 20, 1, 0, log_idx))
+        .collect();
+    let b1 = mk_block(1, [0; 32], first_block);
+    let b2 = mk_block(2, b1.block_hash, vec![mk_log(200, 11, 20, 2, 0, 0)]);
+    svc.ingest_finalized_block(b1).await.expect("ingest b1");
+    svc.ingest_finalized_block(b2).await.expect("ingest b2");
+
+    let expected = (0..40u8)
+        .chain([200])
+        .map(|address| vec![address; 20])
+        .collect::<Vec<_>>();
+    let (all, cursor) = svc
+        .list_indexed_keys(StreamKind::Addr, None, 100)
+        .await
+        .expect("list addresses");
+    assert_eq!(all, expected);
+    assert!(cursor.is_none());
+
+    let mut paged = Vec::new();
+    let mut cursor = None;
+    loop {
+        let (values, next) = svc
+            .list_indexed_keys(StreamKind::Addr, cursor, 7)
+            .await
+            .expect("list page");
+        paged.extend(values);
+        match next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    assert_eq!(paged, expected);
+}
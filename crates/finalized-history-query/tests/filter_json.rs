@@ -0,0 +1,113 @@
+use finalized_history_query::api::QueryLogsRequest;
+use finalized_history_query::{Address, Clause, LogFilter, QueryOrder, Topic};
+use serde_json::{Value, json};
+
+fn hex(byte: u8, len: usize) -> String {
+    format!("0x{}", format!("{byte:02x}").repeat(len))
+}
+
+fn assert_roundtrip<T>(value: &T, expected: Value)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let encoded = serde_json::to_value(value).expect("serialize");
+    assert_eq!(encoded, expected);
+    let decoded: T = serde_json::from_value(encoded).expect("deserialize");
+    assert_eq!(&decoded, value);
+}
+
+#[test]
+fn clause_variants_roundtrip_as_tagged_objects() {
+    assert_roundtrip(&Clause::<Topic>::Any, json!({ "any": true }));
+    assert_roundtrip(
+        &Clause::One(Topic([0xab; 32])),
+        json!({ "one": hex(0xab, 32) }),
+    );
+    assert_roundtrip(
+        &Clause::Or(vec![Address([1; 20]), Address([2; 20])]),
+        json!({ "or": [hex(1, 20), hex(2, 20)] }),
+    );
+    assert_roundtrip(
+        &Clause::Not(Box::new(Clause::One(Address([3; 20])))),
+        json!({ "not": { "one": hex(3, 20) } }),
+    );
+    assert_roundtrip(
+        &Clause::Range {
+            lo: Topic([4; 32]),
+            hi: Topic([5; 32]),
+        },
+        json!({ "range": { "lo": hex(4, 32), "hi": hex(5, 32) } }),
+    );
+}
+
+#[test]
+fn full_log_filter_roundtrips() {
+    let filter = LogFilter {
+        address: Some(Clause::Or(vec![Address([1; 20]), Address([2; 20])])),
+        topic0: Some(Clause::One(Topic([10; 32]))),
+        topic1: Some(Clause::Any),
+        topic2: Some(Clause::Not(Box::new(Clause::One(Topic([12; 32]))))),
+        topic3: Some(Clause::Or(Vec::new())),
+    };
+    assert_roundtrip(
+        &filter,
+        json!({
+            "address": { "or": [hex(1, 20), hex(2, 20)] },
+            "topic0": { "one": hex(10, 32) },
+            "topic1": { "any": true },
+            "topic2": { "not": { "one": hex(12, 32) } },
+            "topic3": { "or": [] },
+        }),
+    );
+    assert_roundtrip(&LogFilter::default(), json!({}));
+}
+
+#[test]
+fn query_request_roundtrips_with_hex_block_hashes() {
+    let request = QueryLogsRequest {
+        from_block: None,
+        to_block: None,
+        from_block_hash: Some([7; 32]),
+        to_block_hash: Some([7; 32]),
+        from_timestamp: None,
+        to_timestamp: None,
+        order: QueryOrder::Descending,
+        resume_id: Some(42),
+        limit: 10,
+        filter: LogFilter {
+            topic0: Some(Clause::One(Topic([10; 32]))),
+            ..Default::default()
+        },
+    };
+    let encoded = serde_json::to_value(&request).expect("serialize");
+    assert_eq!(encoded["from_block_hash"], json!(hex(7, 32)));
+    assert_eq!(encoded["order"], json!("descending"));
+    let decoded: QueryLogsRequest = serde_json::from_value(encoded).expect("deserialize");
+    assert_eq!(decoded, request);
+
+    let minimal: QueryLogsRequest = serde_json::from_value(json!({
+        "from_block": 1,
+        "to_block": 5,
+        "order": "ascending",
+        "limit": 3,
+        "filter": {},
+    }))
+    .expect("deserialize with omitted options");
+    assert_eq!(minimal.from_block_hash, None);
+    assert_eq!(minimal.resume_id, None);
+}
+
+#[test]
+fn deserialize_enforces_address_and_topic_widths() {
+    let address_as_topic = json!({ "address": { "one": hex(1, 32) } });
+    assert!(serde_json::from_value::<LogFilter>(address_as_topic).is_err());
+
+    let topic_as_address = json!({ "topic0": { "one": hex(1, 20) } });
+    assert!(serde_json::from_value::<LogFilter>(topic_as_address).is_err());
+
+    let unprefixed = json!({ "topic0": { "one": hex(1, 32).trim_start_matches("0x") } });
+    assert!(serde_json::from_value::<LogFilter>(unprefixed).is_err());
+
+    assert!(serde_json::from_value::<Clause<Topic>>(json!({ "any": false })).is_err());
+    assert!(serde_json::from_value::<LogFilter>(json!({ "topic4": { "any": true } })).is_err());
+}
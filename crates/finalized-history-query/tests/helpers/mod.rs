@@ -1,5 +1,6 @@
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
 
 use alloy_rlp::Encodable;
 use bytes::Bytes;
@@ -11,7 +12,7 @@ use finalized_history_query::config::Config;
 use finalized_history_query::ingest::authority::LeaseAuthority;
 use finalized_history_query::kernel::codec::StorageCodec;
 use finalized_history_query::logs::table_specs::{BlobTableSpec, BlockLogBlobSpec};
-use finalized_history_query::logs::types::Log;
+use finalized_history_query::logs::types::{Address, Log, Topic};
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
 use finalized_history_query::store::publication::PUBLICATION_STATE_TABLE;
@@ -37,8 +38,8 @@ pub fn mk_log(
     log_idx: u32,
 ) -> Log {
     Log {
-        address: [address; 20],
-        topics: vec![[topic0; 32], [topic1; 32]],
+        address: Address([address; 20]),
+        topics: vec![Topic([topic0; 32]), Topic([topic1; 32])],
         data: vec![address, topic0, topic1],
         block_num,
         tx_idx,
@@ -103,7 +104,7 @@ pub fn mk_ingest_tx(
 
 pub fn indexed_address_filter(address: u8) -> LogFilter {
     LogFilter {
-        address: Some(Clause::One([address; 20])),
+        address: Some(Clause::One(Address([address; 20]))),
         topic0: None,
         topic1: None,
         topic2: None,
@@ -168,12 +169,17 @@ where
             to_block: Some(to_block),
             from_block_hash: None,
             to_block_hash: None,
+            from_timestamp: None,
+            to_timestamp: None,
             order: QueryOrder::Ascending,
             resume_id,
             limit,
             filter,
         },
-        ExecutionBudget { max_results: None },
+        ExecutionBudget {
+            max_results: None,
+            max_result_bytes: None,
+        },
     )
     .await
 }
@@ -197,12 +203,17 @@ where
             to_block: Some(to_block),
             from_block_hash: None,
             to_block_hash: None,
+            from_timestamp: None,
+            to_timestamp: None,
             order: QueryOrder::Ascending,
             resume_id,
             limit,
             filter,
         },
-        ExecutionBudget { max_results: None },
+        ExecutionBudget {
+            max_results: None,
+            max_result_bytes: None,
+        },
     )
     .await
 }
@@ -226,12 +237,17 @@ where
             to_block: Some(to_block),
             from_block_hash: None,
             to_block_hash: None,
+            from_timestamp: None,
+            to_timestamp: None,
             order: QueryOrder::Ascending,
             resume_id,
             limit,
             filter,
         },
-        ExecutionBudget { max_results: None },
+        ExecutionBudget {
+            max_results: None,
+            max_result_bytes: None,
+        },
     )
     .await
 }
@@ -256,7 +272,10 @@ where
             order: QueryOrder::Ascending,
             limit,
         },
-        ExecutionBudget { max_results: None },
+        ExecutionBudget {
+            max_results: None,
+            max_result_bytes: None,
+        },
     )
     .await
 }
@@ -716,3 +735,201 @@ impl BlobStore for CountingBlobStore {
         self.inner.list_prefix(table, prefix, cursor, limit).await
     }
 }
+
+/// Simulates a slow backend: every meta and blob operation sleeps `base`
+/// plus a pseudo-random share of `jitter` before delegating to `inner`. The
+/// jitter is derived from the operation count, so a run is reproducible. The
+/// sleep blocks the calling thread, which suits the `block_on` tests but
+/// would stall the workers of a shared runtime.
+#[derive(Clone)]
+pub struct LatencyStore<S> {
+    pub inner: S,
+    pub base: Duration,
+    pub jitter: Duration,
+    pub ops: Arc<AtomicU64>,
+}
+
+impl<S> LatencyStore<S> {
+    pub fn new(inner: S, base: Duration, jitter: Duration) -> Self {
+        Self {
+            inner,
+            base,
+            jitter,
+            ops: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn delay(&self) {
+        let op = self.ops.fetch_add(1, Ordering::Relaxed);
+        let jitter_nanos = u64::try_from(self.jitter.as_nanos()).unwrap_or(u64::MAX);
+        let extra_nanos = match jitter_nanos {
+            0 => 0,
+            _ => splitmix64(op) % jitter_nanos.saturating_add(1),
+        };
+        std::thread::sleep(self.base + Duration::from_nanos(extra_nanos));
+    }
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl<S: MetaStore> MetaStore for LatencyStore<S> {
+    async fn get(
+        &self,
+        family: TableId,
+        key: &[u8],
+    ) -> finalized_history_query::Result<Option<Record>> {
+        self.delay();
+        self.inner.get(family, key).await
+    }
+
+    async fn multi_get(
+        &self,
+        family: TableId,
+        keys: &[Vec<u8>],
+    ) -> finalized_history_query::Result<Vec<Option<Record>>> {
+        self.delay();
+        self.inner.multi_get(family, keys).await
+    }
+
+    async fn put(
+        &self,
+        family: TableId,
+        key: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> finalized_history_query::Result<PutResult> {
+        self.delay();
+        self.inner.put(family, key, value, cond).await
+    }
+
+    async fn delete(
+        &self,
+        family: TableId,
+        key: &[u8],
+        cond: DelCond,
+    ) -> finalized_history_query::Result<()> {
+        self.delay();
+        self.inner.delete(family, key, cond).await
+    }
+
+    async fn scan_get(
+        &self,
+        family: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+    ) -> finalized_history_query::Result<Option<Record>> {
+        self.delay();
+        self.inner.scan_get(family, partition, clustering).await
+    }
+
+    async fn scan_put(
+        &self,
+        family: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> finalized_history_query::Result<PutResult> {
+        self.delay();
+        self.inner
+            .scan_put(family, partition, clustering, value, cond)
+            .await
+    }
+
+    async fn scan_delete(
+        &self,
+        family: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        cond: DelCond,
+    ) -> finalized_history_query::Result<()> {
+        self.delay();
+        self.inner
+            .scan_delete(family, partition, clustering, cond)
+            .await
+    }
+
+    async fn scan_list(
+        &self,
+        family: ScannableTableId,
+        partition: &[u8],
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> finalized_history_query::Result<Page> {
+        self.delay();
+        self.inner
+            .scan_list(family, partition, prefix, cursor, limit)
+            .await
+    }
+}
+
+impl<S: BlobStore> BlobStore for LatencyStore<S> {
+    async fn put_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: Bytes,
+    ) -> finalized_history_query::Result<()> {
+        self.delay();
+        self.inner.put_blob(table, key, value).await
+    }
+
+    async fn put_blob_with_ttl(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        value: Bytes,
+        ttl: Duration,
+    ) -> finalized_history_query::Result<()> {
+        self.delay();
+        self.inner.put_blob_with_ttl(table, key, value, ttl).await
+    }
+
+    async fn get_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+    ) -> finalized_history_query::Result<Option<Bytes>> {
+        self.delay();
+        self.inner.get_blob(table, key).await
+    }
+
+    async fn read_range(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+        start: u64,
+        end_exclusive: u64,
+    ) -> finalized_history_query::Result<Option<Bytes>> {
+        self.delay();
+        self.inner
+            .read_range(table, key, start, end_exclusive)
+            .await
+    }
+
+    async fn delete_blob(
+        &self,
+        table: BlobTableId,
+        key: &[u8],
+    ) -> finalized_history_query::Result<()> {
+        self.delay();
+        self.inner.delete_blob(table, key).await
+    }
+
+    async fn list_prefix(
+        &self,
+        table: BlobTableId,
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> finalized_history_query::Result<Page> {
+        self.delay();
+        self.inner.list_prefix(table, prefix, cursor, limit).await
+    }
+}
@@ -1,19 +1,27 @@
 #[allow(dead_code, unused_imports)]
 mod helpers;
 
-use finalized_history_query::Error;
-use finalized_history_query::api::FinalizedHistoryService;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use finalized_history_query::api::{ExecutionBudget, FinalizedHistoryService, QueryLogsRequest};
+use finalized_history_query::config::Config;
 use finalized_history_query::core::state::{BLOCK_RECORD_TABLE, BlockRecord, BlockRecordSpec};
 use finalized_history_query::kernel::codec::StorageCodec;
+use finalized_history_query::kernel::sharded_streams::{StreamKind, sharded_stream_id};
 use finalized_history_query::kernel::table_specs::PointTableSpec;
+use finalized_history_query::kernel::table_specs::ScannableTableSpec;
 use finalized_history_query::logs::table_specs::{
-    BlobTableSpec, BlockLogBlobSpec, BlockLogHeaderSpec,
+    BlobTableSpec, BlockLogBlobSpec, BlockLogHeaderSpec, LogBitmapByBlockSpec,
 };
-use finalized_history_query::logs::types::BlockLogHeader;
+use finalized_history_query::logs::types::{BlockLogHeader, Topic};
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
 use finalized_history_query::store::publication::PublicationStore;
 use finalized_history_query::store::traits::{BlobStore, MetaStore};
+use finalized_history_query::{
+    BlockHasher, Clause, Error, EvmBlockHeader, FinalizedBlock, LogFilter,
+};
 use futures::executor::block_on;
 
 use helpers::*;
@@ -92,6 +100,70 @@ fn ingest_rejects_block_gap() {
     });
 }
 
+#[test]
+fn ingest_reports_missing_range_and_ingests_contiguous_prefix() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let arriving = vec![
+            mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]),
+            mk_block(2, [1; 32], vec![mk_log(1, 10, 20, 2, 0, 0)]),
+            mk_block(4, [3; 32], vec![mk_log(1, 10, 20, 4, 0, 0)]),
+        ];
+        assert_eq!(svc.missing_range().await.expect("missing range"), None);
+
+        let prefix = svc
+            .ingestable_prefix(&arriving)
+            .await
+            .expect("ingestable prefix")
+            .to_vec();
+        assert_eq!(
+            prefix
+                .iter()
+                .map(|block| block.block_num)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        svc.ingest_finalized_blocks(prefix)
+            .await
+            .expect("ingest prefix");
+
+        let rest = &arriving[2..];
+        assert!(
+            svc.ingestable_prefix(rest)
+                .await
+                .expect("prefix")
+                .is_empty()
+        );
+        let err = svc
+            .ingest_finalized_blocks(rest.to_vec())
+            .await
+            .expect_err("block gap");
+        assert!(matches!(
+            err,
+            Error::InvalidSequence {
+                expected: 3,
+                got: 4
+            }
+        ));
+
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 2);
+        assert_eq!(
+            svc.missing_range().await.expect("missing range"),
+            Some((3, 3))
+        );
+
+        svc.ingest_finalized_block(mk_block(3, [2; 32], vec![mk_log(1, 10, 20, 3, 0, 0)]))
+            .await
+            .expect("ingest block 3");
+        assert_eq!(svc.missing_range().await.expect("missing range"), None);
+    });
+}
+
 #[test]
 fn ingest_rejects_parent_hash_mismatch() {
     block_on(async {
@@ -115,7 +187,7 @@ fn ingest_rejects_parent_hash_mismatch() {
             ))
             .await
             .expect_err("parent hash mismatch");
-        assert!(matches!(err, Error::InvalidParent));
+        assert!(matches!(err, Error::InvalidParent { index: 0 }));
     });
 }
 
@@ -133,12 +205,261 @@ fn ingest_rejects_parent_hash_mismatch_within_batch() {
         let mut block2 = mk_block(2, [1; 32], vec![mk_log(1, 10, 21, 2, 0, 0)]);
         block2.parent_hash = [99; 32]; // wrong parent
         block2.header.parent_hash = [99; 32];
+        let block3 = mk_block(3, [2; 32], vec![mk_log(1, 10, 22, 3, 0, 0)]);
 
         let err = svc
-            .ingest_finalized_blocks(vec![block1, block2])
+            .ingest_finalized_blocks(vec![block1, block2, block3])
             .await
             .expect_err("intra-batch parent mismatch");
-        assert!(matches!(err, Error::InvalidParent));
+        assert!(matches!(err, Error::InvalidParent { index: 1 }));
+
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 0);
+        for block_num in 1..=3 {
+            assert!(
+                svc.meta_store()
+                    .get(BLOCK_RECORD_TABLE, &BlockRecordSpec::key(block_num))
+                    .await
+                    .expect("load block record")
+                    .is_none(),
+                "block {block_num} record written"
+            );
+            assert!(
+                svc.blob_store()
+                    .get_blob(BlockLogBlobSpec::TABLE, &BlockLogBlobSpec::key(block_num))
+                    .await
+                    .expect("load block log blob")
+                    .is_none(),
+                "block {block_num} log blob written"
+            );
+        }
+    });
+}
+
+#[test]
+fn ingest_rejects_batch_with_five_topic_log_before_writing() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        let block1 = mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]);
+        let mut block2 = mk_block(2, [1; 32], vec![mk_log(1, 10, 21, 2, 0, 0)]);
+        block2.logs[0].topics = (1..=5).map(|topic| Topic([topic; 32])).collect();
+
+        let err = svc
+            .ingest_finalized_blocks(vec![block1, block2])
+            .await
+            .expect_err("five-topic log");
+        assert!(matches!(err, Error::InvalidParams("log topics exceed 4")));
+
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 0);
+        for block_num in 1..=2 {
+            assert!(
+                svc.blob_store()
+                    .get_blob(BlockLogBlobSpec::TABLE, &BlockLogBlobSpec::key(block_num))
+                    .await
+                    .expect("load block log blob")
+                    .is_none(),
+                "block {block_num} log blob written"
+            );
+        }
+    });
+}
+
+#[test]
+fn ingest_publishes_a_parent_linked_three_block_chain() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        let outcome = svc
+            .ingest_finalized_blocks(vec![
+                mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]),
+                mk_block(2, [1; 32], vec![mk_log(1, 10, 21, 2, 0, 0)]),
+                mk_block(3, [2; 32], vec![mk_log(1, 10, 22, 3, 0, 0)]),
+            ])
+            .await
+            .expect("ingest chain");
+
+        assert_eq!(outcome.indexed_finalized_head, 3);
+        for block_num in 1..=3u64 {
+            let record = svc
+                .get_block_record(block_num)
+                .await
+                .expect("load block record")
+                .expect("block record");
+            assert_eq!(record.parent_hash, [(block_num - 1) as u8; 32]);
+        }
+    });
+}
+
+struct XorHasher;
+
+impl BlockHasher for XorHasher {
+    fn hash_header(&self, header: &EvmBlockHeader) -> [u8; 32] {
+        let mut hash = header.parent_hash;
+        for (byte, field) in hash.iter_mut().zip(header.number.to_be_bytes()) {
+            *byte ^= field;
+        }
+        for (byte, field) in hash[8..].iter_mut().zip(header.timestamp.to_be_bytes()) {
+            *byte ^= field;
+        }
+        hash
+    }
+}
+
+fn mk_hashed_block(block_num: u64, parent_hash: [u8; 32], timestamp: u64) -> FinalizedBlock {
+    let logs = vec![mk_log(1, 10, 20, block_num, 0, 0)];
+    let mut block = mk_block(block_num, parent_hash, logs);
+    block.header.timestamp = timestamp;
+    block.block_hash = XorHasher.hash_header(&block.header);
+    block.header.hash = block.block_hash;
+    block
+}
+
+#[test]
+fn block_hasher_rejects_block_whose_hash_does_not_match_contents() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                block_hasher: Some(Arc::new(XorHasher)),
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        let block1 = mk_hashed_block(1, [0; 32], 100);
+        let parent = block1.block_hash;
+        svc.ingest_finalized_block(block1)
+            .await
+            .expect("ingest hashed block");
+
+        let mut tampered = mk_hashed_block(2, parent, 101);
+        tampered.header.timestamp = 102;
+        let err = svc
+            .ingest_finalized_block(tampered)
+            .await
+            .expect_err("tampered block");
+        assert!(matches!(
+            err,
+            Error::InvalidParams("block_hash does not match the block header contents")
+        ));
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 1);
+
+        svc.ingest_finalized_block(mk_hashed_block(2, parent, 102))
+            .await
+            .expect("ingest honest block");
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 2);
+    });
+}
+
+/// Counts calls before delegating to [`XorHasher`].
+#[derive(Default)]
+struct CountingHasher {
+    calls: AtomicU64,
+}
+
+impl BlockHasher for CountingHasher {
+    fn hash_header(&self, header: &EvmBlockHeader) -> [u8; 32] {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        XorHasher.hash_header(header)
+    }
+}
+
+#[test]
+fn block_limits_reject_oversized_blocks_before_hashing_them() {
+    block_on(async {
+        let hasher = Arc::new(CountingHasher::default());
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                block_hasher: Some(hasher.clone()),
+                max_logs_per_block: 1,
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        let mut oversized = mk_hashed_block(1, [0; 32], 100);
+        oversized.logs.push(mk_log(1, 10, 20, 1, 0, 1));
+        let err = svc
+            .ingest_finalized_block(oversized)
+            .await
+            .expect_err("oversized block");
+        assert!(matches!(
+            err,
+            Error::InvalidParams("block exceeds max_logs_per_block")
+        ));
+        assert_eq!(hasher.calls.load(Ordering::Relaxed), 0);
+
+        svc.ingest_finalized_block(mk_hashed_block(1, [0; 32], 100))
+            .await
+            .expect("ingest block within limits");
+        assert_eq!(hasher.calls.load(Ordering::Relaxed), 1);
+    });
+}
+
+#[test]
+fn block_limits_reject_blocks_over_the_configured_size() {
+    block_on(async {
+        let logs = |count: u32| {
+            (0..count)
+                .map(|log_idx| mk_log(1, 10, 20, 1, 0, log_idx))
+                .collect::<Vec<_>>()
+        };
+
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                max_logs_per_block: 3,
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let err = svc
+            .ingest_finalized_block(mk_block(1, [0; 32], logs(4)))
+            .await
+            .expect_err("block over the log limit");
+        assert!(matches!(
+            err,
+            Error::InvalidParams("block exceeds max_logs_per_block")
+        ));
+        svc.ingest_finalized_block(mk_block(1, [0; 32], logs(3)))
+            .await
+            .expect("block at the log limit");
+
+        let at_limit = mk_block(1, [0; 32], logs(2));
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                max_block_bytes: at_limit.estimated_bytes(),
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let mut over_limit = at_limit.clone();
+        over_limit.logs[0].data.push(0);
+        let err = svc
+            .ingest_finalized_block(over_limit)
+            .await
+            .expect_err("block over the byte limit");
+        assert!(matches!(err, Error::InvalidParams("block exceeds max_block_bytes")));
+        svc.ingest_finalized_block(at_limit)
+            .await
+            .expect("block at the byte limit");
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 1);
     });
 }
 
@@ -244,3 +565,216 @@ fn ingest_block_with_zero_logs_followed_by_block_with_logs() {
         assert_eq!(page.items[0].block_num(), 2);
     });
 }
+
+#[test]
+fn run_of_empty_blocks_writes_no_stream_fragments() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        svc.ingest_finalized_block(mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]))
+            .await
+            .expect("ingest block 1");
+        for block_num in 2..=21u64 {
+            let outcome = svc
+                .ingest_finalized_block(mk_block(block_num, [block_num as u8 - 1; 32], vec![]))
+                .await
+                .expect("ingest empty block");
+            assert_eq!(outcome.indexed_finalized_head, block_num);
+        }
+        svc.ingest_finalized_block(mk_block(22, [21; 32], vec![mk_log(1, 10, 20, 22, 0, 0)]))
+            .await
+            .expect("ingest block 22");
+
+        let sid = sharded_stream_id(StreamKind::Addr, &[1; 20], 0);
+        let fragments = meta
+            .scan_list(
+                LogBitmapByBlockSpec::TABLE,
+                &LogBitmapByBlockSpec::partition(&sid, 0),
+                b"",
+                None,
+                1_024,
+            )
+            .await
+            .expect("list fragments");
+        assert_eq!(
+            fragments.keys,
+            vec![
+                LogBitmapByBlockSpec::clustering(1),
+                LogBitmapByBlockSpec::clustering(22),
+            ]
+        );
+
+        let page = query_page(&svc, 1, 22, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query across empty run");
+        assert_eq!(
+            page.items
+                .iter()
+                .map(|log| log.block_num())
+                .collect::<Vec<_>>(),
+            vec![1, 22]
+        );
+        let page = query_page(&svc, 2, 21, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query inside empty run");
+        assert!(page.items.is_empty());
+    });
+}
+
+#[test]
+fn concurrent_stream_appends_land_every_stream_entry() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                stream_append_concurrency: 8,
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let logs = (0..50u8)
+            .map(|i| mk_log(i + 1, 10, 20, 1, 0, u32::from(i)))
+            .collect();
+
+        svc.ingest_finalized_block(mk_block(1, [0; 32], logs))
+            .await
+            .expect("ingest block with 50 address streams");
+
+        for i in 0..50u8 {
+            let page = query_page(&svc, 1, 1, indexed_address_filter(i + 1), 10, None)
+                .await
+                .expect("query address stream");
+            assert_eq!(page.items.len(), 1, "address {}", i + 1);
+            assert_eq!(page.items[0].log_idx(), u32::from(i));
+        }
+    });
+}
+
+#[test]
+fn run_ingest_loop_ingests_a_block_stream_in_order() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let blocks = (1..=10u64).map(|block_num| {
+            Ok(mk_block(
+                block_num,
+                [block_num as u8 - 1; 32],
+                vec![mk_log(1, 10, 20, block_num, 0, 0)],
+            ))
+        });
+
+        let head = svc
+            .run_ingest_loop(futures::stream::iter(blocks))
+            .await
+            .expect("ingest stream");
+
+        assert_eq!(head, 10);
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 10);
+        let page = query_page(&svc, 1, 10, indexed_address_filter(1), 20, None)
+            .await
+            .expect("query streamed blocks");
+        assert_eq!(page.items.len(), 10);
+    });
+}
+
+#[test]
+fn run_ingest_loop_stops_at_the_first_stream_error() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let blocks = vec![
+            Ok(mk_block(1, [0; 32], Vec::new())),
+            Ok(mk_block(2, [1; 32], Vec::new())),
+            Err(Error::Backend("source disconnected".to_string())),
+            Ok(mk_block(3, [2; 32], Vec::new())),
+        ];
+
+        let err = svc
+            .run_ingest_loop(futures::stream::iter(blocks))
+            .await
+            .expect_err("stream error");
+
+        assert!(matches!(err, Error::Backend(message) if message == "source disconnected"));
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 2);
+    });
+}
+
+#[test]
+fn log_rewriter_applies_to_stored_bodies_and_index_streams() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                log_rewriter: Some(Arc::new(|log| log.topics[1] = Topic([0; 32]))),
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 11, 21, 1, 0, 1)],
+        ))
+        .await
+        .expect("ingest");
+
+        let query_topic1 = |topic1: Topic| {
+            let request = QueryLogsRequest::builder()
+                .from_block(1)
+                .to_block(1)
+                .filter(LogFilter::builder().topic1_one(topic1).build())
+                .build()
+                .expect("request");
+            svc.query_logs(request, ExecutionBudget::default())
+        };
+
+        let rewritten = query_topic1(Topic([0; 32])).await.expect("rewritten query");
+        assert_eq!(rewritten.items.len(), 2);
+        assert!(
+            rewritten
+                .items
+                .iter()
+                .all(|log| log.topic(1) == &[0; 32] && log.topic_count() == 2)
+        );
+        assert_eq!(rewritten.items[1].address(), &[2; 20]);
+        assert!(
+            query_topic1(Topic([20; 32]))
+                .await
+                .expect("original query")
+                .items
+                .is_empty()
+        );
+        let topic0 = svc
+            .query_logs(
+                QueryLogsRequest::builder()
+                    .from_block(1)
+                    .to_block(1)
+                    .filter(LogFilter {
+                        topic0: Some(Clause::One(Topic([11; 32]))),
+                        ..Default::default()
+                    })
+                    .build()
+                    .expect("request"),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("untouched topic0 query");
+        assert_eq!(topic0.items.len(), 1);
+    });
+}
@@ -2,27 +2,39 @@
 mod helpers;
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use bytes::Bytes;
-use finalized_history_query::api::FinalizedHistoryService;
-use finalized_history_query::core::layout::{DIRECTORY_SUB_BUCKET_SIZE, MAX_LOCAL_ID};
+use finalized_history_query::api::{
+    ExecutionBudget, FinalizedHistoryService, QueryLogsRequest, QueryOrder,
+};
+use finalized_history_query::config::Config;
+use finalized_history_query::core::ids::LogId;
+use finalized_history_query::core::layout::{
+    DIRECTORY_SUB_BUCKET_SIZE, LOCAL_ID_BITS, MAX_LOCAL_ID,
+};
 use finalized_history_query::core::state::{
     BLOCK_RECORD_TABLE, BlockRecord, BlockRecordSpec, PrimaryWindowRecord,
 };
 use finalized_history_query::kernel::codec::StorageCodec;
-use finalized_history_query::kernel::sharded_streams::page_start_local;
+use finalized_history_query::kernel::sharded_streams::{
+    StreamKind, page_start_local, sharded_stream_id, stream_value_key,
+};
 use finalized_history_query::kernel::table_specs::{
     PointTableSpec, ScannableTableSpec, page_stream_key, stream_page_key, u64_key,
 };
 use finalized_history_query::logs::table_specs::{
     BlobTableSpec, LogBitmapByBlockSpec, LogBitmapPageBlobSpec, LogBitmapPageMetaSpec,
-    LogDirByBlockSpec, LogOpenBitmapPageSpec,
+    LogDirByBlockSpec, LogOpenBitmapPageSpec, LogStreamShardSpec,
 };
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
 use finalized_history_query::store::publication::{MetaPublicationStore, PublicationStore};
-use finalized_history_query::store::traits::{BlobStore, MetaStore, PutCond};
-use finalized_history_query::streams::{BitmapBlob, encode_bitmap_blob};
+use finalized_history_query::store::traits::{
+    BlobStore, DelCond, MetaStore, Page, PutCond, PutResult, Record, ScannableTableId, TableId,
+};
+use finalized_history_query::streams::{BitmapBlob, StreamBitmapMeta, encode_bitmap_blob};
+use finalized_history_query::{Clause, LogFilter, LogRef, QueryPage, Topic};
 use futures::executor::block_on;
 use roaring::RoaringBitmap;
 
@@ -100,6 +112,209 @@ fn ingest_and_query_across_24_bit_log_shard_boundary() {
     });
 }
 
+#[test]
+fn block_split_across_log_shards_returns_every_log() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let blob = InMemoryBlobStore::default();
+        let publication_store = MetaPublicationStore::new(Arc::new(meta.clone()));
+        publication_store
+            .create_if_absent(&seeded_publication_state_with_valid_through(
+                1, [1u8; 16], 1, 0,
+            ))
+            .await
+            .expect("seed publication state");
+        // Block 2's logs take the last two IDs of shard 0 and the first two
+        // of shard 1.
+        let first_log_id = u64::from(MAX_LOCAL_ID) - 1;
+        meta.put(
+            BLOCK_RECORD_TABLE,
+            &BlockRecordSpec::key(1),
+            shared_block_record([1; 32], [0; 32], Some((first_log_id, 0)), Some((0, 0))).encode(),
+            PutCond::Any,
+        )
+        .await
+        .expect("seed block meta");
+
+        let svc = FinalizedHistoryService::new_reader_writer(lease_writer_config(), meta, blob, 1);
+        let logs = (0..4u32)
+            .map(|log_idx| mk_log(7, 10, 20 + (log_idx % 2) as u8, 2, 0, log_idx))
+            .collect();
+        svc.ingest_finalized_block(mk_block(2, [1; 32], logs))
+            .await
+            .expect("ingest block 2");
+        svc.ingest_finalized_block(mk_block(3, [2; 32], vec![mk_log(7, 10, 20, 3, 0, 0)]))
+            .await
+            .expect("ingest block 3");
+
+        let request = |order, filter| {
+            QueryLogsRequest::builder()
+                .from_block(2)
+                .to_block(2)
+                .order(order)
+                .filter(filter)
+                .build()
+                .expect("request")
+        };
+        let log_idxs = |page: QueryPage<LogRef>| {
+            page.items
+                .iter()
+                .map(|log| log.log_idx())
+                .collect::<Vec<_>>()
+        };
+        let ascending = svc
+            .query_logs(
+                request(QueryOrder::Ascending, indexed_address_filter(7)),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("ascending query");
+        assert_eq!(log_idxs(ascending), vec![0, 1, 2, 3]);
+        let descending = svc
+            .query_logs(
+                request(QueryOrder::Descending, indexed_address_filter(7)),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("descending query");
+        assert_eq!(log_idxs(descending), vec![3, 2, 1, 0]);
+
+        // Topic1 21 matches one log on each side of the boundary.
+        let topic1_21 = LogFilter {
+            topic1: Some(Clause::One(Topic([21; 32]))),
+            ..indexed_address_filter(7)
+        };
+        let split_matches = svc
+            .query_logs(
+                request(QueryOrder::Ascending, topic1_21),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("cross-shard intersection");
+        assert_eq!(log_idxs(split_matches), vec![1, 3]);
+
+        let positions = svc
+            .query_log_positions(
+                request(QueryOrder::Ascending, indexed_address_filter(7)),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("positions");
+        let shard_1_start = 1u64 << LOCAL_ID_BITS;
+        assert_eq!(
+            positions
+                .items
+                .iter()
+                .map(|position| position.log_id)
+                .collect::<Vec<_>>(),
+            [first_log_id, first_log_id + 1, shard_1_start, shard_1_start + 1].map(LogId::new)
+        );
+        assert_eq!(
+            svc.count_logs_by_block(indexed_address_filter(7), 2, 3)
+                .await
+                .expect("count by block"),
+            vec![(2, 4), (3, 1)]
+        );
+
+        // Shard 0 has no IDs left, so its last page sealed with both logs.
+        let shard_0_stream = sharded_stream_id(StreamKind::Addr, &[7; 20], 0);
+        let last_page_start = page_start_local(MAX_LOCAL_ID, STREAM_PAGE_LOCAL_ID_SPAN);
+        let page_meta = svc
+            .meta_store()
+            .get(
+                LogBitmapPageMetaSpec::TABLE,
+                &LogBitmapPageMetaSpec::key(&shard_0_stream, last_page_start),
+            )
+            .await
+            .expect("stream page meta")
+            .expect("last shard-0 page sealed");
+        let page_meta = StreamBitmapMeta::decode(&page_meta.value).expect("decode page meta");
+        assert_eq!(page_meta.count, 2);
+    });
+}
+
+#[test]
+fn shard_presence_filter_skips_shards_without_the_queried_value() {
+    block_on(async {
+        // Block 2's first log is the last ID of shard 0 and its second log
+        // the first ID of shard 1. Address 7 only appears in shard 0.
+        let high_shard_stream = sharded_stream_id(StreamKind::Addr, &[7; 20], 1);
+        let mut probes = Vec::new();
+        for shard_presence_filter in [false, true] {
+            let meta = StreamProbeCountingStore {
+                stream_prefix: format!("{high_shard_stream}/").into_bytes(),
+                ..StreamProbeCountingStore::default()
+            };
+            let publication_store = MetaPublicationStore::new(Arc::new(meta.clone()));
+            publication_store
+                .create_if_absent(&seeded_publication_state_with_valid_through(
+                    1, [1u8; 16], 1, 0,
+                ))
+                .await
+                .expect("seed publication state");
+            meta.put(
+                BLOCK_RECORD_TABLE,
+                &BlockRecordSpec::key(1),
+                shared_block_record(
+                    [1; 32],
+                    [0; 32],
+                    Some((u64::from(MAX_LOCAL_ID), 0)),
+                    Some((0, 0)),
+                )
+                .encode(),
+                PutCond::Any,
+            )
+            .await
+            .expect("seed block meta");
+
+            let config = Config {
+                shard_presence_filter,
+                ..lease_writer_config()
+            };
+            let svc = FinalizedHistoryService::new_reader_writer(
+                config,
+                meta.clone(),
+                InMemoryBlobStore::default(),
+                1,
+            );
+            svc.ingest_finalized_block(mk_block(
+                2,
+                [1; 32],
+                vec![mk_log(7, 10, 20, 2, 0, 0), mk_log(8, 10, 21, 2, 0, 1)],
+            ))
+            .await
+            .expect("ingest block 2");
+            assert_eq!(
+                meta.conditional_presence_puts.load(Ordering::Relaxed),
+                0,
+                "presence rows are written without a conditional put"
+            );
+            let presence = meta
+                .inner
+                .scan_list(
+                    LogStreamShardSpec::TABLE,
+                    stream_value_key(StreamKind::Addr, &[7; 20]).as_bytes(),
+                    b"",
+                    None,
+                    16,
+                )
+                .await
+                .expect("list presence rows");
+            assert_eq!(presence.keys, vec![u64_key(0)]);
+
+            meta.probes.store(0, Ordering::Relaxed);
+            let page = query_page(&svc, 2, 2, indexed_address_filter(7), 10, None)
+                .await
+                .expect("query");
+            assert_eq!(page.items.len(), 1);
+            assert_eq!(page.items[0].address(), &[7; 20]);
+            probes.push(meta.probes.load(Ordering::Relaxed));
+        }
+        assert!(probes[0] > 0, "unfiltered query probes the high shard");
+        assert_eq!(probes[1], 0, "filtered query skips the high shard");
+    });
+}
+
 #[test]
 fn sealed_sub_bucket_and_page_compaction_are_written_when_boundaries_close() {
     block_on(async {
@@ -134,7 +349,7 @@ fn sealed_sub_bucket_and_page_compaction_are_written_when_boundaries_close() {
         svc.ingest_finalized_block(block).await.expect("ingest");
 
         let sid = finalized_history_query::kernel::sharded_streams::sharded_stream_id(
-            "addr",
+            StreamKind::Addr,
             &[5; 20],
             finalized_history_query::core::ids::LogShard::new(0)
                 .unwrap()
@@ -247,7 +462,7 @@ fn direct_ingest_repairs_stale_sealed_open_page_markers_before_writing() {
         .expect("seed block meta");
 
         let sid = finalized_history_query::kernel::sharded_streams::sharded_stream_id(
-            "addr",
+            StreamKind::Addr,
             &[5; 20],
             finalized_history_query::core::ids::LogShard::new(0)
                 .unwrap()
@@ -309,3 +524,104 @@ fn direct_ingest_repairs_stale_sealed_open_page_markers_before_writing() {
         );
     });
 }
+
+/// Counts meta reads of one stream's page meta and by-block fragments, and
+/// shard-presence puts that carry a condition.
+#[derive(Clone, Default)]
+struct StreamProbeCountingStore {
+    inner: InMemoryMetaStore,
+    stream_prefix: Vec<u8>,
+    probes: Arc<AtomicU64>,
+    conditional_presence_puts: Arc<AtomicU64>,
+}
+
+impl StreamProbeCountingStore {
+    fn count(&self, key: &[u8]) {
+        if key.starts_with(&self.stream_prefix) {
+            self.probes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl MetaStore for StreamProbeCountingStore {
+    async fn get(
+        &self,
+        table: TableId,
+        key: &[u8],
+    ) -> finalized_history_query::Result<Option<Record>> {
+        self.count(key);
+        self.inner.get(table, key).await
+    }
+
+    async fn put(
+        &self,
+        table: TableId,
+        key: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> finalized_history_query::Result<PutResult> {
+        self.inner.put(table, key, value, cond).await
+    }
+
+    async fn delete(
+        &self,
+        table: TableId,
+        key: &[u8],
+        cond: DelCond,
+    ) -> finalized_history_query::Result<()> {
+        self.inner.delete(table, key, cond).await
+    }
+
+    async fn scan_get(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+    ) -> finalized_history_query::Result<Option<Record>> {
+        self.count(partition);
+        self.inner.scan_get(table, partition, clustering).await
+    }
+
+    async fn scan_put(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> finalized_history_query::Result<PutResult> {
+        if table == LogStreamShardSpec::TABLE && !matches!(cond, PutCond::Any) {
+            self.conditional_presence_puts
+                .fetch_add(1, Ordering::Relaxed);
+        }
+        self.inner
+            .scan_put(table, partition, clustering, value, cond)
+            .await
+    }
+
+    async fn scan_delete(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        cond: DelCond,
+    ) -> finalized_history_query::Result<()> {
+        self.inner
+            .scan_delete(table, partition, clustering, cond)
+            .await
+    }
+
+    async fn scan_list(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> finalized_history_query::Result<Page> {
+        self.count(partition);
+        self.inner
+            .scan_list(table, partition, prefix, cursor, limit)
+            .await
+    }
+}
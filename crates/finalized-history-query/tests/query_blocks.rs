@@ -12,7 +12,7 @@ use finalized_history_query::kernel::codec::StorageCodec;
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
 use finalized_history_query::store::publication::{MetaPublicationStore, PublicationStore};
-use finalized_history_query::store::traits::{MetaStore, PutCond};
+use finalized_history_query::store::traits::{DelCond, MetaStore, PutCond};
 use futures::executor::block_on;
 
 use helpers::*;
@@ -157,6 +157,7 @@ fn query_blocks_budget_clamps_effective_limit() {
                 },
                 ExecutionBudget {
                     max_results: Some(2),
+                    max_result_bytes: None,
                 },
             )
             .await
@@ -347,6 +348,7 @@ fn query_blocks_rejects_zero_budget_even_when_range_is_empty() {
                 },
                 ExecutionBudget {
                     max_results: Some(0),
+                    max_result_bytes: None,
                 },
             )
             .await
@@ -468,3 +470,141 @@ fn query_blocks_fails_closed_when_interior_block_record_is_missing() {
         assert!(matches!(err, Error::NotFound));
     });
 }
+
+#[test]
+fn query_blocks_descending_returns_newest_blocks_first() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let mut parent = [0; 32];
+        for block_num in 1..=4 {
+            let block = mk_block(block_num, parent, Vec::new());
+            parent = block.block_hash;
+            svc.ingest_finalized_block(block)
+                .await
+                .expect("ingest block");
+        }
+
+        let page = svc
+            .query_blocks(
+                QueryBlocksRequest {
+                    from_block: Some(2),
+                    to_block: Some(4),
+                    from_block_hash: None,
+                    to_block_hash: None,
+                    order: QueryOrder::Descending,
+                    limit: 2,
+                },
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("descending blocks");
+
+        let numbers = page
+            .items
+            .iter()
+            .map(|header| header.number)
+            .collect::<Vec<_>>();
+        assert_eq!(numbers, vec![4, 3]);
+        assert!(page.meta.has_more);
+        assert_eq!(page.meta.cursor_block.number, 3);
+    });
+}
+
+#[test]
+fn get_block_record_by_hash_returns_record_for_indexed_hash() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_blocks(vec![
+            mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]),
+            mk_block(
+                2,
+                [1; 32],
+                vec![mk_log(1, 10, 20, 2, 0, 0), mk_log(2, 10, 20, 2, 0, 1)],
+            ),
+        ])
+        .await
+        .expect("ingest blocks");
+
+        let record = svc
+            .get_block_record_by_hash([2; 32])
+            .await
+            .expect("lookup by hash")
+            .expect("record");
+        assert_eq!(record.parent_hash, [1; 32]);
+        assert_eq!(
+            record.logs,
+            Some(PrimaryWindowRecord {
+                first_primary_id: 1,
+                count: 2,
+            })
+        );
+        assert_eq!(
+            svc.get_block_record(2).await.expect("lookup by number"),
+            Some(record)
+        );
+        assert_eq!(
+            svc.get_block_record_by_hash([9; 32])
+                .await
+                .expect("lookup absent hash"),
+            None
+        );
+        assert_eq!(
+            svc.get_block_record(3).await.expect("lookup absent number"),
+            None
+        );
+    });
+}
+
+#[test]
+fn get_block_record_by_hash_ignores_index_entry_without_matching_record() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_blocks(vec![
+            mk_block(1, [0; 32], Vec::new()),
+            mk_block(2, [1; 32], Vec::new()),
+        ])
+        .await
+        .expect("ingest blocks");
+
+        meta.delete(BLOCK_RECORD_TABLE, &BlockRecordSpec::key(1), DelCond::Any)
+            .await
+            .expect("prune block 1 record");
+        meta.put(
+            BLOCK_RECORD_TABLE,
+            &BlockRecordSpec::key(2),
+            shared_block_record([7; 32], [1; 32], Some((0, 0)), Some((0, 0))).encode(),
+            PutCond::Any,
+        )
+        .await
+        .expect("reuse block 2 record for another hash");
+
+        assert_eq!(
+            svc.get_block_record_by_hash([1; 32])
+                .await
+                .expect("lookup pruned hash"),
+            None
+        );
+        assert_eq!(
+            svc.get_block_record_by_hash([2; 32])
+                .await
+                .expect("lookup reused hash"),
+            None
+        );
+    });
+}
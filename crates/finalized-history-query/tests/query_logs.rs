@@ -4,9 +4,15 @@ mod helpers;
 use finalized_history_query::api::{
     ExecutionBudget, FinalizedHistoryService, QueryLogsRequest, QueryOrder,
 };
+use finalized_history_query::config::Config;
+use finalized_history_query::kernel::sharded_streams::{StreamKind, sharded_stream_id};
+use finalized_history_query::kernel::table_specs::ScannableTableSpec;
+use finalized_history_query::logs::table_specs::LogBitmapByBlockSpec;
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
-use finalized_history_query::{Clause, Error, LogFilter};
+use finalized_history_query::store::traits::{MetaStore, PutCond};
+use finalized_history_query::streams::{decode_bitmap_blob, encode_bitmap_blob};
+use finalized_history_query::{Address, Clause, Error, LogFilter, Topic};
 use futures::executor::block_on;
 
 use helpers::*;
@@ -82,6 +88,56 @@ fn query_range_clips_to_published_head() {
 
 // --- RangeResolver edge cases ---
 
+#[test]
+fn empty_boundary_blocks_do_not_admit_neighbouring_logs() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        // Blocks 2 and 4 have no logs, so their windows start at the first log
+        // ID of the following block.
+        let blocks = [
+            vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(1, 11, 21, 1, 0, 1)],
+            Vec::new(),
+            vec![mk_log(1, 12, 22, 3, 0, 0)],
+            Vec::new(),
+            vec![mk_log(1, 13, 23, 5, 0, 0)],
+        ];
+        let mut parent_hash = [0; 32];
+        for (block_num, logs) in (1..).zip(blocks) {
+            let block = mk_block(block_num, parent_hash, logs);
+            parent_hash = block.block_hash;
+            svc.ingest_finalized_block(block)
+                .await
+                .expect("ingest block");
+        }
+
+        let unfiltered = LogFilter {
+            address: Some(Clause::Any),
+            ..Default::default()
+        };
+        for filter in [indexed_address_filter(1), unfiltered] {
+            for (from_block, to_block, expected_blocks) in [
+                (2, 2, vec![]),
+                (4, 4, vec![]),
+                (1, 2, vec![1, 1]),
+                (2, 3, vec![3]),
+                (2, 4, vec![3]),
+                (3, 4, vec![3]),
+            ] {
+                let page = query_page(&svc, from_block, to_block, filter.clone(), 10, None)
+                    .await
+                    .expect("query");
+                let blocks: Vec<u64> = page.items.iter().map(|log| log.block_num()).collect();
+                assert_eq!(blocks, expected_blocks, "{from_block}..={to_block}");
+            }
+        }
+    });
+}
+
 #[test]
 fn query_returns_error_when_from_block_exceeds_to_block() {
     block_on(async {
@@ -102,6 +158,8 @@ fn query_returns_error_when_from_block_exceeds_to_block() {
                     to_block: Some(2),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: None,
                     limit: 10,
@@ -156,6 +214,8 @@ fn query_logs_resolves_block_hash_bounds() {
                     to_block: None,
                     from_block_hash: Some([1; 32]),
                     to_block_hash: Some([1; 32]),
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: None,
                     limit: 10,
@@ -209,6 +269,8 @@ fn query_rejects_resume_log_id_outside_window() {
                     to_block: Some(1),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: Some(999_999),
                     limit: 10,
@@ -276,6 +338,8 @@ fn execution_budget_clamps_effective_limit() {
                     to_block: Some(1),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: None,
                     limit: 10,
@@ -283,6 +347,7 @@ fn execution_budget_clamps_effective_limit() {
                 },
                 ExecutionBudget {
                     max_results: Some(1),
+                    max_result_bytes: None,
                 },
             )
             .await
@@ -312,6 +377,8 @@ fn execution_budget_zero_is_rejected() {
                     to_block: Some(1),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: None,
                     limit: 10,
@@ -319,6 +386,7 @@ fn execution_budget_zero_is_rejected() {
                 },
                 ExecutionBudget {
                     max_results: Some(0),
+                    max_result_bytes: None,
                 },
             )
             .await
@@ -327,6 +395,81 @@ fn execution_budget_zero_is_rejected() {
     });
 }
 
+#[test]
+fn execution_budget_max_result_bytes_admits_logs_that_fit() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let logs = (0..3)
+            .map(|log_idx| {
+                let mut log = mk_log(1, 10, 20 + log_idx as u8, 1, 0, log_idx);
+                log.data = vec![0xaa; 100];
+                log
+            })
+            .collect();
+        svc.ingest_finalized_block(mk_block(1, [0; 32], logs))
+            .await
+            .expect("ingest");
+
+        // Each log encodes to 73 fixed bytes + 2 topics + 100 data bytes = 237,
+        // so a 500-byte budget admits two logs but not the third.
+        let budget = ExecutionBudget {
+            max_results: None,
+            max_result_bytes: Some(500),
+        };
+        for filter in [indexed_address_filter(1), LogFilter::default()] {
+            let request = QueryLogsRequest {
+                from_block: Some(1),
+                to_block: Some(1),
+                from_block_hash: None,
+                to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
+                order: QueryOrder::Ascending,
+                resume_id: None,
+                limit: 10,
+                filter,
+            };
+            let page = svc
+                .query_logs(request.clone(), budget)
+                .await
+                .expect("byte-budgeted query");
+            assert_eq!(
+                page.items
+                    .iter()
+                    .map(|log| log.log_idx())
+                    .collect::<Vec<_>>(),
+                vec![0, 1]
+            );
+            assert!(page.items.iter().all(|log| log.encoded_len() == 237));
+            assert!(page.meta.has_more);
+
+            let rest = svc
+                .query_logs(
+                    QueryLogsRequest {
+                        resume_id: page.meta.next_resume_id,
+                        ..request
+                    },
+                    budget,
+                )
+                .await
+                .expect("resumed query");
+            assert_eq!(
+                rest.items
+                    .iter()
+                    .map(|log| log.log_idx())
+                    .collect::<Vec<_>>(),
+                vec![2]
+            );
+            assert!(!rest.meta.has_more);
+        }
+    });
+}
+
 // --- Unfiltered direct block scan ---
 
 #[test]
@@ -356,6 +499,8 @@ fn query_logs_supports_unfiltered_scan_with_resume() {
                     to_block: Some(2),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: None,
                     limit: 2,
@@ -381,6 +526,8 @@ fn query_logs_supports_unfiltered_scan_with_resume() {
                     to_block: Some(2),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: first.meta.next_resume_id,
                     limit: 2,
@@ -421,8 +568,8 @@ fn indexed_query_filters_out_non_matching_logs_from_same_stream() {
 
         // Filter by address + topic0: only the first log should match.
         let filter = LogFilter {
-            address: Some(Clause::One([1; 20])),
-            topic0: Some(Clause::One([10; 32])),
+            address: Some(Clause::One(Address([1; 20]))),
+            topic0: Some(Clause::One(Topic([10; 32]))),
             ..Default::default()
         };
         let page = query_page(&svc, 1, 1, filter, 10, None)
@@ -432,3 +579,1405 @@ fn indexed_query_filters_out_non_matching_logs_from_same_stream() {
         assert_eq!(page.items[0].topic(0), &[10; 32]);
     });
 }
+
+#[test]
+fn topic1_address_clause_matches_left_padded_address_topics() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let recipient = Address([0x42; 20]);
+        let mut padded = mk_log(1, 10, 0, 1, 0, 0);
+        padded.topics[1] = Topic::from(recipient);
+        let mut unpadded = mk_log(1, 10, 0, 1, 0, 1);
+        unpadded.topics[1] = Topic([0x42; 32]);
+        svc.ingest_finalized_block(mk_block(1, [0; 32], vec![padded, unpadded]))
+            .await
+            .expect("ingest");
+
+        let filter = LogFilter::builder().topic1_address_or([recipient]).build();
+        let page = query_page(&svc, 1, 1, filter, 10, None)
+            .await
+            .expect("topic1 address query");
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].log_idx(), 0);
+        assert_eq!(Topic(*page.items[0].topic(1)).as_address(), Some(recipient));
+    });
+}
+
+#[test]
+fn negated_topic_clause_post_filters_indexed_address_matches() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(
+            1,
+            [0; 32],
+            vec![
+                mk_log(1, 10, 20, 1, 0, 0),
+                mk_log(1, 11, 20, 1, 0, 1),
+                mk_log(2, 12, 20, 1, 0, 2),
+                mk_log(1, 12, 20, 1, 1, 3),
+            ],
+        ))
+        .await
+        .expect("ingest");
+
+        let filter = LogFilter {
+            address: Some(Clause::One(Address([1; 20]))),
+            topic0: Some(Clause::Not(Box::new(Clause::One(Topic([10; 32]))))),
+            ..Default::default()
+        };
+        let page = query_page(&svc, 1, 1, filter, 10, None)
+            .await
+            .expect("negated query");
+        let topics = page
+            .items
+            .iter()
+            .map(|log| (*log.address(), *log.topic(0)))
+            .collect::<Vec<_>>();
+        assert_eq!(topics, vec![([1; 20], [11; 32]), ([1; 20], [12; 32])]);
+    });
+}
+
+#[test]
+fn negation_only_query_is_rejected_as_too_broad() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]))
+            .await
+            .expect("ingest");
+
+        let filter = LogFilter {
+            topic0: Some(Clause::Not(Box::new(Clause::One(Topic([10; 32]))))),
+            ..Default::default()
+        };
+        let err = query_page(&svc, 1, 1, filter, 10, None)
+            .await
+            .expect_err("negation-only query");
+        assert!(matches!(err, Error::NegationWithoutIndexedClause));
+    });
+}
+
+#[test]
+fn topic3_range_clause_post_filters_indexed_matches() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let logs = (0..6u8)
+            .map(|token_id| {
+                let mut log = mk_log(1, 10, 20, 1, 0, u32::from(token_id));
+                log.topics.push(Topic([0; 32]));
+                log.topics.push(Topic([token_id; 32]));
+                log
+            })
+            .chain([mk_log(1, 10, 20, 1, 0, 6)])
+            .collect();
+        svc.ingest_finalized_block(mk_block(1, [0; 32], logs))
+            .await
+            .expect("ingest");
+
+        let range = Clause::Range {
+            lo: Topic([2; 32]),
+            hi: Topic([4; 32]),
+        };
+        let filter = LogFilter {
+            address: Some(Clause::One(Address([1; 20]))),
+            topic3: Some(range.clone()),
+            ..Default::default()
+        };
+        let page = query_page(&svc, 1, 1, filter, 10, None)
+            .await
+            .expect("range query");
+        let token_ids = page
+            .items
+            .iter()
+            .map(|log| log.topic(3)[0])
+            .collect::<Vec<_>>();
+        assert_eq!(token_ids, vec![2, 3, 4]);
+
+        let range_only = LogFilter {
+            topic3: Some(range),
+            ..Default::default()
+        };
+        let err = query_page(&svc, 1, 1, range_only, 10, None)
+            .await
+            .expect_err("range-only query");
+        assert!(matches!(err, Error::NegationWithoutIndexedClause));
+    });
+}
+
+#[test]
+fn logs_by_id_range_spans_blocks_and_respects_bounds() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                max_log_id_range_span: 8,
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 11, 21, 1, 0, 1)],
+        ))
+        .await
+        .expect("ingest block 1");
+        svc.ingest_finalized_block(mk_block(
+            2,
+            [1; 32],
+            vec![mk_log(3, 12, 22, 2, 0, 0), mk_log(4, 13, 23, 2, 0, 1)],
+        ))
+        .await
+        .expect("ingest block 2");
+
+        let logs = svc.logs_by_id_range(1, 7, 10).await.expect("id range");
+        let positions: Vec<_> = logs
+            .iter()
+            .map(|log| (log.block_num, log.log_idx))
+            .collect();
+        assert_eq!(positions, vec![(1, 1), (2, 0), (2, 1)]);
+        assert_eq!(logs[1].address, Address([3; 20]));
+
+        let capped = svc.logs_by_id_range(0, 3, 2).await.expect("capped range");
+        let positions: Vec<_> = capped
+            .iter()
+            .map(|log| (log.block_num, log.log_idx))
+            .collect();
+        assert_eq!(positions, vec![(1, 0), (1, 1)]);
+
+        let err = svc
+            .logs_by_id_range(0, 8, 10)
+            .await
+            .expect_err("span above limit");
+        assert!(matches!(err, Error::InvalidParams(_)));
+        assert!(matches!(
+            svc.logs_by_id_range(3, 2, 10).await,
+            Err(Error::InvalidParams(_))
+        ));
+    });
+}
+
+#[test]
+fn require_topic0_rejects_filters_without_a_signature() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                require_topic0: true,
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(1, 11, 21, 1, 0, 1)],
+        ))
+        .await
+        .expect("ingest block 1");
+
+        let err = query_page(&svc, 1, 1, indexed_address_filter(1), 10, None)
+            .await
+            .expect_err("topic0-less filter");
+        assert!(matches!(err, Error::InvalidParams("topic0 required")));
+
+        let any_topic0 = LogFilter {
+            topic0: Some(Clause::Any),
+            ..indexed_address_filter(1)
+        };
+        let err = query_page(&svc, 1, 1, any_topic0, 10, None)
+            .await
+            .expect_err("topic0 any filter");
+        assert!(matches!(err, Error::InvalidParams("topic0 required")));
+
+        let pinned = LogFilter {
+            topic0: Some(Clause::One(Topic([10; 32]))),
+            ..indexed_address_filter(1)
+        };
+        let page = query_page(&svc, 1, 1, pinned, 10, None)
+            .await
+            .expect("topic0 pinned filter");
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].topic(0), &[10; 32]);
+    });
+}
+
+#[test]
+fn query_log_positions_match_query_logs_pages() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let mut parent = [0; 32];
+        for block_num in 1..=4u64 {
+            let logs = (0..4u32)
+                .map(|log_idx| {
+                    let address = if (block_num + u64::from(log_idx)) % 2 == 0 {
+                        1
+                    } else {
+                        2
+                    };
+                    mk_log(address, 10 + log_idx as u8, 20, block_num, 0, log_idx)
+                })
+                .collect();
+            svc.ingest_finalized_block(mk_block(block_num, parent, logs))
+                .await
+                .expect("ingest");
+            parent = [block_num as u8; 32];
+        }
+
+        let filter = LogFilter {
+            address: Some(Clause::One(Address([1; 20]))),
+            topic0: Some(Clause::Or(vec![Topic([10; 32]), Topic([12; 32])])),
+            ..Default::default()
+        };
+        let mut expected = Vec::new();
+        let mut resume_id = None;
+        loop {
+            let page = query_page(&svc, 1, 4, filter.clone(), 3, resume_id)
+                .await
+                .expect("query logs");
+            expected.extend(
+                page.items
+                    .iter()
+                    .map(|log| (log.block_num(), log.log_idx())),
+            );
+            resume_id = page.meta.next_resume_id;
+            if resume_id.is_none() {
+                break;
+            }
+        }
+        assert!(!expected.is_empty());
+
+        let mut positions = Vec::new();
+        let mut resume_id = None;
+        loop {
+            let page = svc
+                .query_log_positions(
+                    QueryLogsRequest {
+                        from_block: Some(1),
+                        to_block: Some(4),
+                        from_block_hash: None,
+                        to_block_hash: None,
+                        from_timestamp: None,
+                        to_timestamp: None,
+                        order: QueryOrder::Ascending,
+                        resume_id,
+                        limit: 3,
+                        filter: filter.clone(),
+                    },
+                    ExecutionBudget::default(),
+                )
+                .await
+                .expect("query log positions");
+            positions.extend(page.items.iter().copied());
+            resume_id = page.meta.next_resume_id;
+            if resume_id.is_none() {
+                break;
+            }
+        }
+        assert_eq!(
+            positions
+                .iter()
+                .map(|position| (position.block_num, position.log_idx))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        for position in &positions {
+            assert_eq!(
+                position.log_id.get(),
+                (position.block_num - 1) * 4 + u64::from(position.log_idx)
+            );
+        }
+
+        let negated = LogFilter {
+            topic1: Some(Clause::Not(Box::new(Clause::One(Topic([20; 32]))))),
+            ..filter
+        };
+        let err = svc
+            .query_log_positions(
+                QueryLogsRequest {
+                    from_block: Some(1),
+                    to_block: Some(4),
+                    from_block_hash: None,
+                    to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
+                    order: QueryOrder::Ascending,
+                    resume_id: None,
+                    limit: 10,
+                    filter: negated,
+                },
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect_err("negated filter");
+        assert!(matches!(err, Error::InvalidParams(_)));
+    });
+}
+
+#[test]
+fn blocks_with_topic0_returns_exact_matching_block_numbers() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        let mut parent = [0; 32];
+        for block_num in 1..=6u64 {
+            let logs = match block_num {
+                2 => vec![mk_log(1, 10, 20, 2, 0, 0), mk_log(2, 10, 21, 2, 0, 1)],
+                3 => vec![mk_log(1, 11, 20, 3, 0, 0)],
+                5 => vec![mk_log(3, 11, 20, 5, 0, 0), mk_log(3, 10, 20, 5, 1, 1)],
+                _ => Vec::new(),
+            };
+            let block = mk_block(block_num, parent, logs);
+            parent = block.block_hash;
+            svc.ingest_finalized_block(block)
+                .await
+                .expect("ingest block");
+        }
+
+        assert_eq!(
+            svc.blocks_with_topic0(Topic([10; 32]), 1, 6)
+                .await
+                .expect("topic0 blocks"),
+            vec![2, 5]
+        );
+        assert_eq!(
+            svc.blocks_with_topic0(Topic([10; 32]), 3, 6)
+                .await
+                .expect("clipped topic0 blocks"),
+            vec![5]
+        );
+        assert_eq!(
+            svc.blocks_with_topic0(Topic([11; 32]), 1, 99)
+                .await
+                .expect("topic0 blocks clipped to head"),
+            vec![3, 5]
+        );
+        assert!(
+            svc.blocks_with_topic0(Topic([12; 32]), 1, 6)
+                .await
+                .expect("absent topic0")
+                .is_empty()
+        );
+    });
+}
+
+#[test]
+fn count_logs_by_block_matches_grouped_query_results() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        let mut parent = [0; 32];
+        for block_num in 1..=6u64 {
+            let logs = (0..(block_num % 4) as u32)
+                .map(|log_idx| {
+                    let topic1 = if log_idx % 2 == 0 { 20 } else { 21 };
+                    mk_log(1, 10 + (log_idx % 2) as u8, topic1, block_num, 0, log_idx)
+                })
+                .collect();
+            let block = mk_block(block_num, parent, logs);
+            parent = block.block_hash;
+            svc.ingest_finalized_block(block)
+                .await
+                .expect("ingest block");
+        }
+
+        let filters = [
+            indexed_address_filter(1),
+            LogFilter {
+                topic0: Some(Clause::One(Topic([11; 32]))),
+                ..Default::default()
+            },
+            LogFilter {
+                topic1: Some(Clause::Not(Box::new(Clause::One(Topic([21; 32]))))),
+                ..indexed_address_filter(1)
+            },
+        ];
+        for filter in filters {
+            let page = query_page(&svc, 2, 6, filter.clone(), usize::MAX, None)
+                .await
+                .expect("query");
+            let mut expected: Vec<(u64, u64)> = Vec::new();
+            for log in &page.items {
+                match expected.last_mut() {
+                    Some((block_num, count)) if *block_num == log.block_num() => *count += 1,
+                    _ => expected.push((log.block_num(), 1)),
+                }
+            }
+            assert!(!expected.is_empty());
+            assert_eq!(
+                svc.count_logs_by_block(filter, 2, 6)
+                    .await
+                    .expect("count by block"),
+                expected
+            );
+        }
+        assert_eq!(
+            svc.count_logs_by_block(indexed_address_filter(1), 1, 99)
+                .await
+                .expect("count clipped to head"),
+            vec![(1, 1), (2, 2), (3, 3), (5, 1), (6, 2)]
+        );
+    });
+}
+
+#[test]
+fn estimate_log_count_bounds_the_matching_log_count() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        let mut parent = [0; 32];
+        for block_num in 1..=8u64 {
+            let logs = (0..4u32)
+                .map(|log_idx| {
+                    let address = if log_idx == 0 { 1 } else { 2 };
+                    mk_log(address, 10 + (log_idx % 2) as u8, 20, block_num, 0, log_idx)
+                })
+                .collect();
+            let block = mk_block(block_num, parent, logs);
+            parent = block.block_hash;
+            svc.ingest_finalized_block(block)
+                .await
+                .expect("ingest block");
+        }
+
+        let filters = [
+            indexed_address_filter(1),
+            indexed_address_filter(2),
+            LogFilter {
+                topic0: Some(Clause::One(Topic([11; 32]))),
+                ..indexed_address_filter(2)
+            },
+            LogFilter {
+                topic1: Some(Clause::Not(Box::new(Clause::One(Topic([20; 32]))))),
+                ..indexed_address_filter(1)
+            },
+            LogFilter::default(),
+        ];
+        for filter in filters {
+            let actual = query_page(&svc, 3, 6, filter.clone(), usize::MAX, None)
+                .await
+                .expect("query")
+                .items
+                .len() as u64;
+            let request = QueryLogsRequest::builder()
+                .from_block(3)
+                .to_block(6)
+                .filter(filter)
+                .build()
+                .expect("request");
+            let estimate = svc.estimate_log_count(&request).await.expect("estimate");
+            assert!(estimate >= actual, "{estimate} < {actual}");
+        }
+
+        // A selective single clause is estimated from its own by-block
+        // fragments, which cover exactly the window's blocks.
+        let request = QueryLogsRequest::builder()
+            .from_block(3)
+            .to_block(6)
+            .filter(indexed_address_filter(1))
+            .build()
+            .expect("request");
+        assert_eq!(
+            svc.estimate_log_count(&request).await.expect("estimate"),
+            4
+        );
+    });
+}
+
+#[test]
+fn query_logs_rechecks_indexed_clauses_against_decoded_logs() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 10, 20, 1, 0, 1)],
+        ))
+        .await
+        .expect("ingest");
+
+        // Append log 1, which has address 2, to the address-1 stream.
+        let sid = sharded_stream_id(StreamKind::Addr, &[1; 20], 0);
+        let partition = LogBitmapByBlockSpec::partition(&sid, 0);
+        let clustering = LogBitmapByBlockSpec::clustering(1);
+        let fragment = meta
+            .scan_get(LogBitmapByBlockSpec::TABLE, &partition, &clustering)
+            .await
+            .expect("load fragment")
+            .expect("fragment present");
+        let mut blob = decode_bitmap_blob(&fragment.value).expect("decode fragment");
+        blob.bitmap.insert(1);
+        blob.max_local = 1;
+        blob.count = 2;
+        meta.scan_put(
+            LogBitmapByBlockSpec::TABLE,
+            &partition,
+            &clustering,
+            encode_bitmap_blob(&blob).expect("encode fragment"),
+            PutCond::Any,
+        )
+        .await
+        .expect("corrupt fragment");
+
+        let page = query_page(&svc, 1, 1, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query");
+        assert_eq!(
+            page.items
+                .iter()
+                .map(|log| (log.log_idx(), *log.address()))
+                .collect::<Vec<_>>(),
+            vec![(0, [1; 20])]
+        );
+
+        // Positions read no payloads, so they trust the stream.
+        let positions = svc
+            .query_log_positions(
+                QueryLogsRequest::builder()
+                    .from_block(1)
+                    .to_block(1)
+                    .filter(indexed_address_filter(1))
+                    .build()
+                    .expect("request"),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("query positions");
+        assert_eq!(
+            positions
+                .items
+                .iter()
+                .map(|position| position.log_idx)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+    });
+}
+
+#[test]
+fn descending_query_returns_newest_matches_first() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 10, 21, 1, 0, 1)],
+        ))
+        .await
+        .expect("ingest block 1");
+        svc.ingest_finalized_block(mk_block(
+            2,
+            [1; 32],
+            vec![mk_log(1, 11, 22, 2, 0, 0), mk_log(1, 12, 23, 2, 1, 1)],
+        ))
+        .await
+        .expect("ingest block 2");
+        svc.ingest_finalized_block(mk_block(3, [2; 32], vec![mk_log(2, 13, 24, 3, 0, 0)]))
+            .await
+            .expect("ingest block 3");
+
+        let request = |resume_id| QueryLogsRequest {
+            from_block: Some(1),
+            to_block: Some(3),
+            from_block_hash: None,
+            to_block_hash: None,
+            from_timestamp: None,
+            to_timestamp: None,
+            order: QueryOrder::Descending,
+            resume_id,
+            limit: 10,
+            filter: indexed_address_filter(1),
+        };
+        let budget = ExecutionBudget {
+            max_results: Some(2),
+            max_result_bytes: None,
+        };
+
+        let first = svc
+            .query_logs(request(None), budget)
+            .await
+            .expect("first descending page");
+        let positions = first
+            .items
+            .iter()
+            .map(|log| (log.block_num(), log.tx_idx(), log.log_idx()))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(2, 1, 1), (2, 0, 0)]);
+        assert!(first.meta.has_more);
+        assert_eq!(first.meta.next_resume_id, Some(2));
+        assert_eq!(first.meta.cursor_block.number, 2);
+
+        let second = svc
+            .query_logs(request(first.meta.next_resume_id), budget)
+            .await
+            .expect("second descending page");
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].block_num(), 1);
+        assert_eq!(second.items[0].log_idx(), 0);
+        assert!(!second.meta.has_more);
+    });
+}
+
+#[test]
+fn descending_unfiltered_scan_walks_blocks_newest_first() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 11, 21, 1, 0, 1)],
+        ))
+        .await
+        .expect("ingest block 1");
+        svc.ingest_finalized_block(mk_block(2, [1; 32], vec![mk_log(3, 12, 22, 2, 0, 0)]))
+            .await
+            .expect("ingest block 2");
+
+        let page = svc
+            .query_logs(
+                QueryLogsRequest {
+                    from_block: Some(1),
+                    to_block: Some(2),
+                    from_block_hash: None,
+                    to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
+                    order: QueryOrder::Descending,
+                    resume_id: None,
+                    limit: 10,
+                    filter: LogFilter::default(),
+                },
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("descending unfiltered scan");
+        let positions = page
+            .items
+            .iter()
+            .map(|log| (log.block_num(), log.log_idx()))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(2, 0), (1, 1), (1, 0)]);
+    });
+}
+
+#[test]
+fn query_logs_at_block_hashes_without_indexed_clause_scans_each_block() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        svc.ingest_finalized_block(mk_block(
+            1,
+            [0; 32],
+            vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 11, 21, 1, 0, 1)],
+        ))
+        .await
+        .expect("ingest block 1");
+        svc.ingest_finalized_block(mk_block(2, [1; 32], vec![mk_log(3, 12, 22, 2, 0, 0)]))
+            .await
+            .expect("ingest block 2");
+
+        let items = svc
+            .query_logs_at_block_hashes(
+                Clause::One([1; 32]),
+                LogFilter::default(),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("query block hash without indexed clause");
+        assert_eq!(
+            items
+                .iter()
+                .map(|log| (log.block_num(), log.log_idx()))
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (1, 1)]
+        );
+    });
+}
+
+#[test]
+fn query_logs_at_block_hashes_unions_matches_in_block_order() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                max_block_hash_terms: 3,
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        let mut parent = [0; 32];
+        for block_num in 1..=4u64 {
+            let block = mk_block(
+                block_num,
+                parent,
+                vec![
+                    mk_log(1, 10, 20, block_num, 0, 0),
+                    mk_log(2, 10, 20, block_num, 0, 1),
+                    mk_log(1, 11, 20, block_num, 1, 2),
+                ],
+            );
+            parent = block.block_hash;
+            svc.ingest_finalized_block(block)
+                .await
+                .expect("ingest block");
+        }
+
+        let items = svc
+            .query_logs_at_block_hashes(
+                Clause::Or(vec![[4; 32], [2; 32], [4; 32]]),
+                indexed_address_filter(1),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("query block hashes");
+        assert_eq!(
+            items
+                .iter()
+                .map(|log| (log.block_num(), log.log_idx()))
+                .collect::<Vec<_>>(),
+            vec![(2, 0), (2, 2), (4, 0), (4, 2)]
+        );
+
+        let err = svc
+            .query_logs_at_block_hashes(
+                Clause::Or(vec![[1; 32], [2; 32], [3; 32], [4; 32]]),
+                indexed_address_filter(1),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect_err("too many block hashes");
+        assert!(matches!(err, Error::QueryTooBroad { actual: 4, max: 3 }));
+
+        // The limit counts distinct hashes, so repeats do not count against it.
+        let items = svc
+            .query_logs_at_block_hashes(
+                Clause::Or(vec![[1; 32], [1; 32], [1; 32], [1; 32], [3; 32]]),
+                indexed_address_filter(1),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("query repeated block hashes");
+        assert_eq!(items.len(), 4);
+    });
+}
+
+#[test]
+fn query_logs_at_block_hashes_rejects_unknown_hash() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]))
+            .await
+            .expect("ingest block 1");
+
+        let err = svc
+            .query_logs_at_block_hashes(
+                Clause::Or(vec![[1; 32], [9; 32]]),
+                indexed_address_filter(1),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect_err("unknown block hash");
+        assert!(matches!(err, Error::InvalidParams("unknown block hash")));
+    });
+}
+
+#[test]
+fn query_logs_at_block_hashes_stops_when_budget_is_spent() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+
+        let mut parent = [0; 32];
+        for block_num in 1..=3u64 {
+            let block = mk_block(
+                block_num,
+                parent,
+                vec![
+                    mk_log(1, 10, 20, block_num, 0, 0),
+                    mk_log(1, 11, 20, block_num, 0, 1),
+                ],
+            );
+            parent = block.block_hash;
+            svc.ingest_finalized_block(block)
+                .await
+                .expect("ingest block");
+        }
+        let block_hashes = Clause::Or(vec![[3; 32], [1; 32], [2; 32]]);
+
+        let items = svc
+            .query_logs_at_block_hashes(
+                block_hashes.clone(),
+                indexed_address_filter(1),
+                ExecutionBudget {
+                    max_results: Some(3),
+                    max_result_bytes: None,
+                },
+            )
+            .await
+            .expect("query block hashes under a result budget");
+        assert_eq!(
+            items
+                .iter()
+                .map(|log| (log.block_num(), log.log_idx()))
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (1, 1), (2, 0)]
+        );
+
+        let item_bytes = items[0].encoded_len();
+        let items = svc
+            .query_logs_at_block_hashes(
+                block_hashes,
+                indexed_address_filter(1),
+                ExecutionBudget {
+                    max_results: None,
+                    max_result_bytes: Some(2 * item_bytes),
+                },
+            )
+            .await
+            .expect("query block hashes under a byte budget");
+        assert_eq!(
+            items
+                .iter()
+                .map(|log| (log.block_num(), log.log_idx()))
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (1, 1)]
+        );
+    });
+}
+
+#[test]
+fn dense_single_block_results_stay_in_log_order() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        // Two pages of logs, so the block's matches span a sealed page.
+        let logs = (0..2 * 4_096 + 17)
+            .map(|log_idx| mk_log(1 + (log_idx % 3) as u8, 10, 20, 1, log_idx / 100, log_idx))
+            .collect();
+        svc.ingest_finalized_block(mk_block(1, [0; 32], logs))
+            .await
+            .expect("ingest block 1");
+
+        let by_hash = svc
+            .query_logs_at_block_hashes(
+                Clause::One([1; 32]),
+                indexed_address_filter(1),
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect("query block hash");
+        let by_range = query_page(&svc, 1, 1, indexed_address_filter(1), usize::MAX, None)
+            .await
+            .expect("query single block range")
+            .items;
+        let expected = (0..2 * 4_096 + 17).step_by(3).collect::<Vec<u32>>();
+        for items in [by_hash, by_range] {
+            assert!(items.iter().all(|log| log.block_num() == 1));
+            assert_eq!(
+                items.iter().map(|log| log.log_idx()).collect::<Vec<_>>(),
+                expected
+            );
+        }
+    });
+}
+
+#[test]
+fn query_logs_resolves_timestamp_bounds_to_block_range() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        for block_num in 1..=5u64 {
+            let parent_hash = if block_num == 1 {
+                [0; 32]
+            } else {
+                [(block_num - 1) as u8; 32]
+            };
+            let mut block = mk_block(
+                block_num,
+                parent_hash,
+                vec![mk_log(1, 10, 20, block_num, 0, 0)],
+            );
+            block.header.timestamp = 1_000 + 12 * block_num;
+            svc.ingest_finalized_block(block)
+                .await
+                .expect("ingest block");
+        }
+        let request = |from_timestamp, to_timestamp| QueryLogsRequest {
+            from_block: None,
+            to_block: None,
+            from_block_hash: None,
+            to_block_hash: None,
+            from_timestamp: Some(from_timestamp),
+            to_timestamp: Some(to_timestamp),
+            order: QueryOrder::Ascending,
+            resume_id: None,
+            limit: 10,
+            filter: indexed_address_filter(1),
+        };
+        let blocks =
+            |page: &finalized_history_query::QueryPage<finalized_history_query::LogRef>| {
+                page.items
+                    .iter()
+                    .map(|log| log.block_num())
+                    .collect::<Vec<_>>()
+            };
+
+        // Blocks carry timestamps 1012, 1024, 1036, 1048, 1060.
+        let page = svc
+            .query_logs(request(1_020, 1_050), ExecutionBudget::default())
+            .await
+            .expect("timestamp window");
+        assert_eq!(blocks(&page), vec![2, 3, 4]);
+
+        let page = svc
+            .query_logs(request(1_024, 1_036), ExecutionBudget::default())
+            .await
+            .expect("exact timestamp bounds");
+        assert_eq!(blocks(&page), vec![2, 3]);
+
+        let page = svc
+            .query_logs(request(1_025, 1_035), ExecutionBudget::default())
+            .await
+            .expect("window between blocks");
+        assert!(page.items.is_empty());
+
+        let page = svc
+            .query_logs(request(2_000, 3_000), ExecutionBudget::default())
+            .await
+            .expect("window past head");
+        assert!(page.items.is_empty());
+
+        let err = svc
+            .query_logs(
+                QueryLogsRequest {
+                    from_block: Some(1),
+                    ..request(1_000, 1_100)
+                },
+                ExecutionBudget::default(),
+            )
+            .await
+            .expect_err("from_block and from_timestamp");
+        assert!(matches!(err, Error::InvalidParams(_)));
+    });
+}
+
+#[test]
+fn restored_store_snapshot_drops_later_ingest() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let blob = InMemoryBlobStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+        svc.ingest_finalized_blocks(vec![
+            mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]),
+            mk_block(2, [1; 32], vec![mk_log(1, 11, 21, 2, 0, 0)]),
+        ])
+        .await
+        .expect("ingest base blocks");
+        let meta_snapshot = meta.snapshot().expect("snapshot meta");
+        let blob_snapshot = blob.snapshot().expect("snapshot blob");
+
+        svc.ingest_finalized_block(mk_block(3, [2; 32], vec![mk_log(1, 12, 22, 3, 0, 0)]))
+            .await
+            .expect("ingest block 3");
+        drop(svc);
+
+        meta.restore(&meta_snapshot).expect("restore meta");
+        blob.restore(&blob_snapshot).expect("restore blob");
+
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+        assert_eq!(svc.indexed_finalized_head().await.expect("head"), 2);
+        let page = query_page(&svc, 1, 3, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query restored state");
+        assert_eq!(
+            page.items
+                .iter()
+                .map(|log| log.block_num())
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        svc.ingest_finalized_block(mk_block(3, [2; 32], vec![mk_log(2, 13, 23, 3, 0, 0)]))
+            .await
+            .expect("re-ingest block 3 after restore");
+        let page = query_page(&svc, 1, 3, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query after re-ingest");
+        assert_eq!(page.items.len(), 2);
+    });
+}
+
+#[test]
+fn explain_orders_lower_estimate_clause_first() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_blocks(vec![
+            mk_block(
+                1,
+                [0; 32],
+                vec![
+                    mk_log(1, 10, 20, 1, 0, 0),
+                    mk_log(1, 10, 21, 1, 0, 1),
+                    mk_log(1, 10, 22, 1, 0, 2),
+                ],
+            ),
+            mk_block(
+                2,
+                [1; 32],
+                vec![mk_log(1, 10, 20, 2, 0, 0), mk_log(1, 10, 23, 2, 0, 1)],
+            ),
+        ])
+        .await
+        .expect("ingest");
+
+        let request = QueryLogsRequest {
+            from_block: Some(1),
+            to_block: Some(99),
+            from_block_hash: None,
+            to_block_hash: None,
+            from_timestamp: None,
+            to_timestamp: None,
+            order: QueryOrder::Ascending,
+            resume_id: None,
+            limit: 10,
+            filter: LogFilter {
+                address: Some(Clause::One(Address([1; 20]))),
+                topic1: Some(Clause::One(Topic([20; 32]))),
+                ..Default::default()
+            },
+        };
+        let explain = svc.explain_logs(&request).await.expect("explain");
+
+        assert_eq!((explain.from_block, explain.to_block), (1, 2));
+        assert_eq!(explain.id_range, Some((0, 4)));
+        assert!(!explain.block_scan);
+        assert_eq!(explain.shards.len(), 1);
+        let clauses = &explain.shards[0].clauses;
+        assert_eq!(clauses.len(), 2);
+        assert!(clauses[0].stream_ids[0].starts_with("topic1/"));
+        assert_eq!(clauses[0].estimated_count, 2);
+        assert!(clauses[1].stream_ids[0].starts_with("addr/"));
+        assert_eq!(clauses[1].estimated_count, 5);
+
+        let matched = svc
+            .query_logs(request, ExecutionBudget::default())
+            .await
+            .expect("query");
+        assert_eq!(matched.items.len(), 2);
+    });
+}
+
+#[test]
+fn fast_planning_orders_clauses_like_exact_planning() {
+    block_on(async {
+        let blocks = (1..=8u64)
+            .map(|block_num| {
+                mk_block(
+                    block_num,
+                    [block_num as u8 - 1; 32],
+                    vec![
+                        mk_log(1, 10, 20, block_num, 0, 0),
+                        mk_log(1, 10, 21, block_num, 0, 1),
+                        mk_log(1, 10, 22, block_num, 0, 2),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+        let request = QueryLogsRequest {
+            from_block: Some(1),
+            to_block: Some(8),
+            from_block_hash: None,
+            to_block_hash: None,
+            from_timestamp: None,
+            to_timestamp: None,
+            order: QueryOrder::Ascending,
+            resume_id: None,
+            limit: 100,
+            filter: LogFilter {
+                address: Some(Clause::One(Address([1; 20]))),
+                topic1: Some(Clause::One(Topic([20; 32]))),
+                ..Default::default()
+            },
+        };
+
+        let mut plans = Vec::new();
+        for fast_planning in [false, true] {
+            let svc = FinalizedHistoryService::new_reader_writer(
+                Config {
+                    fast_planning,
+                    ..lease_writer_config()
+                },
+                InMemoryMetaStore::default(),
+                InMemoryBlobStore::default(),
+                1,
+            );
+            svc.ingest_finalized_blocks(blocks.clone())
+                .await
+                .expect("ingest");
+            let explain = svc.explain_logs(&request).await.expect("explain");
+            let page = svc
+                .query_logs(request.clone(), ExecutionBudget::default())
+                .await
+                .expect("query");
+            assert_eq!(page.items.len(), 8);
+            plans.push(
+                explain.shards[0]
+                    .clauses
+                    .iter()
+                    .map(|clause| (clause.stream_ids.clone(), clause.estimated_count))
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        assert!(plans[0][0].0[0].starts_with("topic1/"));
+        assert_eq!(
+            plans[0].iter().map(|clause| clause.1).collect::<Vec<_>>(),
+            vec![8, 24]
+        );
+        assert_eq!(plans[1], plans[0]);
+    });
+}
+
+#[test]
+fn request_builder_matches_literal_construction() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]))
+            .await
+            .expect("ingest");
+
+        let filter = LogFilter::builder()
+            .address_one(Address([1; 20]))
+            .topic1_or([Topic([20; 32]), Topic([21; 32])])
+            .build();
+        let built = QueryLogsRequest::builder()
+            .from_block(1)
+            .to_block(9)
+            .order(QueryOrder::Descending)
+            .limit(10)
+            .filter(filter)
+            .build()
+            .expect("build request");
+        let literal = QueryLogsRequest {
+            from_block: Some(1),
+            to_block: Some(9),
+            from_block_hash: None,
+            to_block_hash: None,
+            from_timestamp: None,
+            to_timestamp: None,
+            order: QueryOrder::Descending,
+            resume_id: None,
+            limit: 10,
+            filter: LogFilter {
+                address: Some(Clause::One(Address([1; 20]))),
+                topic1: Some(Clause::Or(vec![Topic([20; 32]), Topic([21; 32])])),
+                ..Default::default()
+            },
+        };
+        assert_eq!(built, literal);
+
+        let by_hash = QueryLogsRequest::builder()
+            .block_hash([7; 32])
+            .build()
+            .expect("build block-hash request");
+        assert_eq!(by_hash.from_block_hash, Some([7; 32]));
+        assert_eq!(by_hash.to_block_hash, Some([7; 32]));
+
+        let page = svc
+            .query_logs(built, ExecutionBudget::default())
+            .await
+            .expect("query built request");
+        assert_eq!(page.items.len(), 1);
+    });
+}
+
+#[test]
+fn request_builder_rejects_conflicting_bounds() {
+    let hash_and_number = QueryLogsRequest::builder()
+        .block_hash([7; 32])
+        .from_block(1)
+        .build();
+    assert!(matches!(hash_and_number, Err(Error::InvalidParams(_))));
+
+    let two_from_bounds = QueryLogsRequest::builder()
+        .from_block(1)
+        .from_timestamp(100)
+        .to_block(2)
+        .build();
+    assert!(matches!(two_from_bounds, Err(Error::InvalidParams(_))));
+
+    let missing_to = QueryLogsRequest::builder().from_block(1).build();
+    assert!(matches!(missing_to, Err(Error::InvalidParams(_))));
+}
+
+#[test]
+fn store_log_data_false_returns_logs_without_data() {
+    block_on(async {
+        let blob = InMemoryBlobStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                store_log_data: false,
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            blob.clone(),
+            1,
+        );
+        let full = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let block = || {
+            mk_block(
+                1,
+                [0; 32],
+                vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(1, 11, 21, 1, 1, 1)],
+            )
+        };
+        svc.ingest_finalized_block(block()).await.expect("ingest");
+        full.ingest_finalized_block(block())
+            .await
+            .expect("ingest full");
+
+        let page = query_page(&svc, 1, 1, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query");
+        let expected = query_page(&full, 1, 1, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query full");
+
+        assert_eq!(page.items.len(), 2);
+        for (log, full_log) in page.items.iter().zip(&expected.items) {
+            assert!(log.data().is_empty());
+            assert!(!full_log.data().is_empty());
+            assert_eq!(log.address(), full_log.address());
+            assert_eq!(
+                log.topics().collect::<Vec<_>>(),
+                full_log.topics().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                (log.block_num(), log.tx_idx(), log.log_idx()),
+                (full_log.block_num(), full_log.tx_idx(), full_log.log_idx())
+            );
+        }
+
+        let topic_page = query_page(
+            &svc,
+            1,
+            1,
+            LogFilter {
+                topic0: Some(Clause::One(Topic([11; 32]))),
+                ..Default::default()
+            },
+            10,
+            None,
+        )
+        .await
+        .expect("topic query");
+        assert_eq!(topic_page.items.len(), 1);
+        assert_eq!(topic_page.items[0].log_idx(), 1);
+    });
+}
@@ -331,6 +331,8 @@ fn query_traces_support_is_top_level_only_filter() {
                     to_block: Some(1),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: finalized_history_query::QueryOrder::Ascending,
                     resume_id: None,
                     limit: 10,
@@ -385,6 +387,8 @@ fn query_traces_resolves_block_hash_bounds() {
                     to_block: None,
                     from_block_hash: Some([1; 32]),
                     to_block_hash: Some([1; 32]),
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: finalized_history_query::QueryOrder::Ascending,
                     resume_id: None,
                     limit: 10,
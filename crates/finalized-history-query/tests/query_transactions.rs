@@ -188,6 +188,8 @@ fn query_transactions_support_unfiltered_scan() {
                     to_block: Some(1),
                     from_block_hash: None,
                     to_block_hash: None,
+                    from_timestamp: None,
+                    to_timestamp: None,
                     order: QueryOrder::Ascending,
                     resume_id: None,
                     limit: 10,
@@ -5,10 +5,15 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use bytes::Bytes;
+use finalized_history_query::Config;
+use finalized_history_query::Error;
 use finalized_history_query::api::FinalizedHistoryService;
 use finalized_history_query::core::state::{
     BLOCK_RECORD_TABLE, BlockRecord, BlockRecordSpec, PrimaryWindowRecord,
 };
+use finalized_history_query::ingest::recovery::{
+    RecoveredHead, RecoveryOptions, recover_head_from_block_records,
+};
 use finalized_history_query::kernel::codec::StorageCodec;
 use finalized_history_query::kernel::table_specs::ScannableTableSpec;
 use finalized_history_query::logs::table_specs::LogOpenBitmapPageSpec;
@@ -50,12 +55,17 @@ fn shared_block_record(
 struct CountingMetaStore {
     inner: InMemoryMetaStore,
     open_page_scan_lists: Arc<AtomicUsize>,
+    block_record_gets: Arc<AtomicUsize>,
 }
 
 impl CountingMetaStore {
     fn take_open_page_scan_lists(&self) -> usize {
         self.open_page_scan_lists.swap(0, Ordering::Relaxed)
     }
+
+    fn take_block_record_gets(&self) -> usize {
+        self.block_record_gets.swap(0, Ordering::Relaxed)
+    }
 }
 
 impl MetaStore for CountingMetaStore {
@@ -64,6 +74,9 @@ impl MetaStore for CountingMetaStore {
         table: TableId,
         key: &[u8],
     ) -> finalized_history_query::Result<Option<Record>> {
+        if table == BLOCK_RECORD_TABLE {
+            self.block_record_gets.fetch_add(1, Ordering::Relaxed);
+        }
         self.inner.get(table, key).await
     }
 
@@ -203,3 +216,225 @@ fn ingest_ignores_unpublished_suffix_artifacts_during_recovery() {
         );
     });
 }
+
+#[test]
+fn writer_rebuilds_lost_publication_state_from_block_records() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let blob = InMemoryBlobStore::default();
+        let writer = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+        writer
+            .ingest_finalized_blocks(vec![
+                mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]),
+                mk_block(
+                    2,
+                    [1; 32],
+                    vec![mk_log(1, 10, 20, 2, 0, 0), mk_log(2, 11, 21, 2, 0, 1)],
+                ),
+                mk_block(3, [2; 32], vec![mk_log(3, 12, 22, 3, 0, 0)]),
+            ])
+            .await
+            .expect("ingest three blocks");
+        drop(writer);
+
+        meta.delete(
+            finalized_history_query::store::publication::PUBLICATION_STATE_TABLE,
+            finalized_history_query::store::publication::PUBLICATION_STATE_SUFFIX,
+            DelCond::Any,
+        )
+        .await
+        .expect("delete publication state");
+
+        let recovered = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob,
+            2,
+        );
+        let outcome = recovered
+            .ingest_finalized_block(mk_block(4, [3; 32], vec![mk_log(4, 13, 23, 4, 0, 0)]))
+            .await
+            .expect("ingest continues after recovered head");
+        let status = recovered.status().await.expect("status");
+
+        assert_eq!(outcome.indexed_finalized_head, 4);
+        assert_eq!(status.head_state.indexed_finalized_head, 4);
+        assert_eq!(status.log_state.next_log_id.get(), 5);
+        let page = query_page(&recovered, 1, 4, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query recovered blocks");
+        assert_eq!(page.items.len(), 2);
+    });
+}
+
+#[test]
+fn recovered_head_stops_at_first_broken_parent_link() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let blob = InMemoryBlobStore::default();
+        let writer = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+        writer
+            .ingest_finalized_blocks(vec![
+                mk_block(1, [0; 32], Vec::new()),
+                mk_block(2, [1; 32], Vec::new()),
+            ])
+            .await
+            .expect("ingest two blocks");
+        meta.put(
+            BLOCK_RECORD_TABLE,
+            &BlockRecordSpec::key(3),
+            shared_block_record([3; 32], [9; 32], Some((0, 0)), Some((0, 0))).encode(),
+            PutCond::Any,
+        )
+        .await
+        .expect("seed unlinked block record");
+
+        let recovered = recover_head_from_block_records(&meta, usize::MAX)
+            .await
+            .expect("recover head");
+        assert_eq!(recovered.head, 2);
+        assert!(!recovered.capped);
+    });
+}
+
+/// Ingests blocks 1..=3, rewrites block 3's record with a parent hash that
+/// does not link to block 2, and deletes publication state.
+async fn seed_lost_state_with_broken_link<M: MetaStore>(meta: &M) {
+    let writer = FinalizedHistoryService::new_reader_writer(
+        lease_writer_config(),
+        meta.clone(),
+        InMemoryBlobStore::default(),
+        1,
+    );
+    writer
+        .ingest_finalized_blocks(vec![
+            mk_block(1, [0; 32], Vec::new()),
+            mk_block(2, [1; 32], Vec::new()),
+            mk_block(3, [2; 32], Vec::new()),
+        ])
+        .await
+        .expect("ingest three blocks");
+    drop(writer);
+    meta.put(
+        BLOCK_RECORD_TABLE,
+        &BlockRecordSpec::key(3),
+        shared_block_record([3; 32], [9; 32], Some((0, 0)), Some((0, 0))).encode(),
+        PutCond::Any,
+    )
+    .await
+    .expect("seed unlinked block record");
+    meta.delete(
+        finalized_history_query::store::publication::PUBLICATION_STATE_TABLE,
+        finalized_history_query::store::publication::PUBLICATION_STATE_SUFFIX,
+        DelCond::Any,
+    )
+    .await
+    .expect("delete publication state");
+}
+
+#[test]
+fn fast_recovery_reads_no_block_records_and_restarts_lost_head() {
+    block_on(async {
+        let meta = CountingMetaStore::default();
+        seed_lost_state_with_broken_link(&meta).await;
+        meta.take_block_record_gets();
+
+        let writer = FinalizedHistoryService::new_reader_writer(
+            Config {
+                recovery: RecoveryOptions {
+                    verify_blocks: false,
+                    max_scan: usize::MAX,
+                },
+                ..lease_writer_config()
+            },
+            meta.clone(),
+            InMemoryBlobStore::default(),
+            2,
+        );
+        let outcome = writer
+            .ingest_finalized_block(mk_block(1, [0; 32], Vec::new()))
+            .await
+            .expect("fast mode restarts from block 1");
+
+        assert_eq!(outcome.indexed_finalized_head, 1);
+        assert_eq!(meta.take_block_record_gets(), 0);
+    });
+}
+
+#[test]
+fn thorough_recovery_stops_at_broken_parent_link() {
+    block_on(async {
+        let meta = CountingMetaStore::default();
+        seed_lost_state_with_broken_link(&meta).await;
+        meta.take_block_record_gets();
+
+        let writer = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            InMemoryBlobStore::default(),
+            2,
+        );
+        let outcome = writer
+            .ingest_finalized_block(mk_block(3, [2; 32], Vec::new()))
+            .await
+            .expect("ingest resumes after the verified prefix");
+
+        assert_eq!(outcome.indexed_finalized_head, 3);
+        assert!(meta.take_block_record_gets() >= 3);
+    });
+}
+
+#[test]
+fn thorough_recovery_does_not_publish_a_scan_capped_by_max_scan() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        seed_lost_state_with_broken_link(&meta).await;
+
+        // Blocks 1 and 2 chain, so a one-block scan stops on the cap below the
+        // verified head.
+        let recovered = recover_head_from_block_records(&meta, 1)
+            .await
+            .expect("recover head");
+        assert_eq!(
+            recovered,
+            RecoveredHead {
+                head: 1,
+                capped: true,
+            }
+        );
+
+        let writer = FinalizedHistoryService::new_reader_writer(
+            Config {
+                recovery: RecoveryOptions {
+                    verify_blocks: true,
+                    max_scan: 1,
+                },
+                ..lease_writer_config()
+            },
+            meta,
+            InMemoryBlobStore::default(),
+            2,
+        );
+        let err = writer
+            .ingest_finalized_block(mk_block(2, [1; 32], Vec::new()))
+            .await
+            .expect_err("a capped scan publishes no head");
+        assert!(matches!(err, Error::InvalidSequence { expected: 1, .. }), "got {err}");
+        assert_eq!(writer.indexed_finalized_head().await.expect("head"), 0);
+        writer
+            .ingest_finalized_block(mk_block(1, [0; 32], Vec::new()))
+            .await
+            .expect("ingest restarts from block 1");
+        assert_eq!(writer.indexed_finalized_head().await.expect("head"), 1);
+    });
+}
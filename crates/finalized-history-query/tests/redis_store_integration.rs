@@ -0,0 +1,191 @@
+#![cfg(feature = "redis-store")]
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use finalized_history_query::api::{
+    ExecutionBudget, FinalizedHistoryService, QueryLogsRequest, QueryOrder,
+};
+use finalized_history_query::config::Config;
+use finalized_history_query::logs::types::{Address, Log, Topic};
+use finalized_history_query::store::blob::InMemoryBlobStore;
+use finalized_history_query::store::redis::RedisMetaStore;
+use finalized_history_query::store::traits::{
+    DelCond, MetaStore, PutCond, ScannableTableId, TableId,
+};
+use finalized_history_query::{Clause, EvmBlockHeader, FinalizedBlock, LogFilter};
+
+const REDIS_URL: &str = "redis://127.0.0.1:6379";
+
+fn mk_log(address: u8, topic0: u8, topic1: u8, block_num: u64, tx_idx: u32, log_idx: u32) -> Log {
+    Log {
+        address: Address([address; 20]),
+        topics: vec![Topic([topic0; 32]), Topic([topic1; 32])],
+        data: vec![address, topic0, topic1],
+        block_num,
+        tx_idx,
+        log_idx,
+        block_hash: [block_num as u8; 32],
+    }
+}
+
+fn mk_block(block_num: u64, parent_hash: [u8; 32], logs: Vec<Log>) -> FinalizedBlock {
+    FinalizedBlock {
+        block_num,
+        block_hash: [block_num as u8; 32],
+        parent_hash,
+        header: EvmBlockHeader::minimal(block_num, [block_num as u8; 32], parent_hash),
+        logs,
+        txs: Vec::new(),
+        trace_rlp: Vec::new(),
+    }
+}
+
+async fn fresh_store(label: &str) -> RedisMetaStore {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    RedisMetaStore::new(REDIS_URL, &format!("fhq_it_{label}_{stamp:x}"))
+        .await
+        .expect("connect redis")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn redis_roundtrip_query() {
+    let meta = fresh_store("roundtrip").await;
+    meta.set_min_epoch(1).await.expect("set min epoch");
+    assert_eq!(meta.min_epoch().await.expect("min epoch"), 1);
+
+    let svc = FinalizedHistoryService::new_reader_writer(
+        Config {
+            observe_upstream_finalized_block: Arc::new(|| Some(u64::MAX / 4)),
+            ..Config::default()
+        },
+        meta,
+        InMemoryBlobStore::default(),
+        1,
+    );
+
+    let b1 = mk_block(
+        1,
+        [0; 32],
+        vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 11, 21, 1, 0, 1)],
+    );
+    let b2 = mk_block(
+        2,
+        b1.block_hash,
+        vec![mk_log(1, 10, 22, 2, 0, 0), mk_log(3, 12, 23, 2, 0, 1)],
+    );
+
+    svc.ingest_finalized_block(b1).await.expect("ingest b1");
+    svc.ingest_finalized_block(b2).await.expect("ingest b2");
+
+    let filter = LogFilter {
+        address: Some(Clause::One(Address([1; 20]))),
+        topic0: Some(Clause::One(Topic([10; 32]))),
+        topic1: None,
+        topic2: None,
+        topic3: None,
+    };
+
+    let got = svc
+        .query_logs(
+            QueryLogsRequest {
+                from_block: Some(1),
+                to_block: Some(2),
+                from_block_hash: None,
+                to_block_hash: None,
+                from_timestamp: None,
+                to_timestamp: None,
+                order: QueryOrder::Ascending,
+                resume_id: None,
+                limit: 100,
+                filter,
+            },
+            ExecutionBudget::default(),
+        )
+        .await
+        .expect("query");
+
+    assert_eq!(got.items.len(), 2);
+    assert_eq!(got.items[0].block_num(), 1);
+    assert_eq!(got.items[1].block_num(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn redis_conditional_writes_and_scan_list_follow_the_store_contract() {
+    let meta = fresh_store("cas").await;
+    let table = TableId::new("redis_it_point");
+
+    let first = meta
+        .put(table, b"k", Bytes::from_static(b"a"), PutCond::IfAbsent)
+        .await
+        .expect("put if absent");
+    assert!(first.applied);
+    assert_eq!(first.version, Some(1));
+    let again = meta
+        .put(table, b"k", Bytes::from_static(b"b"), PutCond::IfAbsent)
+        .await
+        .expect("second put if absent");
+    assert!(!again.applied);
+    assert_eq!(again.version, Some(1));
+
+    let stale = meta
+        .put(table, b"k", Bytes::from_static(b"b"), PutCond::IfVersion(7))
+        .await
+        .expect("stale put");
+    assert!(!stale.applied);
+    let bumped = meta
+        .put(table, b"k", Bytes::from_static(b"b"), PutCond::IfVersion(1))
+        .await
+        .expect("versioned put");
+    assert!(bumped.applied);
+    assert_eq!(bumped.version, Some(2));
+
+    let got = meta
+        .multi_get(table, &[b"k".to_vec(), b"missing".to_vec()])
+        .await
+        .expect("multi get");
+    let record = got[0].as_ref().expect("k present");
+    assert_eq!((record.value.as_ref(), record.version), (&b"b"[..], 2));
+    assert!(got[1].is_none());
+
+    meta.delete(table, b"k", DelCond::IfVersion(1))
+        .await
+        .expect("stale delete");
+    assert!(meta.get(table, b"k").await.expect("get").is_some());
+    meta.delete(table, b"k", DelCond::IfVersion(2))
+        .await
+        .expect("delete");
+    assert!(meta.get(table, b"k").await.expect("get").is_none());
+
+    let scannable = ScannableTableId::new("redis_it_scan");
+    for clustering in [&b"p/3"[..], b"p/1", b"q/1", b"p/2", b"p*"] {
+        meta.scan_put(
+            scannable,
+            b"part",
+            clustering,
+            Bytes::from_static(b"x"),
+            PutCond::Any,
+        )
+        .await
+        .expect("scan put");
+    }
+    let first_page = meta
+        .scan_list(scannable, b"part", b"p/", None, 2)
+        .await
+        .expect("first page");
+    assert_eq!(first_page.keys, vec![b"p/1".to_vec(), b"p/2".to_vec()]);
+    let second_page = meta
+        .scan_list(scannable, b"part", b"p/", first_page.next_cursor, 2)
+        .await
+        .expect("second page");
+    assert_eq!(second_page.keys, vec![b"p/3".to_vec()]);
+    assert!(second_page.next_cursor.is_none());
+    let literal = meta
+        .scan_list(scannable, b"part", b"p*", None, 10)
+        .await
+        .expect("literal prefix");
+    assert_eq!(literal.keys, vec![b"p*".to_vec()]);
+}
@@ -0,0 +1,232 @@
+#![cfg(feature = "rpc-adapter")]
+
+#[allow(dead_code, unused_imports)]
+mod helpers;
+
+use finalized_history_query::api::{ExecutionBudget, FinalizedHistoryService};
+use finalized_history_query::rpc::eth_get_logs;
+use finalized_history_query::store::blob::InMemoryBlobStore;
+use finalized_history_query::store::meta::InMemoryMetaStore;
+use finalized_history_query::store::publication::MetaPublicationStore;
+use finalized_history_query::{Error, LeaseAuthority};
+use futures::executor::block_on;
+use serde_json::{Value, json};
+
+use helpers::*;
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::from("0x");
+    for byte in bytes {
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
+
+type Service = FinalizedHistoryService<
+    LeaseAuthority<MetaPublicationStore<InMemoryMetaStore>>,
+    InMemoryMetaStore,
+    InMemoryBlobStore,
+>;
+
+async fn seeded_service() -> Service {
+    let svc = FinalizedHistoryService::new_reader_writer(
+        lease_writer_config(),
+        InMemoryMetaStore::default(),
+        InMemoryBlobStore::default(),
+        1,
+    );
+    svc.ingest_finalized_block(mk_block(
+        1,
+        [0; 32],
+        vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 11, 21, 1, 0, 1)],
+    ))
+    .await
+    .expect("ingest block 1");
+    svc.ingest_finalized_block(mk_block(
+        2,
+        [1; 32],
+        vec![mk_log(1, 12, 22, 2, 0, 0), mk_log(3, 10, 23, 2, 1, 1)],
+    ))
+    .await
+    .expect("ingest block 2");
+    svc
+}
+
+fn positions(result: &Value) -> Vec<(String, String)> {
+    result
+        .as_array()
+        .expect("result array")
+        .iter()
+        .map(|log| {
+            (
+                log["blockNumber"].as_str().expect("blockNumber").to_owned(),
+                log["logIndex"].as_str().expect("logIndex").to_owned(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn eth_get_logs_filters_by_single_address() {
+    block_on(async {
+        let svc = seeded_service().await;
+
+        let result = eth_get_logs(
+            &svc,
+            &json!({ "fromBlock": "0x1", "toBlock": "latest", "address": hex(&[1; 20]) }),
+            ExecutionBudget::default(),
+        )
+        .await
+        .expect("eth_getLogs");
+
+        assert_eq!(
+            positions(&result),
+            vec![
+                ("0x1".to_owned(), "0x0".to_owned()),
+                ("0x2".to_owned(), "0x0".to_owned())
+            ]
+        );
+        let first = &result[0];
+        assert_eq!(first["address"], json!(hex(&[1; 20])));
+        assert_eq!(first["topics"], json!([hex(&[10; 32]), hex(&[20; 32])]));
+        assert_eq!(first["data"], json!("0x010a14"));
+        assert_eq!(first["blockHash"], json!(hex(&[1; 32])));
+        assert_eq!(first["transactionIndex"], json!("0x0"));
+        assert_eq!(first["removed"], json!(false));
+    });
+}
+
+#[test]
+fn eth_get_logs_treats_topic_arrays_as_or() {
+    block_on(async {
+        let svc = seeded_service().await;
+
+        let result = eth_get_logs(
+            &svc,
+            &json!({
+                "fromBlock": "earliest",
+                "toBlock": "0x2",
+                "topics": [[hex(&[11; 32]), hex(&[12; 32])]],
+            }),
+            ExecutionBudget::default(),
+        )
+        .await
+        .expect("eth_getLogs");
+
+        assert_eq!(
+            positions(&result),
+            vec![
+                ("0x1".to_owned(), "0x1".to_owned()),
+                ("0x2".to_owned(), "0x0".to_owned())
+            ]
+        );
+    });
+}
+
+#[test]
+fn eth_get_logs_treats_null_topic_as_wildcard() {
+    block_on(async {
+        let svc = seeded_service().await;
+
+        let result = eth_get_logs(
+            &svc,
+            &json!({ "fromBlock": "0x1", "topics": [null, hex(&[23; 32])] }),
+            ExecutionBudget::default(),
+        )
+        .await
+        .expect("eth_getLogs");
+
+        assert_eq!(
+            positions(&result),
+            vec![("0x2".to_owned(), "0x1".to_owned())]
+        );
+    });
+}
+
+#[test]
+fn eth_get_logs_rejects_block_hash_with_range() {
+    block_on(async {
+        let svc = seeded_service().await;
+
+        let err = eth_get_logs(
+            &svc,
+            &json!({ "blockHash": hex(&[1; 32]), "fromBlock": "0x1" }),
+            ExecutionBudget::default(),
+        )
+        .await
+        .expect_err("blockHash with fromBlock");
+
+        assert!(matches!(err, Error::InvalidParams(_)));
+    });
+}
+
+#[test]
+fn eth_get_logs_fails_instead_of_truncating_past_budget() {
+    block_on(async {
+        let svc = seeded_service().await;
+
+        let err = eth_get_logs(
+            &svc,
+            &json!({ "fromBlock": "0x1", "toBlock": "0x2" }),
+            ExecutionBudget {
+                max_results: Some(3),
+                max_result_bytes: None,
+            },
+        )
+        .await
+        .expect_err("over budget");
+
+        assert!(matches!(err, Error::InvalidParams(_)));
+    });
+}
+
+#[test]
+fn eth_get_logs_by_block_hash_without_filter_returns_the_whole_block() {
+    block_on(async {
+        let svc = seeded_service().await;
+
+        let result = eth_get_logs(
+            &svc,
+            &json!({ "blockHash": hex(&[2; 32]) }),
+            ExecutionBudget::default(),
+        )
+        .await
+        .expect("eth_getLogs");
+
+        assert_eq!(
+            positions(&result),
+            vec![
+                ("0x2".to_owned(), "0x0".to_owned()),
+                ("0x2".to_owned(), "0x1".to_owned())
+            ]
+        );
+    });
+}
+
+#[test]
+fn eth_get_logs_rejects_mis_sized_values_by_position() {
+    block_on(async {
+        let svc = seeded_service().await;
+
+        let address_in_topic = json!({
+            "fromBlock": "0x1",
+            "topics": [null, [hex(&[20; 32]), hex(&[1; 20])]],
+        });
+        let err = eth_get_logs(&svc, &address_in_topic, ExecutionBudget::default())
+            .await
+            .expect_err("20-byte topic");
+        assert!(matches!(
+            err,
+            Error::InvalidParams("invalid topic at position 1: expected 32-byte hex")
+        ));
+
+        let topic_as_address = json!({ "fromBlock": "0x1", "address": [hex(&[10; 32])] });
+        let err = eth_get_logs(&svc, &topic_as_address, ExecutionBudget::default())
+            .await
+            .expect_err("32-byte address");
+        assert!(matches!(
+            err,
+            Error::InvalidParams("invalid address: expected 20-byte hex")
+        ));
+    });
+}
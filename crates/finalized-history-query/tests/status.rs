@@ -1,14 +1,20 @@
 #[allow(dead_code, unused_imports)]
 mod helpers;
 
+use bytes::Bytes;
 use finalized_history_query::Config;
 use finalized_history_query::Error;
+use finalized_history_query::Result;
 use finalized_history_query::api::FinalizedHistoryService;
+use finalized_history_query::core::ids::LogShard;
 use finalized_history_query::core::state::{
     BLOCK_RECORD_TABLE, BlockRecord, BlockRecordSpec, PrimaryWindowRecord,
 };
 use finalized_history_query::family::Families;
 use finalized_history_query::kernel::codec::StorageCodec;
+use finalized_history_query::kernel::sharded_streams::{StreamKind, sharded_stream_id};
+use finalized_history_query::kernel::table_specs::ScannableTableSpec;
+use finalized_history_query::logs::table_specs::LogBitmapByBlockSpec;
 use finalized_history_query::status::service_status;
 use finalized_history_query::store::blob::InMemoryBlobStore;
 use finalized_history_query::store::meta::InMemoryMetaStore;
@@ -16,9 +22,12 @@ use finalized_history_query::store::publication::{MetaPublicationStore, Publicat
 use finalized_history_query::store::publication::{
     PUBLICATION_STATE_SUFFIX, PUBLICATION_STATE_TABLE,
 };
-use finalized_history_query::store::traits::{MetaStore, PutCond};
+use finalized_history_query::store::traits::{
+    DelCond, MetaStore, Page, PutCond, PutResult, Record, ScannableTableId, TableId,
+};
 use futures::executor::block_on;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use helpers::*;
 
@@ -204,3 +213,467 @@ fn status_rejects_missing_trace_records_for_nonzero_published_head() {
         assert!(matches!(err, finalized_history_query::Error::NotFound));
     });
 }
+
+#[test]
+fn health_detailed_reflects_ingested_head_and_store_probe() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            5,
+        );
+        svc.ingest_finalized_block(mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]))
+            .await
+            .expect("ingest block 1");
+        svc.ingest_finalized_block(mk_block(
+            2,
+            [1; 32],
+            vec![mk_log(1, 10, 20, 2, 0, 0), mk_log(2, 11, 21, 2, 0, 1)],
+        ))
+        .await
+        .expect("ingest block 2");
+
+        let health = svc.health_detailed().await.expect("health");
+        let publication = health.publication.expect("publication state");
+
+        assert_eq!(health.indexed_finalized_head, Some(2));
+        assert_eq!(health.next_log_id.map(|id| id.get()), Some(3));
+        assert_eq!(publication.owner_id, 5);
+        assert!(publication.lease_valid_through_block >= 2);
+        assert_eq!(health.consecutive_backend_errors, 0);
+        assert!(health.store_probe.reachable);
+    });
+}
+
+#[test]
+fn corrupt_bitmap_fragment_is_a_codec_error_not_a_backend_error() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]))
+            .await
+            .expect("ingest block 1");
+
+        let sid = sharded_stream_id(StreamKind::Addr, &[1; 20], LogShard::new(0).unwrap().get());
+        let partition = LogBitmapByBlockSpec::partition(&sid, 0);
+        let clustering = LogBitmapByBlockSpec::clustering(1);
+        let fragment = meta
+            .scan_get(LogBitmapByBlockSpec::TABLE, &partition, &clustering)
+            .await
+            .expect("load fragment")
+            .expect("fragment present");
+        let truncated = fragment.value.slice(..fragment.value.len() - 1);
+        meta.scan_put(
+            LogBitmapByBlockSpec::TABLE,
+            &partition,
+            &clustering,
+            truncated,
+            PutCond::Any,
+        )
+        .await
+        .expect("corrupt fragment");
+
+        // Crossing the first page boundary seals page 0 and decodes its fragments.
+        let logs = (0..4_096u32)
+            .map(|log_idx| mk_log(2, 10, 20, 2, 0, log_idx))
+            .collect();
+        let err = svc
+            .ingest_finalized_block(mk_block(2, [1; 32], logs))
+            .await
+            .expect_err("sealing a corrupt fragment should fail");
+
+        assert!(matches!(err, Error::Codec(_)), "got {err}");
+        let health = svc.health_detailed().await.expect("health");
+        assert_eq!(health.indexed_finalized_head, Some(1));
+        assert_eq!(health.consecutive_backend_errors, 0);
+    });
+}
+
+/// Meta store that fails every call with a backend error while `down` is set,
+/// and every block record read while `block_records_down` is set.
+#[derive(Clone, Default)]
+struct SwitchableMetaStore {
+    inner: InMemoryMetaStore,
+    down: Arc<AtomicBool>,
+    block_records_down: Arc<AtomicBool>,
+}
+
+impl SwitchableMetaStore {
+    fn check(&self) -> Result<()> {
+        if self.down.load(Ordering::Relaxed) {
+            return Err(Error::Backend("meta store down".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl MetaStore for SwitchableMetaStore {
+    async fn get(&self, table: TableId, key: &[u8]) -> Result<Option<Record>> {
+        self.check()?;
+        if table == BLOCK_RECORD_TABLE && self.block_records_down.load(Ordering::Relaxed) {
+            return Err(Error::Backend("block records down".to_string()));
+        }
+        self.inner.get(table, key).await
+    }
+
+    async fn put(
+        &self,
+        table: TableId,
+        key: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> Result<PutResult> {
+        self.check()?;
+        self.inner.put(table, key, value, cond).await
+    }
+
+    async fn delete(&self, table: TableId, key: &[u8], cond: DelCond) -> Result<()> {
+        self.check()?;
+        self.inner.delete(table, key, cond).await
+    }
+
+    async fn scan_get(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+    ) -> Result<Option<Record>> {
+        self.check()?;
+        self.inner.scan_get(table, partition, clustering).await
+    }
+
+    async fn scan_put(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        value: Bytes,
+        cond: PutCond,
+    ) -> Result<PutResult> {
+        self.check()?;
+        self.inner
+            .scan_put(table, partition, clustering, value, cond)
+            .await
+    }
+
+    async fn scan_delete(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        clustering: &[u8],
+        cond: DelCond,
+    ) -> Result<()> {
+        self.check()?;
+        self.inner
+            .scan_delete(table, partition, clustering, cond)
+            .await
+    }
+
+    async fn scan_list(
+        &self,
+        table: ScannableTableId,
+        partition: &[u8],
+        prefix: &[u8],
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> Result<Page> {
+        self.check()?;
+        self.inner
+            .scan_list(table, partition, prefix, cursor, limit)
+            .await
+    }
+}
+
+#[test]
+fn try_recover_resets_backend_error_streak_once_probe_succeeds() {
+    block_on(async {
+        let meta = SwitchableMetaStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]))
+            .await
+            .expect("ingest block 1");
+
+        meta.down.store(true, Ordering::Relaxed);
+        for _ in 0..2 {
+            let err = svc
+                .ingest_finalized_block(mk_block(2, [1; 32], vec![mk_log(1, 10, 20, 2, 0, 0)]))
+                .await
+                .expect_err("meta store down");
+            assert!(matches!(err, Error::Backend(_)), "got {err}");
+        }
+        assert!(!svc.try_recover().await.expect("failed probe"));
+
+        meta.down.store(false, Ordering::Relaxed);
+        let health = svc.health_detailed().await.expect("health");
+        assert_eq!(health.consecutive_backend_errors, 2);
+
+        assert!(svc.try_recover().await.expect("probe"));
+        let health = svc.health_detailed().await.expect("health");
+        assert_eq!(health.consecutive_backend_errors, 0);
+        svc.ingest_finalized_block(mk_block(2, [1; 32], vec![mk_log(1, 10, 20, 2, 0, 0)]))
+            .await
+            .expect("ingest block 2 after recovery");
+    });
+}
+
+#[test]
+fn health_detailed_reports_failing_meta_store_as_unreachable() {
+    block_on(async {
+        let meta = SwitchableMetaStore::default();
+        let writer = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        writer
+            .ingest_finalized_block(mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]))
+            .await
+            .expect("ingest block 1");
+        // A fresh reader has no cached block record, so the head is read from
+        // the store.
+        let svc = FinalizedHistoryService::new_reader_only(
+            lease_writer_config(),
+            meta.clone(),
+            InMemoryBlobStore::default(),
+        );
+
+        meta.block_records_down.store(true, Ordering::Relaxed);
+        let health = svc.health_detailed().await.expect("health");
+        assert!(!health.store_probe.reachable);
+        assert!(health.publication.is_some());
+        assert_eq!(health.indexed_finalized_head, None);
+        assert_eq!(health.next_log_id, None);
+
+        meta.down.store(true, Ordering::Relaxed);
+        let health = svc.health_detailed().await.expect("health");
+        assert!(!health.store_probe.reachable);
+        assert!(health.publication.is_none());
+        assert_eq!(health.indexed_finalized_head, None);
+        assert_eq!(health.next_log_id, None);
+
+        meta.down.store(false, Ordering::Relaxed);
+        meta.block_records_down.store(false, Ordering::Relaxed);
+        let health = svc.health_detailed().await.expect("health");
+        assert!(health.store_probe.reachable);
+        assert_eq!(health.indexed_finalized_head, Some(1));
+    });
+}
+
+#[test]
+fn try_recover_keeps_missing_range_from_sequence_rejection() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        svc.ingest_finalized_block(mk_block(1, [0; 32], vec![mk_log(1, 10, 20, 1, 0, 0)]))
+            .await
+            .expect("ingest block 1");
+        let err = svc
+            .ingest_finalized_block(mk_block(3, [2; 32], vec![mk_log(1, 10, 20, 3, 0, 0)]))
+            .await
+            .expect_err("block gap");
+        assert!(matches!(err, Error::InvalidSequence { .. }), "got {err}");
+
+        assert!(svc.try_recover().await.expect("probe"));
+        assert_eq!(
+            svc.missing_range().await.expect("missing range"),
+            Some((2, 2))
+        );
+    });
+}
+
+#[test]
+fn latency_snapshot_tracks_queries_and_ingest() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        assert_eq!(svc.latency_snapshot().query.count, 0);
+
+        for block_num in 1..=3 {
+            svc.ingest_finalized_block(mk_block(
+                block_num,
+                if block_num == 1 {
+                    [0; 32]
+                } else {
+                    [(block_num - 1) as u8; 32]
+                },
+                vec![mk_log(1, 10, 20, block_num, 0, 0)],
+            ))
+            .await
+            .expect("ingest block");
+        }
+        for _ in 0..5 {
+            query_page(&svc, 1, 3, indexed_address_filter(1), 10, None)
+                .await
+                .expect("query logs");
+        }
+
+        let latency = svc.latency_snapshot();
+        assert_eq!(latency.ingest.count, 3);
+        assert_eq!(latency.query.count, 5);
+        for snapshot in [latency.query, latency.ingest] {
+            assert!(snapshot.p50 > std::time::Duration::ZERO);
+            assert!(snapshot.p50 <= snapshot.p95);
+            assert!(snapshot.p95 <= snapshot.p99);
+            assert!(snapshot.p99 <= snapshot.max);
+        }
+    });
+}
+
+#[test]
+fn latency_store_slows_queries_without_changing_results() {
+    block_on(async {
+        let base = std::time::Duration::from_millis(1);
+        let jitter = std::time::Duration::from_micros(500);
+        let meta = LatencyStore::new(InMemoryMetaStore::default(), base, jitter);
+        let blob = LatencyStore::new(InMemoryBlobStore::default(), base, jitter);
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+
+        for block_num in 1..=3 {
+            svc.ingest_finalized_block(mk_block(
+                block_num,
+                [(block_num - 1) as u8; 32],
+                vec![
+                    mk_log(1, 10, 20, block_num, 0, 0),
+                    mk_log(2, 10, 20, block_num, 0, 1),
+                ],
+            ))
+            .await
+            .expect("ingest block");
+        }
+
+        let ops_before = meta.ops.load(Ordering::Relaxed) + blob.ops.load(Ordering::Relaxed);
+        let page = query_page(&svc, 1, 3, indexed_address_filter(1), 10, None)
+            .await
+            .expect("query logs");
+        let ops_after = meta.ops.load(Ordering::Relaxed) + blob.ops.load(Ordering::Relaxed);
+        assert_eq!(
+            page.items
+                .iter()
+                .map(|log| (log.block_num(), log.log_idx()))
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (2, 0), (3, 0)]
+        );
+        assert!(ops_after > ops_before);
+
+        let latency = svc.latency_snapshot();
+        assert_eq!(latency.query.count, 1);
+        assert!(latency.query.p50 >= base * (ops_after - ops_before) as u32);
+        assert!(latency.ingest.p50 >= base);
+    });
+}
+
+#[test]
+fn range_size_estimate_tracks_stored_bytes() {
+    block_on(async {
+        let meta = InMemoryMetaStore::default();
+        let blob = InMemoryBlobStore::default();
+        let svc = FinalizedHistoryService::new_reader_writer(
+            lease_writer_config(),
+            meta.clone(),
+            blob.clone(),
+            1,
+        );
+        let mut parent = [0; 32];
+        for block_num in 1..=120u64 {
+            let logs = (0..40u32)
+                .map(|i| {
+                    let mut log = mk_log((i % 7) as u8, (i % 3) as u8, i as u8, block_num, i, i);
+                    log.data = vec![i as u8; 64];
+                    log
+                })
+                .collect();
+            svc.ingest_finalized_block(mk_block(block_num, parent, logs))
+                .await
+                .expect("ingest");
+            parent = [block_num as u8; 32];
+        }
+
+        let full = svc.estimate_range_bytes(1, 500).await.expect("estimate");
+        assert_eq!(full.blocks, 120);
+        assert_eq!(full.logs, 4_800);
+        assert!(full.stream_bytes > 0 && full.meta_record_bytes > 0);
+
+        let stored =
+            meta.stored_bytes().expect("meta bytes") + blob.stored_bytes().expect("blob bytes");
+        let ratio = full.total_bytes() as f64 / stored as f64;
+        assert!(
+            (0.5..=1.0).contains(&ratio),
+            "estimate {} vs stored {stored}",
+            full.total_bytes()
+        );
+
+        let half = svc
+            .estimate_range_bytes(1, 60)
+            .await
+            .expect("half estimate");
+        assert_eq!(half.logs, 2_400);
+        let half_ratio = half.total_bytes() as f64 / full.total_bytes() as f64;
+        assert!((0.4..=0.6).contains(&half_ratio), "half ratio {half_ratio}");
+
+        assert_eq!(
+            svc.estimate_range_bytes(121, 130).await.expect("past head"),
+            Default::default()
+        );
+        assert!(matches!(
+            svc.estimate_range_bytes(5, 4).await,
+            Err(Error::InvalidParams(_))
+        ));
+    });
+}
+
+#[test]
+fn range_size_estimate_rejects_ranges_over_max_log_id_range_span() {
+    block_on(async {
+        let svc = FinalizedHistoryService::new_reader_writer(
+            Config {
+                max_log_id_range_span: 5,
+                ..lease_writer_config()
+            },
+            InMemoryMetaStore::default(),
+            InMemoryBlobStore::default(),
+            1,
+        );
+        let mut parent = [0; 32];
+        for block_num in 1..=3u64 {
+            let logs = (0..2u32)
+                .map(|i| mk_log(1, 2, 3, block_num, i, i))
+                .collect();
+            svc.ingest_finalized_block(mk_block(block_num, parent, logs))
+                .await
+                .expect("ingest");
+            parent = [block_num as u8; 32];
+        }
+
+        let within = svc.estimate_range_bytes(1, 2).await.expect("estimate");
+        assert_eq!(within.logs, 4);
+        assert!(matches!(
+            svc.estimate_range_bytes(1, 3).await,
+            Err(Error::InvalidParams(_))
+        ));
+    });
+}
@@ -0,0 +1,96 @@
+use finalized_history_query::kernel::sharded_streams::{
+    StreamKind, parse_stream_id, parse_stream_shard, sharded_stream_id, split_stream_page_key,
+};
+use finalized_history_query::kernel::table_specs::stream_page_key;
+
+#[test]
+fn stream_ids_roundtrip_across_kinds_and_value_lengths() {
+    let values: [&[u8]; 5] = [&[], &[0x00], &[0x2f, 0x2f], &[0xab; 20], &[0xff; 32]];
+    for kind in StreamKind::ALL {
+        for value in values {
+            for shard in [0, 1, 0xff, (1u64 << 40) - 1] {
+                let stream_id = sharded_stream_id(kind, value, shard);
+                assert_eq!(
+                    parse_stream_id(&stream_id),
+                    Some((kind, value.to_vec(), shard)),
+                    "{stream_id}"
+                );
+                assert_eq!(parse_stream_shard(&stream_id), Some(shard));
+            }
+        }
+    }
+}
+
+#[test]
+fn parse_stream_id_rejects_strings_sharded_stream_id_never_emits() {
+    // `0x2f` is `/`, but a value is always hex-encoded, so its bytes never
+    // add a separator.
+    let slash_value = sharded_stream_id(StreamKind::Addr, b"/", 2);
+    assert_eq!(slash_value, "addr/2f/0000000002");
+    assert_eq!(
+        parse_stream_id(&slash_value),
+        Some((StreamKind::Addr, b"/".to_vec(), 2))
+    );
+
+    for malformed in [
+        "addr",
+        "addr/0000000002",
+        "/2f/0000000002",
+        "addr/2/0000000002",
+        "addr/2F/0000000002",
+        "addr/+f/0000000002",
+        "addr/2f/2",
+        "addr/2f/+000000002",
+        "addr/2f/00000000002",
+        "addr/2f/0000000002/",
+        "addr/2f/aa/0000000002",
+        "tx_from/2f/0000000002",
+        "Addr/2f/0000000002",
+    ] {
+        assert_eq!(parse_stream_id(malformed), None, "{malformed}");
+    }
+}
+
+#[test]
+fn stream_kinds_keep_their_stored_ids() {
+    // Every kind is written into stored stream IDs and page blob keys, so a
+    // renamed kind would silently start a parallel empty stream.
+    let expected = [
+        (StreamKind::Addr, "addr/ab/0000000003"),
+        (StreamKind::Topic0, "topic0/ab/0000000003"),
+        (StreamKind::Topic1, "topic1/ab/0000000003"),
+        (StreamKind::Topic2, "topic2/ab/0000000003"),
+        (StreamKind::Topic3, "topic3/ab/0000000003"),
+        (StreamKind::From, "from/ab/0000000003"),
+        (StreamKind::To, "to/ab/0000000003"),
+        (StreamKind::Selector, "selector/ab/0000000003"),
+        (StreamKind::HasValue, "has_value/ab/0000000003"),
+    ];
+    assert_eq!(
+        expected.map(|(kind, _)| kind),
+        StreamKind::ALL,
+        "every kind is listed"
+    );
+    for (kind, stream_id) in expected {
+        assert_eq!(sharded_stream_id(kind, &[0xab], 3), stream_id);
+        assert_eq!(StreamKind::parse(kind.as_str()), Some(kind));
+    }
+}
+
+#[test]
+fn stream_page_keys_split_back_into_stream_and_page_start() {
+    let stream_id = sharded_stream_id(StreamKind::Topic0, &[0x2f; 4], 7);
+    for page_start in [0, 4_096, u32::MAX] {
+        let key = stream_page_key(&stream_id, page_start);
+        assert_eq!(
+            split_stream_page_key(&key),
+            Some((stream_id.as_str(), page_start))
+        );
+    }
+
+    let mut past_u32 = format!("{stream_id}/").into_bytes();
+    past_u32.extend_from_slice(&(u64::from(u32::MAX) + 1).to_be_bytes());
+    for malformed in [&b"short"[..], &stream_id.as_bytes()[..], &past_u32[..]] {
+        assert_eq!(split_stream_page_key(malformed), None);
+    }
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "tracing")]
+
+#[allow(dead_code, unused_imports)]
+mod helpers;
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use finalized_history_query::api::FinalizedHistoryService;
+use finalized_history_query::store::blob::InMemoryBlobStore;
+use finalized_history_query::store::meta::InMemoryMetaStore;
+use futures::executor::block_on;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use helpers::*;
+
+/// Captures the creation-time fields of every span, keyed by span name.
+#[derive(Clone, Default)]
+struct SpanCapture {
+    spans: Arc<Mutex<Vec<(&'static str, Vec<(String, String)>)>>>,
+}
+
+struct FieldVisitor(Vec<(String, String)>);
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{value:?}")));
+    }
+}
+
+impl Subscriber for SpanCapture {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut visitor = FieldVisitor(Vec::new());
+        span.record(&mut visitor);
+        let mut spans = self.spans.lock().expect("span capture lock");
+        spans.push((span.metadata().name(), visitor.0));
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn ingest_emits_block_span_with_block_num() {
+    let capture = SpanCapture::default();
+    tracing::subscriber::with_default(capture.clone(), || {
+        block_on(async {
+            let svc = FinalizedHistoryService::new_reader_writer(
+                lease_writer_config(),
+                InMemoryMetaStore::default(),
+                InMemoryBlobStore::default(),
+                1,
+            );
+            svc.ingest_finalized_block(mk_block(
+                1,
+                [0; 32],
+                vec![mk_log(1, 10, 20, 1, 0, 0), mk_log(2, 10, 21, 1, 0, 1)],
+            ))
+            .await
+            .expect("ingest block 1");
+        });
+    });
+
+    let spans = capture.spans.lock().expect("span capture lock");
+    let (_, fields) = spans
+        .iter()
+        .find(|(name, _)| *name == "ingest_block")
+        .expect("ingest_block span");
+    assert!(fields.contains(&("block_num".to_string(), "1".to_string())));
+    assert!(fields.contains(&("logs".to_string(), "2".to_string())));
+}
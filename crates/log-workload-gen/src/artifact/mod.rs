@@ -2,14 +2,17 @@ mod manifest;
 mod parquet;
 mod trace;
 
+use crate::config::ParquetCompression;
 use crate::error::Error;
 use crate::stats::{CooccurrenceRow, KeyStatsRow, RangeStatsRow};
 use crate::types::DatasetManifest;
 use std::fs;
 use std::path::Path;
 
-pub use parquet::ParquetStats;
-pub use trace::write_trace_jsonl;
+pub use parquet::{KeyStatsProjection, ParquetStats};
+pub use trace::{
+    combined_trace_file_name, read_trace_jsonl, trace_file_name, write_trace_jsonl,
+};
 
 pub fn write_dataset_artifacts(
     dataset_dir: &Path,
@@ -17,6 +20,7 @@ pub fn write_dataset_artifacts(
     key_stats: &[KeyStatsRow],
     cooccurrence: &[CooccurrenceRow],
     range_stats: &[RangeStatsRow],
+    compression: ParquetCompression,
 ) -> Result<(), Error> {
     let tmp_dir = dataset_dir.with_extension(format!("tmp.{}", std::process::id()));
     if tmp_dir.exists() {
@@ -25,9 +29,14 @@ pub fn write_dataset_artifacts(
     fs::create_dir_all(&tmp_dir).map_err(|e| Error::Io(format!("create tmp dir: {e}")))?;
 
     manifest::write_manifest(&tmp_dir.join("dataset_manifest.json"), manifest)?;
-    parquet::write_key_stats_parquet(&tmp_dir.join("key_stats.parquet"), key_stats)?;
-    parquet::write_cooccurrence_parquet(&tmp_dir.join("cooccurrence.parquet"), cooccurrence)?;
-    parquet::write_range_stats_parquet(&tmp_dir.join("range_stats.parquet"), range_stats)?;
+    let props = parquet::writer_properties(compression);
+    parquet::write_key_stats_parquet(&tmp_dir.join("key_stats.parquet"), key_stats, &props)?;
+    parquet::write_cooccurrence_parquet(
+        &tmp_dir.join("cooccurrence.parquet"),
+        cooccurrence,
+        &props,
+    )?;
+    parquet::write_range_stats_parquet(&tmp_dir.join("range_stats.parquet"), range_stats, &props)?;
 
     fs::rename(&tmp_dir, dataset_dir).map_err(|e| Error::Io(format!("rename dataset dir: {e}")))?;
     Ok(())
@@ -50,3 +59,22 @@ pub fn read_parquet_stats(dataset_dir: &Path) -> Result<ParquetStats, Error> {
         range_stats: parquet::read_range_stats_parquet(&dataset_dir.join("range_stats.parquet"))?,
     })
 }
+
+/// Reads only `key_stats.parquet`, decoding the columns in `projection`.
+pub fn read_key_stats(
+    dataset_dir: &Path,
+    projection: KeyStatsProjection,
+) -> Result<Vec<KeyStatsRow>, Error> {
+    parquet::read_key_stats_parquet_projected(&dataset_dir.join("key_stats.parquet"), projection)
+}
+
+/// Reads what trace generation uses: key stats under
+/// [`KeyStatsProjection::POOL`]. The cooccurrence and range stats files are
+/// not read and come back empty.
+pub fn read_generation_stats(dataset_dir: &Path) -> Result<ParquetStats, Error> {
+    Ok(ParquetStats {
+        key_stats: read_key_stats(dataset_dir, KeyStatsProjection::POOL)?,
+        cooccurrence: Vec::new(),
+        range_stats: Vec::new(),
+    })
+}
@@ -1,10 +1,13 @@
+use crate::config::ParquetCompression;
 use crate::error::Error;
 use crate::stats::{CooccurrenceRow, KeyStatsRow, KeyType, PairType, RangeMetric, RangeStatsRow};
 use arrow::array::{Array, Float64Array, StringArray, UInt64Array};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
-use parquet::arrow::ArrowWriter;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
 use std::fs::File;
 use std::path::Path;
 use std::sync::Arc;
@@ -15,7 +18,11 @@ pub struct ParquetStats {
     pub range_stats: Vec<RangeStatsRow>,
 }
 
-pub fn write_key_stats_parquet(path: &Path, rows: &[KeyStatsRow]) -> Result<(), Error> {
+pub fn write_key_stats_parquet(
+    path: &Path,
+    rows: &[KeyStatsRow],
+    props: &WriterProperties,
+) -> Result<(), Error> {
     let schema = Arc::new(Schema::new(vec![
         Field::new("key_type", DataType::Utf8, false),
         Field::new("key_value", DataType::Utf8, false),
@@ -62,10 +69,14 @@ pub fn write_key_stats_parquet(path: &Path, rows: &[KeyStatsRow]) -> Result<(),
     )
     .map_err(|e| Error::Serialization(format!("build key_stats batch: {e}")))?;
 
-    write_batch(path, schema, batch)
+    write_batch(path, schema, batch, props)
 }
 
-pub fn write_cooccurrence_parquet(path: &Path, rows: &[CooccurrenceRow]) -> Result<(), Error> {
+pub fn write_cooccurrence_parquet(
+    path: &Path,
+    rows: &[CooccurrenceRow],
+    props: &WriterProperties,
+) -> Result<(), Error> {
     let schema = Arc::new(Schema::new(vec![
         Field::new("pair_type", DataType::Utf8, false),
         Field::new("left_key", DataType::Utf8, false),
@@ -106,10 +117,14 @@ pub fn write_cooccurrence_parquet(path: &Path, rows: &[CooccurrenceRow]) -> Resu
     )
     .map_err(|e| Error::Serialization(format!("build cooccurrence batch: {e}")))?;
 
-    write_batch(path, schema, batch)
+    write_batch(path, schema, batch, props)
 }
 
-pub fn write_range_stats_parquet(path: &Path, rows: &[RangeStatsRow]) -> Result<(), Error> {
+pub fn write_range_stats_parquet(
+    path: &Path,
+    rows: &[RangeStatsRow],
+    props: &WriterProperties,
+) -> Result<(), Error> {
     let schema = Arc::new(Schema::new(vec![
         Field::new("metric", DataType::Utf8, false),
         Field::new("bucket_lower", DataType::UInt64, false),
@@ -144,43 +159,129 @@ pub fn write_range_stats_parquet(path: &Path, rows: &[RangeStatsRow]) -> Result<
     )
     .map_err(|e| Error::Serialization(format!("build range_stats batch: {e}")))?;
 
-    write_batch(path, schema, batch)
+    write_batch(path, schema, batch, props)
+}
+
+/// Columns of `key_stats.parquet` a read decodes beyond `key_type` and
+/// `key_value`, which are always read. Rows from a projected read carry zero
+/// or `None` in every column left out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KeyStatsProjection {
+    pub count_total: bool,
+    pub first_block: bool,
+    pub last_block: bool,
+    pub active_block_count: bool,
+    pub distinct_partner_estimate: bool,
+}
+
+impl KeyStatsProjection {
+    pub const ALL: Self = Self {
+        count_total: true,
+        first_block: true,
+        last_block: true,
+        active_block_count: true,
+        distinct_partner_estimate: true,
+    };
+
+    /// `key_type` and `key_value` only.
+    pub const KEYS: Self = Self {
+        count_total: false,
+        first_block: false,
+        last_block: false,
+        active_block_count: false,
+        distinct_partner_estimate: false,
+    };
+
+    /// What trace generation reads: keys plus the `count_total` pool weights.
+    pub const POOL: Self = Self {
+        count_total: true,
+        ..Self::KEYS
+    };
+
+    fn column_names(self) -> Vec<&'static str> {
+        let mut names = vec!["key_type", "key_value"];
+        for (included, name) in [
+            (self.count_total, "count_total"),
+            (self.first_block, "first_block"),
+            (self.last_block, "last_block"),
+            (self.active_block_count, "active_block_count"),
+            (self.distinct_partner_estimate, "distinct_partner_estimate"),
+        ] {
+            if included {
+                names.push(name);
+            }
+        }
+        names
+    }
 }
 
 pub fn read_key_stats_parquet(path: &Path) -> Result<Vec<KeyStatsRow>, Error> {
-    let reader = ParquetRecordBatchReaderBuilder::try_new(
+    read_key_stats_parquet_projected(path, KeyStatsProjection::ALL)
+}
+
+/// Reads `key_stats.parquet`, decoding only the columns in `projection`.
+/// Unprojected column chunks are skipped by the parquet reader.
+pub fn read_key_stats_parquet_projected(
+    path: &Path,
+    projection: KeyStatsProjection,
+) -> Result<Vec<KeyStatsRow>, Error> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(
         File::open(path).map_err(|e| Error::Io(format!("open key_stats parquet: {e}")))?,
     )
-    .map_err(|e| Error::Serialization(format!("build key_stats reader: {e}")))?
-    .build()
-    .map_err(|e| Error::Serialization(format!("open key_stats batch reader: {e}")))?;
+    .map_err(|e| Error::Serialization(format!("build key_stats reader: {e}")))?;
+    let names = projection.column_names();
+    let indices = builder
+        .schema()
+        .fields()
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| names.contains(&field.name().as_str()))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    if indices.len() != names.len() {
+        return Err(Error::Serialization(format!(
+            "key_stats parquet is missing projected columns {names:?}"
+        )));
+    }
+    let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+    let reader = builder
+        .with_projection(mask)
+        .build()
+        .map_err(|e| Error::Serialization(format!("open key_stats batch reader: {e}")))?;
 
     let mut out = Vec::new();
     for batch in reader {
         let batch =
             batch.map_err(|e| Error::Serialization(format!("read key_stats batch: {e}")))?;
-        let key_type = str_col(&batch, 0)?;
-        let key_value = str_col(&batch, 1)?;
-        let count_total = u64_col(&batch, 2)?;
-        let first_block = u64_col(&batch, 3)?;
-        let last_block = u64_col(&batch, 4)?;
-        let active_block_count = u64_col(&batch, 5)?;
-        let distinct = f64_opt_col(&batch, 6)?;
+        let key_type = str_col(&batch, named_col(&batch, "key_type")?)?;
+        let key_value = str_col(&batch, named_col(&batch, "key_value")?)?;
+        let optional_u64 = |included: bool, name: &str| -> Result<Option<&UInt64Array>, Error> {
+            included
+                .then(|| u64_col(&batch, named_col(&batch, name)?))
+                .transpose()
+        };
+        let count_total = optional_u64(projection.count_total, "count_total")?;
+        let first_block = optional_u64(projection.first_block, "first_block")?;
+        let last_block = optional_u64(projection.last_block, "last_block")?;
+        let active_block_count = optional_u64(projection.active_block_count, "active_block_count")?;
+        let distinct = projection
+            .distinct_partner_estimate
+            .then(|| f64_opt_col(&batch, named_col(&batch, "distinct_partner_estimate")?))
+            .transpose()?;
+        let value_at = |col: Option<&UInt64Array>, i: usize| col.map_or(0, |col| col.value(i));
 
         for i in 0..batch.num_rows() {
             out.push(KeyStatsRow {
                 key_type: str_to_key_type(key_type.value(i))?,
                 key_value: hex::decode(key_value.value(i))
                     .map_err(|e| Error::Serialization(format!("decode key_value hex: {e}")))?,
-                count_total: count_total.value(i),
-                first_block: first_block.value(i),
-                last_block: last_block.value(i),
-                active_block_count: active_block_count.value(i),
-                distinct_partner_estimate: if distinct.is_null(i) {
-                    None
-                } else {
-                    Some(distinct.value(i))
-                },
+                count_total: value_at(count_total, i),
+                first_block: value_at(first_block, i),
+                last_block: value_at(last_block, i),
+                active_block_count: value_at(active_block_count, i),
+                distinct_partner_estimate: distinct
+                    .filter(|distinct| !distinct.is_null(i))
+                    .map(|distinct| distinct.value(i)),
             });
         }
     }
@@ -257,9 +358,25 @@ pub fn read_range_stats_parquet(path: &Path) -> Result<Vec<RangeStatsRow>, Error
     Ok(out)
 }
 
-fn write_batch(path: &Path, schema: Arc<Schema>, batch: RecordBatch) -> Result<(), Error> {
+pub fn writer_properties(compression: ParquetCompression) -> WriterProperties {
+    let compression = match compression {
+        ParquetCompression::Snappy => Compression::SNAPPY,
+        ParquetCompression::Zstd => Compression::ZSTD(ZstdLevel::default()),
+        ParquetCompression::None => Compression::UNCOMPRESSED,
+    };
+    WriterProperties::builder()
+        .set_compression(compression)
+        .build()
+}
+
+fn write_batch(
+    path: &Path,
+    schema: Arc<Schema>,
+    batch: RecordBatch,
+    props: &WriterProperties,
+) -> Result<(), Error> {
     let file = File::create(path).map_err(|e| Error::Io(format!("create parquet file: {e}")))?;
-    let mut writer = ArrowWriter::try_new(file, schema, None)
+    let mut writer = ArrowWriter::try_new(file, schema, Some(props.clone()))
         .map_err(|e| Error::Serialization(format!("create parquet writer: {e}")))?;
     writer
         .write(&batch)
@@ -278,6 +395,13 @@ fn str_col(batch: &RecordBatch, idx: usize) -> Result<&StringArray, Error> {
         .ok_or_else(|| Error::InternalInvariant(format!("expected utf8 at col {idx}")))
 }
 
+fn named_col(batch: &RecordBatch, name: &str) -> Result<usize, Error> {
+    batch
+        .schema()
+        .index_of(name)
+        .map_err(|_| Error::InternalInvariant(format!("missing column {name}")))
+}
+
 fn u64_col(batch: &RecordBatch, idx: usize) -> Result<&UInt64Array, Error> {
     batch
         .column(idx)
@@ -325,6 +449,7 @@ fn pair_type_to_str(v: PairType) -> &'static str {
     match v {
         PairType::AddressTopic0 => "address_topic0",
         PairType::Topic0Topic1 => "topic0_topic1",
+        PairType::AddressTopic1 => "address_topic1",
     }
 }
 
@@ -332,6 +457,7 @@ fn str_to_pair_type(v: &str) -> Result<PairType, Error> {
     match v {
         "address_topic0" => Ok(PairType::AddressTopic0),
         "topic0_topic1" => Ok(PairType::Topic0Topic1),
+        "address_topic1" => Ok(PairType::AddressTopic1),
         _ => Err(Error::Serialization(format!("unknown pair_type: {v}"))),
     }
 }
@@ -1,18 +1,99 @@
+use crate::config::TraceCompression;
 use crate::error::Error;
-use crate::types::TraceEntry;
+use crate::types::{TraceEntry, TraceProfile};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
-pub fn write_trace_jsonl(path: &Path, entries: &[TraceEntry]) -> Result<(), Error> {
-    let mut file = File::create(path).map_err(|e| Error::Io(format!("create trace file: {e}")))?;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// File name of a profile's trace inside a dataset directory.
+pub fn trace_file_name(profile: &TraceProfile, compression: TraceCompression) -> &'static str {
+    match (profile, compression) {
+        (TraceProfile::Expected, TraceCompression::None) => "trace_expected.jsonl",
+        (TraceProfile::Stress, TraceCompression::None) => "trace_stress.jsonl",
+        (TraceProfile::Adversarial, TraceCompression::None) => "trace_adversarial.jsonl",
+        (TraceProfile::Expected, TraceCompression::Gzip) => "trace_expected.jsonl.gz",
+        (TraceProfile::Stress, TraceCompression::Gzip) => "trace_stress.jsonl.gz",
+        (TraceProfile::Adversarial, TraceCompression::Gzip) => "trace_adversarial.jsonl.gz",
+    }
+}
+
+/// File name of the single trace that holds every profile's entries.
+pub fn combined_trace_file_name(compression: TraceCompression) -> &'static str {
+    match compression {
+        TraceCompression::None => "traces.jsonl",
+        TraceCompression::Gzip => "traces.jsonl.gz",
+    }
+}
+
+pub fn write_trace_jsonl(
+    path: &Path,
+    entries: &[TraceEntry],
+    compression: TraceCompression,
+) -> Result<(), Error> {
+    let file = File::create(path).map_err(|e| Error::Io(format!("create trace file: {e}")))?;
+    match compression {
+        TraceCompression::None => {
+            let mut out = BufWriter::new(file);
+            write_lines(&mut out, entries)?;
+            out.flush()
+                .map_err(|e| Error::Io(format!("flush trace file: {e}")))?;
+        }
+        TraceCompression::Gzip => {
+            let mut out = GzEncoder::new(BufWriter::new(file), Compression::default());
+            write_lines(&mut out, entries)?;
+            out.finish()
+                .and_then(|mut inner| inner.flush())
+                .map_err(|e| Error::Io(format!("finish gzip trace file: {e}")))?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a trace written by [`write_trace_jsonl`] with either compression;
+/// gzip input is detected from its magic bytes.
+pub fn read_trace_jsonl(path: &Path) -> Result<Vec<TraceEntry>, Error> {
+    let mut file = File::open(path).map_err(|e| Error::Io(format!("open trace file: {e}")))?;
+    let mut magic = [0u8; 2];
+    let magic_len = file
+        .read(&mut magic)
+        .map_err(|e| Error::Io(format!("read trace file: {e}")))?;
+    let prefix = &magic[..magic_len];
+    let input = prefix.chain(file);
+    if prefix == GZIP_MAGIC {
+        read_lines(BufReader::new(GzDecoder::new(input)))
+    } else {
+        read_lines(BufReader::new(input))
+    }
+}
+
+fn write_lines(out: &mut impl Write, entries: &[TraceEntry]) -> Result<(), Error> {
     for entry in entries {
         let line = serde_json::to_string(entry)
             .map_err(|e| Error::Serialization(format!("serialize trace entry: {e}")))?;
-        file.write_all(line.as_bytes())
+        out.write_all(line.as_bytes())
             .map_err(|e| Error::Io(format!("write trace line: {e}")))?;
-        file.write_all(b"\n")
+        out.write_all(b"\n")
             .map_err(|e| Error::Io(format!("write trace newline: {e}")))?;
     }
     Ok(())
 }
+
+fn read_lines(input: impl BufRead) -> Result<Vec<TraceEntry>, Error> {
+    let mut entries = Vec::new();
+    for line in input.lines() {
+        let line = line.map_err(|e| Error::Io(format!("read trace line: {e}")))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(
+            serde_json::from_str(&line)
+                .map_err(|e| Error::Serialization(format!("parse trace entry: {e}")))?,
+        );
+    }
+    Ok(entries)
+}
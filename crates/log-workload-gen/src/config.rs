@@ -1,4 +1,5 @@
 use crate::Error;
+use crate::stats::HyperLogLog;
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
@@ -15,6 +16,94 @@ pub struct GeneratorConfig {
     pub cooccurrence_top_k_per_type: u64,
     pub logs_per_window_size_blocks: u64,
     pub profiles: ProfilesConfig,
+    /// Codec applied to every stats parquet file in the dataset artifacts.
+    #[serde(default)]
+    pub parquet_compression: ParquetCompression,
+    /// How key stats count distinct active blocks and distinct partners.
+    #[serde(default)]
+    pub active_blocks_estimation: ActiveBlocksEstimation,
+    /// Codec applied to the generated trace JSONL files.
+    #[serde(default)]
+    pub trace_compression: TraceCompression,
+    /// Write every profile's entries to one trace file instead of one file
+    /// per profile. Each entry's `profile` field still names its profile.
+    #[serde(default)]
+    pub combined_traces: bool,
+    /// How the interarrival histogram treats a timestamp that goes backwards.
+    #[serde(default)]
+    pub interarrival_policy: InterarrivalPolicy,
+    /// Seed derivation and generator behind trace sampling.
+    #[serde(default)]
+    pub rng_version: RngVersion,
+}
+
+/// A pinned trace sampling scheme. The dataset manifest records the scheme's
+/// name, and offline generation refuses a dataset recorded under another
+/// scheme, so traces regenerated from a long-lived dataset never drift
+/// silently.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RngVersion {
+    /// `ChaCha20Rng` seeded with SHA-256 over the root seed and the profile
+    /// name.
+    #[default]
+    #[serde(rename = "chacha20_sha256_v1")]
+    ChaCha20Sha256V1,
+}
+
+impl RngVersion {
+    /// The name recorded in the dataset manifest.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ChaCha20Sha256V1 => "chacha20_sha256_v1",
+        }
+    }
+}
+
+/// Interarrival recorded when a block's timestamp is earlier than the
+/// previous block's. Equal timestamps are a 0-second interarrival under
+/// every policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterarrivalPolicy {
+    /// Record nothing for the transition.
+    #[default]
+    DropNonMonotonic,
+    /// Record a 0-second interarrival.
+    ClampZero,
+    /// Record the absolute difference.
+    AbsDelta,
+}
+
+/// `Gzip` writes `trace_<profile>.jsonl.gz` instead of `trace_<profile>.jsonl`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceCompression {
+    #[default]
+    None,
+    Gzip,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetCompression {
+    #[default]
+    Snappy,
+    Zstd,
+    None,
+}
+
+/// `Exact` keeps a set per key. `HyperLogLog` bounds per-key memory to
+/// `2^precision` bytes at a relative standard error of about
+/// `1.04 / sqrt(2^precision)`; keys with few distinct values stay in a
+/// smaller sparse form.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActiveBlocksEstimation {
+    #[default]
+    Exact,
+    HyperLogLog {
+        precision: u8,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -37,6 +126,10 @@ pub struct ProfileConfig {
     pub topic0_or_width: WidthRange,
     pub block_range_blocks: BlockRangeConfig,
     pub empty_result_target_share: f64,
+    /// Draw OR-list keys proportionally to their observed `count_total`
+    /// instead of uniformly across the key pool.
+    #[serde(default)]
+    pub frequency_weighted: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -143,11 +236,95 @@ impl Default for GeneratorConfig {
                     0.10,
                 ),
             },
+            parquet_compression: ParquetCompression::default(),
+            active_blocks_estimation: ActiveBlocksEstimation::default(),
+            trace_compression: TraceCompression::default(),
+            combined_traces: false,
+            interarrival_policy: InterarrivalPolicy::default(),
+            rng_version: RngVersion::default(),
         }
     }
 }
 
+/// Names accepted by [`GeneratorConfig::preset`].
+const PRESETS: [&str; 3] = ["high-cardinality", "range-heavy", "mostly-empty"];
+
 impl GeneratorConfig {
+    /// Returns a curated variant of the defaults. Each preset reshapes all
+    /// three profiles the same way and leaves runtime settings at their
+    /// defaults:
+    /// - `high-cardinality`: OR-heavy mixes with widths four times the defaults
+    /// - `range-heavy`: single-key mixes over near-full-range block windows
+    /// - `mostly-empty`: conjunction-heavy mixes targeting 80% empty results
+    pub fn preset(name: &str) -> Result<Self, Error> {
+        let mut config = Self::default();
+        let profiles = [
+            &mut config.profiles.expected,
+            &mut config.profiles.stress,
+            &mut config.profiles.adversarial,
+        ];
+        match name {
+            "high-cardinality" => {
+                for profile in profiles {
+                    profile.template_mix = BTreeMap::from([
+                        (QueryTemplate::SingleAddress, 0.05),
+                        (QueryTemplate::SingleTopic0, 0.05),
+                        (QueryTemplate::AddressTopic0, 0.10),
+                        (QueryTemplate::MultiAddress, 0.35),
+                        (QueryTemplate::MultiTopic0, 0.35),
+                        (QueryTemplate::Compound, 0.10),
+                    ]);
+                    for width in [&mut profile.address_or_width, &mut profile.topic0_or_width] {
+                        width.min *= 4;
+                        width.max *= 4;
+                    }
+                }
+            }
+            "range-heavy" => {
+                for profile in profiles {
+                    profile.template_mix = BTreeMap::from([
+                        (QueryTemplate::SingleAddress, 0.35),
+                        (QueryTemplate::SingleTopic0, 0.35),
+                        (QueryTemplate::AddressTopic0, 0.20),
+                        (QueryTemplate::MultiAddress, 0.05),
+                        (QueryTemplate::MultiTopic0, 0.05),
+                        (QueryTemplate::Compound, 0.0),
+                    ]);
+                    profile.block_range_blocks = BlockRangeConfig {
+                        source: BlockRangeSource::HeavyNearFullRange,
+                        min: profile.block_range_blocks.min.max(10_000),
+                        max: BlockRangeMax::FullRange,
+                    };
+                }
+            }
+            "mostly-empty" => {
+                for profile in profiles {
+                    profile.template_mix = BTreeMap::from([
+                        (QueryTemplate::SingleAddress, 0.10),
+                        (QueryTemplate::SingleTopic0, 0.10),
+                        (QueryTemplate::AddressTopic0, 0.40),
+                        (QueryTemplate::MultiAddress, 0.05),
+                        (QueryTemplate::MultiTopic0, 0.05),
+                        (QueryTemplate::Compound, 0.30),
+                    ]);
+                    profile.empty_result_target_share = 0.80;
+                }
+            }
+            _ => {
+                return Err(Error::ConfigInvalid(format!(
+                    "unknown preset {name:?}; expected one of {}",
+                    PRESETS.join(", ")
+                )));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Names accepted by [`GeneratorConfig::preset`].
+    pub fn list_presets() -> &'static [&'static str] {
+        &PRESETS
+    }
+
     pub fn validate(&self) -> Result<(), Error> {
         if self.trace_size_per_profile < 1 {
             return Err(Error::ConfigInvalid(
@@ -184,6 +361,15 @@ impl GeneratorConfig {
                 "logs_per_window_size_blocks must be >= 1".to_string(),
             ));
         }
+        if let ActiveBlocksEstimation::HyperLogLog { precision } = self.active_blocks_estimation
+            && !(HyperLogLog::MIN_PRECISION..=HyperLogLog::MAX_PRECISION).contains(&precision)
+        {
+            return Err(Error::ConfigInvalid(format!(
+                "active_blocks_estimation.hyper_log_log.precision must be in [{}, {}]",
+                HyperLogLog::MIN_PRECISION,
+                HyperLogLog::MAX_PRECISION
+            )));
+        }
         validate_profile("expected", &self.profiles.expected)?;
         validate_profile("stress", &self.profiles.stress)?;
         validate_profile("adversarial", &self.profiles.adversarial)?;
@@ -214,6 +400,7 @@ fn profile(
         topic0_or_width,
         block_range_blocks,
         empty_result_target_share,
+        frequency_weighted: false,
     }
 }
 
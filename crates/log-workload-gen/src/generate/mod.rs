@@ -9,7 +9,9 @@ use crate::types::{DatasetManifest, TraceEntry, TraceProfile, TraceSummary};
 use planner::{derive_seed, observed_coverage_ratio, selectivity_bucket};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
-use sampler::{extract_pool, sample_block_range, sample_or, sample_template, sample_width};
+use sampler::{
+    KeyPool, extract_pool, sample_block_range, sample_or, sample_template, sample_width,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct GeneratedTraces {
@@ -35,6 +37,15 @@ pub fn generate_traces(
     seed: u64,
 ) -> Result<GeneratedTraces, Error> {
     config.validate()?;
+    if let Some(rng_version) = &manifest.rng_version
+        && rng_version != config.rng_version.as_str()
+    {
+        return Err(Error::InputInvalid(format!(
+            "dataset manifest rng_version {rng_version:?} does not match the configured {:?}; \
+             traces would not reproduce",
+            config.rng_version.as_str()
+        )));
+    }
 
     let address_pool = extract_pool(stats, KeyType::Address);
     let topic0_pool = extract_pool(stats, KeyType::Topic0);
@@ -81,24 +92,29 @@ fn generate_profile(
     profile: TraceProfile,
     profile_cfg: &ProfileConfig,
     manifest: &DatasetManifest,
-    address_pool: &[Vec<u8>],
-    topic0_pool: &[Vec<u8>],
+    address_pool: &KeyPool,
+    topic0_pool: &KeyPool,
     seed: [u8; 32],
     size: u64,
 ) -> Result<Vec<TraceEntry>, Error> {
     let mut rng = ChaCha20Rng::from_seed(seed);
     let mut out = Vec::with_capacity(size as usize);
+    let weighted = profile_cfg.frequency_weighted;
 
     for id in 0..size {
         let template = sample_template(&mut rng, profile_cfg)?;
         let (from_block, to_block) = sample_block_range(&mut rng, profile_cfg, manifest)?;
 
         let (address_or, topic0_or) = match template {
-            QueryTemplate::SingleAddress => (sample_or(&mut rng, address_pool, 1), Vec::new()),
-            QueryTemplate::SingleTopic0 => (Vec::new(), sample_or(&mut rng, topic0_pool, 1)),
+            QueryTemplate::SingleAddress => {
+                (sample_or(&mut rng, address_pool, 1, weighted), Vec::new())
+            }
+            QueryTemplate::SingleTopic0 => {
+                (Vec::new(), sample_or(&mut rng, topic0_pool, 1, weighted))
+            }
             QueryTemplate::AddressTopic0 => (
-                sample_or(&mut rng, address_pool, 1),
-                sample_or(&mut rng, topic0_pool, 1),
+                sample_or(&mut rng, address_pool, 1, weighted),
+                sample_or(&mut rng, topic0_pool, 1, weighted),
             ),
             QueryTemplate::MultiAddress => {
                 let width = sample_width(
@@ -106,7 +122,10 @@ fn generate_profile(
                     profile_cfg.address_or_width.min,
                     profile_cfg.address_or_width.max,
                 );
-                (sample_or(&mut rng, address_pool, width), Vec::new())
+                (
+                    sample_or(&mut rng, address_pool, width, weighted),
+                    Vec::new(),
+                )
             }
             QueryTemplate::MultiTopic0 => {
                 let width = sample_width(
@@ -114,7 +133,10 @@ fn generate_profile(
                     profile_cfg.topic0_or_width.min,
                     profile_cfg.topic0_or_width.max,
                 );
-                (Vec::new(), sample_or(&mut rng, topic0_pool, width))
+                (
+                    Vec::new(),
+                    sample_or(&mut rng, topic0_pool, width, weighted),
+                )
             }
             QueryTemplate::Compound => {
                 let aw = sample_width(
@@ -128,8 +150,8 @@ fn generate_profile(
                     profile_cfg.topic0_or_width.max,
                 );
                 (
-                    sample_or(&mut rng, address_pool, aw),
-                    sample_or(&mut rng, topic0_pool, tw),
+                    sample_or(&mut rng, address_pool, aw, weighted),
+                    sample_or(&mut rng, topic0_pool, tw, weighted),
                 )
             }
         };
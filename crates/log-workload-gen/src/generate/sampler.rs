@@ -65,20 +65,53 @@ pub fn sample_width(rng: &mut ChaCha20Rng, min: u32, max: u32) -> usize {
     }
 }
 
-pub fn sample_or(rng: &mut ChaCha20Rng, pool: &[Vec<u8>], width: usize) -> Vec<Vec<u8>> {
+/// Keys of one type with cumulative `count_total` weights, aligned by index.
+pub struct KeyPool {
+    keys: Vec<Vec<u8>>,
+    cumulative_weights: Vec<u64>,
+}
+
+impl KeyPool {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    fn total_weight(&self) -> u64 {
+        self.cumulative_weights.last().copied().unwrap_or(0)
+    }
+}
+
+pub fn sample_or(
+    rng: &mut ChaCha20Rng,
+    pool: &KeyPool,
+    width: usize,
+    frequency_weighted: bool,
+) -> Vec<Vec<u8>> {
+    let total_weight = pool.total_weight();
     let mut out = Vec::with_capacity(width);
     for _ in 0..width.max(1) {
-        let idx = rng.random_range(0..pool.len());
-        out.push(pool[idx].clone());
+        let idx = if frequency_weighted && total_weight > 0 {
+            let roll = rng.random_range(0..total_weight);
+            pool.cumulative_weights.partition_point(|&c| c <= roll)
+        } else {
+            rng.random_range(0..pool.keys.len())
+        };
+        out.push(pool.keys[idx].clone());
     }
     out
 }
 
-pub fn extract_pool(stats: &ParquetStats, key_type: KeyType) -> Vec<Vec<u8>> {
-    stats
-        .key_stats
-        .iter()
-        .filter(|r| r.key_type == key_type)
-        .map(|r| r.key_value.clone())
-        .collect()
+pub fn extract_pool(stats: &ParquetStats, key_type: KeyType) -> KeyPool {
+    let mut keys = Vec::new();
+    let mut cumulative_weights = Vec::new();
+    let mut total = 0u64;
+    for row in stats.key_stats.iter().filter(|r| r.key_type == key_type) {
+        total = total.saturating_add(row.count_total);
+        keys.push(row.key_value.clone());
+        cumulative_weights.push(total);
+    }
+    KeyPool {
+        keys,
+        cumulative_weights,
+    }
 }
@@ -59,6 +59,7 @@ impl Validator {
             missing_block_ranges: self.gap_tracker.missing_block_ranges(),
             event_count: self.event_count,
             log_count: self.log_count,
+            non_monotonic_timestamps: 0,
         }
     }
 
@@ -1,14 +1,19 @@
 use crate::artifact::{
-    read_dataset_manifest, read_parquet_stats, write_dataset_artifacts, write_dataset_manifest,
-    write_trace_jsonl,
+    combined_trace_file_name, read_dataset_manifest, read_generation_stats, trace_file_name,
+    write_dataset_artifacts, write_dataset_manifest, write_trace_jsonl,
 };
 use crate::config::GeneratorConfig;
 use crate::error::Error;
 use crate::generate::generate_traces;
 use crate::ingest::consume_messages_with_events;
-use crate::runtime::bounded_queue::bounded;
-use crate::stats::{CooccurrenceAccumulator, KeyStatsAccumulator, RangeStatsAccumulator};
-use crate::types::{DatasetManifest, DatasetSummary, RunSummary, TraceSummary};
+use crate::runtime::bounded_queue::{QueueDepth, bounded_with_depth};
+use crate::stats::{
+    CooccurrenceAccumulator, KeyStatsAccumulator, KeyStatsRow, RangeStatsAccumulator,
+};
+use crate::types::{
+    ChainEvent, DatasetManifest, DatasetSummary, RunSummary, TraceProfile, TraceSummary,
+};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
@@ -20,15 +25,33 @@ pub async fn run_collect(
     config: GeneratorConfig,
     receiver: Receiver<crate::types::Message>,
     dataset_path: &Path,
+) -> Result<DatasetSummary, Error> {
+    run_collect_with_queue_depth(config, receiver, dataset_path, QueueDepth::new()).await
+}
+
+/// Like [`run_collect`], but reports the event queue into `queue_depth` so the
+/// caller can poll its live depth and high-water mark during collection.
+pub async fn run_collect_with_queue_depth(
+    config: GeneratorConfig,
+    receiver: Receiver<crate::types::Message>,
+    dataset_path: &Path,
+    queue_depth: QueueDepth,
 ) -> Result<DatasetSummary, Error> {
     config.validate()?;
     let collect_started = Instant::now();
     let (summary, key_rows, co_rows, range_rows, max_queue_depth) =
-        collect_and_build_stats(&config, receiver).await?;
+        collect_and_build_stats(&config, receiver, queue_depth).await?;
 
     let artifact_started = Instant::now();
-    let manifest = build_manifest(&config, &summary, None)?;
-    write_dataset_artifacts(dataset_path, &manifest, &key_rows, &co_rows, &range_rows)?;
+    let manifest = build_manifest(&config, &summary, &key_rows, None)?;
+    write_dataset_artifacts(
+        dataset_path,
+        &manifest,
+        &key_rows,
+        &co_rows,
+        &range_rows,
+        config.parquet_compression,
+    )?;
     write_run_summary(
         dataset_path,
         &RunSummary {
@@ -79,21 +102,38 @@ pub async fn run_offline_generate(
     config.validate()?;
     let generate_started = Instant::now();
     let mut manifest = read_dataset_manifest(dataset_path)?;
-    let stats = read_parquet_stats(dataset_path)?;
+    let stats = read_generation_stats(dataset_path)?;
+    let generated = generate_traces(&config, &manifest, &stats, seed)?;
     manifest.seed = Some(seed);
+    manifest.rng_version = Some(config.rng_version.as_str().to_string());
     write_dataset_manifest(dataset_path, &manifest)?;
 
-    let generated = generate_traces(&config, &manifest, &stats, seed)?;
-
-    write_trace_jsonl(
-        &dataset_path.join("trace_expected.jsonl"),
-        &generated.expected,
-    )?;
-    write_trace_jsonl(&dataset_path.join("trace_stress.jsonl"), &generated.stress)?;
-    write_trace_jsonl(
-        &dataset_path.join("trace_adversarial.jsonl"),
-        &generated.adversarial,
-    )?;
+    if config.combined_traces {
+        let entries: Vec<_> = generated
+            .expected
+            .iter()
+            .chain(&generated.stress)
+            .chain(&generated.adversarial)
+            .cloned()
+            .collect();
+        write_trace_jsonl(
+            &dataset_path.join(combined_trace_file_name(config.trace_compression)),
+            &entries,
+            config.trace_compression,
+        )?;
+    } else {
+        for (profile, entries) in [
+            (TraceProfile::Expected, &generated.expected),
+            (TraceProfile::Stress, &generated.stress),
+            (TraceProfile::Adversarial, &generated.adversarial),
+        ] {
+            write_trace_jsonl(
+                &dataset_path.join(trace_file_name(&profile, config.trace_compression)),
+                entries,
+                config.trace_compression,
+            )?;
+        }
+    }
 
     let trace_summary = TraceSummary {
         expected: generated.expected.len() as u64,
@@ -131,6 +171,7 @@ pub async fn run_offline_generate(
 async fn collect_and_build_stats(
     config: &GeneratorConfig,
     receiver: Receiver<crate::types::Message>,
+    queue_depth: QueueDepth,
 ) -> Result<
     (
         DatasetSummary,
@@ -141,7 +182,8 @@ async fn collect_and_build_stats(
     ),
     Error,
 > {
-    let (qtx, mut qrx, depth) = bounded(config.event_queue_capacity as usize)?;
+    let (qtx, mut qrx, depth) =
+        bounded_with_depth(config.event_queue_capacity as usize, queue_depth)?;
     let producer = tokio::spawn(async move {
         let mut receiver = receiver;
         while let Some(msg) = receiver.recv().await {
@@ -159,19 +201,11 @@ async fn collect_and_build_stats(
         .await
         .map_err(|e| Error::InternalInvariant(format!("message producer join error: {e}")))?;
 
-    let (summary, events) = consume_messages_with_events(messages);
+    let (mut summary, events) = consume_messages_with_events(messages);
 
-    let mut key_stats = KeyStatsAccumulator::new();
-    let mut cooccurrence = CooccurrenceAccumulator::new(config.cooccurrence_top_k_per_type);
-    let mut range_stats = RangeStatsAccumulator::new(config.logs_per_window_size_blocks);
-
-    for event in &events {
-        for log in &event.logs {
-            key_stats.observe_log(event.block_number, log);
-            cooccurrence.observe_log(event.block_number, log);
-        }
-        range_stats.observe_block(event.block_number, event.logs.len() as u64, event.timestamp);
-    }
+    let (key_stats, cooccurrence, range_stats) =
+        accumulate_stats(config, &events, resolve_max_threads(config) as usize)?;
+    summary.non_monotonic_timestamps = range_stats.non_monotonic_transitions();
 
     let key_rows = key_stats.finalize();
     let co_rows = cooccurrence.finalize();
@@ -180,12 +214,84 @@ async fn collect_and_build_stats(
     } else {
         Vec::new()
     };
-    Ok((summary, key_rows, co_rows, range_rows, depth.max() as u64))
+    Ok((
+        summary,
+        key_rows,
+        co_rows,
+        range_rows,
+        depth.high_water() as u64,
+    ))
+}
+
+type StatsAccumulators = (
+    KeyStatsAccumulator,
+    CooccurrenceAccumulator,
+    RangeStatsAccumulator,
+);
+
+/// Splits `events` into contiguous chunks, one per worker thread, and merges
+/// the per-chunk accumulators in chunk order. Contiguous chunks keep the
+/// order-dependent interarrival histogram identical to a sequential pass.
+fn accumulate_stats(
+    config: &GeneratorConfig,
+    events: &[ChainEvent],
+    max_threads: usize,
+) -> Result<StatsAccumulators, Error> {
+    let workers = max_threads.clamp(1, events.len().max(1));
+    if workers == 1 {
+        return Ok(accumulate_chunk(config, events));
+    }
+
+    let chunk_size = events.len().div_ceil(workers);
+    let partials = std::thread::scope(|scope| {
+        let handles: Vec<_> = events
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || accumulate_chunk(config, chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle.join().map_err(|_| {
+                    Error::InternalInvariant("stats worker thread panicked".to_string())
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()
+    })?;
+
+    let mut partials = partials.into_iter();
+    let (mut key_stats, mut cooccurrence, mut range_stats) = partials
+        .next()
+        .ok_or_else(|| Error::InternalInvariant("no stats partitions".to_string()))?;
+    for (keys, pairs, ranges) in partials {
+        key_stats.merge(keys);
+        cooccurrence.merge(pairs);
+        range_stats.merge(ranges);
+    }
+    Ok((key_stats, cooccurrence, range_stats))
+}
+
+fn accumulate_chunk(config: &GeneratorConfig, events: &[ChainEvent]) -> StatsAccumulators {
+    let mut key_stats = KeyStatsAccumulator::with_estimation(config.active_blocks_estimation);
+    let mut cooccurrence = CooccurrenceAccumulator::new(config.cooccurrence_top_k_per_type);
+    let mut range_stats = RangeStatsAccumulator::with_interarrival_policy(
+        config.logs_per_window_size_blocks,
+        config.interarrival_policy,
+    );
+
+    for event in events {
+        for log in &event.logs {
+            key_stats.observe_log(event.block_number, log);
+            cooccurrence.observe_log(event.block_number, log);
+        }
+        range_stats.observe_block(event.block_number, event.logs.len() as u64, event.timestamp);
+    }
+    (key_stats, cooccurrence, range_stats)
 }
 
 fn build_manifest(
     config: &GeneratorConfig,
     summary: &DatasetSummary,
+    key_rows: &[KeyStatsRow],
     seed: Option<u64>,
 ) -> Result<DatasetManifest, Error> {
     let created_at = SystemTime::now()
@@ -193,9 +299,13 @@ fn build_manifest(
         .map_err(|e| Error::InternalInvariant(format!("system time before epoch: {e}")))?
         .as_secs()
         .to_string();
+    let mut key_cardinality = BTreeMap::new();
+    for row in key_rows {
+        *key_cardinality.entry(row.key_type).or_insert(0) += 1;
+    }
 
     Ok(DatasetManifest {
-        schema_version: "1.0.0".to_string(),
+        schema_version: "1.1.0".to_string(),
         crate_version: env!("CARGO_PKG_VERSION").to_string(),
         chain_id: 1,
         start_block: summary.start_block.unwrap_or(0),
@@ -210,6 +320,8 @@ fn build_manifest(
         seed,
         valid: summary.valid,
         invalid_reason: summary.invalid_reason.clone(),
+        rng_version: Some(config.rng_version.as_str().to_string()),
+        key_cardinality,
     })
 }
 
@@ -3,20 +3,38 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::sync::mpsc;
 
-#[derive(Clone)]
+/// Live depth gauge for a bounded queue. Clones share the same counters, so a
+/// caller can hold one and poll it while the queue is in use.
+///
+/// A depth that stays near `capacity` means the consumer is the bottleneck;
+/// one that stays near zero means the producer is.
+#[derive(Clone, Default)]
 pub struct QueueDepth {
     current: Arc<AtomicUsize>,
     max: Arc<AtomicUsize>,
+    capacity: Arc<AtomicUsize>,
 }
 
 impl QueueDepth {
-    pub fn current(&self) -> usize {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Items sent and not yet received.
+    pub fn depth(&self) -> usize {
         self.current.load(Ordering::Relaxed)
     }
 
-    pub fn max(&self) -> usize {
+    /// Largest depth observed since the queue was created.
+    pub fn high_water(&self) -> usize {
         self.max.load(Ordering::Relaxed)
     }
+
+    /// Capacity of the queue this gauge is attached to, or zero before it
+    /// is attached.
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
 }
 
 #[derive(Clone)]
@@ -32,6 +50,15 @@ pub struct BoundedReceiver<T> {
 
 pub fn bounded<T>(
     capacity: usize,
+) -> Result<(BoundedSender<T>, BoundedReceiver<T>, QueueDepth), Error> {
+    bounded_with_depth(capacity, QueueDepth::new())
+}
+
+/// Like [`bounded`], but reports into a caller-supplied gauge so the caller
+/// can poll depth without access to the queue. The gauge should be fresh.
+pub fn bounded_with_depth<T>(
+    capacity: usize,
+    depth: QueueDepth,
 ) -> Result<(BoundedSender<T>, BoundedReceiver<T>, QueueDepth), Error> {
     if capacity == 0 {
         return Err(Error::ConfigInvalid(
@@ -40,10 +67,7 @@ pub fn bounded<T>(
     }
 
     let (tx, rx) = mpsc::channel(capacity);
-    let depth = QueueDepth {
-        current: Arc::new(AtomicUsize::new(0)),
-        max: Arc::new(AtomicUsize::new(0)),
-    };
+    depth.capacity.store(capacity, Ordering::Relaxed);
     Ok((
         BoundedSender {
             inner: tx,
@@ -5,6 +5,7 @@ use std::collections::HashMap;
 pub enum PairType {
     AddressTopic0,
     Topic0Topic1,
+    AddressTopic1,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -49,9 +50,34 @@ impl CooccurrenceAccumulator {
                 log.topics[1].to_vec(),
                 block_number,
             );
+            self.observe_pair(
+                PairType::AddressTopic1,
+                log.address.to_vec(),
+                log.topics[1].to_vec(),
+                block_number,
+            );
+        }
+    }
+
+    /// Folds another accumulator's pair counts into this one. Top-k selection
+    /// happens in `finalize`, so merging before it loses no pairs.
+    pub fn merge(&mut self, other: Self) {
+        for (key, (count_total, first_block, last_block)) in other.by_pair {
+            let entry = self
+                .by_pair
+                .entry(key)
+                .or_insert((0, first_block, last_block));
+            entry.0 += count_total;
+            entry.1 = entry.1.min(first_block);
+            entry.2 = entry.2.max(last_block);
         }
     }
 
+    /// Returns the top `top_k_per_type` rows of each pair type, ranked by
+    /// `count_total` descending, then `left_key` and `right_key` ascending.
+    /// Pairs are unique within a type, so the ranking is total and a cut
+    /// inside a tie keeps the lexicographically smallest pairs regardless of
+    /// observation or merge order.
     pub fn finalize(self) -> Vec<CooccurrenceRow> {
         let mut grouped: HashMap<PairType, Vec<CooccurrenceRow>> = HashMap::new();
         for ((pair_type, left_key, right_key), (count_total, first_block, last_block)) in
@@ -68,7 +94,11 @@ impl CooccurrenceAccumulator {
         }
 
         let mut out = Vec::new();
-        for pair_type in [PairType::AddressTopic0, PairType::Topic0Topic1] {
+        for pair_type in [
+            PairType::AddressTopic0,
+            PairType::Topic0Topic1,
+            PairType::AddressTopic1,
+        ] {
             if let Some(mut rows) = grouped.remove(&pair_type) {
                 rows.sort_by(|a, b| {
                     b.count_total
@@ -1,7 +1,12 @@
+use crate::config::ActiveBlocksEstimation;
+use crate::stats::HyperLogLog;
 use crate::types::LogEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum KeyType {
     Address,
     Topic0,
@@ -22,15 +27,92 @@ pub struct KeyStatsRow {
     pub distinct_partner_estimate: Option<f64>,
 }
 
-#[derive(Default)]
+/// A distinct-value count that is either exact or a HyperLogLog estimate.
+enum DistinctCount<T> {
+    Exact(HashSet<T>),
+    Estimated(HyperLogLog),
+}
+
+impl<T: Eq + std::hash::Hash + AsHllInput> DistinctCount<T> {
+    fn new(estimation: ActiveBlocksEstimation) -> Self {
+        match estimation {
+            ActiveBlocksEstimation::Exact => Self::Exact(HashSet::new()),
+            ActiveBlocksEstimation::HyperLogLog { precision } => {
+                Self::Estimated(HyperLogLog::new(precision))
+            }
+        }
+    }
+
+    fn insert(&mut self, value: T) {
+        match self {
+            Self::Exact(set) => {
+                set.insert(value);
+            }
+            Self::Estimated(hll) => value.insert_into(hll),
+        }
+    }
+
+    fn count(&self) -> f64 {
+        match self {
+            Self::Exact(set) => set.len() as f64,
+            Self::Estimated(hll) => hll.estimate(),
+        }
+    }
+
+    fn merge(&mut self, other: Self) {
+        match (self, other) {
+            (Self::Exact(set), Self::Exact(other)) => set.extend(other),
+            (Self::Estimated(hll), Self::Estimated(other)) => hll.merge(&other),
+            (Self::Estimated(hll), Self::Exact(other)) => {
+                for value in other {
+                    value.insert_into(hll);
+                }
+            }
+            (this, Self::Estimated(mut hll)) => {
+                if let Self::Exact(set) = this {
+                    for value in set.iter() {
+                        value.insert_into(&mut hll);
+                    }
+                }
+                *this = Self::Estimated(hll);
+            }
+        }
+    }
+}
+
+trait AsHllInput {
+    fn insert_into(&self, hll: &mut HyperLogLog);
+}
+
+impl AsHllInput for u64 {
+    fn insert_into(&self, hll: &mut HyperLogLog) {
+        hll.insert_u64(*self);
+    }
+}
+
+impl AsHllInput for Vec<u8> {
+    fn insert_into(&self, hll: &mut HyperLogLog) {
+        hll.insert_bytes(self);
+    }
+}
+
 struct KeyAgg {
     count_total: u64,
     first_block: u64,
     last_block: u64,
-    active_blocks: HashSet<u64>,
+    active_blocks: DistinctCount<u64>,
 }
 
 impl KeyAgg {
+    fn new(estimation: ActiveBlocksEstimation) -> Self {
+        Self {
+            count_total: 0,
+            first_block: 0,
+            last_block: 0,
+            active_blocks: DistinctCount::new(estimation),
+        }
+    }
+
     fn observe(&mut self, block_number: u64) {
         self.count_total += 1;
         if self.count_total == 1 {
@@ -42,17 +124,40 @@ impl KeyAgg {
         }
         self.active_blocks.insert(block_number);
     }
+
+    fn merge(&mut self, other: Self) {
+        if other.count_total == 0 {
+            return;
+        }
+        if self.count_total == 0 {
+            self.first_block = other.first_block;
+            self.last_block = other.last_block;
+        } else {
+            self.first_block = self.first_block.min(other.first_block);
+            self.last_block = self.last_block.max(other.last_block);
+        }
+        self.count_total += other.count_total;
+        self.active_blocks.merge(other.active_blocks);
+    }
 }
 
 pub struct KeyStatsAccumulator {
+    estimation: ActiveBlocksEstimation,
     by_key: HashMap<(KeyType, Vec<u8>), KeyAgg>,
-    partner_topic0_by_address: HashMap<Vec<u8>, HashSet<Vec<u8>>>,
-    partner_address_by_topic0: HashMap<Vec<u8>, HashSet<Vec<u8>>>,
+    partner_topic0_by_address: HashMap<Vec<u8>, DistinctCount<Vec<u8>>>,
+    partner_address_by_topic0: HashMap<Vec<u8>, DistinctCount<Vec<u8>>>,
 }
 
 impl KeyStatsAccumulator {
     pub fn new() -> Self {
+        Self::with_estimation(ActiveBlocksEstimation::Exact)
+    }
+
+    /// Counts `active_block_count` and `distinct_partner_estimate` exactly or
+    /// with one HyperLogLog per key, as `estimation` selects.
+    pub fn with_estimation(estimation: ActiveBlocksEstimation) -> Self {
         Self {
+            estimation,
             by_key: HashMap::new(),
             partner_topic0_by_address: HashMap::new(),
             partner_address_by_topic0: HashMap::new(),
@@ -75,11 +180,11 @@ impl KeyStatsAccumulator {
                     );
                     self.partner_topic0_by_address
                         .entry(address.clone())
-                        .or_default()
+                        .or_insert_with(|| DistinctCount::new(self.estimation))
                         .insert(topic_vec.clone());
                     self.partner_address_by_topic0
                         .entry(topic_vec)
-                        .or_default()
+                        .or_insert_with(|| DistinctCount::new(self.estimation))
                         .insert(address.clone());
                 }
                 1 => self.observe_key(block_number, KeyType::Topic1, topic_vec),
@@ -90,6 +195,27 @@ impl KeyStatsAccumulator {
         }
     }
 
+    /// Folds another accumulator's observations into this one. Rows are the
+    /// same as if one accumulator had observed both inputs.
+    pub fn merge(&mut self, other: Self) {
+        for (key, agg) in other.by_key {
+            match self.by_key.entry(key) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(agg),
+                Entry::Vacant(entry) => {
+                    entry.insert(agg);
+                }
+            }
+        }
+        merge_partners(
+            &mut self.partner_topic0_by_address,
+            other.partner_topic0_by_address,
+        );
+        merge_partners(
+            &mut self.partner_address_by_topic0,
+            other.partner_address_by_topic0,
+        );
+    }
+
     pub fn finalize(self) -> Vec<KeyStatsRow> {
         let mut out = Vec::with_capacity(self.by_key.len());
         for ((key_type, key_value), agg) in self.by_key {
@@ -97,11 +223,11 @@ impl KeyStatsAccumulator {
                 KeyType::Address => self
                     .partner_topic0_by_address
                     .get(&key_value)
-                    .map(|s| s.len() as f64),
+                    .map(DistinctCount::count),
                 KeyType::Topic0 => self
                     .partner_address_by_topic0
                     .get(&key_value)
-                    .map(|s| s.len() as f64),
+                    .map(DistinctCount::count),
                 _ => None,
             };
 
@@ -111,7 +237,7 @@ impl KeyStatsAccumulator {
                 count_total: agg.count_total,
                 first_block: agg.first_block,
                 last_block: agg.last_block,
-                active_block_count: agg.active_blocks.len() as u64,
+                active_block_count: agg.active_blocks.count().round() as u64,
                 distinct_partner_estimate,
             });
         }
@@ -123,11 +249,25 @@ impl KeyStatsAccumulator {
     fn observe_key(&mut self, block_number: u64, key_type: KeyType, key_value: Vec<u8>) {
         self.by_key
             .entry((key_type, key_value))
-            .or_default()
+            .or_insert_with(|| KeyAgg::new(self.estimation))
             .observe(block_number);
     }
 }
 
+fn merge_partners(
+    into: &mut HashMap<Vec<u8>, DistinctCount<Vec<u8>>>,
+    from: HashMap<Vec<u8>, DistinctCount<Vec<u8>>>,
+) {
+    for (key, partners) in from {
+        match into.entry(key) {
+            Entry::Occupied(mut entry) => entry.get_mut().merge(partners),
+            Entry::Vacant(entry) => {
+                entry.insert(partners);
+            }
+        }
+    }
+}
+
 impl Default for KeyStatsAccumulator {
     fn default() -> Self {
         Self::new()
@@ -1,7 +1,9 @@
 mod cooccurrence;
+mod hll;
 mod key_stats;
 mod range_stats;
 
 pub use cooccurrence::{CooccurrenceAccumulator, CooccurrenceRow, PairType};
+pub use hll::HyperLogLog;
 pub use key_stats::{KeyStatsAccumulator, KeyStatsRow, KeyType};
 pub use range_stats::{RangeMetric, RangeStatsAccumulator, RangeStatsRow};
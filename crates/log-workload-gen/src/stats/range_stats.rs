@@ -1,5 +1,7 @@
 use std::collections::{BTreeMap, HashMap};
 
+use crate::config::InterarrivalPolicy;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum RangeMetric {
     InterarrivalSeconds,
@@ -18,19 +20,31 @@ pub struct RangeStatsRow {
 
 pub struct RangeStatsAccumulator {
     window_size_blocks: u64,
+    interarrival_policy: InterarrivalPolicy,
+    non_monotonic_transitions: u64,
     logs_per_block_hist: BTreeMap<(u64, u64), u64>,
     interarrival_hist: BTreeMap<(u64, u64), u64>,
     block_logs: HashMap<u64, u64>,
+    first_timestamp: Option<u64>,
     prev_timestamp: Option<u64>,
 }
 
 impl RangeStatsAccumulator {
     pub fn new(window_size_blocks: u64) -> Self {
+        Self::with_interarrival_policy(window_size_blocks, InterarrivalPolicy::default())
+    }
+
+    /// Records the interarrival to a block whose timestamp is earlier than
+    /// the previous block's as `policy` selects.
+    pub fn with_interarrival_policy(window_size_blocks: u64, policy: InterarrivalPolicy) -> Self {
         Self {
             window_size_blocks,
+            interarrival_policy: policy,
+            non_monotonic_transitions: 0,
             logs_per_block_hist: BTreeMap::new(),
             interarrival_hist: BTreeMap::new(),
             block_logs: HashMap::new(),
+            first_timestamp: None,
             prev_timestamp: None,
         }
     }
@@ -40,15 +54,57 @@ impl RangeStatsAccumulator {
         inc_bucket(&mut self.logs_per_block_hist, log_count);
 
         if timestamp > 0 {
-            if let Some(prev) = self.prev_timestamp
-                && timestamp >= prev
-            {
-                inc_bucket(&mut self.interarrival_hist, timestamp - prev);
+            if let Some(prev) = self.prev_timestamp {
+                self.record_interarrival(prev, timestamp);
             }
+            self.first_timestamp.get_or_insert(timestamp);
             self.prev_timestamp = Some(timestamp);
         }
     }
 
+    /// Appends the observations of `next`, which must have observed the
+    /// blocks that directly follow this accumulator's blocks. The
+    /// interarrival between the last timestamp here and the first one in
+    /// `next` is counted, so contiguous partitions merged in order match a
+    /// single sequential pass.
+    pub fn merge(&mut self, next: Self) {
+        self.block_logs.extend(next.block_logs);
+        merge_hist(&mut self.logs_per_block_hist, next.logs_per_block_hist);
+        merge_hist(&mut self.interarrival_hist, next.interarrival_hist);
+        self.non_monotonic_transitions += next.non_monotonic_transitions;
+
+        if let (Some(prev), Some(first)) = (self.prev_timestamp, next.first_timestamp) {
+            self.record_interarrival(prev, first);
+        }
+        if self.first_timestamp.is_none() {
+            self.first_timestamp = next.first_timestamp;
+        }
+        if next.prev_timestamp.is_some() {
+            self.prev_timestamp = next.prev_timestamp;
+        }
+    }
+
+    /// Number of consecutive timestamped blocks whose timestamp went
+    /// backwards, whatever the interarrival policy.
+    pub fn non_monotonic_transitions(&self) -> u64 {
+        self.non_monotonic_transitions
+    }
+
+    fn record_interarrival(&mut self, prev: u64, timestamp: u64) {
+        if timestamp >= prev {
+            inc_bucket(&mut self.interarrival_hist, timestamp - prev);
+            return;
+        }
+        self.non_monotonic_transitions += 1;
+        match self.interarrival_policy {
+            InterarrivalPolicy::DropNonMonotonic => {}
+            InterarrivalPolicy::ClampZero => inc_bucket(&mut self.interarrival_hist, 0),
+            InterarrivalPolicy::AbsDelta => {
+                inc_bucket(&mut self.interarrival_hist, prev - timestamp)
+            }
+        }
+    }
+
     pub fn finalize(self, start_block: u64, end_block: u64) -> Vec<RangeStatsRow> {
         let mut out = Vec::new();
 
@@ -108,6 +164,12 @@ impl RangeStatsAccumulator {
     }
 }
 
+fn merge_hist(into: &mut BTreeMap<(u64, u64), u64>, from: BTreeMap<(u64, u64), u64>) {
+    for (bucket, count) in from {
+        *into.entry(bucket).or_insert(0) += count;
+    }
+}
+
 fn inc_bucket(hist: &mut BTreeMap<(u64, u64), u64>, value: u64) {
     let (lower, upper) = log2_bucket(value);
     *hist.entry((lower, upper)).or_insert(0) += 1;
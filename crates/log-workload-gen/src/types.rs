@@ -1,4 +1,6 @@
+use crate::stats::KeyType;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Message {
@@ -17,6 +19,9 @@ pub struct DatasetSummary {
     pub missing_block_ranges: Option<Vec<[u64; 2]>>,
     pub event_count: u64,
     pub log_count: u64,
+    /// Consecutive timestamped blocks whose timestamp went backwards.
+    #[serde(default)]
+    pub non_monotonic_timestamps: u64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -36,6 +41,12 @@ pub struct DatasetManifest {
     pub seed: Option<u64>,
     pub valid: bool,
     pub invalid_reason: Option<String>,
+    #[serde(default)]
+    pub rng_version: Option<String>,
+    /// Distinct key values per key type, counted from the key stats rows.
+    /// Types with no rows are absent.
+    #[serde(default)]
+    pub key_cardinality: BTreeMap<KeyType, u64>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -123,6 +134,7 @@ impl DatasetSummary {
             missing_block_ranges: None,
             event_count: 0,
             log_count: 0,
+            non_monotonic_timestamps: 0,
         }
     }
 }
@@ -1,10 +1,14 @@
 use log_workload_gen::artifact::{
-    read_dataset_manifest, read_parquet_stats, write_dataset_artifacts,
+    KeyStatsProjection, read_dataset_manifest, read_generation_stats, read_key_stats,
+    read_parquet_stats, write_dataset_artifacts,
 };
+use log_workload_gen::config::{GeneratorConfig, ParquetCompression};
+use log_workload_gen::generate::generate_traces;
 use log_workload_gen::stats::{
     CooccurrenceRow, KeyStatsRow, KeyType, PairType, RangeMetric, RangeStatsRow,
 };
 use log_workload_gen::types::DatasetManifest;
+use std::collections::BTreeMap;
 use tempfile::tempdir;
 
 #[test]
@@ -18,6 +22,7 @@ fn writes_and_reads_dataset_manifest_json() {
         &sample_key_stats(),
         &sample_cooccurrence(),
         &sample_range_stats(),
+        ParquetCompression::default(),
     )
     .expect("write dataset artifacts");
 
@@ -29,6 +34,26 @@ fn writes_and_reads_dataset_manifest_json() {
     assert!(got.valid);
 }
 
+#[test]
+fn manifest_without_key_cardinality_reads_as_empty() {
+    let temp = tempdir().expect("tempdir");
+    let dataset_dir = temp.path().join("dataset");
+    std::fs::create_dir_all(&dataset_dir).expect("create dataset dir");
+
+    let mut json = serde_json::to_value(manifest()).expect("serialize manifest");
+    json.as_object_mut()
+        .expect("manifest object")
+        .remove("key_cardinality");
+    std::fs::write(
+        dataset_dir.join("dataset_manifest.json"),
+        serde_json::to_vec(&json).expect("encode manifest"),
+    )
+    .expect("write manifest");
+
+    let got = read_dataset_manifest(&dataset_dir).expect("read manifest");
+    assert!(got.key_cardinality.is_empty());
+}
+
 #[test]
 fn parquet_roundtrip_preserves_row_counts() {
     let temp = tempdir().expect("tempdir");
@@ -40,6 +65,7 @@ fn parquet_roundtrip_preserves_row_counts() {
         &sample_key_stats(),
         &sample_cooccurrence(),
         &sample_range_stats(),
+        ParquetCompression::default(),
     )
     .expect("write dataset artifacts");
 
@@ -49,6 +75,89 @@ fn parquet_roundtrip_preserves_row_counts() {
     assert_eq!(stats.range_stats.len(), 3);
 }
 
+#[test]
+fn compressed_parquet_reads_back_identical_rows() {
+    let temp = tempdir().expect("tempdir");
+    let read_with = |name: &str, compression: ParquetCompression| {
+        let dataset_dir = temp.path().join(name);
+        write_dataset_artifacts(
+            &dataset_dir,
+            &manifest(),
+            &sample_key_stats(),
+            &sample_cooccurrence(),
+            &sample_range_stats(),
+            compression,
+        )
+        .expect("write dataset artifacts");
+        read_parquet_stats(&dataset_dir).expect("read parquet stats")
+    };
+
+    let uncompressed = read_with("none", ParquetCompression::None);
+    for (name, compression) in [
+        ("snappy", ParquetCompression::Snappy),
+        ("zstd", ParquetCompression::Zstd),
+    ] {
+        let stats = read_with(name, compression);
+        assert_eq!(stats.key_stats, uncompressed.key_stats, "{name}");
+        assert_eq!(stats.cooccurrence, uncompressed.cooccurrence, "{name}");
+        assert_eq!(stats.range_stats, uncompressed.range_stats, "{name}");
+    }
+    assert_eq!(uncompressed.key_stats, sample_key_stats());
+}
+
+#[test]
+fn projected_key_stats_reads_only_requested_columns() {
+    let temp = tempdir().expect("tempdir");
+    let dataset_dir = temp.path().join("dataset");
+    write_dataset_artifacts(
+        &dataset_dir,
+        &manifest(),
+        &sample_key_stats(),
+        &sample_cooccurrence(),
+        &sample_range_stats(),
+        ParquetCompression::default(),
+    )
+    .expect("write dataset artifacts");
+    let full = read_parquet_stats(&dataset_dir).expect("read parquet stats");
+
+    let keys = read_key_stats(&dataset_dir, KeyStatsProjection::KEYS).expect("read keys");
+    let expected_keys = full
+        .key_stats
+        .iter()
+        .map(|row| KeyStatsRow {
+            key_type: row.key_type,
+            key_value: row.key_value.clone(),
+            count_total: 0,
+            first_block: 0,
+            last_block: 0,
+            active_block_count: 0,
+            distinct_partner_estimate: None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(keys, expected_keys);
+
+    let generation = read_generation_stats(&dataset_dir).expect("read generation stats");
+    assert!(generation.cooccurrence.is_empty() && generation.range_stats.is_empty());
+    for (projected, row) in generation.key_stats.iter().zip(&full.key_stats) {
+        assert_eq!(
+            (
+                projected.key_type,
+                &projected.key_value,
+                projected.count_total
+            ),
+            (row.key_type, &row.key_value, row.count_total)
+        );
+    }
+    let cfg = GeneratorConfig {
+        trace_size_per_profile: 16,
+        ..GeneratorConfig::default()
+    };
+    assert_eq!(
+        generate_traces(&cfg, &manifest(), &generation, 3).expect("projected traces"),
+        generate_traces(&cfg, &manifest(), &full, 3).expect("full traces")
+    );
+}
+
 fn manifest() -> DatasetManifest {
     DatasetManifest {
         schema_version: "1.0.0".to_string(),
@@ -66,6 +175,8 @@ fn manifest() -> DatasetManifest {
         seed: Some(7),
         valid: true,
         invalid_reason: None,
+        rng_version: None,
+        key_cardinality: BTreeMap::new(),
     }
 }
 
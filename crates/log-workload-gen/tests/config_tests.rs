@@ -1,4 +1,4 @@
-use log_workload_gen::config::GeneratorConfig;
+use log_workload_gen::config::{ActiveBlocksEstimation, GeneratorConfig, QueryTemplate};
 use sha2::{Digest, Sha256};
 
 #[test]
@@ -38,3 +38,47 @@ fn config_hash_matches_rfc8785_sha256() {
 
     assert_eq!(got, expected);
 }
+
+#[test]
+fn every_preset_validates() {
+    let presets = GeneratorConfig::list_presets();
+    assert_eq!(presets.len(), 3);
+    for name in presets {
+        let cfg = GeneratorConfig::preset(name).expect("known preset");
+        cfg.validate()
+            .unwrap_or_else(|err| panic!("preset {name} must validate: {err}"));
+    }
+}
+
+#[test]
+fn presets_produce_distinct_template_mixes() {
+    let high_cardinality = GeneratorConfig::preset("high-cardinality").expect("preset");
+    let range_heavy = GeneratorConfig::preset("range-heavy").expect("preset");
+
+    let multi_share = |cfg: &GeneratorConfig| {
+        let mix = &cfg.profiles.expected.template_mix;
+        mix[&QueryTemplate::MultiAddress] + mix[&QueryTemplate::MultiTopic0]
+    };
+    assert!(multi_share(&high_cardinality) - multi_share(&range_heavy) >= 0.5);
+    assert_ne!(
+        high_cardinality.profiles.stress.template_mix,
+        range_heavy.profiles.stress.template_mix
+    );
+}
+
+#[test]
+fn unknown_preset_is_rejected() {
+    let err = GeneratorConfig::preset("nope").expect_err("unknown preset");
+    assert!(err.to_string().contains("high-cardinality"));
+}
+
+#[test]
+fn validate_rejects_out_of_range_hyperloglog_precision() {
+    let cfg = GeneratorConfig {
+        active_blocks_estimation: ActiveBlocksEstimation::HyperLogLog { precision: 2 },
+        ..GeneratorConfig::default()
+    };
+
+    let err = cfg.validate().expect_err("precision must be rejected");
+    assert!(err.to_string().contains("precision"));
+}
@@ -1,7 +1,12 @@
-use log_workload_gen::artifact::read_dataset_manifest;
-use log_workload_gen::config::GeneratorConfig;
+use log_workload_gen::artifact::{
+    combined_trace_file_name, read_dataset_manifest, read_parquet_stats, read_trace_jsonl,
+    trace_file_name, write_dataset_manifest,
+};
+use log_workload_gen::config::{GeneratorConfig, MaxThreads, RngVersion, TraceCompression};
+use log_workload_gen::generate::generate_traces;
 use log_workload_gen::pipeline::{run_collect, run_collect_and_generate, run_offline_generate};
-use log_workload_gen::types::{ChainEvent, LogEntry, Message};
+use log_workload_gen::stats::KeyType;
+use log_workload_gen::types::{ChainEvent, LogEntry, Message, TraceProfile};
 use tempfile::tempdir;
 use tokio::sync::mpsc;
 
@@ -60,6 +65,34 @@ async fn run_collect_writes_required_artifacts() {
     assert!(!dataset_dir.join("trace_expected.jsonl").exists());
 }
 
+#[tokio::test]
+async fn run_collect_records_key_cardinality_in_manifest() {
+    let temp = tempdir().expect("tempdir");
+    let dataset_dir = temp.path().join("dataset_cardinality");
+
+    let rx = feed(vec![
+        ev(100, 0x10, 0xa1, 0xb1),
+        ev(101, 0x11, 0xa1, 0xb2),
+        ev(102, 0x12, 0xa2, 0xb1),
+        ev(103, 0x13, 0xa3, 0xb1),
+        Message::EndOfStream {
+            expected_end_block: 103,
+        },
+    ])
+    .await;
+
+    run_collect(GeneratorConfig::default(), rx, &dataset_dir)
+        .await
+        .expect("run_collect");
+
+    let manifest = read_dataset_manifest(&dataset_dir).expect("manifest");
+    assert_eq!(manifest.schema_version, "1.1.0");
+    assert_eq!(manifest.key_cardinality.get(&KeyType::Address), Some(&3));
+    assert_eq!(manifest.key_cardinality.get(&KeyType::Topic0), Some(&2));
+    assert_eq!(manifest.key_cardinality.get(&KeyType::AddressTopic0), Some(&4));
+    assert_eq!(manifest.key_cardinality.get(&KeyType::Topic1), None);
+}
+
 #[tokio::test]
 async fn run_collect_respects_small_event_queue_capacity() {
     let temp = tempdir().expect("tempdir");
@@ -153,3 +186,185 @@ async fn run_offline_generate_uses_existing_dataset() {
     let manifest = read_dataset_manifest(&dataset_dir).expect("manifest");
     assert_eq!(manifest.seed, Some(99));
 }
+
+#[tokio::test]
+async fn run_offline_generate_rejects_unknown_rng_version() {
+    let temp = tempdir().expect("tempdir");
+    let dataset_dir = temp.path().join("dataset_rng_version");
+    let cfg = GeneratorConfig {
+        trace_size_per_profile: 4,
+        ..GeneratorConfig::default()
+    };
+
+    let rx = feed(vec![
+        ev(200, 0x20, 0xa1, 0xb1),
+        ev(201, 0x21, 0xa2, 0xb2),
+        Message::EndOfStream {
+            expected_end_block: 201,
+        },
+    ])
+    .await;
+    run_collect(cfg.clone(), rx, &dataset_dir)
+        .await
+        .expect("collect");
+
+    let mut manifest = read_dataset_manifest(&dataset_dir).expect("manifest");
+    assert_eq!(
+        manifest.rng_version.as_deref(),
+        Some(RngVersion::ChaCha20Sha256V1.as_str())
+    );
+    manifest.rng_version = Some("pcg64_v9".to_string());
+    write_dataset_manifest(&dataset_dir, &manifest).expect("write manifest");
+
+    let err = run_offline_generate(cfg, &dataset_dir, 99)
+        .await
+        .expect_err("unknown rng_version must fail");
+    let message = err.to_string();
+    assert!(message.contains("pcg64_v9"), "{message}");
+    assert!(message.contains("chacha20_sha256_v1"), "{message}");
+    assert!(!dataset_dir.join("trace_expected.jsonl").exists());
+    let manifest = read_dataset_manifest(&dataset_dir).expect("manifest");
+    assert_eq!(manifest.seed, None);
+}
+
+#[tokio::test]
+async fn run_offline_generate_writes_gzip_traces_that_read_back() {
+    let temp = tempdir().expect("tempdir");
+    let dataset_dir = temp.path().join("dataset_gzip");
+    let cfg = GeneratorConfig {
+        trace_size_per_profile: 6,
+        trace_compression: TraceCompression::Gzip,
+        ..GeneratorConfig::default()
+    };
+
+    let rx = feed(vec![
+        ev(300, 0x30, 0xa1, 0xb1),
+        ev(301, 0x31, 0xa2, 0xb2),
+        ev(302, 0x32, 0xa3, 0xb3),
+        Message::EndOfStream {
+            expected_end_block: 302,
+        },
+    ])
+    .await;
+    run_collect(cfg.clone(), rx, &dataset_dir)
+        .await
+        .expect("collect");
+    run_offline_generate(cfg.clone(), &dataset_dir, 5)
+        .await
+        .expect("offline generate");
+
+    let manifest = read_dataset_manifest(&dataset_dir).expect("manifest");
+    let stats = read_parquet_stats(&dataset_dir).expect("stats");
+    let generated = generate_traces(&cfg, &manifest, &stats, 5).expect("generate");
+
+    assert!(!dataset_dir.join("trace_expected.jsonl").exists());
+    for (profile, expected) in [
+        (TraceProfile::Expected, &generated.expected),
+        (TraceProfile::Stress, &generated.stress),
+        (TraceProfile::Adversarial, &generated.adversarial),
+    ] {
+        let path = dataset_dir.join(trace_file_name(&profile, TraceCompression::Gzip));
+        let bytes = std::fs::read(&path).expect("read gzip trace");
+        assert_eq!(&bytes[..2], &[0x1f, 0x8b]);
+        assert_eq!(&read_trace_jsonl(&path).expect("read trace"), expected);
+    }
+}
+
+#[tokio::test]
+async fn run_offline_generate_writes_one_combined_trace_file() {
+    let temp = tempdir().expect("tempdir");
+    let dataset_dir = temp.path().join("dataset_combined");
+    let cfg = GeneratorConfig {
+        trace_size_per_profile: 4,
+        combined_traces: true,
+        ..GeneratorConfig::default()
+    };
+
+    let rx = feed(vec![
+        ev(400, 0x40, 0xa1, 0xb1),
+        ev(401, 0x41, 0xa2, 0xb2),
+        ev(402, 0x42, 0xa3, 0xb3),
+        Message::EndOfStream {
+            expected_end_block: 402,
+        },
+    ])
+    .await;
+    run_collect(cfg.clone(), rx, &dataset_dir)
+        .await
+        .expect("collect");
+    let summary = run_offline_generate(cfg.clone(), &dataset_dir, 9)
+        .await
+        .expect("offline generate");
+
+    let trace_files: Vec<_> = std::fs::read_dir(&dataset_dir)
+        .expect("list dataset")
+        .map(|entry| entry.expect("dir entry").file_name())
+        .filter(|name| name.to_string_lossy().contains("trace"))
+        .collect();
+    assert_eq!(trace_files, vec![combined_trace_file_name(TraceCompression::None)]);
+
+    let entries = read_trace_jsonl(
+        &dataset_dir.join(combined_trace_file_name(TraceCompression::None)),
+    )
+    .expect("read combined trace");
+    assert_eq!(
+        entries.len() as u64,
+        summary.expected + summary.stress + summary.adversarial
+    );
+    for (profile, count) in [
+        (TraceProfile::Expected, summary.expected),
+        (TraceProfile::Stress, summary.stress),
+        (TraceProfile::Adversarial, summary.adversarial),
+    ] {
+        assert_eq!(count, 4);
+        let tagged = entries.iter().filter(|entry| entry.profile == profile).count();
+        assert_eq!(tagged as u64, count);
+    }
+}
+
+#[tokio::test]
+async fn collect_is_invariant_to_max_threads_setting() {
+    let temp = tempdir().expect("tempdir");
+    let mut messages = Vec::new();
+    for block_number in 100..140u64 {
+        let logs = (0..(block_number % 4))
+            .map(|i| LogEntry {
+                tx_index: 0,
+                log_index: i as u32,
+                address: [(block_number % 5) as u8; 20],
+                topics: vec![[(block_number % 3) as u8; 32], [i as u8; 32]],
+            })
+            .collect();
+        messages.push(Message::ChainEvent(ChainEvent {
+            chain_id: 1,
+            block_number,
+            block_hash: [block_number as u8; 32],
+            timestamp: 1_700_000_000 + block_number * block_number,
+            logs,
+        }));
+    }
+    messages.push(Message::EndOfStream {
+        expected_end_block: 139,
+    });
+
+    let mut collected = Vec::new();
+    for threads in [1, 3, 64] {
+        let dataset_dir = temp.path().join(format!("dataset_threads_{threads}"));
+        let cfg = GeneratorConfig {
+            max_threads: MaxThreads::Value(threads),
+            logs_per_window_size_blocks: 7,
+            ..GeneratorConfig::default()
+        };
+        run_collect(cfg, feed(messages.clone()).await, &dataset_dir)
+            .await
+            .expect("run_collect");
+        collected.push(read_parquet_stats(&dataset_dir).expect("read stats"));
+    }
+
+    let single = &collected[0];
+    for parallel in &collected[1..] {
+        assert_eq!(parallel.key_stats, single.key_stats);
+        assert_eq!(parallel.cooccurrence, single.cooccurrence);
+        assert_eq!(parallel.range_stats, single.range_stats);
+    }
+}
@@ -1,4 +1,4 @@
-use log_workload_gen::runtime::bounded_queue::bounded;
+use log_workload_gen::runtime::bounded_queue::{QueueDepth, bounded, bounded_with_depth};
 
 #[tokio::test]
 async fn bounded_queue_tracks_depth_and_max_depth() {
@@ -7,14 +7,41 @@ async fn bounded_queue_tracks_depth_and_max_depth() {
     tx.send(1).await.expect("send 1");
     tx.send(2).await.expect("send 2");
 
-    assert_eq!(depth.current(), 2);
-    assert_eq!(depth.max(), 2);
+    assert_eq!(depth.depth(), 2);
+    assert_eq!(depth.high_water(), 2);
 
     let a = rx.recv().await;
     let b = rx.recv().await;
     assert_eq!(a, Some(1));
     assert_eq!(b, Some(2));
 
-    assert_eq!(depth.current(), 0);
-    assert_eq!(depth.max(), 2);
+    assert_eq!(depth.depth(), 0);
+    assert_eq!(depth.high_water(), 2);
+}
+
+#[tokio::test]
+async fn bounded_queue_high_water_reaches_capacity_when_full() {
+    let capacity = 4;
+    let gauge = QueueDepth::new();
+    let (tx, mut rx, depth) = bounded_with_depth::<u64>(capacity, gauge.clone()).expect("queue");
+    assert_eq!(gauge.capacity(), capacity);
+
+    for item in 0..capacity as u64 {
+        tx.send(item).await.expect("send");
+    }
+    assert_eq!(gauge.depth(), capacity);
+    assert_eq!(gauge.high_water(), capacity);
+
+    // A full queue makes the producer wait instead of growing past capacity.
+    tokio::select! {
+        biased;
+        _ = tx.send(99) => panic!("send completed on a full queue"),
+        _ = tokio::task::yield_now() => {}
+    }
+    assert_eq!(depth.high_water(), capacity);
+
+    while gauge.depth() > 0 {
+        rx.recv().await.expect("recv");
+    }
+    assert_eq!(gauge.high_water(), capacity);
 }
@@ -1,5 +1,6 @@
+use log_workload_gen::config::{ActiveBlocksEstimation, InterarrivalPolicy};
 use log_workload_gen::stats::{
-    CooccurrenceAccumulator, KeyStatsAccumulator, KeyType, PairType, RangeMetric,
+    CooccurrenceAccumulator, HyperLogLog, KeyStatsAccumulator, KeyType, PairType, RangeMetric,
     RangeStatsAccumulator,
 };
 use log_workload_gen::types::LogEntry;
@@ -88,7 +89,36 @@ fn cooccurrence_tracks_pairs_and_applies_top_k_per_type() {
         .expect("top topic0_topic1");
     assert_eq!(t01.count_total, 2);
 
+    assert_eq!(rows.len(), 3);
+}
+
+#[test]
+fn cooccurrence_tracks_address_topic1_pairs() {
+    let mut acc = CooccurrenceAccumulator::new(10);
+
+    acc.observe_log(3, &mk_log(0xa1, vec![0xb1, 0xc1]));
+    acc.observe_log(5, &mk_log(0xa1, vec![0xb2, 0xc1]));
+    acc.observe_log(9, &mk_log(0xa1, vec![0xb3, 0xc1]));
+    acc.observe_log(4, &mk_log(0xa2, vec![0xb1, 0xc2]));
+    acc.observe_log(6, &mk_log(0xa3, vec![0xb1]));
+
+    let rows = acc
+        .finalize()
+        .into_iter()
+        .filter(|r| r.pair_type == PairType::AddressTopic1)
+        .collect::<Vec<_>>();
+
     assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].left_key, vec![0xa1; 20]);
+    assert_eq!(rows[0].right_key, vec![0xc1; 32]);
+    assert_eq!(rows[0].count_total, 3);
+    assert_eq!(rows[0].first_block, 3);
+    assert_eq!(rows[0].last_block, 9);
+    assert_eq!(rows[1].left_key, vec![0xa2; 20]);
+    assert_eq!(rows[1].right_key, vec![0xc2; 32]);
+    assert_eq!(rows[1].count_total, 1);
+    assert_eq!(rows[1].first_block, 4);
+    assert_eq!(rows[1].last_block, 4);
 }
 
 #[test]
@@ -130,3 +160,292 @@ fn range_stats_histograms_and_windows_are_correct() {
         .expect("interarrival 32..64 bucket");
     assert_eq!(interarrival_32_64.count, 1);
 }
+
+fn interarrival_buckets(
+    policy: InterarrivalPolicy,
+    timestamps: &[u64],
+) -> (Vec<(u64, u64, u64)>, u64) {
+    let mut acc = RangeStatsAccumulator::with_interarrival_policy(2, policy);
+    for (index, timestamp) in timestamps.iter().enumerate() {
+        acc.observe_block(index as u64 + 1, 1, *timestamp);
+    }
+    let non_monotonic = acc.non_monotonic_transitions();
+    let buckets = acc
+        .finalize(1, timestamps.len() as u64)
+        .into_iter()
+        .filter(|r| r.metric == RangeMetric::InterarrivalSeconds)
+        .map(|r| (r.bucket_lower, r.bucket_upper, r.count))
+        .collect();
+    (buckets, non_monotonic)
+}
+
+#[test]
+fn equal_timestamps_record_zero_interarrival_under_every_policy() {
+    for policy in [
+        InterarrivalPolicy::DropNonMonotonic,
+        InterarrivalPolicy::ClampZero,
+        InterarrivalPolicy::AbsDelta,
+    ] {
+        let (buckets, non_monotonic) = interarrival_buckets(policy, &[100, 100, 100, 102]);
+        assert_eq!(buckets, vec![(0, 1, 2), (2, 4, 1)], "{policy:?}");
+        assert_eq!(non_monotonic, 0, "{policy:?}");
+    }
+}
+
+#[test]
+fn backwards_timestamps_follow_interarrival_policy() {
+    let timestamps = [100, 110, 105, 112];
+    let (dropped, non_monotonic) =
+        interarrival_buckets(InterarrivalPolicy::DropNonMonotonic, &timestamps);
+    assert_eq!(dropped, vec![(4, 8, 1), (8, 16, 1)]);
+    assert_eq!(non_monotonic, 1);
+
+    let (clamped, non_monotonic) = interarrival_buckets(InterarrivalPolicy::ClampZero, &timestamps);
+    assert_eq!(clamped, vec![(0, 1, 1), (4, 8, 1), (8, 16, 1)]);
+    assert_eq!(non_monotonic, 1);
+
+    let (abs, non_monotonic) = interarrival_buckets(InterarrivalPolicy::AbsDelta, &timestamps);
+    assert_eq!(abs, vec![(4, 8, 2), (8, 16, 1)]);
+    assert_eq!(non_monotonic, 1);
+}
+
+#[test]
+fn merged_range_stats_count_non_monotonic_transition_at_boundary() {
+    let mut merged =
+        RangeStatsAccumulator::with_interarrival_policy(2, InterarrivalPolicy::AbsDelta);
+    let mut first =
+        RangeStatsAccumulator::with_interarrival_policy(2, InterarrivalPolicy::AbsDelta);
+    first.observe_block(1, 1, 100);
+    first.observe_block(2, 1, 90);
+    let mut second =
+        RangeStatsAccumulator::with_interarrival_policy(2, InterarrivalPolicy::AbsDelta);
+    second.observe_block(3, 1, 80);
+    merged.merge(first);
+    merged.merge(second);
+    assert_eq!(merged.non_monotonic_transitions(), 2);
+}
+
+#[test]
+fn hyperloglog_estimate_is_within_error_bound() {
+    const DISTINCT: u64 = 50_000;
+    let precision = 12;
+    let mut hll = HyperLogLog::new(precision);
+    for block in 0..DISTINCT {
+        hll.insert_u64(block);
+        hll.insert_u64(block);
+    }
+
+    // Four standard errors keeps this deterministic check far from flaky.
+    let std_error = 1.04 / f64::from(1u32 << precision).sqrt();
+    let relative_error = (hll.estimate() - DISTINCT as f64).abs() / DISTINCT as f64;
+    assert!(
+        relative_error <= 4.0 * std_error,
+        "relative error {relative_error} exceeds bound {}",
+        4.0 * std_error
+    );
+}
+
+#[test]
+fn key_stats_hyperloglog_estimates_active_blocks() {
+    const BLOCKS: u64 = 20_000;
+    let mut acc =
+        KeyStatsAccumulator::with_estimation(ActiveBlocksEstimation::HyperLogLog { precision: 12 });
+    let log = mk_log(0xa1, vec![0xb1]);
+    for block in 1..=BLOCKS {
+        acc.observe_log(block, &log);
+        acc.observe_log(block, &log);
+    }
+
+    let rows = acc.finalize();
+    let addr = rows
+        .iter()
+        .find(|r| r.key_type == KeyType::Address)
+        .expect("address row");
+    assert_eq!(addr.count_total, 2 * BLOCKS);
+    assert_eq!((addr.first_block, addr.last_block), (1, BLOCKS));
+    let relative_error = (addr.active_block_count as f64 - BLOCKS as f64).abs() / BLOCKS as f64;
+    assert!(relative_error <= 4.0 * 1.04 / 64.0);
+    let partners = addr.distinct_partner_estimate.expect("partner estimate");
+    assert!((partners - 1.0).abs() < 0.5);
+}
+
+#[test]
+fn merged_range_stats_match_sequential_pass() {
+    let blocks = [
+        (1, 0, 100),
+        (2, 1, 110),
+        (3, 3, 150),
+        (4, 2, 0),
+        (5, 5, 230),
+    ];
+    let mut sequential = RangeStatsAccumulator::new(2);
+    for (block, logs, timestamp) in blocks {
+        sequential.observe_block(block, logs, timestamp);
+    }
+
+    let mut merged = RangeStatsAccumulator::new(2);
+    for chunk in blocks.chunks(2) {
+        let mut part = RangeStatsAccumulator::new(2);
+        for (block, logs, timestamp) in chunk {
+            part.observe_block(*block, *logs, *timestamp);
+        }
+        merged.merge(part);
+    }
+
+    assert_eq!(merged.finalize(1, 5), sequential.finalize(1, 5));
+}
+
+#[test]
+fn merged_key_stats_and_cooccurrence_match_single_accumulator() {
+    let logs = [
+        (1, mk_log(0xa1, vec![0xb1, 0xc1])),
+        (2, mk_log(0xa2, vec![0xb1])),
+        (3, mk_log(0xa1, vec![0xb2, 0xc1])),
+        (4, mk_log(0xa1, vec![0xb1, 0xc2])),
+    ];
+    for estimation in [
+        ActiveBlocksEstimation::Exact,
+        ActiveBlocksEstimation::HyperLogLog { precision: 8 },
+    ] {
+        let mut single_keys = KeyStatsAccumulator::with_estimation(estimation);
+        let mut single_pairs = CooccurrenceAccumulator::new(2);
+        for (block, log) in &logs {
+            single_keys.observe_log(*block, log);
+            single_pairs.observe_log(*block, log);
+        }
+
+        let mut merged_keys = KeyStatsAccumulator::with_estimation(estimation);
+        let mut merged_pairs = CooccurrenceAccumulator::new(2);
+        for chunk in logs.chunks(3) {
+            let mut keys = KeyStatsAccumulator::with_estimation(estimation);
+            let mut pairs = CooccurrenceAccumulator::new(2);
+            for (block, log) in chunk {
+                keys.observe_log(*block, log);
+                pairs.observe_log(*block, log);
+            }
+            merged_keys.merge(keys);
+            merged_pairs.merge(pairs);
+        }
+
+        assert_eq!(merged_keys.finalize(), single_keys.finalize());
+        assert_eq!(merged_pairs.finalize(), single_pairs.finalize());
+    }
+}
+
+#[test]
+fn cooccurrence_merge_applies_top_k_only_at_finalize() {
+    // Each half alone ranks a different pair first; only the merged counts
+    // make 0xa2 the top address_topic0 pair.
+    let mut left = CooccurrenceAccumulator::new(1);
+    left.observe_log(1, &mk_log(0xa1, vec![0xb1]));
+    left.observe_log(1, &mk_log(0xa1, vec![0xb1]));
+    left.observe_log(2, &mk_log(0xa2, vec![0xb1]));
+    let mut right = CooccurrenceAccumulator::new(1);
+    right.observe_log(3, &mk_log(0xa3, vec![0xb1]));
+    right.observe_log(3, &mk_log(0xa3, vec![0xb1]));
+    right.observe_log(4, &mk_log(0xa2, vec![0xb1]));
+    right.observe_log(5, &mk_log(0xa2, vec![0xb1]));
+
+    left.merge(right);
+    let rows = left.finalize();
+
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].left_key, vec![0xa2; 20]);
+    assert_eq!(rows[0].count_total, 3);
+    assert_eq!(rows[0].first_block, 2);
+    assert_eq!(rows[0].last_block, 5);
+}
+
+#[test]
+fn cooccurrence_top_k_cut_inside_a_tie_keeps_smallest_pairs() {
+    // Five address_topic0 pairs with one observation each, split across
+    // accumulators in an order unrelated to key order.
+    let addresses = [0xa4, 0xa1, 0xa5, 0xa3, 0xa2];
+    let mut merged = CooccurrenceAccumulator::new(3);
+    for chunk in addresses.chunks(2).rev() {
+        let mut part = CooccurrenceAccumulator::new(3);
+        for address in chunk {
+            part.observe_log(1, &mk_log(*address, vec![0xb1]));
+        }
+        merged.merge(part);
+    }
+    let mut sequential = CooccurrenceAccumulator::new(3);
+    for address in addresses {
+        sequential.observe_log(1, &mk_log(address, vec![0xb1]));
+    }
+
+    let rows = merged.finalize();
+    let retained = rows
+        .iter()
+        .map(|row| (row.left_key[0], row.count_total))
+        .collect::<Vec<_>>();
+    assert_eq!(retained, vec![(0xa1, 1), (0xa2, 1), (0xa3, 1)]);
+    assert_eq!(rows, sequential.finalize());
+}
+
+#[test]
+fn key_stats_merge_unions_active_blocks_and_partners() {
+    let mut left = KeyStatsAccumulator::new();
+    left.observe_log(1, &mk_log(0xa1, vec![0xb1]));
+    left.observe_log(2, &mk_log(0xa1, vec![0xb2]));
+    let mut right = KeyStatsAccumulator::new();
+    right.observe_log(2, &mk_log(0xa1, vec![0xb1]));
+    right.observe_log(6, &mk_log(0xa1, vec![0xb3]));
+
+    left.merge(right);
+    let rows = left.finalize();
+
+    let addr = rows
+        .iter()
+        .find(|r| r.key_type == KeyType::Address)
+        .expect("address row");
+    assert_eq!(addr.count_total, 4);
+    assert_eq!(addr.first_block, 1);
+    assert_eq!(addr.last_block, 6);
+    assert_eq!(addr.active_block_count, 3);
+    assert_eq!(addr.distinct_partner_estimate, Some(3.0));
+}
+
+#[test]
+fn hyperloglog_merge_matches_single_sketch() {
+    let mut single = HyperLogLog::new(10);
+    let mut left = HyperLogLog::new(10);
+    let mut right = HyperLogLog::new(10);
+    for value in 0..2_000u64 {
+        single.insert_u64(value);
+        if value % 2 == 0 {
+            left.insert_u64(value);
+        } else {
+            right.insert_u64(value);
+        }
+    }
+
+    left.merge(&right);
+
+    assert_eq!(left.estimate(), single.estimate());
+}
+
+#[test]
+fn hyperloglog_sparse_and_dense_sketches_merge_in_either_direction() {
+    let mut single = HyperLogLog::new(10);
+    let mut many = HyperLogLog::new(10);
+    let mut few = HyperLogLog::new(10);
+    for value in 0..2_000u64 {
+        single.insert_u64(value);
+        if value < 1_990 {
+            many.insert_u64(value);
+        } else {
+            few.insert_u64(value);
+        }
+    }
+    // Ten distinct values leave `few` far below the dense threshold, and its
+    // estimate is already close.
+    assert!((few.estimate() - 10.0).abs() < 0.5);
+
+    let mut dense_into_sparse = few.clone();
+    dense_into_sparse.merge(&many);
+    many.merge(&few);
+
+    assert_eq!(many.estimate(), single.estimate());
+    assert_eq!(dense_into_sparse.estimate(), single.estimate());
+}
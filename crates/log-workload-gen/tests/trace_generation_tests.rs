@@ -2,7 +2,8 @@ use log_workload_gen::artifact::ParquetStats;
 use log_workload_gen::config::{GeneratorConfig, MaxThreads};
 use log_workload_gen::generate::generate_traces;
 use log_workload_gen::stats::{KeyStatsRow, KeyType};
-use log_workload_gen::types::DatasetManifest;
+use log_workload_gen::types::{DatasetManifest, TraceEntry};
+use std::collections::BTreeMap;
 
 #[test]
 fn generation_is_deterministic_for_same_seed() {
@@ -86,6 +87,39 @@ fn generation_is_invariant_to_max_threads_setting() {
     assert_eq!(a, b);
 }
 
+#[test]
+fn frequency_weighted_sampling_favors_hot_keys() {
+    let mut cfg = GeneratorConfig {
+        trace_size_per_profile: 2_000,
+        scale_factor: 1.0,
+        ..GeneratorConfig::default()
+    };
+    let manifest = manifest();
+    let mut stats = stats();
+    for row in &mut stats.key_stats {
+        row.count_total = if row.key_value[0] == 0xaa { 9_900 } else { 100 };
+    }
+
+    let hot = hex::encode(vec![0xaa; 20]);
+    let hot_share = |entries: &[TraceEntry]| {
+        let addresses: Vec<&String> = entries.iter().flat_map(|e| &e.address_or).collect();
+        let hot_count = addresses.iter().filter(|a| ***a == hot).count();
+        hot_count as f64 / addresses.len() as f64
+    };
+
+    let uniform = generate_traces(&cfg, &manifest, &stats, 42).expect("generate traces");
+    cfg.profiles.expected.frequency_weighted = true;
+    let weighted = generate_traces(&cfg, &manifest, &stats, 42).expect("generate traces");
+    let weighted_again = generate_traces(&cfg, &manifest, &stats, 42).expect("generate traces");
+
+    assert_eq!(weighted, weighted_again);
+    assert_eq!(weighted.stress, uniform.stress);
+    let uniform_share = hot_share(&uniform.expected);
+    let weighted_share = hot_share(&weighted.expected);
+    assert!((0.4..0.6).contains(&uniform_share), "{uniform_share}");
+    assert!(weighted_share > 0.95, "{weighted_share}");
+}
+
 fn manifest() -> DatasetManifest {
     DatasetManifest {
         schema_version: "1.0.0".to_string(),
@@ -103,6 +137,8 @@ fn manifest() -> DatasetManifest {
         seed: Some(7),
         valid: true,
         invalid_reason: None,
+        rng_version: None,
+        key_cardinality: BTreeMap::new(),
     }
 }
 